@@ -0,0 +1,61 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.crypto;
+
+import org.signal.libsignal.internal.Native;
+import org.signal.libsignal.internal.NativeHandleGuard;
+import org.signal.libsignal.protocol.InvalidKeyException;
+import org.signal.libsignal.protocol.InvalidMessageException;
+
+/**
+ * The account's master key, from which registration lock and storage service keys are derived.
+ */
+public class MasterKey {
+  public static final int SIZE = 32;
+
+  private final byte[] bytes;
+
+  public MasterKey(byte[] bytes) throws InvalidKeyException {
+    if (bytes.length != SIZE) {
+      throw new InvalidKeyException("master key must be " + SIZE + " bytes");
+    }
+    this.bytes = bytes.clone();
+  }
+
+  public byte[] serialize() {
+    return bytes.clone();
+  }
+
+  /** Encrypts the master key under a PIN's encryption key for upload to the recovery service. */
+  public byte[] wrap(PinHash pinHash) {
+    try (NativeHandleGuard guard = new NativeHandleGuard(pinHash)) {
+      return Native.MasterKey_Wrap(bytes, guard.nativeHandle());
+    }
+  }
+
+  /**
+   * Reverses {@link #wrap}.
+   *
+   * @throws InvalidMessageException if the PIN is wrong or {@code wrapped} has been modified
+   */
+  public static MasterKey unwrap(PinHash pinHash, byte[] wrapped) throws InvalidMessageException {
+    try (NativeHandleGuard guard = new NativeHandleGuard(pinHash)) {
+      return new MasterKey(Native.MasterKey_Unwrap(guard.nativeHandle(), wrapped));
+    } catch (InvalidKeyException e) {
+      throw new AssertionError(e);
+    }
+  }
+
+  /** The registration lock token, as the lowercase hex string the server expects. */
+  public String deriveRegistrationLock() {
+    return Native.MasterKey_RegistrationLock(bytes);
+  }
+
+  /** The root key for storage service encryption. */
+  public byte[] deriveStorageServiceKey() {
+    return Native.StorageKey_DeriveFromMasterKey(bytes);
+  }
+}
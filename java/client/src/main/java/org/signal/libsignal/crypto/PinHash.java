@@ -0,0 +1,59 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.crypto;
+
+import org.signal.libsignal.internal.Native;
+import org.signal.libsignal.internal.NativeHandleGuard;
+
+/**
+ * A PIN stretched with Argon2id, split into a key for wrapping the {@link MasterKey} and a key
+ * for proving knowledge of the PIN to the recovery service.
+ */
+public class PinHash implements NativeHandleGuard.Owner {
+  private final long unsafeHandle;
+
+  /**
+   * Puts a PIN into the canonical form that is hashed.
+   *
+   * Surrounding whitespace is removed, PINs made up of decimal digits in any script are converted
+   * to ASCII digits, and anything else is put into Unicode normalization form NFKD.
+   */
+  public static String normalize(String pin) {
+    return Native.Pin_Normalize(pin);
+  }
+
+  /**
+   * Hashes a PIN that has already been passed through {@link #normalize}.
+   *
+   * @param salt 32 bytes unique to the account, such as the recovery service's backup ID
+   */
+  public PinHash(byte[] normalizedPin, byte[] salt) {
+    this.unsafeHandle = Native.PinHash_Create(normalizedPin, salt);
+  }
+
+  @Override
+  protected void finalize() {
+    Native.PinHash_Destroy(this.unsafeHandle);
+  }
+
+  public long unsafeNativeHandleWithoutGuard() {
+    return this.unsafeHandle;
+  }
+
+  /** The key used to wrap the master key; never leaves the device. */
+  public byte[] encryptionKey() {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      return Native.PinHash_EncryptionKey(guard.nativeHandle());
+    }
+  }
+
+  /** The key sent to the recovery service in place of the PIN. */
+  public byte[] accessKey() {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      return Native.PinHash_AccessKey(guard.nativeHandle());
+    }
+  }
+}
@@ -17,6 +17,7 @@ import org.signal.libsignal.protocol.NoSessionException;
 import org.signal.libsignal.protocol.SessionCipher;
 import org.signal.libsignal.protocol.SignalProtocolAddress;
 import org.signal.libsignal.protocol.UntrustedIdentityException;
+import org.signal.libsignal.protocol.ecc.ECPublicKey;
 import org.signal.libsignal.protocol.groups.GroupCipher;
 import org.signal.libsignal.protocol.message.CiphertextMessage;
 import org.signal.libsignal.protocol.message.PlaintextContent;
@@ -152,10 +153,11 @@ public class SealedSessionCipher {
       SelfSendException
   {
     UnidentifiedSenderMessageContent content;
+    ECPublicKey                      trustRoot;
     try {
       content = new UnidentifiedSenderMessageContent(
         Native.SealedSessionCipher_DecryptToUsmc(ciphertext, this.signalProtocolStore, null));
-      validator.validate(content.getSenderCertificate(), timestamp);
+      trustRoot = validator.validate(content.getSenderCertificate(), timestamp);
     } catch (Exception e) {
       throw new InvalidMetadataMessageException(e);
     }
@@ -173,6 +175,7 @@ public class SealedSessionCipher {
                                   content.getSenderCertificate().getSenderDeviceId(),
                                   content.getType(),
                                   content.getGroupId(),
+                                  trustRoot,
                                   decrypt(content));
     } catch (InvalidMessageException e) {
       throw new ProtocolInvalidMessageException(e, content);
@@ -226,14 +229,16 @@ public class SealedSessionCipher {
     private final int              deviceId;
     private final int              messageType;
     private final Optional<byte[]> groupId;
+    private final ECPublicKey      trustRoot;
     private final byte[]           paddedMessage;
 
-    private DecryptionResult(String senderUuid, Optional<String> senderE164, int deviceId, int messageType, Optional<byte[]> groupId, byte[] paddedMessage) {
+    private DecryptionResult(String senderUuid, Optional<String> senderE164, int deviceId, int messageType, Optional<byte[]> groupId, ECPublicKey trustRoot, byte[] paddedMessage) {
       this.senderUuid    = senderUuid;
       this.senderE164    = senderE164;
       this.deviceId      = deviceId;
       this.messageType   = messageType;
       this.groupId       = groupId;
+      this.trustRoot     = trustRoot;
       this.paddedMessage = paddedMessage;
     }
 
@@ -260,5 +265,10 @@ public class SealedSessionCipher {
     public Optional<byte[]> getGroupId() {
       return groupId;
     }
+
+    /** The trust root that the sender certificate was validated against. */
+    public ECPublicKey getTrustRoot() {
+      return trustRoot;
+    }
   }
 }
@@ -7,25 +7,54 @@ import org.signal.libsignal.protocol.ecc.Curve;
 import org.signal.libsignal.protocol.ecc.ECPublicKey;
 import org.signal.libsignal.protocol.InvalidKeyException;
 
+import java.util.Collections;
+import java.util.List;
+
 public class CertificateValidator {
-  private final ECPublicKey trustRoot;
+  private final List<TrustRoot> trustRoots;
 
   public CertificateValidator(ECPublicKey trustRoot) {
-    this.trustRoot = trustRoot;
+    this(Collections.singletonList(new TrustRoot(trustRoot)));
+  }
+
+  /**
+   * Accepts certificates signed by any of {@code trustRoots} that is valid at the time of
+   * validation.
+   */
+  public CertificateValidator(List<TrustRoot> trustRoots) {
+    this.trustRoots = trustRoots;
   }
 
+  /** Returns the key of the first trust root. */
   public ECPublicKey getTrustRoot() {
-    return this.trustRoot;
+    return this.trustRoots.get(0).getKey();
   }
 
-  public void validate(SenderCertificate certificate, long validationTime) throws InvalidCertificateException {
-    try (
-      NativeHandleGuard certificateGuard = new NativeHandleGuard(certificate);
-      NativeHandleGuard trustRootGuard = new NativeHandleGuard(trustRoot);
-    ) {
-       if (!Native.SenderCertificate_Validate(certificateGuard.nativeHandle(), trustRootGuard.nativeHandle(), validationTime)) {
+  public List<TrustRoot> getTrustRoots() {
+    return this.trustRoots;
+  }
+
+  /**
+   * Validates {@code certificate} against the trust roots.
+   *
+   * @return the key of the trust root that signed the certificate
+   */
+  public ECPublicKey validate(SenderCertificate certificate, long validationTime) throws InvalidCertificateException {
+    long[] trustRootHandles = new long[trustRoots.size()];
+    int i = 0;
+    for (TrustRoot trustRoot : trustRoots) {
+      trustRootHandles[i] = trustRoot.unsafeNativeHandleWithoutGuard();
+      i++;
+    }
+
+    try (NativeHandleGuard certificateGuard = new NativeHandleGuard(certificate)) {
+       long validatedBy = Native.SenderCertificate_ValidateWithTrustRoots(certificateGuard.nativeHandle(), trustRootHandles, validationTime);
+       if (validatedBy == 0) {
          throw new InvalidCertificateException("Validation failed");
        }
+       return new ECPublicKey(validatedBy);
+    } catch (InvalidCertificateException e) {
+      throw e;
     } catch (Exception e) {
       throw new InvalidCertificateException(e);
     }
@@ -34,9 +63,12 @@ public class CertificateValidator {
   // VisibleForTesting
   void validate(ServerCertificate certificate) throws InvalidCertificateException {
     try {
-      if (!Curve.verifySignature(trustRoot, certificate.getCertificate(), certificate.getSignature())) {
-        throw new InvalidCertificateException("Signature failed");
+      for (TrustRoot trustRoot : trustRoots) {
+        if (Curve.verifySignature(trustRoot.getKey(), certificate.getCertificate(), certificate.getSignature())) {
+          return;
+        }
       }
+      throw new InvalidCertificateException("Signature failed");
     } catch (InvalidKeyException e) {
       throw new InvalidCertificateException(e);
     }
@@ -0,0 +1,43 @@
+package org.signal.libsignal.metadata.certificate;
+
+import org.signal.libsignal.internal.Native;
+import org.signal.libsignal.internal.NativeHandleGuard;
+
+import org.signal.libsignal.protocol.ecc.ECPublicKey;
+
+/**
+ * A key that server certificates may be signed with, optionally only trusted within a window of
+ * time.
+ */
+public class TrustRoot implements NativeHandleGuard.Owner {
+  private final long unsafeHandle;
+
+  @Override
+  protected void finalize() {
+     Native.TrustRoot_Destroy(this.unsafeHandle);
+  }
+
+  /** A trust root that is always valid. */
+  public TrustRoot(ECPublicKey key) {
+    try (NativeHandleGuard keyGuard = new NativeHandleGuard(key)) {
+      this.unsafeHandle = Native.TrustRoot_New(keyGuard.nativeHandle());
+    }
+  }
+
+  /** A trust root that is only valid from {@code notBefore} through {@code notAfter}, inclusive. */
+  public TrustRoot(ECPublicKey key, long notBefore, long notAfter) {
+    try (NativeHandleGuard keyGuard = new NativeHandleGuard(key)) {
+      this.unsafeHandle = Native.TrustRoot_NewWithValidity(keyGuard.nativeHandle(), notBefore, notAfter);
+    }
+  }
+
+  public ECPublicKey getKey() {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      return new ECPublicKey(Native.TrustRoot_GetKey(guard.nativeHandle()));
+    }
+  }
+
+  public long unsafeNativeHandleWithoutGuard() {
+    return this.unsafeHandle;
+  }
+}
@@ -0,0 +1,59 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.crypto;
+
+import java.nio.charset.StandardCharsets;
+import junit.framework.TestCase;
+import org.signal.libsignal.protocol.InvalidMessageException;
+import org.signal.libsignal.protocol.util.Hex;
+
+// Keep in sync with the known-answer tests in rust/crypto/tests/pin.rs.
+public class PinTests extends TestCase {
+  private static final byte[] SALT =
+      Hex.fromStringCondensedAssert("202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f");
+
+  public void testPinHashKat() throws Exception {
+    PinHash pinHash = new PinHash(PinHash.normalize("password").getBytes(StandardCharsets.UTF_8), SALT);
+
+    assertEquals("175f63e11c5974891a27413776e079402ac441faaee5e7fe8b3b757888472638",
+                 Hex.toStringCondensed(pinHash.encryptionKey()));
+    assertEquals("02a16780b3d79d48106e9196c598b36f889c5801e7c8717e3377099bf1dd319a",
+                 Hex.toStringCondensed(pinHash.accessKey()));
+
+    MasterKey masterKey = new MasterKey(SALT);
+    byte[] wrapped = masterKey.wrap(pinHash);
+    assertEquals("5eced03868a6c20d3edf31ecaf0180cb3e0ad7f660851d192c7e9d1c686bd9d7e1668c0773209a85e8ba72a521bb9d51",
+                 Hex.toStringCondensed(wrapped));
+    assertEquals(Hex.toStringCondensed(SALT),
+                 Hex.toStringCondensed(MasterKey.unwrap(pinHash, wrapped).serialize()));
+
+    wrapped[20] ^= 1;
+    try {
+      MasterKey.unwrap(pinHash, wrapped);
+      fail("modified wrapped key accepted");
+    } catch (InvalidMessageException e) {
+      /* good */
+    }
+  }
+
+  public void testMasterKeyDerivations() throws Exception {
+    MasterKey masterKey = new MasterKey(SALT);
+    assertEquals("2bf7988224ba35d3554966c65e8dc8c54974b034bdd44cabfd3f15fdb185e3c6",
+                 masterKey.deriveRegistrationLock());
+    assertEquals("47d3b98d12d2f826f27f2d8c63ba0e0cad6019675659623a6c3dac297d1b0889",
+                 Hex.toStringCondensed(masterKey.deriveStorageServiceKey()));
+  }
+
+  public void testPinNormalization() {
+    assertEquals("1234", PinHash.normalize(" 1234\n"));
+    // Arabic-Indic, Devanagari, and fullwidth digits.
+    assertEquals("1234", PinHash.normalize("\u0661\u0662\u0663\u0664"));
+    assertEquals("1234", PinHash.normalize("\u0967\u0968\u0969\u096a"));
+    assertEquals("1234", PinHash.normalize("\uff11\uff12\uff13\uff14"));
+    // Alphanumeric PINs are kept, but decomposed.
+    assertEquals("cafe\u0301", PinHash.normalize("caf\u00e9"));
+  }
+}
@@ -77,6 +77,7 @@ public class SealedSessionCipherTest extends TestCase {
     assertEquals(plaintext.getSenderUuid(), "9d0652a3-dcc3-4d11-975f-74d61598733f");
     assertEquals(plaintext.getSenderE164().get(), "+14151111111");
     assertEquals(plaintext.getDeviceId(), 1);
+    assertEquals(plaintext.getTrustRoot(), trustRoot.getPublicKey());
   }
 
   public void testEncryptDecryptUntrusted() throws Exception {
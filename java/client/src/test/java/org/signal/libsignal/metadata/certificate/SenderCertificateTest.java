@@ -11,6 +11,7 @@ import org.signal.libsignal.protocol.ecc.ECPrivateKey;
 import org.signal.libsignal.internal.Native;
 import org.signal.libsignal.internal.NativeHandleGuard;
 
+import java.util.Arrays;
 import java.util.UUID;
 
 public class SenderCertificateTest extends TestCase {
@@ -55,6 +56,29 @@ public class SenderCertificateTest extends TestCase {
     }
   }
 
+  public void testMultipleTrustRoots() throws InvalidCertificateException, InvalidKeyException {
+    ECKeyPair key          = Curve.generateKeyPair();
+    ECKeyPair newTrustRoot = Curve.generateKeyPair();
+
+    SenderCertificate oldCertificate = createCertificateFor(trustRoot, UUID.fromString("9d0652a3-dcc3-4d11-975f-74d61598733f"), "+14151111111", 31337, key.getPublicKey(), 31337);
+    SenderCertificate newCertificate = createCertificateFor(newTrustRoot, UUID.fromString("9d0652a3-dcc3-4d11-975f-74d61598733f"), "+14151111111", 31337, key.getPublicKey(), 31337);
+
+    CertificateValidator validator = new CertificateValidator(Arrays.asList(
+      new TrustRoot(trustRoot.getPublicKey(), 0, 31300),
+      new TrustRoot(newTrustRoot.getPublicKey())));
+
+    assertEquals(trustRoot.getPublicKey(), validator.validate(oldCertificate, 31300));
+    assertEquals(newTrustRoot.getPublicKey(), validator.validate(newCertificate, 31300));
+    assertEquals(newTrustRoot.getPublicKey(), validator.validate(newCertificate, 31336));
+
+    try {
+      validator.validate(oldCertificate, 31336);
+      throw new AssertionError();
+    } catch (InvalidCertificateException e) {
+      // good
+    }
+  }
+
   private SenderCertificate createCertificateFor(ECKeyPair trustRoot, UUID uuid, String e164, int deviceId, ECPublicKey identityKey, long expires)
       throws InvalidKeyException, InvalidCertificateException {
     ECKeyPair serverKey = Curve.generateKeyPair();
@@ -192,11 +192,35 @@ public final class Native {
   public static native void Logger_Initialize(int maxLevel, Class loggerClass);
   public static native void Logger_SetMaxLevel(int maxLevel);
 
+  public static native String MasterKey_RegistrationLock(byte[] masterKey);
+  public static native byte[] MasterKey_Unwrap(long pinHash, byte[] wrapped);
+  public static native byte[] MasterKey_Wrap(byte[] masterKey, long pinHash);
+
+  public static native byte[] MessageBackupDecryptor_DecryptFrame(long decryptor, byte[] record);
+  public static native void MessageBackupDecryptor_Destroy(long handle);
+  public static native boolean MessageBackupDecryptor_IsFinished(long decryptor);
+  public static native long MessageBackupDecryptor_NewWithPassphrase(String passphrase, byte[] serializedHeader);
+  public static native long MessageBackupDecryptor_NewWithRecoveryKey(byte[] recoveryKey, byte[] serializedHeader);
+
+  public static native void MessageBackupEncryptor_Destroy(long handle);
+  public static native byte[] MessageBackupEncryptor_EncryptFrame(long encryptor, byte[] serializedFrame);
+  public static native byte[] MessageBackupEncryptor_Finish(long encryptor);
+  public static native byte[] MessageBackupEncryptor_GetArchivePrefix(long encryptor);
+  public static native long MessageBackupEncryptor_NewWithPassphrase(String passphrase);
+  public static native long MessageBackupEncryptor_NewWithRecoveryKey(byte[] recoveryKey);
+
   public static native void NumericFingerprintGenerator_Destroy(long handle);
   public static native String NumericFingerprintGenerator_GetDisplayString(long obj);
   public static native byte[] NumericFingerprintGenerator_GetScannableEncoding(long obj);
   public static native long NumericFingerprintGenerator_New(int iterations, int version, byte[] localIdentifier, byte[] localKey, byte[] remoteIdentifier, byte[] remoteKey);
 
+  public static native byte[] PinHash_AccessKey(long pinHash);
+  public static native long PinHash_Create(byte[] normalizedPin, byte[] salt);
+  public static native void PinHash_Destroy(long handle);
+  public static native byte[] PinHash_EncryptionKey(long pinHash);
+
+  public static native String Pin_Normalize(String pin);
+
   public static native long PlaintextContent_Deserialize(byte[] data);
   public static native byte[] PlaintextContent_DeserializeAndGetContent(byte[] bytes);
   public static native void PlaintextContent_Destroy(long handle);
@@ -309,6 +333,7 @@ public final class Native {
   public static native byte[] SenderCertificate_GetSignature(long obj);
   public static native long SenderCertificate_New(String senderUuid, String senderE164, int senderDeviceId, long senderKey, long expiration, long signerCert, long signerKey);
   public static native boolean SenderCertificate_Validate(long cert, long key, long time);
+  public static native long SenderCertificate_ValidateWithTrustRoots(long cert, long[] trustRoots, long time);
 
   public static native long SenderKeyDistributionMessage_Deserialize(byte[] data);
   public static native void SenderKeyDistributionMessage_Destroy(long handle);
@@ -417,6 +442,21 @@ public final class Native {
   public static native long SignedPreKeyRecord_GetTimestamp(long obj);
   public static native long SignedPreKeyRecord_New(int id, long timestamp, long pubKey, long privKey, byte[] signature);
 
+  public static native byte[] StorageItemKey_Decrypt(byte[] key, byte[] ciphertext);
+  public static native byte[] StorageItemKey_Encrypt(byte[] key, byte[] plaintext);
+
+  public static native byte[] StorageKey_DeriveFromMasterKey(byte[] masterKey);
+  public static native byte[] StorageKey_DeriveItemKey(byte[] storageKey, byte[] rawId);
+  public static native byte[] StorageKey_DeriveManifestKey(byte[] storageKey, long version);
+
+  public static native byte[] StorageManifestKey_Decrypt(byte[] key, byte[] ciphertext);
+  public static native byte[] StorageManifestKey_Encrypt(byte[] key, byte[] plaintext);
+
+  public static native void TrustRoot_Destroy(long handle);
+  public static native long TrustRoot_GetKey(long obj);
+  public static native long TrustRoot_New(long key);
+  public static native long TrustRoot_NewWithValidity(long key, long notBefore, long notAfter);
+
   public static native long UnidentifiedSenderMessageContent_Deserialize(byte[] data);
   public static native void UnidentifiedSenderMessageContent_Destroy(long handle);
   public static native int UnidentifiedSenderMessageContent_GetContentHint(long m);
@@ -6,4 +6,6 @@
 package org.signal.libsignal.zkgroup;
 
 public class InvalidRedemptionTimeException extends Exception {
+    public InvalidRedemptionTimeException() { super(); }
+    public InvalidRedemptionTimeException(String msg) { super(msg); }
 }
@@ -164,6 +164,86 @@ export class Aes256GcmSiv {
   }
 }
 
+/**
+ * Puts a PIN into the canonical form that is hashed.
+ *
+ * Surrounding whitespace is removed, PINs made up of decimal digits in any script are converted
+ * to ASCII digits, and anything else is put into Unicode normalization form NFKD.
+ */
+export function normalizePin(pin: string): string {
+  return Native.Pin_Normalize(pin);
+}
+
+/**
+ * A PIN stretched with Argon2id, split into a key for wrapping the {@link MasterKey} and a key
+ * for proving knowledge of the PIN to the recovery service.
+ */
+export class PinHash {
+  readonly _nativeHandle: Native.PinHash;
+
+  private constructor(handle: Native.PinHash) {
+    this._nativeHandle = handle;
+  }
+
+  /**
+   * Hashes a PIN that has already been passed through {@link normalizePin}.
+   *
+   * `salt` must be 32 bytes unique to the account, such as the recovery service's backup ID.
+   */
+  static create(normalizedPin: Buffer, salt: Buffer): PinHash {
+    return new PinHash(Native.PinHash_Create(normalizedPin, salt));
+  }
+
+  /** The key used to wrap the master key; never leaves the device. */
+  encryptionKey(): Buffer {
+    return Native.PinHash_EncryptionKey(this);
+  }
+
+  /** The key sent to the recovery service in place of the PIN. */
+  accessKey(): Buffer {
+    return Native.PinHash_AccessKey(this);
+  }
+}
+
+/**
+ * The account's master key, from which the registration lock token is derived.
+ */
+export class MasterKey {
+  static readonly SIZE = 32;
+
+  private readonly bytes: Buffer;
+
+  private constructor(bytes: Buffer) {
+    this.bytes = bytes;
+  }
+
+  static deserialize(bytes: Buffer): MasterKey {
+    if (bytes.length != MasterKey.SIZE) {
+      throw new Error(`master key must be ${MasterKey.SIZE} bytes`);
+    }
+    return new MasterKey(Buffer.from(bytes));
+  }
+
+  serialize(): Buffer {
+    return Buffer.from(this.bytes);
+  }
+
+  /** Encrypts the master key under a PIN's encryption key for upload to the recovery service. */
+  wrap(pinHash: PinHash): Buffer {
+    return Native.MasterKey_Wrap(this.bytes, pinHash);
+  }
+
+  /** Reverses {@link wrap}, throwing if the PIN is wrong or `wrapped` has been modified. */
+  static unwrap(pinHash: PinHash, wrapped: Buffer): MasterKey {
+    return new MasterKey(Native.MasterKey_Unwrap(pinHash, wrapped));
+  }
+
+  /** The registration lock token, as the lowercase hex string the server expects. */
+  registrationLock(): string {
+    return Native.MasterKey_RegistrationLock(this.bytes);
+  }
+}
+
 export class PublicKey {
   readonly _nativeHandle: Native.PublicKey;
 
@@ -754,6 +834,41 @@ export class SenderCertificate {
   }
 }
 
+/**
+ * A key that server certificates may be signed with, optionally only trusted
+ * within a window of time.
+ */
+export class TrustRoot {
+  readonly _nativeHandle: Native.TrustRoot;
+
+  private constructor(nativeHandle: Native.TrustRoot) {
+    this._nativeHandle = nativeHandle;
+  }
+
+  /** A trust root that is always valid. */
+  static new(key: PublicKey): TrustRoot {
+    return new TrustRoot(Native.TrustRoot_New(key));
+  }
+
+  /**
+   * A trust root that is only valid from `notBefore` through `notAfter`,
+   * inclusive.
+   */
+  static withValidity(
+    key: PublicKey,
+    notBefore: number,
+    notAfter: number
+  ): TrustRoot {
+    return new TrustRoot(
+      Native.TrustRoot_NewWithValidity(key, notBefore, notAfter)
+    );
+  }
+
+  key(): PublicKey {
+    return PublicKey._fromNativeHandle(Native.TrustRoot_GetKey(this));
+  }
+}
+
 export class SenderKeyDistributionMessage {
   readonly _nativeHandle: Native.SenderKeyDistributionMessage;
 
@@ -1172,6 +1287,13 @@ export class SealedSenderDecryptionResult {
   deviceId(): number {
     return Native.SealedSenderDecryptionResult_GetDeviceId(this);
   }
+
+  /** The trust root that the sender certificate was validated against. */
+  trustRoot(): PublicKey {
+    return PublicKey._fromNativeHandle(
+      Native.SealedSenderDecryptionResult_GetTrustRoot(this)
+    );
+  }
 }
 
 interface CiphertextMessageConvertible {
@@ -1429,10 +1551,40 @@ export async function sealedSenderDecryptMessage(
   identityStore: IdentityKeyStore,
   prekeyStore: PreKeyStore,
   signedPrekeyStore: SignedPreKeyStore
+): Promise<SealedSenderDecryptionResult> {
+  return sealedSenderDecryptMessageWithTrustRoots(
+    message,
+    [TrustRoot.new(trustRoot)],
+    timestamp,
+    localE164,
+    localUuid,
+    localDeviceId,
+    sessionStore,
+    identityStore,
+    prekeyStore,
+    signedPrekeyStore
+  );
+}
+
+/**
+ * Like {@link sealedSenderDecryptMessage}, but accepts a sender certificate
+ * signed by any of `trustRoots` that is valid at `timestamp`.
+ */
+export async function sealedSenderDecryptMessageWithTrustRoots(
+  message: Buffer,
+  trustRoots: TrustRoot[],
+  timestamp: number,
+  localE164: string | null,
+  localUuid: string,
+  localDeviceId: number,
+  sessionStore: SessionStore,
+  identityStore: IdentityKeyStore,
+  prekeyStore: PreKeyStore,
+  signedPrekeyStore: SignedPreKeyStore
 ): Promise<SealedSenderDecryptionResult> {
   const ssdr = await Native.SealedSender_DecryptMessage(
     message,
-    trustRoot,
+    trustRoots,
     timestamp,
     localE164,
     localUuid,
@@ -1056,6 +1056,10 @@ describe('SignalClient', () => {
         assert.deepEqual(bPlaintext.senderE164(), aE164);
         assert.deepEqual(bPlaintext.senderUuid(), aUuid);
         assert.deepEqual(bPlaintext.deviceId(), aDeviceId);
+        assert.deepEqual(
+          bPlaintext.trustRoot().serialize(),
+          trustRoot.getPublicKey().serialize()
+        );
       }
 
       const innerMessage = await SignalClient.signalEncrypt(
@@ -1665,6 +1669,52 @@ describe('SignalClient', () => {
 
     assert.deepEqual(decrypted.toString('hex'), '02000000');
   });
+  // Keep in sync with the known-answer tests in rust/crypto/tests/pin.rs.
+  it('PIN hash and master key test vectors', () => {
+    const salt = Buffer.from(
+      '202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f',
+      'hex'
+    );
+    const pinHash = SignalClient.PinHash.create(
+      Buffer.from(SignalClient.normalizePin('password'), 'utf8'),
+      salt
+    );
+    assert.equal(
+      pinHash.encryptionKey().toString('hex'),
+      '175f63e11c5974891a27413776e079402ac441faaee5e7fe8b3b757888472638'
+    );
+    assert.equal(
+      pinHash.accessKey().toString('hex'),
+      '02a16780b3d79d48106e9196c598b36f889c5801e7c8717e3377099bf1dd319a'
+    );
+
+    const masterKey = SignalClient.MasterKey.deserialize(salt);
+    const wrapped = masterKey.wrap(pinHash);
+    assert.equal(
+      wrapped.toString('hex'),
+      '5eced03868a6c20d3edf31ecaf0180cb3e0ad7f660851d192c7e9d1c686bd9d7e1668c0773209a85e8ba72a521bb9d51'
+    );
+    assert.deepEqual(
+      SignalClient.MasterKey.unwrap(pinHash, wrapped).serialize(),
+      salt
+    );
+    assert.equal(
+      masterKey.registrationLock(),
+      '2bf7988224ba35d3554966c65e8dc8c54974b034bdd44cabfd3f15fdb185e3c6'
+    );
+
+    wrapped[20] ^= 1;
+    assert.throws(() => SignalClient.MasterKey.unwrap(pinHash, wrapped));
+  });
+  it('normalizes PINs', () => {
+    assert.equal(SignalClient.normalizePin(' 1234\n'), '1234');
+    // Arabic-Indic, Devanagari, and fullwidth digits.
+    assert.equal(SignalClient.normalizePin('\u0661\u0662\u0663\u0664'), '1234');
+    assert.equal(SignalClient.normalizePin('\u0967\u0968\u0969\u096a'), '1234');
+    assert.equal(SignalClient.normalizePin('\uff11\uff12\uff13\uff14'), '1234');
+    // Alphanumeric PINs are kept, but decomposed.
+    assert.equal(SignalClient.normalizePin('caf\u00e9'), 'cafe\u0301');
+  });
   it('ECC signatures work', () => {
     const priv_a = SignalClient.PrivateKey.generate();
     const priv_b = SignalClient.PrivateKey.generate();
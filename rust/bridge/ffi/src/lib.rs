@@ -151,8 +151,9 @@ pub unsafe extern "C" fn signal_sealed_session_cipher_decrypt(
     sender_e164: *mut *const c_char,
     sender_uuid: *mut *const c_char,
     sender_device_id: *mut u32,
+    sender_trust_root: *mut *mut PublicKey,
     ctext: BorrowedSliceOf<c_uchar>,
-    trust_root: *const PublicKey,
+    trust_roots: BorrowedSliceOf<*const TrustRoot>,
     timestamp: u64,
     local_e164: *const c_char,
     local_uuid: *const c_char,
@@ -165,7 +166,11 @@ pub unsafe extern "C" fn signal_sealed_session_cipher_decrypt(
 ) -> *mut SignalFfiError {
     run_ffi_safe(|| {
         let ctext = ctext.as_slice()?;
-        let trust_root = native_handle_cast::<PublicKey>(trust_root)?;
+        let trust_roots = trust_roots
+            .as_slice()?
+            .iter()
+            .map(|trust_root| native_handle_cast::<TrustRoot>(*trust_root).map(|root| *root))
+            .collect::<SignalFfiResult<Vec<TrustRoot>>>()?;
         let mut identity_store = identity_store.as_ref().ok_or(SignalFfiError::NullPointer)?;
         let mut session_store = session_store.as_ref().ok_or(SignalFfiError::NullPointer)?;
         let mut prekey_store = prekey_store.as_ref().ok_or(SignalFfiError::NullPointer)?;
@@ -176,9 +181,13 @@ pub unsafe extern "C" fn signal_sealed_session_cipher_decrypt(
         let local_e164 = Option::convert_from(local_e164)?;
         let local_uuid = Option::convert_from(local_uuid)?.ok_or(SignalFfiError::NullPointer)?;
 
-        let decrypted = sealed_sender_decrypt(
+        let decrypted = sealed_sender_decrypt_with_options(
             ctext,
-            trust_root,
+            SealedSenderDecryptionOptions {
+                trust_roots: &trust_roots,
+                certificate_cache: None,
+                accepted_versions: SealedSenderVersion::ALL,
+            },
             timestamp,
             local_e164,
             local_uuid,
@@ -195,6 +204,7 @@ pub unsafe extern "C" fn signal_sealed_session_cipher_decrypt(
         write_optional_cstr_to(sender_e164, Ok(decrypted.sender_e164))?;
         write_cstr_to(sender_uuid, Ok(decrypted.sender_uuid))?;
         write_result_to(sender_device_id, decrypted.device_id)?;
+        write_result_to(sender_trust_root, decrypted.trust_root)?;
         write_bytearray_to(out, out_len, Some(decrypted.message))
     })
 }
@@ -8,6 +8,7 @@ use hsm_enclave::Error as HsmEnclaveError;
 use libc::c_char;
 use libsignal_bridge::ffi::*;
 use libsignal_protocol::*;
+use message_backup::Error as MessageBackupError;
 use signal_crypto::Error as SignalCryptoError;
 use std::ffi::CString;
 use zkgroup::ZkGroupError;
@@ -53,16 +54,22 @@ pub enum SignalErrorCode {
     CallbackError = 100,
 
     VerificationFailure = 110,
+    InvalidRedemptionTime = 111,
 }
 
 impl From<&SignalFfiError> for SignalErrorCode {
     fn from(err: &SignalFfiError) -> Self {
         match err {
             SignalFfiError::NullPointer => SignalErrorCode::NullParameter,
-            SignalFfiError::InvalidType => SignalErrorCode::InvalidType,
+            SignalFfiError::InvalidType
+            | SignalFfiError::ZkGroup(ZkGroupError::Deserialization(_)) => {
+                SignalErrorCode::InvalidType
+            }
 
             SignalFfiError::UnexpectedPanic(_)
             | SignalFfiError::DeviceTransfer(DeviceTransferError::InternalError(_))
+            | SignalFfiError::MessageBackup(MessageBackupError::Io(_))
+            | SignalFfiError::MessageBackup(MessageBackupError::Crypto(_))
             | SignalFfiError::Signal(SignalProtocolError::FfiBindingError(_)) => {
                 SignalErrorCode::InternalError
             }
@@ -118,7 +125,8 @@ impl From<&SignalFfiError> for SignalErrorCode {
             }
 
             SignalFfiError::Signal(SignalProtocolError::UnrecognizedMessageVersion(_))
-            | SignalFfiError::Signal(SignalProtocolError::UnknownSealedSenderVersion(_)) => {
+            | SignalFfiError::Signal(SignalProtocolError::UnknownSealedSenderVersion(_))
+            | SignalFfiError::MessageBackup(MessageBackupError::UnsupportedVersion(_)) => {
                 SignalErrorCode::UnrecognizedMessageVersion
             }
 
@@ -130,9 +138,15 @@ impl From<&SignalFfiError> for SignalErrorCode {
             | SignalFfiError::Signal(SignalProtocolError::CiphertextMessageTooShort(_))
             | SignalFfiError::Signal(SignalProtocolError::InvalidSealedSenderMessage(_))
             | SignalFfiError::SignalCrypto(SignalCryptoError::InvalidTag)
-            | SignalFfiError::HsmEnclave(HsmEnclaveError::HSMCommunicationError(_)) => {
-                SignalErrorCode::InvalidMessage
-            }
+            | SignalFfiError::HsmEnclave(HsmEnclaveError::HSMCommunicationError(_))
+            | SignalFfiError::MessageBackup(
+                MessageBackupError::NotABackup
+                | MessageBackupError::InvalidHeader(_)
+                | MessageBackupError::BadMac { .. }
+                | MessageBackupError::InvalidRecord(_)
+                | MessageBackupError::Truncated
+                | MessageBackupError::TrailingData,
+            ) => SignalErrorCode::InvalidMessage,
 
             SignalFfiError::Signal(SignalProtocolError::LegacyCiphertextVersion(_)) => {
                 SignalErrorCode::LegacyCiphertextVersion
@@ -144,7 +158,8 @@ impl From<&SignalFfiError> for SignalErrorCode {
             }
 
             SignalFfiError::Signal(SignalProtocolError::InvalidState(_, _))
-            | SignalFfiError::HsmEnclave(HsmEnclaveError::InvalidBridgeStateError) => {
+            | SignalFfiError::HsmEnclave(HsmEnclaveError::InvalidBridgeStateError)
+            | SignalFfiError::MessageBackup(MessageBackupError::InvalidState(_)) => {
                 SignalErrorCode::InvalidState
             }
 
@@ -158,19 +173,38 @@ impl From<&SignalFfiError> for SignalErrorCode {
 
             SignalFfiError::Signal(SignalProtocolError::InvalidArgument(_))
             | SignalFfiError::HsmEnclave(HsmEnclaveError::InvalidCodeHashError)
+            | SignalFfiError::DeviceTransfer(DeviceTransferError::UnsupportedAlgorithm(_))
+            | SignalFfiError::DeviceTransfer(DeviceTransferError::InvalidCertificateOptions(_))
+            | SignalFfiError::DeviceTransfer(DeviceTransferError::CertificateDecodingFailed)
+            | SignalFfiError::MessageBackup(MessageBackupError::WrongSecretKind)
+            | SignalFfiError::MessageBackup(MessageBackupError::RecordTooLarge(_))
             | SignalFfiError::SignalCrypto(_)
-            | SignalFfiError::ZkGroup(ZkGroupError::BadArgs) => SignalErrorCode::InvalidArgument,
+            | SignalFfiError::ZkGroup(
+                ZkGroupError::BadArgs
+                | ZkGroupError::InvalidAttribute { .. }
+                | ZkGroupError::MissingCredentialKey { .. }
+                | ZkGroupError::RedemptionTimeNotDayAligned { .. },
+            ) => SignalErrorCode::InvalidArgument,
 
             SignalFfiError::Signal(SignalProtocolError::ApplicationCallbackError(_, _)) => {
                 SignalErrorCode::CallbackError
             }
 
             SignalFfiError::ZkGroup(
-                ZkGroupError::DecryptionFailure
+                ZkGroupError::DecryptionFailure(_)
                 | ZkGroupError::MacVerificationFailure
-                | ZkGroupError::ProofVerificationFailure
-                | ZkGroupError::SignatureVerificationFailure,
+                | ZkGroupError::ProofVerificationFailure { .. }
+                | ZkGroupError::ProofForOtherGroup { .. }
+                | ZkGroupError::SignatureVerificationFailure
+                | ZkGroupError::ReceiptAlreadyRedeemed
+                | ZkGroupError::ReceiptExpired { .. }
+                | ZkGroupError::ReceiptNotYetValid { .. }
+                | ZkGroupError::ReceiptAttributeNotDisclosed { .. },
             ) => SignalErrorCode::VerificationFailure,
+
+            SignalFfiError::ZkGroup(ZkGroupError::RedemptionTimeOutOfRange { .. }) => {
+                SignalErrorCode::InvalidRedemptionTime
+            }
         }
     }
 }
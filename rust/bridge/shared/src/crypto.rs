@@ -9,6 +9,7 @@ use signal_crypto::*;
 
 use aes_gcm_siv::aead::generic_array::typenum::Unsigned;
 use aes_gcm_siv::aead::{AeadCore, AeadInPlace, NewAead};
+use std::convert::TryInto;
 
 use crate::support::*;
 use crate::*;
@@ -81,6 +82,7 @@ bridge_handle!(Aes256GcmSiv, clone = false);
 bridge_handle!(Aes256Ctr32, clone = false, mut = true, node = false);
 bridge_handle!(Aes256GcmEncryption, clone = false, mut = true, node = false);
 bridge_handle!(Aes256GcmDecryption, clone = false, mut = true, node = false);
+bridge_handle!(PinHash, clone = false);
 
 #[bridge_fn(node = false)]
 fn Aes256Ctr32_New(key: &[u8], nonce: &[u8], initial_ctr: u32) -> Result<Aes256Ctr32> {
@@ -205,6 +207,92 @@ fn Aes256GcmSiv_Decrypt(
     Ok(buf)
 }
 
+#[bridge_fn_buffer(node = false)]
+fn StorageKey_DeriveFromMasterKey(master_key: &[u8; MASTER_KEY_LEN]) -> Vec<u8> {
+    StorageKey::from_master_key(&MasterKey::new(*master_key))
+        .as_bytes()
+        .to_vec()
+}
+
+#[bridge_fn_buffer(node = false)]
+fn StorageKey_DeriveManifestKey(storage_key: &[u8; STORAGE_KEY_LEN], version: u64) -> Vec<u8> {
+    StorageKey::new(*storage_key)
+        .derive_manifest_key(version)
+        .as_bytes()
+        .to_vec()
+}
+
+#[bridge_fn_buffer(node = false)]
+fn StorageKey_DeriveItemKey(storage_key: &[u8; STORAGE_KEY_LEN], raw_id: &[u8]) -> Vec<u8> {
+    StorageKey::new(*storage_key)
+        .derive_item_key(raw_id)
+        .as_bytes()
+        .to_vec()
+}
+
+#[bridge_fn_buffer(node = false)]
+fn StorageManifestKey_Encrypt(key: &[u8; STORAGE_KEY_LEN], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let mut rng = rand::rngs::OsRng;
+    StorageManifestKey::new(*key).encrypt(plaintext, &mut rng)
+}
+
+#[bridge_fn_buffer(node = false)]
+fn StorageManifestKey_Decrypt(key: &[u8; STORAGE_KEY_LEN], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    StorageManifestKey::new(*key).decrypt(ciphertext)
+}
+
+#[bridge_fn_buffer(node = false)]
+fn StorageItemKey_Encrypt(key: &[u8; STORAGE_KEY_LEN], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let mut rng = rand::rngs::OsRng;
+    StorageItemKey::new(*key).encrypt(plaintext, &mut rng)
+}
+
+#[bridge_fn_buffer(node = false)]
+fn StorageItemKey_Decrypt(key: &[u8; STORAGE_KEY_LEN], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    StorageItemKey::new(*key).decrypt(ciphertext)
+}
+
+#[bridge_fn]
+fn Pin_Normalize(pin: String) -> String {
+    normalize_pin(&pin)
+}
+
+#[bridge_fn]
+fn PinHash_Create(normalized_pin: &[u8], salt: &[u8]) -> Result<PinHash> {
+    let salt = salt.try_into().map_err(|_| Error::InvalidInputSize)?;
+    PinHash::create(normalized_pin, salt)
+}
+
+#[bridge_fn_buffer]
+fn PinHash_EncryptionKey(pin_hash: &PinHash) -> Vec<u8> {
+    pin_hash.encryption_key().to_vec()
+}
+
+#[bridge_fn_buffer]
+fn PinHash_AccessKey(pin_hash: &PinHash) -> Vec<u8> {
+    pin_hash.access_key().to_vec()
+}
+
+fn master_key_from_slice(master_key: &[u8]) -> Result<MasterKey> {
+    let master_key = master_key.try_into().map_err(|_| Error::InvalidKeySize)?;
+    Ok(MasterKey::new(master_key))
+}
+
+#[bridge_fn_buffer]
+fn MasterKey_Wrap(master_key: &[u8], pin_hash: &PinHash) -> Result<Vec<u8>> {
+    Ok(master_key_from_slice(master_key)?.wrap(pin_hash).to_vec())
+}
+
+#[bridge_fn_buffer]
+fn MasterKey_Unwrap(pin_hash: &PinHash, wrapped: &[u8]) -> Result<Vec<u8>> {
+    Ok(MasterKey::unwrap(pin_hash, wrapped)?.as_bytes().to_vec())
+}
+
+#[bridge_fn]
+fn MasterKey_RegistrationLock(master_key: &[u8]) -> Result<String> {
+    Ok(master_key_from_slice(master_key)?.registration_lock())
+}
+
 #[bridge_fn(ffi = false, node = false)]
 fn CryptographicHash_New(algo: String) -> Result<CryptographicHash> {
     CryptographicHash::new(&algo)
@@ -10,7 +10,7 @@ use std::convert::TryInto;
 use std::ffi::CStr;
 use std::ops::Deref;
 
-use crate::support::{FixedLengthBincodeSerializable, Serialized};
+use crate::support::{FixedLengthBincodeSerializable, Serialized, Versioned};
 
 use super::*;
 
@@ -278,6 +278,13 @@ impl<T: ResultTypeInfo> ResultTypeInfo for Result<T, device_transfer::Error> {
     }
 }
 
+impl<T: ResultTypeInfo> ResultTypeInfo for Result<T, message_backup::Error> {
+    type ResultType = T::ResultType;
+    fn convert_into(self) -> SignalFfiResult<Self::ResultType> {
+        T::convert_into(self?)
+    }
+}
+
 impl<T: ResultTypeInfo> ResultTypeInfo for Result<T, signal_crypto::Error> {
     type ResultType = T::ResultType;
     fn convert_into(self) -> SignalFfiResult<Self::ResultType> {
@@ -437,12 +444,24 @@ where
 
     fn convert_from(foreign: Self::ArgType) -> SignalFfiResult<Self> {
         let array = unsafe { foreign.as_ref() }.ok_or(SignalFfiError::NullPointer)?;
-        let result: T =
-            bincode::deserialize(array.as_ref()).map_err(|_| SignalFfiError::InvalidType)?;
+        let result = T::deserialize_bytes(array.as_ref()).map_err(zkgroup::ZkGroupError::from)?;
         Ok(Serialized::from(result))
     }
 }
 
+impl<T> SimpleArgTypeInfo for Versioned<T>
+where
+    T: zkgroup::VersionedSerializable,
+{
+    type ArgType = BorrowedSliceOf<c_uchar>;
+
+    fn convert_from(foreign: Self::ArgType) -> SignalFfiResult<Self> {
+        let bytes = unsafe { foreign.as_slice()? };
+        let result = zkgroup::deserialize(bytes).map_err(zkgroup::ZkGroupError::from)?;
+        Ok(Versioned::from(result))
+    }
+}
+
 impl<T> ResultTypeInfo for Serialized<T>
 where
     T: FixedLengthBincodeSerializable + serde::Serialize,
@@ -540,6 +559,7 @@ macro_rules! ffi_arg_type {
     // In order to provide a fixed-sized array of the correct length,
     // a serialized type FooBar must have a constant FOO_BAR_LEN that's in scope (and exposed to C).
     (Serialized<$typ:ident>) => (*const [libc::c_uchar; paste!([<$typ:snake:upper _LEN>])]);
+    (Versioned<$typ:ident>) => (ffi::BorrowedSliceOf<libc::c_uchar>);
 }
 
 /// Syntactically translates `bridge_fn` result types to FFI types for `cbindgen`.
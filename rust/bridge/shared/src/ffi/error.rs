@@ -9,6 +9,7 @@ use std::fmt;
 use device_transfer::Error as DeviceTransferError;
 use hsm_enclave::Error as HsmEnclaveError;
 use libsignal_protocol::*;
+use message_backup::Error as MessageBackupError;
 use signal_crypto::Error as SignalCryptoError;
 use zkgroup::ZkGroupError;
 
@@ -22,6 +23,7 @@ pub enum SignalFfiError {
     Signal(SignalProtocolError),
     DeviceTransfer(DeviceTransferError),
     HsmEnclave(HsmEnclaveError),
+    MessageBackup(MessageBackupError),
     SignalCrypto(SignalCryptoError),
     ZkGroup(ZkGroupError),
     NullPointer,
@@ -40,6 +42,9 @@ impl fmt::Display for SignalFfiError {
             SignalFfiError::HsmEnclave(e) => {
                 write!(f, "HSM enclave operation failed: {}", e)
             }
+            SignalFfiError::MessageBackup(e) => {
+                write!(f, "Message backup operation failed: {}", e)
+            }
             SignalFfiError::SignalCrypto(c) => {
                 write!(f, "Cryptographic operation failed: {}", c)
             }
@@ -72,6 +77,12 @@ impl From<HsmEnclaveError> for SignalFfiError {
     }
 }
 
+impl From<MessageBackupError> for SignalFfiError {
+    fn from(e: MessageBackupError) -> SignalFfiError {
+        SignalFfiError::MessageBackup(e)
+    }
+}
+
 impl From<SignalCryptoError> for SignalFfiError {
     fn from(e: SignalCryptoError) -> SignalFfiError {
         SignalFfiError::SignalCrypto(e)
@@ -11,7 +11,7 @@ use paste::paste;
 use std::convert::TryInto;
 use std::ops::Deref;
 
-use crate::support::{Array, FixedLengthBincodeSerializable, Serialized};
+use crate::support::{FixedLengthBincodeSerializable, Serialized, Versioned};
 
 use super::*;
 
@@ -696,6 +696,16 @@ impl<T: ResultTypeInfo> ResultTypeInfo for Result<T, hsm_enclave::Error> {
     }
 }
 
+impl<T: ResultTypeInfo> ResultTypeInfo for Result<T, message_backup::Error> {
+    type ResultType = T::ResultType;
+    fn convert_into(self, env: &JNIEnv) -> SignalJniResult<Self::ResultType> {
+        T::convert_into(self?, env)
+    }
+    fn convert_into_jobject(signal_jni_result: &SignalJniResult<Self::ResultType>) -> JObject {
+        <T as ResultTypeInfo>::convert_into_jobject(signal_jni_result)
+    }
+}
+
 impl<T: ResultTypeInfo> ResultTypeInfo for Result<T, signal_crypto::Error> {
     type ResultType = T::ResultType;
     fn convert_into(self, env: &JNIEnv) -> SignalJniResult<Self::ResultType> {
@@ -838,16 +848,10 @@ where
     fn convert_from(env: &jni_crate::JNIEnv, foreign: Self::ArgType) -> SignalJniResult<Self> {
         let borrowed_array = env.get_byte_array_elements(foreign, ReleaseMode::NoCopyBack)?;
         let len = borrowed_array.size()? as usize;
-        if len != T::Array::LEN {
-            return Err(SignalJniError::DeserializationFailed(
-                std::any::type_name::<T>(),
-            ));
-        }
         // Convert from i8 to u8.
         let bytes =
             unsafe { std::slice::from_raw_parts(borrowed_array.as_ptr() as *const u8, len) };
-        let result: T = bincode::deserialize(bytes)
-            .map_err(|_| SignalJniError::DeserializationFailed(std::any::type_name::<T>()))?;
+        let result = T::deserialize_bytes(bytes).map_err(zkgroup::ZkGroupError::from)?;
         Ok(Serialized::from(result))
     }
 }
@@ -870,6 +874,23 @@ where
     }
 }
 
+impl<T> SimpleArgTypeInfo<'_> for Versioned<T>
+where
+    T: zkgroup::VersionedSerializable,
+{
+    type ArgType = jbyteArray;
+
+    fn convert_from(env: &jni_crate::JNIEnv, foreign: Self::ArgType) -> SignalJniResult<Self> {
+        let borrowed_array = env.get_byte_array_elements(foreign, ReleaseMode::NoCopyBack)?;
+        let len = borrowed_array.size()? as usize;
+        // Convert from i8 to u8.
+        let bytes =
+            unsafe { std::slice::from_raw_parts(borrowed_array.as_ptr() as *const u8, len) };
+        let result = zkgroup::deserialize(bytes).map_err(zkgroup::ZkGroupError::from)?;
+        Ok(Versioned::from(result))
+    }
+}
+
 /// Implementation of [`bridge_handle`](crate::support::bridge_handle) for JNI.
 macro_rules! jni_bridge_handle {
     ( $typ:ty as false $(, $($_:tt)*)? ) => {};
@@ -1009,6 +1030,9 @@ macro_rules! jni_arg_type {
     (Serialized<$typ:ident>) => {
         jni::jbyteArray
     };
+    (Versioned<$typ:ident>) => {
+        jni::jbyteArray
+    };
 }
 
 /// Syntactically translates `bridge_fn` result types to JNI types for `cbindgen` and
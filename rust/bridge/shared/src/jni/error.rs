@@ -10,6 +10,7 @@ use std::fmt;
 use device_transfer::Error as DeviceTransferError;
 use hsm_enclave::Error as HsmEnclaveError;
 use libsignal_protocol::*;
+use message_backup::Error as MessageBackupError;
 use signal_crypto::Error as SignalCryptoError;
 use zkgroup::ZkGroupError;
 
@@ -24,6 +25,7 @@ pub enum SignalJniError {
     DeviceTransfer(DeviceTransferError),
     SignalCrypto(SignalCryptoError),
     HsmEnclave(HsmEnclaveError),
+    MessageBackup(MessageBackupError),
     ZkGroup(ZkGroupError),
     Jni(jni::errors::Error),
     BadJniParameter(&'static str),
@@ -40,6 +42,7 @@ impl fmt::Display for SignalJniError {
             SignalJniError::Signal(s) => write!(f, "{}", s),
             SignalJniError::DeviceTransfer(s) => write!(f, "{}", s),
             SignalJniError::HsmEnclave(e) => write!(f, "{}", e),
+            SignalJniError::MessageBackup(e) => write!(f, "{}", e),
             SignalJniError::SignalCrypto(s) => write!(f, "{}", s),
             SignalJniError::ZkGroup(e) => write!(f, "{}", e),
             SignalJniError::Jni(s) => write!(f, "JNI error {}", s),
@@ -79,6 +82,12 @@ impl From<HsmEnclaveError> for SignalJniError {
     }
 }
 
+impl From<MessageBackupError> for SignalJniError {
+    fn from(e: MessageBackupError) -> SignalJniError {
+        SignalJniError::MessageBackup(e)
+    }
+}
+
 impl From<SignalCryptoError> for SignalJniError {
     fn from(e: SignalCryptoError) -> SignalJniError {
         SignalJniError::SignalCrypto(e)
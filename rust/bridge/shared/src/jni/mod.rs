@@ -11,6 +11,7 @@ use jni::sys::jobject;
 use device_transfer::Error as DeviceTransferError;
 use hsm_enclave::Error as HsmEnclaveError;
 use libsignal_protocol::*;
+use message_backup::Error as MessageBackupError;
 use signal_crypto::Error as SignalCryptoError;
 use std::convert::{TryFrom, TryInto};
 use std::error::Error;
@@ -199,15 +200,22 @@ fn throw_error(env: &JNIEnv, error: SignalJniError) {
         SignalJniError::NullHandle => jni_class_name!(java.lang.NullPointerException),
 
         SignalJniError::Signal(SignalProtocolError::InvalidState(_, _))
-        | SignalJniError::SignalCrypto(SignalCryptoError::InvalidState) => {
+        | SignalJniError::SignalCrypto(SignalCryptoError::InvalidState)
+        | SignalJniError::MessageBackup(MessageBackupError::InvalidState(_)) => {
             jni_class_name!(java.lang.IllegalStateException)
         }
 
         SignalJniError::Signal(SignalProtocolError::InvalidArgument(_))
         | SignalJniError::SignalCrypto(SignalCryptoError::UnknownAlgorithm(_, _))
+        | SignalJniError::DeviceTransfer(DeviceTransferError::UnsupportedAlgorithm(_))
+        | SignalJniError::DeviceTransfer(DeviceTransferError::InvalidCertificateOptions(_))
+        | SignalJniError::DeviceTransfer(DeviceTransferError::CertificateDecodingFailed)
+        | SignalJniError::MessageBackup(MessageBackupError::WrongSecretKind)
+        | SignalJniError::MessageBackup(MessageBackupError::RecordTooLarge(_))
         | SignalJniError::SignalCrypto(SignalCryptoError::InvalidInputSize)
         | SignalJniError::SignalCrypto(SignalCryptoError::InvalidNonceSize)
-        | SignalJniError::DeserializationFailed(_) => {
+        | SignalJniError::DeserializationFailed(_)
+        | SignalJniError::ZkGroup(ZkGroupError::Deserialization(_)) => {
             jni_class_name!(java.lang.IllegalArgumentException)
         }
 
@@ -216,7 +224,9 @@ fn throw_error(env: &JNIEnv, error: SignalJniError) {
         | SignalJniError::Signal(SignalProtocolError::ApplicationCallbackError(_, _))
         | SignalJniError::Signal(SignalProtocolError::FfiBindingError(_))
         | SignalJniError::DeviceTransfer(DeviceTransferError::InternalError(_))
-        | SignalJniError::DeviceTransfer(DeviceTransferError::KeyDecodingFailed) => {
+        | SignalJniError::DeviceTransfer(DeviceTransferError::KeyDecodingFailed)
+        | SignalJniError::MessageBackup(MessageBackupError::Io(_))
+        | SignalJniError::MessageBackup(MessageBackupError::Crypto(_)) => {
             jni_class_name!(java.lang.RuntimeException)
         }
 
@@ -251,13 +261,22 @@ fn throw_error(env: &JNIEnv, error: SignalJniError) {
         | SignalJniError::Signal(SignalProtocolError::CiphertextMessageTooShort(_))
         | SignalJniError::Signal(SignalProtocolError::InvalidProtobufEncoding)
         | SignalJniError::Signal(SignalProtocolError::InvalidSealedSenderMessage(_))
-        | SignalJniError::SignalCrypto(SignalCryptoError::InvalidTag) => {
+        | SignalJniError::SignalCrypto(SignalCryptoError::InvalidTag)
+        | SignalJniError::MessageBackup(
+            MessageBackupError::NotABackup
+            | MessageBackupError::InvalidHeader(_)
+            | MessageBackupError::BadMac { .. }
+            | MessageBackupError::InvalidRecord(_)
+            | MessageBackupError::Truncated
+            | MessageBackupError::TrailingData,
+        ) => {
             jni_class_name!(org.signal.libsignal.protocol.InvalidMessageException)
         }
 
         SignalJniError::Signal(SignalProtocolError::UnrecognizedCiphertextVersion(_))
         | SignalJniError::Signal(SignalProtocolError::UnrecognizedMessageVersion(_))
-        | SignalJniError::Signal(SignalProtocolError::UnknownSealedSenderVersion(_)) => {
+        | SignalJniError::Signal(SignalProtocolError::UnknownSealedSenderVersion(_))
+        | SignalJniError::MessageBackup(MessageBackupError::UnsupportedVersion(_)) => {
             jni_class_name!(org.signal.libsignal.protocol.InvalidVersionException)
         }
 
@@ -305,14 +324,27 @@ fn throw_error(env: &JNIEnv, error: SignalJniError) {
             jni_class_name!(java.lang.IllegalStateException)
         }
 
-        SignalJniError::ZkGroup(ZkGroupError::BadArgs) => {
+        SignalJniError::ZkGroup(
+            ZkGroupError::BadArgs
+            | ZkGroupError::InvalidAttribute { .. }
+            | ZkGroupError::MissingCredentialKey { .. }
+            | ZkGroupError::RedemptionTimeNotDayAligned { .. },
+        ) => {
             jni_class_name!(org.signal.libsignal.zkgroup.InvalidInputException)
         }
+        SignalJniError::ZkGroup(ZkGroupError::RedemptionTimeOutOfRange { .. }) => {
+            jni_class_name!(org.signal.libsignal.zkgroup.InvalidRedemptionTimeException)
+        }
         SignalJniError::ZkGroup(
-            ZkGroupError::DecryptionFailure
+            ZkGroupError::DecryptionFailure(_)
             | ZkGroupError::MacVerificationFailure
-            | ZkGroupError::ProofVerificationFailure
-            | ZkGroupError::SignatureVerificationFailure,
+            | ZkGroupError::ProofVerificationFailure { .. }
+            | ZkGroupError::ProofForOtherGroup { .. }
+            | ZkGroupError::SignatureVerificationFailure
+            | ZkGroupError::ReceiptAlreadyRedeemed
+            | ZkGroupError::ReceiptExpired { .. }
+            | ZkGroupError::ReceiptNotYetValid { .. }
+            | ZkGroupError::ReceiptAttributeNotDisclosed { .. },
         ) => jni_class_name!(org.signal.libsignal.zkgroup.VerificationFailedException),
     };
 
@@ -34,4 +34,8 @@ pub mod device_transfer;
 
 pub mod hsm_enclave;
 
+// Desktop does not make use of message backups
+#[cfg(any(feature = "jni", feature = "ffi"))]
+pub mod message_backup;
+
 pub mod zkgroup;
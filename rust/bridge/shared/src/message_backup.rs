@@ -0,0 +1,95 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use ::message_backup;
+use libsignal_bridge_macros::*;
+use message_backup::{BackupDecryptor, BackupEncryptor, BackupSecret, Result};
+use rand::rngs::OsRng;
+
+// Not used by the Java bridge.
+#[allow(unused_imports)]
+use crate::support::*;
+use crate::*;
+
+// Explicit wrappers for cbindgen purposes.
+pub struct MessageBackupEncryptor(BackupEncryptor);
+pub struct MessageBackupDecryptor(BackupDecryptor);
+
+bridge_handle!(MessageBackupEncryptor, clone = false, mut = true, node = false);
+bridge_handle!(MessageBackupDecryptor, clone = false, mut = true, node = false);
+
+#[bridge_fn(node = false)]
+fn MessageBackupEncryptor_NewWithPassphrase(passphrase: String) -> Result<MessageBackupEncryptor> {
+    let secret = BackupSecret::Passphrase(passphrase);
+    Ok(MessageBackupEncryptor(BackupEncryptor::new(
+        &secret, &mut OsRng,
+    )?))
+}
+
+#[bridge_fn(node = false)]
+fn MessageBackupEncryptor_NewWithRecoveryKey(
+    recovery_key: &[u8; message_backup::RECOVERY_KEY_LEN],
+) -> Result<MessageBackupEncryptor> {
+    let secret = BackupSecret::RecoveryKey(*recovery_key);
+    Ok(MessageBackupEncryptor(BackupEncryptor::new(
+        &secret, &mut OsRng,
+    )?))
+}
+
+#[bridge_fn_buffer(node = false)]
+fn MessageBackupEncryptor_GetArchivePrefix(encryptor: &MessageBackupEncryptor) -> Vec<u8> {
+    encryptor.0.archive_prefix()
+}
+
+#[bridge_fn_buffer(node = false)]
+fn MessageBackupEncryptor_EncryptFrame(
+    encryptor: &mut MessageBackupEncryptor,
+    serialized_frame: &[u8],
+) -> Result<Vec<u8>> {
+    encryptor.0.encrypt_serialized_frame(serialized_frame)
+}
+
+#[bridge_fn_buffer(node = false)]
+fn MessageBackupEncryptor_Finish(encryptor: &mut MessageBackupEncryptor) -> Result<Vec<u8>> {
+    encryptor.0.finish()
+}
+
+#[bridge_fn(node = false)]
+fn MessageBackupDecryptor_NewWithPassphrase(
+    passphrase: String,
+    serialized_header: &[u8],
+) -> Result<MessageBackupDecryptor> {
+    let secret = BackupSecret::Passphrase(passphrase);
+    Ok(MessageBackupDecryptor(BackupDecryptor::new(
+        &secret,
+        serialized_header,
+    )?))
+}
+
+#[bridge_fn(node = false)]
+fn MessageBackupDecryptor_NewWithRecoveryKey(
+    recovery_key: &[u8; message_backup::RECOVERY_KEY_LEN],
+    serialized_header: &[u8],
+) -> Result<MessageBackupDecryptor> {
+    let secret = BackupSecret::RecoveryKey(*recovery_key);
+    Ok(MessageBackupDecryptor(BackupDecryptor::new(
+        &secret,
+        serialized_header,
+    )?))
+}
+
+/// Returns the serialized frame, or `None` for the end-of-archive record.
+#[bridge_fn_buffer(node = false)]
+fn MessageBackupDecryptor_DecryptFrame(
+    decryptor: &mut MessageBackupDecryptor,
+    record: &[u8],
+) -> Result<Option<Vec<u8>>> {
+    decryptor.0.decrypt_serialized_frame(record)
+}
+
+#[bridge_fn(node = false)]
+fn MessageBackupDecryptor_IsFinished(decryptor: &MessageBackupDecryptor) -> bool {
+    decryptor.0.is_finished()
+}
@@ -12,7 +12,7 @@ use std::hash::Hasher;
 use std::ops::{Deref, DerefMut, RangeInclusive};
 use std::slice;
 
-use crate::support::{Array, FixedLengthBincodeSerializable, Serialized};
+use crate::support::{FixedLengthBincodeSerializable, Serialized, Versioned};
 
 use super::*;
 
@@ -717,15 +717,24 @@ where
 
     fn convert_from(cx: &mut FunctionContext, foreign: Handle<Self::ArgType>) -> NeonResult<Self> {
         let bytes = foreign.as_slice(cx);
-        if bytes.len() != T::Array::LEN {
-            return cx.throw_error("failed to deserialize");
-        }
-        let result: T =
-            bincode::deserialize(bytes).or_else(|_| cx.throw_error("failed to deserialize"))?;
+        let result = T::deserialize_bytes(bytes).or_else(|e| cx.throw_error(e.to_string()))?;
         Ok(Serialized::from(result))
     }
 }
 
+impl<T> SimpleArgTypeInfo for Versioned<T>
+where
+    T: zkgroup::VersionedSerializable,
+{
+    type ArgType = JsBuffer;
+
+    fn convert_from(cx: &mut FunctionContext, foreign: Handle<Self::ArgType>) -> NeonResult<Self> {
+        let bytes = foreign.as_slice(cx);
+        let result = zkgroup::deserialize(bytes).or_else(|e| cx.throw_error(e.to_string()))?;
+        Ok(Versioned::from(result))
+    }
+}
+
 impl<'a, T> crate::node::ResultTypeInfo<'a> for Serialized<T>
 where
     T: FixedLengthBincodeSerializable + serde::Serialize,
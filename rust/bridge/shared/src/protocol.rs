@@ -35,6 +35,7 @@ bridge_handle!(ServerCertificate);
 bridge_handle!(SessionRecord, mut = true);
 bridge_handle!(SignalMessage, ffi = message);
 bridge_handle!(SignedPreKeyRecord);
+bridge_handle!(TrustRoot);
 bridge_handle!(UnidentifiedSenderMessageContent, clone = false);
 bridge_handle!(SealedSenderDecryptionResult, ffi = false, jni = false);
 
@@ -591,6 +592,26 @@ fn ServerCertificate_New(
     ServerCertificate::new(key_id, *server_key, trust_root, &mut rng)
 }
 
+#[bridge_fn]
+fn TrustRoot_New(key: &PublicKey) -> TrustRoot {
+    TrustRoot::new(*key)
+}
+
+#[bridge_fn]
+fn TrustRoot_NewWithValidity(
+    key: &PublicKey,
+    not_before: Timestamp,
+    not_after: Timestamp,
+) -> TrustRoot {
+    TrustRoot::with_validity(
+        *key,
+        Some(not_before.as_millis()),
+        Some(not_after.as_millis()),
+    )
+}
+
+bridge_get!(TrustRoot::key -> PublicKey);
+
 bridge_deserialize!(SenderCertificate::deserialize);
 bridge_get_buffer!(SenderCertificate::serialized -> &[u8]);
 bridge_get_buffer!(SenderCertificate::certificate -> &[u8]);
@@ -610,6 +631,16 @@ fn SenderCertificate_Validate(
     cert.validate(key, time.as_millis())
 }
 
+#[bridge_fn(node = false)]
+fn SenderCertificate_ValidateWithTrustRoots(
+    cert: &SenderCertificate,
+    trust_roots: &[&TrustRoot],
+    time: Timestamp,
+) -> Result<Option<PublicKey>> {
+    let trust_roots: Vec<TrustRoot> = trust_roots.iter().map(|root| **root).collect();
+    cert.validate_with_trust_roots(&trust_roots, time.as_millis())
+}
+
 #[bridge_fn]
 fn SenderCertificate_GetServerCertificate(cert: &SenderCertificate) -> Result<ServerCertificate> {
     Ok(cert.signer()?.clone())
@@ -844,6 +875,7 @@ bridge_get!(SessionRecord::has_sender_chain as HasSenderChain -> bool, ffi = fal
 bridge_get!(SealedSenderDecryptionResult::sender_uuid -> String, ffi = false, jni = false);
 bridge_get!(SealedSenderDecryptionResult::sender_e164 -> Option<String>, ffi = false, jni = false);
 bridge_get!(SealedSenderDecryptionResult::device_id -> u32, ffi = false, jni = false);
+bridge_get!(SealedSenderDecryptionResult::trust_root -> PublicKey, ffi = false, jni = false);
 bridge_get_buffer!(
     SealedSenderDecryptionResult::message as Message -> &[u8],
     ffi = false,
@@ -1094,7 +1126,7 @@ async fn SealedSessionCipher_DecryptToUsmc(
 #[bridge_fn(ffi = false, jni = false)]
 async fn SealedSender_DecryptMessage(
     message: &[u8],
-    trust_root: &PublicKey,
+    trust_roots: &[&TrustRoot],
     timestamp: Timestamp,
     local_e164: Option<String>,
     local_uuid: String,
@@ -1104,9 +1136,14 @@ async fn SealedSender_DecryptMessage(
     prekey_store: &mut dyn PreKeyStore,
     signed_prekey_store: &mut dyn SignedPreKeyStore,
 ) -> Result<SealedSenderDecryptionResult> {
-    sealed_sender_decrypt(
+    let trust_roots: Vec<TrustRoot> = trust_roots.iter().map(|root| **root).collect();
+    sealed_sender_decrypt_with_options(
         message,
-        trust_root,
+        SealedSenderDecryptionOptions {
+            trust_roots: &trust_roots,
+            certificate_cache: None,
+            accepted_versions: SealedSenderVersion::ALL,
+        },
         timestamp.as_millis(),
         local_e164,
         local_uuid,
@@ -15,9 +15,28 @@ impl<T, const LEN: usize> Array<T> for [T; LEN] {
 }
 
 /// Represents a type that can be serialized into an array.
-pub(crate) trait FixedLengthBincodeSerializable: 'static {
+pub(crate) trait FixedLengthBincodeSerializable: Sized + 'static {
     /// Should be an actual byte array type, like `[u8; 7]`.
     type Array: Array<u8> + for<'a> TryFrom<&'a [u8], Error = std::array::TryFromSliceError>;
+
+    /// The name of the type in [`zkgroup::DeserializationError`]s.
+    const NAME: &'static str;
+
+    /// Decodes a value, which must be exactly `Self::Array::LEN` bytes.
+    ///
+    /// Versioned zkgroup types should override this to check their version byte.
+    fn deserialize_bytes(bytes: &[u8]) -> Result<Self, zkgroup::DeserializationError>
+    where
+        Self: for<'a> serde::Deserialize<'a>,
+    {
+        let malformed = || zkgroup::DeserializationError::Malformed {
+            type_name: Self::NAME,
+        };
+        if bytes.len() != Self::Array::LEN {
+            return Err(malformed());
+        }
+        bincode::deserialize(bytes).map_err(|_| malformed())
+    }
 }
 
 /// A wrapper type that indicates that `T` should be serialized across the bridges.
@@ -42,3 +61,30 @@ impl<T> From<T> for Serialized<T> {
         Self(value)
     }
 }
+
+/// A wrapper type that indicates that `T` should be passed across the bridges in its versioned
+/// encoding.
+///
+/// Unlike [`Serialized`], this is passed as a buffer of any length rather than a fixed-length
+/// array, for types whose older versions have a different length.
+pub(crate) struct Versioned<T>(T);
+
+impl<T> Versioned<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Versioned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> From<T> for Versioned<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
@@ -23,16 +23,31 @@ type Result<T> = std::result::Result<T, ZkGroupError>;
 ///
 /// `fixed_length_serializable!(FooBar)` generates
 /// - `impl FixedLengthSerializable for FooBar`, using `[u8; FOO_BAR_LEN]` as the associated array
-///   type.
+///   type and checking the version byte through [`zkgroup::deserialize`].
 /// - `#[bridge_fn] fn FooBar_CheckValidContents`, which checks that the type can be deserialized.
+///
+/// `fixed_length_serializable!(FooBar, unversioned)` is the same, but for plain byte wrappers that
+/// have no version byte.
 macro_rules! fixed_length_serializable {
     ($typ:ident) => {
+        fixed_length_serializable!($typ, {
+            fn deserialize_bytes(bytes: &[u8]) -> std::result::Result<Self, DeserializationError> {
+                zkgroup::deserialize(bytes)
+            }
+        });
+    };
+    ($typ:ident, unversioned) => {
+        fixed_length_serializable!($typ, {});
+    };
+    ($typ:ident, { $($body:tt)* }) => {
         paste! {
             // Declare a marker type for TypeScript, the same as bridge_handle.
             // (This is harmless for the other bridges.)
             #[doc = "ts: interface " $typ " { readonly __type: unique symbol; }"]
             impl FixedLengthBincodeSerializable for $typ {
                 type Array = [u8; [<$typ:snake:upper _LEN>]];
+                const NAME: &'static str = stringify!($typ);
+                $($body)*
             }
             #[bridge_fn]
             fn [<$typ _CheckValidContents>](_obj: Serialized<$typ>) {
@@ -45,14 +60,14 @@ macro_rules! fixed_length_serializable {
 fixed_length_serializable!(AuthCredential);
 fixed_length_serializable!(AuthCredentialPresentation);
 fixed_length_serializable!(AuthCredentialResponse);
-fixed_length_serializable!(GroupMasterKey);
+fixed_length_serializable!(GroupMasterKey, unversioned);
 fixed_length_serializable!(GroupPublicParams);
 fixed_length_serializable!(GroupSecretParams);
 fixed_length_serializable!(PniCredential);
 fixed_length_serializable!(PniCredentialPresentation);
 fixed_length_serializable!(PniCredentialRequestContext);
 fixed_length_serializable!(PniCredentialResponse);
-fixed_length_serializable!(ProfileKey);
+fixed_length_serializable!(ProfileKey, unversioned);
 fixed_length_serializable!(ProfileKeyCiphertext);
 fixed_length_serializable!(ProfileKeyCommitment);
 fixed_length_serializable!(ProfileKeyCredential);
@@ -65,10 +80,29 @@ fixed_length_serializable!(ReceiptCredentialPresentation);
 fixed_length_serializable!(ReceiptCredentialRequest);
 fixed_length_serializable!(ReceiptCredentialRequestContext);
 fixed_length_serializable!(ReceiptCredentialResponse);
-fixed_length_serializable!(ServerPublicParams);
-fixed_length_serializable!(ServerSecretParams);
 fixed_length_serializable!(UuidCiphertext);
 
+/// Exposes a ZKGroup type whose older versions have a different length via [`Versioned`].
+///
+/// `variable_length_serializable!(FooBar)` generates `#[bridge_fn] fn FooBar_CheckValidContents`,
+/// which checks that the type can be deserialized.
+macro_rules! variable_length_serializable {
+    ($typ:ident) => {
+        paste! {
+            // Declare a marker type for TypeScript, the same as bridge_handle.
+            // (This is harmless for the other bridges.)
+            #[doc = "ts: interface " $typ " { readonly __type: unique symbol; }"]
+            #[bridge_fn]
+            fn [<$typ _CheckValidContents>](_obj: Versioned<$typ>) {
+                // Do nothing; if we successfully deserialized the value, it's valid.
+            }
+        }
+    };
+}
+
+variable_length_serializable!(ServerPublicParams);
+variable_length_serializable!(ServerSecretParams);
+
 #[bridge_fn]
 fn ProfileKey_GetCommitment(
     profile_key: Serialized<ProfileKey>,
@@ -173,24 +207,20 @@ fn GroupSecretParams_DecryptBlobWithPadding(
     params.decrypt_blob_with_padding(ciphertext)
 }
 
-#[bridge_fn]
-fn ServerSecretParams_GenerateDeterministic(
-    randomness: &[u8; RANDOMNESS_LEN],
-) -> Result<Serialized<ServerSecretParams>> {
-    Ok(ServerSecretParams::generate(*randomness).into())
+#[bridge_fn_buffer]
+fn ServerSecretParams_GenerateDeterministic(randomness: &[u8; RANDOMNESS_LEN]) -> Vec<u8> {
+    zkgroup::serialize(&ServerSecretParams::generate(*randomness))
 }
 
 // FIXME: Could be bridge_get!
-#[bridge_fn]
-fn ServerSecretParams_GetPublicParams(
-    params: Serialized<ServerSecretParams>,
-) -> Serialized<ServerPublicParams> {
-    params.get_public_params().into()
+#[bridge_fn_buffer]
+fn ServerSecretParams_GetPublicParams(params: Versioned<ServerSecretParams>) -> Vec<u8> {
+    zkgroup::serialize(&params.get_public_params())
 }
 
 #[bridge_fn]
 fn ServerSecretParams_SignDeterministic(
-    params: Serialized<ServerSecretParams>,
+    params: Versioned<ServerSecretParams>,
     randomness: &[u8; RANDOMNESS_LEN],
     message: &[u8],
 ) -> Result<[u8; SIGNATURE_LEN]> {
@@ -199,7 +229,7 @@ fn ServerSecretParams_SignDeterministic(
 
 #[bridge_fn]
 fn ServerPublicParams_ReceiveAuthCredential(
-    params: Serialized<ServerPublicParams>,
+    params: Versioned<ServerPublicParams>,
     uuid: Uuid,
     redemption_time: u32,
     response: Serialized<AuthCredentialResponse>,
@@ -211,7 +241,7 @@ fn ServerPublicParams_ReceiveAuthCredential(
 
 #[bridge_fn]
 fn ServerPublicParams_CreateAuthCredentialPresentationDeterministic(
-    server_public_params: Serialized<ServerPublicParams>,
+    server_public_params: Versioned<ServerPublicParams>,
     randomness: &[u8; RANDOMNESS_LEN],
     group_secret_params: Serialized<GroupSecretParams>,
     auth_credential: Serialized<AuthCredential>,
@@ -227,7 +257,7 @@ fn ServerPublicParams_CreateAuthCredentialPresentationDeterministic(
 
 #[bridge_fn]
 fn ServerPublicParams_CreateProfileKeyCredentialRequestContextDeterministic(
-    server_public_params: Serialized<ServerPublicParams>,
+    server_public_params: Versioned<ServerPublicParams>,
     randomness: &[u8; RANDOMNESS_LEN],
     uuid: Uuid,
     profile_key: Serialized<ProfileKey>,
@@ -243,7 +273,7 @@ fn ServerPublicParams_CreateProfileKeyCredentialRequestContextDeterministic(
 
 #[bridge_fn]
 fn ServerPublicParams_CreatePniCredentialRequestContextDeterministic(
-    server_public_params: Serialized<ServerPublicParams>,
+    server_public_params: Versioned<ServerPublicParams>,
     randomness: &[u8; RANDOMNESS_LEN],
     aci: Uuid,
     pni: Uuid,
@@ -261,7 +291,7 @@ fn ServerPublicParams_CreatePniCredentialRequestContextDeterministic(
 
 #[bridge_fn]
 fn ServerPublicParams_ReceiveProfileKeyCredential(
-    server_public_params: Serialized<ServerPublicParams>,
+    server_public_params: Versioned<ServerPublicParams>,
     request_context: Serialized<ProfileKeyCredentialRequestContext>,
     response: Serialized<ProfileKeyCredentialResponse>,
 ) -> Result<Serialized<ProfileKeyCredential>> {
@@ -272,7 +302,7 @@ fn ServerPublicParams_ReceiveProfileKeyCredential(
 
 #[bridge_fn]
 fn ServerPublicParams_ReceivePniCredential(
-    server_public_params: Serialized<ServerPublicParams>,
+    server_public_params: Versioned<ServerPublicParams>,
     request_context: Serialized<PniCredentialRequestContext>,
     response: Serialized<PniCredentialResponse>,
 ) -> Result<Serialized<PniCredential>> {
@@ -283,7 +313,7 @@ fn ServerPublicParams_ReceivePniCredential(
 
 #[bridge_fn]
 fn ServerPublicParams_CreateProfileKeyCredentialPresentationDeterministic(
-    server_public_params: Serialized<ServerPublicParams>,
+    server_public_params: Versioned<ServerPublicParams>,
     randomness: &[u8; RANDOMNESS_LEN],
     group_secret_params: Serialized<GroupSecretParams>,
     profile_key_credential: Serialized<ProfileKeyCredential>,
@@ -299,7 +329,7 @@ fn ServerPublicParams_CreateProfileKeyCredentialPresentationDeterministic(
 
 #[bridge_fn]
 fn ServerPublicParams_CreatePniCredentialPresentationDeterministic(
-    server_public_params: Serialized<ServerPublicParams>,
+    server_public_params: Versioned<ServerPublicParams>,
     randomness: &[u8; RANDOMNESS_LEN],
     group_secret_params: Serialized<GroupSecretParams>,
     profile_key_credential: Serialized<PniCredential>,
@@ -315,7 +345,7 @@ fn ServerPublicParams_CreatePniCredentialPresentationDeterministic(
 
 #[bridge_fn]
 fn ServerPublicParams_CreateReceiptCredentialRequestContextDeterministic(
-    server_public_params: Serialized<ServerPublicParams>,
+    server_public_params: Versioned<ServerPublicParams>,
     randomness: &[u8; RANDOMNESS_LEN],
     receipt_serial: &[u8; RECEIPT_SERIAL_LEN],
 ) -> Result<Serialized<ReceiptCredentialRequestContext>> {
@@ -326,7 +356,7 @@ fn ServerPublicParams_CreateReceiptCredentialRequestContextDeterministic(
 
 #[bridge_fn]
 fn ServerPublicParams_ReceiveReceiptCredential(
-    server_public_params: Serialized<ServerPublicParams>,
+    server_public_params: Versioned<ServerPublicParams>,
     request_context: Serialized<ReceiptCredentialRequestContext>,
     response: Serialized<ReceiptCredentialResponse>,
 ) -> Result<Serialized<ReceiptCredential>> {
@@ -337,7 +367,7 @@ fn ServerPublicParams_ReceiveReceiptCredential(
 
 #[bridge_fn]
 fn ServerPublicParams_CreateReceiptCredentialPresentationDeterministic(
-    server_public_params: Serialized<ServerPublicParams>,
+    server_public_params: Versioned<ServerPublicParams>,
     randomness: &[u8; RANDOMNESS_LEN],
     receipt_credential: Serialized<ReceiptCredential>,
 ) -> Result<Serialized<ReceiptCredentialPresentation>> {
@@ -348,7 +378,7 @@ fn ServerPublicParams_CreateReceiptCredentialPresentationDeterministic(
 
 #[bridge_fn]
 fn ServerSecretParams_IssueAuthCredentialDeterministic(
-    server_secret_params: Serialized<ServerSecretParams>,
+    server_secret_params: Versioned<ServerSecretParams>,
     randomness: &[u8; RANDOMNESS_LEN],
     uuid: Uuid,
     redemption_time: u32,
@@ -360,7 +390,7 @@ fn ServerSecretParams_IssueAuthCredentialDeterministic(
 
 #[bridge_fn_void]
 fn ServerSecretParams_VerifyAuthCredentialPresentation(
-    server_secret_params: Serialized<ServerSecretParams>,
+    server_secret_params: Versioned<ServerSecretParams>,
     group_public_params: Serialized<GroupPublicParams>,
     presentation: Serialized<AuthCredentialPresentation>,
 ) -> Result<()> {
@@ -370,7 +400,7 @@ fn ServerSecretParams_VerifyAuthCredentialPresentation(
 
 #[bridge_fn]
 fn ServerSecretParams_IssueProfileKeyCredentialDeterministic(
-    server_secret_params: Serialized<ServerSecretParams>,
+    server_secret_params: Versioned<ServerSecretParams>,
     randomness: &[u8; RANDOMNESS_LEN],
     request: Serialized<ProfileKeyCredentialRequest>,
     uuid: Uuid,
@@ -388,7 +418,7 @@ fn ServerSecretParams_IssueProfileKeyCredentialDeterministic(
 
 #[bridge_fn]
 fn ServerSecretParams_IssuePniCredentialDeterministic(
-    server_secret_params: Serialized<ServerSecretParams>,
+    server_secret_params: Versioned<ServerSecretParams>,
     randomness: &[u8; RANDOMNESS_LEN],
     request: Serialized<ProfileKeyCredentialRequest>,
     aci: Uuid,
@@ -408,7 +438,7 @@ fn ServerSecretParams_IssuePniCredentialDeterministic(
 
 #[bridge_fn_void]
 fn ServerSecretParams_VerifyProfileKeyCredentialPresentation(
-    server_secret_params: Serialized<ServerSecretParams>,
+    server_secret_params: Versioned<ServerSecretParams>,
     group_public_params: Serialized<GroupPublicParams>,
     presentation: Serialized<ProfileKeyCredentialPresentation>,
 ) -> Result<()> {
@@ -418,7 +448,7 @@ fn ServerSecretParams_VerifyProfileKeyCredentialPresentation(
 
 #[bridge_fn_void]
 fn ServerSecretParams_VerifyPniCredentialPresentation(
-    server_secret_params: Serialized<ServerSecretParams>,
+    server_secret_params: Versioned<ServerSecretParams>,
     group_public_params: Serialized<GroupPublicParams>,
     presentation: Serialized<PniCredentialPresentation>,
 ) -> Result<()> {
@@ -428,7 +458,7 @@ fn ServerSecretParams_VerifyPniCredentialPresentation(
 
 #[bridge_fn]
 fn ServerSecretParams_IssueReceiptCredentialDeterministic(
-    server_secret_params: Serialized<ServerSecretParams>,
+    server_secret_params: Versioned<ServerSecretParams>,
     randomness: &[u8; RANDOMNESS_LEN],
     request: Serialized<ReceiptCredentialRequest>,
     receipt_expiration_time: u64,
@@ -446,7 +476,7 @@ fn ServerSecretParams_IssueReceiptCredentialDeterministic(
 
 #[bridge_fn_void]
 fn ServerSecretParams_VerifyReceiptCredentialPresentation(
-    server_secret_params: Serialized<ServerSecretParams>,
+    server_secret_params: Versioned<ServerSecretParams>,
     presentation: Serialized<ReceiptCredentialPresentation>,
 ) -> Result<()> {
     server_secret_params.verify_receipt_credential_presentation(&presentation)
@@ -462,7 +492,7 @@ fn GroupPublicParams_GetGroupIdentifier(
 
 #[bridge_fn_void]
 fn ServerPublicParams_VerifySignature(
-    server_public_params: Serialized<ServerPublicParams>,
+    server_public_params: Versioned<ServerPublicParams>,
     message: &[u8],
     notary_signature: &[u8; SIGNATURE_LEN],
 ) -> Result<()> {
@@ -11,9 +11,17 @@ mod hash;
 mod aes_ctr;
 mod aes_gcm;
 
+mod pin;
+mod storage;
+
 pub use {
     aes_ctr::Aes256Ctr32,
     aes_gcm::{Aes256GcmDecryption, Aes256GcmEncryption},
     error::{Error, Result},
     hash::{CryptographicHash, CryptographicMac},
+    pin::{
+        normalize_pin, MasterKey, PinHash, MASTER_KEY_LEN, PIN_HASH_SALT_LEN,
+        WRAPPED_MASTER_KEY_LEN,
+    },
+    storage::{StorageItemKey, StorageKey, StorageManifestKey, STORAGE_KEY_LEN},
 };
@@ -0,0 +1,194 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use crate::{Error, Result};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+use unicode_normalization::UnicodeNormalization;
+
+pub const MASTER_KEY_LEN: usize = 32;
+pub const PIN_HASH_SALT_LEN: usize = 32;
+pub const WRAPPED_MASTER_KEY_LEN: usize = WRAP_IV_LEN + MASTER_KEY_LEN;
+
+const WRAP_IV_LEN: usize = 16;
+
+const ARGON2_MEMORY_KIB: u32 = 16 * 1024;
+const ARGON2_ITERATIONS: u32 = 32;
+const ARGON2_PARALLELISM: u32 = 1;
+
+/// The first code point of each run of ten Unicode decimal digits (general category Nd).
+const DECIMAL_DIGIT_ZEROS: &[u32] = &[
+    0x0030, 0x0660, 0x06F0, 0x07C0, 0x0966, 0x09E6, 0x0A66, 0x0AE6, 0x0B66, 0x0BE6, 0x0C66, 0x0CE6,
+    0x0D66, 0x0DE6, 0x0E50, 0x0ED0, 0x0F20, 0x1040, 0x1090, 0x17E0, 0x1810, 0x1946, 0x19D0, 0x1A80,
+    0x1A90, 0x1B50, 0x1BB0, 0x1C40, 0x1C50, 0xA620, 0xA8D0, 0xA900, 0xA9D0, 0xA9F0, 0xAA50, 0xABF0,
+    0xFF10, 0x104A0, 0x10D30, 0x11066, 0x110F0, 0x11136, 0x111D0, 0x112F0, 0x11450, 0x114D0,
+    0x11650, 0x116C0, 0x11730, 0x118E0, 0x11950, 0x11C50, 0x11D50, 0x11DA0, 0x16A60, 0x16B50,
+    0x1D7CE, 0x1D7D8, 0x1D7E2, 0x1D7EC, 0x1D7F6, 0x1E140, 0x1E2F0, 0x1E950, 0x1FBF0,
+];
+
+fn decimal_digit_value(c: char) -> Option<u32> {
+    let c = c as u32;
+    DECIMAL_DIGIT_ZEROS
+        .iter()
+        .find(|&&zero| (zero..zero + 10).contains(&c))
+        .map(|zero| c - zero)
+}
+
+/// Puts a PIN into the canonical form that is hashed.
+///
+/// Surrounding whitespace is removed. A PIN made up entirely of decimal digits, in any script, is
+/// converted to ASCII digits. Finally the result is put into Unicode normalization form NFKD, so
+/// that the same PIN typed on different keyboards hashes the same way.
+pub fn normalize_pin(pin: &str) -> String {
+    let pin = pin.trim();
+    let digits: Option<String> = pin
+        .chars()
+        .map(|c| decimal_digit_value(c).and_then(|value| std::char::from_digit(value, 10)))
+        .collect();
+    match digits {
+        Some(digits) if !digits.is_empty() => digits,
+        _ => pin.nfkd().collect(),
+    }
+}
+
+fn hmac_sha256(key: &[u8], input: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(input);
+    mac.finalize().into_bytes().into()
+}
+
+/// A PIN stretched with Argon2id, split into a key for wrapping the master key and a key for
+/// proving knowledge of the PIN to the recovery service.
+#[derive(Clone)]
+pub struct PinHash {
+    encryption_key: [u8; 32],
+    access_key: [u8; 32],
+}
+
+impl PinHash {
+    /// Hashes a PIN that has already been passed through [`normalize_pin`].
+    ///
+    /// `salt` should be unique to the account, such as the recovery service's backup ID.
+    pub fn create(normalized_pin: &[u8], salt: &[u8; PIN_HASH_SALT_LEN]) -> Result<Self> {
+        let params = Params::new(
+            ARGON2_MEMORY_KIB,
+            ARGON2_ITERATIONS,
+            ARGON2_PARALLELISM,
+            Some(64),
+        )
+        .expect("valid Argon2 parameters");
+        let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+
+        let mut output = [0u8; 64];
+        argon2
+            .hash_password_into(normalized_pin, salt, &mut output)
+            .map_err(|_| Error::InvalidInputSize)?;
+
+        let mut encryption_key = [0u8; 32];
+        let mut access_key = [0u8; 32];
+        encryption_key.copy_from_slice(&output[..32]);
+        access_key.copy_from_slice(&output[32..]);
+        Ok(Self {
+            encryption_key,
+            access_key,
+        })
+    }
+
+    /// The key used to wrap the master key; never leaves the device.
+    pub fn encryption_key(&self) -> &[u8; 32] {
+        &self.encryption_key
+    }
+
+    /// The key sent to the recovery service in place of the PIN.
+    pub fn access_key(&self) -> &[u8; 32] {
+        &self.access_key
+    }
+}
+
+/// The account's master key, from which registration lock and storage service keys are derived.
+#[derive(Clone)]
+pub struct MasterKey([u8; MASTER_KEY_LEN]);
+
+impl MasterKey {
+    pub fn new(bytes: [u8; MASTER_KEY_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; MASTER_KEY_LEN] {
+        &self.0
+    }
+
+    fn derive(&self, label: &[u8]) -> [u8; 32] {
+        hmac_sha256(&self.0, label)
+    }
+
+    /// The registration lock token, as the lowercase hex string the server expects.
+    pub fn registration_lock(&self) -> String {
+        self.derive(b"Registration Lock")
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// The root key for storage service encryption.
+    pub fn storage_service_key(&self) -> [u8; 32] {
+        self.derive(b"Storage Service Encryption")
+    }
+
+    /// Encrypts the master key under a PIN's encryption key for upload to the recovery service.
+    ///
+    /// This is a deterministic HMAC-SIV construction: the IV is a MAC of the master key, and
+    /// unwrapping checks it. The result is [`WRAPPED_MASTER_KEY_LEN`] bytes.
+    pub fn wrap(&self, pin_hash: &PinHash) -> [u8; WRAPPED_MASTER_KEY_LEN] {
+        let (auth_key, cipher_key) = wrapping_keys(pin_hash);
+
+        let mut iv = [0u8; WRAP_IV_LEN];
+        iv.copy_from_slice(&hmac_sha256(&auth_key, &self.0)[..WRAP_IV_LEN]);
+
+        let mut wrapped = [0u8; WRAPPED_MASTER_KEY_LEN];
+        wrapped[..WRAP_IV_LEN].copy_from_slice(&iv);
+        let keystream = hmac_sha256(&cipher_key, &iv);
+        for ((out, key), pad) in wrapped[WRAP_IV_LEN..]
+            .iter_mut()
+            .zip(&self.0)
+            .zip(&keystream)
+        {
+            *out = key ^ pad;
+        }
+        wrapped
+    }
+
+    /// Reverses [`MasterKey::wrap`], failing with [`Error::InvalidTag`] if the PIN is wrong or
+    /// the data has been modified.
+    pub fn unwrap(pin_hash: &PinHash, wrapped: &[u8]) -> Result<Self> {
+        if wrapped.len() != WRAPPED_MASTER_KEY_LEN {
+            return Err(Error::InvalidInputSize);
+        }
+        let (auth_key, cipher_key) = wrapping_keys(pin_hash);
+        let (iv, ciphertext) = wrapped.split_at(WRAP_IV_LEN);
+
+        let keystream = hmac_sha256(&cipher_key, iv);
+        let mut master_key = [0u8; MASTER_KEY_LEN];
+        for ((out, c), pad) in master_key.iter_mut().zip(ciphertext).zip(&keystream) {
+            *out = c ^ pad;
+        }
+
+        let expected_iv = hmac_sha256(&auth_key, &master_key);
+        if !bool::from(expected_iv[..WRAP_IV_LEN].ct_eq(iv)) {
+            return Err(Error::InvalidTag);
+        }
+        Ok(Self(master_key))
+    }
+}
+
+fn wrapping_keys(pin_hash: &PinHash) -> ([u8; 32], [u8; 32]) {
+    (
+        hmac_sha256(pin_hash.encryption_key(), b"auth"),
+        hmac_sha256(pin_hash.encryption_key(), b"enc"),
+    )
+}
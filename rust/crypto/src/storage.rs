@@ -0,0 +1,130 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use crate::aes_gcm::{NONCE_SIZE, TAG_SIZE};
+use crate::{Aes256GcmDecryption, Aes256GcmEncryption, Error, MasterKey, Result};
+
+use hmac::{Hmac, Mac, NewMac};
+use rand::{CryptoRng, Rng};
+use sha2::Sha256;
+
+pub const STORAGE_KEY_LEN: usize = 32;
+
+fn hmac_sha256(key: &[u8], input: &[u8]) -> [u8; STORAGE_KEY_LEN] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(input);
+    mac.finalize().into_bytes().into()
+}
+
+/// Encrypts with AES-256-GCM under a fresh random nonce, producing `nonce || ciphertext || tag`.
+fn encrypt<R: Rng + CryptoRng>(
+    key: &[u8; STORAGE_KEY_LEN],
+    plaintext: &[u8],
+    rng: &mut R,
+) -> Result<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_SIZE];
+    rng.fill_bytes(&mut nonce);
+
+    let mut buf = Vec::with_capacity(NONCE_SIZE + plaintext.len() + TAG_SIZE);
+    buf.extend_from_slice(&nonce);
+    buf.extend_from_slice(plaintext);
+
+    let mut gcm = Aes256GcmEncryption::new(key, &nonce, &[])?;
+    gcm.encrypt(&mut buf[NONCE_SIZE..])?;
+    buf.extend_from_slice(&gcm.compute_tag()?);
+    Ok(buf)
+}
+
+fn decrypt(key: &[u8; STORAGE_KEY_LEN], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    if ciphertext.len() < NONCE_SIZE + TAG_SIZE {
+        return Err(Error::InvalidInputSize);
+    }
+    let (nonce, rest) = ciphertext.split_at(NONCE_SIZE);
+    let (ciphertext, tag) = rest.split_at(rest.len() - TAG_SIZE);
+
+    let mut buf = ciphertext.to_vec();
+    let mut gcm = Aes256GcmDecryption::new(key, nonce, &[])?;
+    gcm.decrypt(&mut buf)?;
+    gcm.verify_tag(tag)?;
+    Ok(buf)
+}
+
+/// The root key for storage service encryption, derived from the account's master key.
+#[derive(Clone)]
+pub struct StorageKey([u8; STORAGE_KEY_LEN]);
+
+impl StorageKey {
+    pub fn new(bytes: [u8; STORAGE_KEY_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn from_master_key(master_key: &MasterKey) -> Self {
+        Self(master_key.storage_service_key())
+    }
+
+    pub fn as_bytes(&self) -> &[u8; STORAGE_KEY_LEN] {
+        &self.0
+    }
+
+    /// The key for the manifest with the given version.
+    pub fn derive_manifest_key(&self, version: u64) -> StorageManifestKey {
+        StorageManifestKey(hmac_sha256(
+            &self.0,
+            format!("Manifest_{}", version).as_bytes(),
+        ))
+    }
+
+    /// The key for the record with the given raw ID, as listed in the manifest.
+    pub fn derive_item_key(&self, raw_id: &[u8]) -> StorageItemKey {
+        StorageItemKey(hmac_sha256(
+            &self.0,
+            format!("Item_{}", base64::encode(raw_id)).as_bytes(),
+        ))
+    }
+}
+
+/// Encrypts one version of the storage service manifest.
+#[derive(Clone)]
+pub struct StorageManifestKey([u8; STORAGE_KEY_LEN]);
+
+impl StorageManifestKey {
+    pub fn new(bytes: [u8; STORAGE_KEY_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; STORAGE_KEY_LEN] {
+        &self.0
+    }
+
+    pub fn encrypt<R: Rng + CryptoRng>(&self, plaintext: &[u8], rng: &mut R) -> Result<Vec<u8>> {
+        encrypt(&self.0, plaintext, rng)
+    }
+
+    pub fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        decrypt(&self.0, ciphertext)
+    }
+}
+
+/// Encrypts a single storage service record.
+#[derive(Clone)]
+pub struct StorageItemKey([u8; STORAGE_KEY_LEN]);
+
+impl StorageItemKey {
+    pub fn new(bytes: [u8; STORAGE_KEY_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; STORAGE_KEY_LEN] {
+        &self.0
+    }
+
+    pub fn encrypt<R: Rng + CryptoRng>(&self, plaintext: &[u8], rng: &mut R) -> Result<Vec<u8>> {
+        encrypt(&self.0, plaintext, rng)
+    }
+
+    pub fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        decrypt(&self.0, ciphertext)
+    }
+}
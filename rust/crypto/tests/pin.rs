@@ -0,0 +1,96 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use signal_crypto::{normalize_pin, MasterKey, PinHash};
+use std::convert::TryInto;
+
+// These vectors are also checked by the Java, Swift, and Node tests; keep them in sync.
+
+fn salt() -> [u8; 32] {
+    hex::decode("202122232425262728292A2B2C2D2E2F303132333435363738393A3B3C3D3E3F")
+        .expect("valid hex")
+        .try_into()
+        .expect("correct length")
+}
+
+#[test]
+fn pin_hash_known_answer() -> Result<(), signal_crypto::Error> {
+    let pin_hash = PinHash::create(normalize_pin("password").as_bytes(), &salt())?;
+
+    assert_eq!(
+        hex::encode(pin_hash.encryption_key()),
+        "175f63e11c5974891a27413776e079402ac441faaee5e7fe8b3b757888472638"
+    );
+    assert_eq!(
+        hex::encode(pin_hash.access_key()),
+        "02a16780b3d79d48106e9196c598b36f889c5801e7c8717e3377099bf1dd319a"
+    );
+
+    let master_key = MasterKey::new(salt());
+    let wrapped = master_key.wrap(&pin_hash);
+    assert_eq!(
+        hex::encode(wrapped),
+        "5eced03868a6c20d3edf31ecaf0180cb3e0ad7f660851d192c7e9d1c686bd9d7e1668c0773209a85e8ba72a521bb9d51"
+    );
+    assert_eq!(
+        MasterKey::unwrap(&pin_hash, &wrapped)?.as_bytes(),
+        master_key.as_bytes()
+    );
+
+    let mut modified = wrapped;
+    modified[20] ^= 1;
+    assert_eq!(
+        MasterKey::unwrap(&pin_hash, &modified).err(),
+        Some(signal_crypto::Error::InvalidTag)
+    );
+    assert_eq!(
+        MasterKey::unwrap(&pin_hash, &wrapped[1..]).err(),
+        Some(signal_crypto::Error::InvalidInputSize)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn master_key_derivations() {
+    let master_key = MasterKey::new(salt());
+    assert_eq!(
+        master_key.registration_lock(),
+        "2bf7988224ba35d3554966c65e8dc8c54974b034bdd44cabfd3f15fdb185e3c6"
+    );
+    assert_eq!(
+        hex::encode(master_key.storage_service_key()),
+        "47d3b98d12d2f826f27f2d8c63ba0e0cad6019675659623a6c3dac297d1b0889"
+    );
+}
+
+#[test]
+fn wrong_pin_does_not_unwrap() -> Result<(), signal_crypto::Error> {
+    let master_key = MasterKey::new([7; 32]);
+    let right = PinHash::create(normalize_pin("1234").as_bytes(), &salt())?;
+    let wrong = PinHash::create(normalize_pin("1235").as_bytes(), &salt())?;
+    assert_eq!(
+        MasterKey::unwrap(&wrong, &master_key.wrap(&right)).err(),
+        Some(signal_crypto::Error::InvalidTag)
+    );
+    Ok(())
+}
+
+#[test]
+fn pin_normalization() {
+    assert_eq!(normalize_pin(" 1234\n"), "1234");
+    // Arabic-Indic, Devanagari, and fullwidth digits.
+    assert_eq!(normalize_pin("\u{661}\u{662}\u{663}\u{664}"), "1234");
+    assert_eq!(normalize_pin("\u{967}\u{968}\u{969}\u{96A}"), "1234");
+    assert_eq!(normalize_pin("\u{FF11}\u{FF12}\u{FF13}\u{FF14}"), "1234");
+    // Mixed scripts are still all digits.
+    assert_eq!(normalize_pin("1\u{662}3\u{FF14}"), "1234");
+
+    // Alphanumeric PINs are kept, but decomposed.
+    assert_eq!(normalize_pin("pass word"), "pass word");
+    assert_eq!(normalize_pin("caf\u{E9}"), "cafe\u{301}");
+    assert_eq!(normalize_pin("\u{FF21}1"), "A1");
+    assert_eq!(normalize_pin("   "), "");
+}
@@ -0,0 +1,55 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use rand::rngs::OsRng;
+use signal_crypto::{Error, MasterKey, StorageKey};
+
+#[test]
+fn storage_key_derivations() {
+    let master_key = MasterKey::new([0x20; 32]);
+    let storage_key = StorageKey::from_master_key(&master_key);
+    assert_eq!(
+        hex::encode(storage_key.as_bytes()),
+        "a36a154f9e2d48f0b81143054dd189f4e96f3c553b3306f5319265ba8f3431fb"
+    );
+    assert_eq!(
+        hex::encode(storage_key.derive_manifest_key(5).as_bytes()),
+        "6011520d7b33373083608afdc54b3b7bd2043d6c50671e720cd8ade17ddae815"
+    );
+    let raw_id: Vec<u8> = (1..=16).collect();
+    assert_eq!(
+        hex::encode(storage_key.derive_item_key(&raw_id).as_bytes()),
+        "141e9dd3b4919348fadcce517e31aede9293f4b6bbc217b15fa8dc7304a7c5e3"
+    );
+}
+
+#[test]
+fn storage_encrypt_decrypt() -> Result<(), Error> {
+    let storage_key = StorageKey::new([0x42; 32]);
+    let manifest_key = storage_key.derive_manifest_key(1);
+    let item_key = storage_key.derive_item_key(b"record");
+
+    let plaintext = b"contact record";
+    let ciphertext = item_key.encrypt(plaintext, &mut OsRng)?;
+    assert_eq!(ciphertext.len(), 12 + plaintext.len() + 16);
+    assert_eq!(item_key.decrypt(&ciphertext)?, plaintext);
+
+    // Fresh nonces each time.
+    assert_ne!(item_key.encrypt(plaintext, &mut OsRng)?, ciphertext);
+
+    // Wrong key, modified data, or truncated input all fail.
+    assert_eq!(manifest_key.decrypt(&ciphertext), Err(Error::InvalidTag));
+    let mut modified = ciphertext.clone();
+    modified[15] ^= 1;
+    assert_eq!(item_key.decrypt(&modified), Err(Error::InvalidTag));
+    assert_eq!(
+        item_key.decrypt(&ciphertext[..27]),
+        Err(Error::InvalidInputSize)
+    );
+
+    let empty = manifest_key.encrypt(&[], &mut OsRng)?;
+    assert_eq!(manifest_key.decrypt(&empty)?, Vec::<u8>::new());
+    Ok(())
+}
@@ -8,18 +8,26 @@
 #![deny(unsafe_code)]
 #![warn(missing_docs)]
 
-use chrono::{Datelike, Duration, Utc};
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
 use picky::key::PrivateKey;
-use picky::x509::name::{DirectoryName, NameAttr};
-use picky::x509::{certificate::CertificateBuilder, date::UTCDate};
+use picky::x509::name::{DirectoryName, GeneralName, GeneralNames, NameAttr};
+use picky::x509::{certificate::CertificateBuilder, date::UTCDate, Cert};
 use picky::{hash::HashAlgorithm, signature::SignatureAlgorithm};
+use rand::{rngs::OsRng, RngCore};
+use sha2::{Digest, Sha256};
 use std::fmt;
 
 /// Error types for device transfer.
 #[derive(Copy, Clone, Debug)]
 pub enum Error {
-    /// Failure to decode some provided RSA private key.
+    /// Failure to decode some provided private key.
     KeyDecodingFailed,
+    /// The requested or provided key uses an algorithm that is not supported.
+    UnsupportedAlgorithm(&'static str),
+    /// Some provided [CertificateOptions] could not be encoded in a certificate.
+    InvalidCertificateOptions(&'static str),
+    /// Failure to decode some provided certificate.
+    CertificateDecodingFailed,
     /// Internal error in device transfer.
     InternalError(&'static str),
 }
@@ -27,12 +35,108 @@ pub enum Error {
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Error::KeyDecodingFailed => write!(f, "Decoding provided RSA private key failed"),
+            Error::KeyDecodingFailed => write!(f, "Decoding provided private key failed"),
+            Error::UnsupportedAlgorithm(s) => write!(f, "Unsupported key algorithm ({})", s),
+            Error::InvalidCertificateOptions(s) => {
+                write!(f, "Invalid certificate options ({})", s)
+            }
+            Error::CertificateDecodingFailed => write!(f, "Decoding provided certificate failed"),
             Error::InternalError(s) => write!(f, "Internal error in device transfer ({})", s),
         }
     }
 }
 
+/// The kind of key used for a device transfer certificate.
+///
+/// RSA is kept for compatibility with older peers. The elliptic-curve options are much cheaper to
+/// generate on low-end devices, but the version of picky we depend on can only generate and sign
+/// with RSA keys, so for now they are recognized and rejected with [Error::UnsupportedAlgorithm].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KeyType {
+    /// RSA, signed with PKCS#1 v1.5 and SHA-256.
+    Rsa,
+    /// Ed25519.
+    Ed25519,
+    /// ECDSA over NIST P-256, signed with SHA-256.
+    EcdsaP256,
+}
+
+impl KeyType {
+    /// The key size used by [create_private_key] for [KeyType::Rsa].
+    pub const DEFAULT_RSA_KEY_BITS: usize = 4096;
+
+    /// Determines the key type of a PKCS8-encoded private key.
+    ///
+    /// Only the algorithm identifier is inspected; the key itself is validated when it is used.
+    pub fn of_pkcs8_key(pkcs8: &[u8]) -> Result<Self, Error> {
+        const RSA_ENCRYPTION: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+        const EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+        const PRIME256V1: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+        const ED25519: &[u8] = &[0x2b, 0x65, 0x70];
+
+        // PrivateKeyInfo ::= SEQUENCE { version INTEGER, privateKeyAlgorithm AlgorithmIdentifier, ... }
+        let (private_key_info, _) = der_element(pkcs8, DER_SEQUENCE)?;
+        let (_version, rest) = der_element(private_key_info, DER_INTEGER)?;
+        let (algorithm_identifier, _) = der_element(rest, DER_SEQUENCE)?;
+        let (algorithm, parameters) = der_element(algorithm_identifier, DER_OBJECT_IDENTIFIER)?;
+
+        match algorithm {
+            RSA_ENCRYPTION => Ok(KeyType::Rsa),
+            ED25519 => Ok(KeyType::Ed25519),
+            EC_PUBLIC_KEY => match der_element(parameters, DER_OBJECT_IDENTIFIER)? {
+                (PRIME256V1, _) => Ok(KeyType::EcdsaP256),
+                _ => Err(Error::UnsupportedAlgorithm(
+                    "only the P-256 curve is supported",
+                )),
+            },
+            _ => Err(Error::UnsupportedAlgorithm(
+                "unrecognized private key algorithm",
+            )),
+        }
+    }
+
+    fn signature_algorithm(self) -> Result<SignatureAlgorithm, Error> {
+        match self {
+            KeyType::Rsa => Ok(SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_256)),
+            KeyType::Ed25519 => Err(Error::UnsupportedAlgorithm(
+                "Ed25519 keys are not yet supported",
+            )),
+            KeyType::EcdsaP256 => Err(Error::UnsupportedAlgorithm(
+                "P-256 keys are not yet supported",
+            )),
+        }
+    }
+}
+
+const DER_INTEGER: u8 = 0x02;
+const DER_OBJECT_IDENTIFIER: u8 = 0x06;
+const DER_SEQUENCE: u8 = 0x30;
+
+/// Splits off a single DER element with the given tag, returning its contents and the remainder.
+fn der_element(input: &[u8], tag: u8) -> Result<(&[u8], &[u8]), Error> {
+    match input {
+        [t, first_len, rest @ ..] if *t == tag => {
+            let (len, rest) = if *first_len < 0x80 {
+                (*first_len as usize, rest)
+            } else {
+                let len_bytes = (*first_len & 0x7f) as usize;
+                if len_bytes == 0 || len_bytes > 4 || rest.len() < len_bytes {
+                    return Err(Error::KeyDecodingFailed);
+                }
+                let len = rest[..len_bytes]
+                    .iter()
+                    .fold(0usize, |acc, b| (acc << 8) | *b as usize);
+                (len, &rest[len_bytes..])
+            };
+            if rest.len() < len {
+                return Err(Error::KeyDecodingFailed);
+            }
+            Ok(rest.split_at(len))
+        }
+        _ => Err(Error::KeyDecodingFailed),
+    }
+}
+
 /// Generate a private key of size `bits` and export to PKCS8 format.
 pub fn create_rsa_private_key(bits: usize) -> Result<Vec<u8>, Error> {
     let key = PrivateKey::generate_rsa(bits)
@@ -41,41 +145,231 @@ pub fn create_rsa_private_key(bits: usize) -> Result<Vec<u8>, Error> {
         .map_err(|_| Error::InternalError("Exporting to PKCS8 failed"))
 }
 
+/// Generate a private key of the given type and export to PKCS8 format.
+///
+/// RSA keys are [KeyType::DEFAULT_RSA_KEY_BITS] bits; use [create_rsa_private_key] to pick a
+/// different size. Elliptic-curve key types currently fail with [Error::UnsupportedAlgorithm].
+pub fn create_private_key(key_type: KeyType) -> Result<Vec<u8>, Error> {
+    // Don't generate a key that create_self_signed_cert would refuse to use.
+    key_type.signature_algorithm()?;
+    create_rsa_private_key(KeyType::DEFAULT_RSA_KEY_BITS)
+}
+
+/// Subject, validity and extension settings for [create_self_signed_cert_with_options].
+#[derive(Clone, Debug)]
+pub struct CertificateOptions {
+    /// The subject and issuer common name.
+    pub common_name: String,
+    /// The subject and issuer organization, if any.
+    pub organization: Option<String>,
+    /// The subject and issuer organizational unit, if any.
+    pub organizational_unit: Option<String>,
+    /// How long the certificate is valid for, starting from the time it is created.
+    pub valid_for: Duration,
+    /// How far into the past to move the start of the validity period.
+    ///
+    /// This lets a peer whose clock is slightly behind ours accept the certificate.
+    pub not_before_skew: Duration,
+    /// The certificate serial number as big-endian bytes, or `None` to generate a random one.
+    pub serial_number: Option<Vec<u8>>,
+    /// The key usage extension, or `None` to omit it.
+    pub key_usage: Option<KeyUsage>,
+    /// DNS names to include in a subjectAltName extension; omitted if empty.
+    pub subject_alt_names: Vec<String>,
+}
+
+impl CertificateOptions {
+    /// Options for a device transfer certificate named `name`, valid for `days_to_expire` days.
+    ///
+    /// This uses the subject of [create_self_signed_cert], a random serial number, an hour of
+    /// allowance for clock skew, and a digital-signature key usage.
+    pub fn new(name: &str, days_to_expire: u32) -> Self {
+        Self {
+            common_name: name.to_string(),
+            organization: Some("Signal Foundation".to_string()),
+            organizational_unit: Some("Device Transfer".to_string()),
+            valid_for: Duration::days(days_to_expire.into()),
+            not_before_skew: Duration::hours(1),
+            serial_number: None,
+            key_usage: Some(KeyUsage::DIGITAL_SIGNATURE),
+            subject_alt_names: vec![],
+        }
+    }
+}
+
+/// The subset of X.509 key usages relevant to TLS certificates.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct KeyUsage {
+    /// The key may be used to verify signatures, including TLS handshake signatures.
+    pub digital_signature: bool,
+    /// The key may be used to encrypt keys (RSA key exchange).
+    pub key_encipherment: bool,
+    /// The key may be used for key agreement.
+    pub key_agreement: bool,
+}
+
+impl KeyUsage {
+    /// Only `digitalSignature`, which is sufficient for TLS 1.3 and (EC)DHE cipher suites.
+    pub const DIGITAL_SIGNATURE: Self = Self {
+        digital_signature: true,
+        key_encipherment: false,
+        key_agreement: false,
+    };
+
+    fn to_extension(self) -> picky::x509::extension::KeyUsage {
+        let mut key_usage = picky::x509::extension::KeyUsage::new(7);
+        key_usage.set_digital_signature(self.digital_signature);
+        key_usage.set_key_encipherment(self.key_encipherment);
+        key_usage.set_key_agreement(self.key_agreement);
+        key_usage
+    }
+}
+
 /// Generate a self-signed certificate of name `name`, expiring in `days_to_expire`.
 ///
-/// `rsa_key_pkcs8` should be the output of [create_rsa_private_key].
+/// `key_pkcs8` should be the output of [create_private_key] or [create_rsa_private_key]; the
+/// signature algorithm is chosen to match the key. Equivalent to
+/// [create_self_signed_cert_with_options] with [CertificateOptions::new].
 pub fn create_self_signed_cert(
-    rsa_key_pkcs8: &[u8],
+    key_pkcs8: &[u8],
     name: &str,
     days_to_expire: u32,
 ) -> Result<Vec<u8>, Error> {
-    let rsa_key = PrivateKey::from_pkcs8(rsa_key_pkcs8).map_err(|_| Error::KeyDecodingFailed)?;
+    create_self_signed_cert_with_options(key_pkcs8, &CertificateOptions::new(name, days_to_expire))
+}
 
-    let mut dn = DirectoryName::new_common_name(name);
-    dn.add_attr(NameAttr::OrganizationName, "Signal Foundation");
-    dn.add_attr(NameAttr::OrganizationalUnitName, "Device Transfer");
+/// Generate a self-signed certificate described by `options`.
+///
+/// `key_pkcs8` should be the output of [create_private_key] or [create_rsa_private_key]; the
+/// signature algorithm is chosen to match the key.
+pub fn create_self_signed_cert_with_options(
+    key_pkcs8: &[u8],
+    options: &CertificateOptions,
+) -> Result<Vec<u8>, Error> {
+    let signature_algorithm = KeyType::of_pkcs8_key(key_pkcs8)?.signature_algorithm()?;
+    let key = PrivateKey::from_pkcs8(key_pkcs8).map_err(|_| Error::KeyDecodingFailed)?;
 
-    let now = Utc::now();
-    let expires = now + Duration::days(days_to_expire.into());
+    let mut dn = DirectoryName::new_common_name(options.common_name.as_str());
+    if let Some(organization) = &options.organization {
+        dn.add_attr(NameAttr::OrganizationName, organization.as_str());
+    }
+    if let Some(organizational_unit) = &options.organizational_unit {
+        dn.add_attr(
+            NameAttr::OrganizationalUnitName,
+            organizational_unit.as_str(),
+        );
+    }
 
-    let started_at = UTCDate::ymd(now.year() as u16, now.month() as u8, now.day() as u8)
+    let now = Utc::now();
+    let started_at = to_utc_date(now - options.not_before_skew)
         .ok_or(Error::InternalError("Cannot map current time to UTCDate"))?;
-    let ends_at = UTCDate::ymd(
-        expires.year() as u16,
-        expires.month() as u8,
-        expires.day() as u8,
-    )
-    .ok_or(Error::InternalError(
+    let ends_at = to_utc_date(now + options.valid_for).ok_or(Error::InternalError(
         "Cannot map expiration time to UTCDate",
     ))?;
 
-    let cert = CertificateBuilder::new()
+    let serial_number = match &options.serial_number {
+        Some(serial_number) if serial_number.is_empty() || serial_number.len() > 20 => {
+            return Err(Error::InvalidCertificateOptions(
+                "serial number must be between 1 and 20 bytes",
+            ))
+        }
+        Some(serial_number) => serial_number.clone(),
+        None => random_serial_number(),
+    };
+
+    let builder = CertificateBuilder::new();
+    builder
         .validity(started_at, ends_at)
-        .self_signed(dn, &rsa_key)
-        .signature_hash_type(SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_256))
+        .self_signed(dn, &key)
+        .signature_hash_type(signature_algorithm)
+        .serial_number(serial_number);
+
+    if let Some(key_usage) = options.key_usage {
+        builder.key_usage(key_usage.to_extension());
+    }
+
+    if !options.subject_alt_names.is_empty() {
+        let names = options
+            .subject_alt_names
+            .iter()
+            .map(|name| {
+                GeneralName::new_dns_name(name.as_str()).map_err(|_| {
+                    Error::InvalidCertificateOptions("subjectAltName is not a valid DNS name")
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        builder.subject_alt_name(GeneralNames::from(names));
+    }
+
+    let cert = builder
         .build()
         .map_err(|_| Error::InternalError("Creating certificate failed"))?;
 
     cert.to_der()
         .map_err(|_| Error::InternalError("Converting cert to DER failed"))
 }
+
+/// Summary of a DER-encoded certificate, as returned by [inspect_certificate].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CertificateInfo {
+    /// SHA-256 of the DER encoding of the certificate.
+    pub fingerprint: [u8; 32],
+    /// The start of the validity period.
+    pub not_before: DateTime<Utc>,
+    /// The end of the validity period.
+    pub not_after: DateTime<Utc>,
+}
+
+impl CertificateInfo {
+    /// Whether `time` falls within the certificate's validity period.
+    pub fn is_valid_at(&self, time: DateTime<Utc>) -> bool {
+        self.not_before <= time && time <= self.not_after
+    }
+}
+
+/// Parse a DER-encoded certificate (such as one received from a peer) and summarize it.
+///
+/// The certificate's signature is not checked; compare the fingerprint against one obtained
+/// out-of-band instead.
+pub fn inspect_certificate(cert_der: &[u8]) -> Result<CertificateInfo, Error> {
+    let cert = Cert::from_der(cert_der).map_err(|_| Error::CertificateDecodingFailed)?;
+
+    let mut fingerprint = [0u8; 32];
+    fingerprint.copy_from_slice(&Sha256::digest(cert_der));
+
+    Ok(CertificateInfo {
+        fingerprint,
+        not_before: from_utc_date(&cert.valid_not_before())?,
+        not_after: from_utc_date(&cert.valid_not_after())?,
+    })
+}
+
+fn to_utc_date(time: DateTime<Utc>) -> Option<UTCDate> {
+    UTCDate::new(
+        time.year() as u16,
+        time.month() as u8,
+        time.day() as u8,
+        time.hour() as u8,
+        time.minute() as u8,
+        time.second() as u8,
+    )
+}
+
+fn from_utc_date(date: &UTCDate) -> Result<DateTime<Utc>, Error> {
+    Utc.ymd_opt(date.year().into(), date.month().into(), date.day().into())
+        .and_hms_opt(
+            date.hour().into(),
+            date.minute().into(),
+            date.second().into(),
+        )
+        .single()
+        .ok_or(Error::CertificateDecodingFailed)
+}
+
+fn random_serial_number() -> Vec<u8> {
+    let mut serial_number = vec![0u8; 16];
+    OsRng.fill_bytes(&mut serial_number);
+    // Serial numbers are positive DER INTEGERs with no leading zero byte.
+    serial_number[0] = (serial_number[0] & 0x7f) | 0x40;
+    serial_number
+}
@@ -39,3 +39,113 @@ fn test_generate_and_parse() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[test]
+fn test_elliptic_curve_keys_unsupported() {
+    // The Ed25519 private key from RFC 8410, section 10.3.
+    let ed25519_key = hex::decode(
+        "302e020100300506032b657004220420d4ee72dbf913584ad5b6d8f1f769f8ad3afe7c28cbf1d4fbe097a88f44755842",
+    )
+    .unwrap();
+
+    assert_eq!(
+        KeyType::of_pkcs8_key(&ed25519_key).unwrap(),
+        KeyType::Ed25519
+    );
+    assert!(matches!(
+        create_self_signed_cert(&ed25519_key, "test", 10),
+        Err(Error::UnsupportedAlgorithm(_))
+    ));
+
+    for key_type in [KeyType::Ed25519, KeyType::EcdsaP256] {
+        assert!(matches!(
+            create_private_key(key_type),
+            Err(Error::UnsupportedAlgorithm(_))
+        ));
+    }
+}
+
+#[test]
+fn test_unsupported_key_algorithm() {
+    // A PKCS8 X25519 key: key agreement only, so it can't sign a certificate.
+    let x25519_key = hex::decode(
+        "302e020100300506032b656e04220420a8abababababababababababababababababababababababababababababab6e",
+    )
+    .unwrap();
+
+    assert!(matches!(
+        KeyType::of_pkcs8_key(&x25519_key),
+        Err(Error::UnsupportedAlgorithm(_))
+    ));
+    assert!(matches!(
+        create_self_signed_cert(&x25519_key, "test", 10),
+        Err(Error::UnsupportedAlgorithm(_))
+    ));
+    assert!(matches!(
+        KeyType::of_pkcs8_key(&[0x30, 0x03, 0x02, 0x01]),
+        Err(Error::KeyDecodingFailed)
+    ));
+}
+
+#[test]
+fn test_certificate_options_and_inspect() -> Result<(), Error> {
+    let key = create_rsa_private_key(2048)?;
+
+    let mut options = CertificateOptions::new("test", 10);
+    options.organization = None;
+    options.serial_number = Some(vec![0x01, 0x02, 0x03]);
+    options.subject_alt_names = vec!["transfer.signal.invalid".to_string()];
+    let cert = create_self_signed_cert_with_options(&key, &options)?;
+
+    let openssl_cert = X509::from_der(&cert).expect("OpenSSL can parse our certificate");
+    assert_eq!(
+        openssl_cert.serial_number().to_bn().unwrap().to_vec(),
+        vec![0x01, 0x02, 0x03]
+    );
+    let alt_names = openssl_cert
+        .subject_alt_names()
+        .expect("has subjectAltName");
+    assert_eq!(alt_names.len(), 1);
+    assert_eq!(
+        alt_names.get(0).unwrap().dnsname(),
+        Some("transfer.signal.invalid")
+    );
+
+    let info = inspect_certificate(&cert)?;
+    assert_eq!(
+        info.fingerprint[..],
+        openssl_cert
+            .digest(openssl::hash::MessageDigest::sha256())
+            .unwrap()[..]
+    );
+    let now = chrono::Utc::now();
+    assert!(info.is_valid_at(now));
+    assert!(info.is_valid_at(now - chrono::Duration::minutes(30)));
+    assert!(!info.is_valid_at(now - chrono::Duration::hours(2)));
+    assert!(!info.is_valid_at(now + chrono::Duration::days(11)));
+
+    options.serial_number = Some(vec![]);
+    assert!(matches!(
+        create_self_signed_cert_with_options(&key, &options),
+        Err(Error::InvalidCertificateOptions(_))
+    ));
+
+    assert!(matches!(
+        inspect_certificate(&cert[..cert.len() - 1]),
+        Err(Error::CertificateDecodingFailed)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_random_serial_numbers() -> Result<(), Error> {
+    let key = create_rsa_private_key(2048)?;
+    let first = X509::from_der(&create_self_signed_cert(&key, "test", 10)?).unwrap();
+    let second = X509::from_der(&create_self_signed_cert(&key, "test", 10)?).unwrap();
+    assert_ne!(
+        first.serial_number().to_bn().unwrap(),
+        second.serial_number().to_bn().unwrap()
+    );
+    Ok(())
+}
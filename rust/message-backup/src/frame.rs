@@ -0,0 +1,231 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use crate::key::{ArchiveKey, BackupSecret, FrameKeys, PBKDF2_ITERATIONS, SALT_LEN};
+use crate::proto::backup::{Frame, Header};
+use crate::{Error, Result};
+
+use prost::Message;
+use rand::{CryptoRng, Rng};
+use signal_crypto::{Aes256Ctr32, CryptographicMac};
+use subtle::ConstantTimeEq;
+
+/// Identifies a file as a Signal backup archive.
+pub const MAGIC: &[u8; 8] = b"SIGNLBAK";
+/// The current (and only) archive format version.
+pub const VERSION: u32 = 1;
+/// The largest record the reader will accept, to bound memory use on malformed input.
+pub const MAX_RECORD_LEN: usize = 16 * 1024 * 1024;
+/// The size of the length prefix in front of the header and each record.
+pub const RECORD_LENGTH_PREFIX_LEN: usize = 4;
+
+const MAC_LEN: usize = 32;
+const FLAGS_LEN: usize = 1;
+
+const FLAG_FINAL: u8 = 0x01;
+
+fn length_prefixed(body: Vec<u8>) -> Vec<u8> {
+    let mut record = Vec::with_capacity(RECORD_LENGTH_PREFIX_LEN + body.len());
+    record.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    record.extend_from_slice(&body);
+    record
+}
+
+fn frame_cipher(keys: &FrameKeys) -> Aes256Ctr32 {
+    // Every frame has its own key, so a fixed nonce is safe.
+    Aes256Ctr32::from_key(&keys.cipher_key, &[0u8; Aes256Ctr32::NONCE_SIZE], 0)
+        .expect("valid key and nonce size")
+}
+
+fn frame_mac(keys: &FrameKeys, flags: u8, ciphertext: &[u8]) -> Vec<u8> {
+    let mut mac = CryptographicMac::new("HmacSha256", &keys.mac_key).expect("known algorithm");
+    mac.update(&[flags]).expect("infallible");
+    mac.update(ciphertext).expect("infallible");
+    mac.finalize().expect("infallible")
+}
+
+/// Produces an archive one record at a time, without doing any I/O itself.
+///
+/// An archive consists of [`BackupEncryptor::archive_prefix`], followed by the output of
+/// [`BackupEncryptor::encrypt_frame`] for each frame, followed by the output of
+/// [`BackupEncryptor::finish`]. See [`crate::BackupWriter`] for a wrapper around [`std::io::Write`].
+pub struct BackupEncryptor {
+    key: ArchiveKey,
+    serialized_header: Vec<u8>,
+    next_frame_index: u64,
+    finished: bool,
+}
+
+impl BackupEncryptor {
+    pub fn new<R: Rng + CryptoRng>(secret: &BackupSecret, rng: &mut R) -> Result<Self> {
+        // The salt also keeps frame keys from repeating between archives made with the same
+        // recovery key, since the whole header goes into the key derivation.
+        let mut salt = vec![0u8; SALT_LEN];
+        rng.fill_bytes(&mut salt);
+        let iterations = match secret {
+            BackupSecret::Passphrase(_) => PBKDF2_ITERATIONS,
+            BackupSecret::RecoveryKey(_) => 0,
+        };
+        let header = Header {
+            version: VERSION,
+            key_derivation: secret.key_derivation().into(),
+            salt,
+            iterations,
+        };
+        let serialized_header = header.encode_to_vec();
+        let key = ArchiveKey::derive(secret, &header, &serialized_header)?;
+
+        Ok(Self {
+            key,
+            serialized_header,
+            next_frame_index: 0,
+            finished: false,
+        })
+    }
+
+    /// The bytes that start the archive: the magic number and the length-prefixed header.
+    pub fn archive_prefix(&self) -> Vec<u8> {
+        let mut prefix = MAGIC.to_vec();
+        prefix.extend(length_prefixed(self.serialized_header.clone()));
+        prefix
+    }
+
+    /// Encrypts `frame` into a length-prefixed record.
+    pub fn encrypt_frame(&mut self, frame: &Frame) -> Result<Vec<u8>> {
+        self.encrypt_record(0, frame.encode_to_vec())
+    }
+
+    /// Like [`encrypt_frame`](Self::encrypt_frame), but for an already-serialized [`Frame`].
+    ///
+    /// The frame is checked to be well-formed before encrypting it.
+    pub fn encrypt_serialized_frame(&mut self, serialized_frame: &[u8]) -> Result<Vec<u8>> {
+        Frame::decode(serialized_frame)
+            .map_err(|_| Error::InvalidRecord("not a valid protobuf"))?;
+        self.encrypt_record(0, serialized_frame.to_vec())
+    }
+
+    /// Produces the length-prefixed record that marks the end of the archive.
+    ///
+    /// Readers reject archives without this record, which detects truncation.
+    pub fn finish(&mut self) -> Result<Vec<u8>> {
+        let record = self.encrypt_record(FLAG_FINAL, vec![])?;
+        self.finished = true;
+        Ok(record)
+    }
+
+    fn encrypt_record(&mut self, flags: u8, mut contents: Vec<u8>) -> Result<Vec<u8>> {
+        if self.finished {
+            return Err(Error::InvalidState("archive already finished"));
+        }
+        if FLAGS_LEN + contents.len() + MAC_LEN > MAX_RECORD_LEN {
+            return Err(Error::RecordTooLarge(contents.len()));
+        }
+
+        let keys = self.key.frame_keys(self.next_frame_index);
+        self.next_frame_index += 1;
+
+        frame_cipher(&keys).process(&mut contents)?;
+        let mac = frame_mac(&keys, flags, &contents);
+
+        let mut body = Vec::with_capacity(FLAGS_LEN + contents.len() + MAC_LEN);
+        body.push(flags);
+        body.extend_from_slice(&contents);
+        body.extend_from_slice(&mac);
+        Ok(length_prefixed(body))
+    }
+}
+
+/// Reads an archive one record at a time, without doing any I/O itself.
+///
+/// The caller is responsible for checking [`MAGIC`] and splitting the input at the
+/// [`RECORD_LENGTH_PREFIX_LEN`]-byte big-endian length prefixes. See [`crate::BackupReader`] for a
+/// wrapper around [`std::io::Read`] that does this.
+pub struct BackupDecryptor {
+    key: ArchiveKey,
+    next_frame_index: u64,
+    finished: bool,
+}
+
+impl BackupDecryptor {
+    /// Prepares to decrypt an archive with the given header (without its length prefix).
+    pub fn new(secret: &BackupSecret, serialized_header: &[u8]) -> Result<Self> {
+        let header = Header::decode(serialized_header)
+            .map_err(|_| Error::InvalidHeader("not a valid protobuf"))?;
+        if header.version != VERSION {
+            return Err(Error::UnsupportedVersion(header.version));
+        }
+        let key = ArchiveKey::derive(secret, &header, serialized_header)?;
+        Ok(Self {
+            key,
+            next_frame_index: 0,
+            finished: false,
+        })
+    }
+
+    /// Decrypts and authenticates a record (without its length prefix).
+    ///
+    /// Returns `None` for the record that marks the end of the archive.
+    pub fn decrypt_frame(&mut self, record: &[u8]) -> Result<Option<Frame>> {
+        match self.decrypt_record(record)? {
+            None => Ok(None),
+            Some(plaintext) => Frame::decode(plaintext.as_slice())
+                .map(Some)
+                .map_err(|_| Error::InvalidRecord("not a valid protobuf")),
+        }
+    }
+
+    /// Like [`decrypt_frame`](Self::decrypt_frame), but returns the serialized [`Frame`].
+    ///
+    /// The frame is checked to be well-formed before returning it.
+    pub fn decrypt_serialized_frame(&mut self, record: &[u8]) -> Result<Option<Vec<u8>>> {
+        let plaintext = self.decrypt_record(record)?;
+        if let Some(plaintext) = &plaintext {
+            Frame::decode(plaintext.as_slice())
+                .map_err(|_| Error::InvalidRecord("not a valid protobuf"))?;
+        }
+        Ok(plaintext)
+    }
+
+    fn decrypt_record(&mut self, record: &[u8]) -> Result<Option<Vec<u8>>> {
+        if self.finished {
+            return Err(Error::TrailingData);
+        }
+        if record.len() < FLAGS_LEN + MAC_LEN {
+            return Err(Error::InvalidRecord("too short"));
+        }
+        if record.len() > MAX_RECORD_LEN {
+            return Err(Error::RecordTooLarge(record.len()));
+        }
+
+        let frame_index = self.next_frame_index;
+        let keys = self.key.frame_keys(frame_index);
+        let (flags, rest) = record.split_at(FLAGS_LEN);
+        let (ciphertext, their_mac) = rest.split_at(rest.len() - MAC_LEN);
+
+        let our_mac = frame_mac(&keys, flags[0], ciphertext);
+        if !bool::from(our_mac.ct_eq(their_mac)) {
+            return Err(Error::BadMac { frame_index });
+        }
+        self.next_frame_index += 1;
+
+        match flags[0] {
+            0 => {}
+            FLAG_FINAL if ciphertext.is_empty() => {
+                self.finished = true;
+                return Ok(None);
+            }
+            _ => return Err(Error::InvalidRecord("unknown flags")),
+        }
+
+        let mut plaintext = ciphertext.to_vec();
+        frame_cipher(&keys).process(&mut plaintext)?;
+        Ok(Some(plaintext))
+    }
+
+    /// Whether the record marking the end of the archive has been seen.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
@@ -0,0 +1,101 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use crate::proto::backup::header::KeyDerivation;
+use crate::proto::backup::Header;
+use crate::{Error, Result};
+
+use hkdf::Hkdf;
+use hmac::Hmac;
+use sha2::Sha256;
+
+pub const RECOVERY_KEY_LEN: usize = 32;
+pub(crate) const SALT_LEN: usize = 16;
+
+/// The number of PBKDF2 iterations used for newly-created passphrase-protected archives.
+pub const PBKDF2_ITERATIONS: u32 = 250_000;
+/// Archives claiming more iterations than this are rejected rather than tying up the device.
+const MAX_PBKDF2_ITERATIONS: u32 = 10_000_000;
+
+const FRAME_KEY_LABEL: &[u8] = b"Signal_Backup_Frame_Keys_20211201";
+
+/// The user-held secret that an archive is encrypted with.
+#[derive(Clone)]
+pub enum BackupSecret {
+    /// A user-chosen passphrase, stretched with PBKDF2-HMAC-SHA256.
+    Passphrase(String),
+    /// A high-entropy recovery key, used directly as key material.
+    RecoveryKey([u8; RECOVERY_KEY_LEN]),
+}
+
+impl BackupSecret {
+    pub(crate) fn key_derivation(&self) -> KeyDerivation {
+        match self {
+            BackupSecret::Passphrase(_) => KeyDerivation::PassphrasePbkdf2Sha256,
+            BackupSecret::RecoveryKey(_) => KeyDerivation::RecoveryKey,
+        }
+    }
+}
+
+pub(crate) struct FrameKeys {
+    pub(crate) cipher_key: [u8; 32],
+    pub(crate) mac_key: [u8; 32],
+}
+
+/// The root of the per-frame key hierarchy for a single archive.
+pub(crate) struct ArchiveKey(Hkdf<Sha256>);
+
+impl ArchiveKey {
+    /// Derives the archive key from `secret` and the archive's header.
+    ///
+    /// `serialized_header` is mixed in as the HKDF salt, so any modification to the header will
+    /// cause every frame to fail authentication.
+    pub(crate) fn derive(
+        secret: &BackupSecret,
+        header: &Header,
+        serialized_header: &[u8],
+    ) -> Result<Self> {
+        if header.key_derivation != secret.key_derivation() as i32 {
+            return Err(Error::WrongSecretKind);
+        }
+
+        let ikm = match secret {
+            BackupSecret::Passphrase(passphrase) => {
+                if header.iterations == 0 || header.iterations > MAX_PBKDF2_ITERATIONS {
+                    return Err(Error::InvalidHeader("unreasonable PBKDF2 iteration count"));
+                }
+                if header.salt.len() != SALT_LEN {
+                    return Err(Error::InvalidHeader("wrong salt length"));
+                }
+                let mut stretched = [0u8; 32];
+                pbkdf2::pbkdf2::<Hmac<Sha256>>(
+                    passphrase.as_bytes(),
+                    &header.salt,
+                    header.iterations,
+                    &mut stretched,
+                );
+                stretched
+            }
+            BackupSecret::RecoveryKey(key) => *key,
+        };
+
+        Ok(Self(Hkdf::<Sha256>::new(Some(serialized_header), &ikm)))
+    }
+
+    pub(crate) fn frame_keys(&self, frame_index: u64) -> FrameKeys {
+        let mut okm = [0u8; 64];
+        self.0
+            .expand_multi_info(&[FRAME_KEY_LABEL, &frame_index.to_be_bytes()], &mut okm)
+            .expect("valid output length");
+
+        let mut keys = FrameKeys {
+            cipher_key: [0u8; 32],
+            mac_key: [0u8; 32],
+        };
+        keys.cipher_key.copy_from_slice(&okm[..32]);
+        keys.mac_key.copy_from_slice(&okm[32..]);
+        keys
+    }
+}
@@ -0,0 +1,73 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! An encrypted, authenticated archive format for backing up message history.
+//!
+//! An archive is a sequence of protobuf [`Frame`]s, each encrypted with AES-256-CTR and
+//! authenticated with HMAC-SHA256 under its own keys. Frame keys are derived with HKDF from the
+//! user's [`BackupSecret`] and the archive header, and depend on the frame's position in the
+//! archive, so frames cannot be reordered, dropped, or moved between archives. A final
+//! end-of-archive record detects truncation.
+//!
+//! ```text
+//! archive = MAGIC || u32be(len) || Header || record* || final record
+//! record  = u32be(len) || flags || AES-256-CTR(frame) || HMAC-SHA256(flags || ciphertext)
+//! ```
+
+#![warn(clippy::unwrap_used)]
+#![deny(unsafe_code)]
+
+mod frame;
+mod key;
+mod proto;
+mod stream;
+
+use displaydoc::Display;
+use thiserror::Error;
+
+pub use {
+    frame::{
+        BackupDecryptor, BackupEncryptor, MAGIC, MAX_RECORD_LEN, RECORD_LENGTH_PREFIX_LEN, VERSION,
+    },
+    key::{BackupSecret, PBKDF2_ITERATIONS, RECOVERY_KEY_LEN},
+    proto::backup::{frame::Item, Attachment, AttachmentData, Frame, KeyEntry, Message},
+    stream::{BackupReader, BackupWriter},
+};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Display, Error)]
+pub enum Error {
+    /// I/O error: {0}
+    Io(#[from] std::io::Error),
+    /// input is not a backup archive
+    NotABackup,
+    /// unsupported backup version {0}
+    UnsupportedVersion(u32),
+    /// invalid backup header: {0}
+    InvalidHeader(&'static str),
+    /// archive was created with a different kind of secret
+    WrongSecretKind,
+    /// record too large ({0} bytes)
+    RecordTooLarge(usize),
+    /// authentication failed for frame {frame_index} (wrong secret or corrupted archive)
+    BadMac { frame_index: u64 },
+    /// invalid record: {0}
+    InvalidRecord(&'static str),
+    /// archive ended before the end-of-archive marker
+    Truncated,
+    /// unexpected data after the end-of-archive marker
+    TrailingData,
+    /// invalid state: {0}
+    InvalidState(&'static str),
+    /// cryptographic operation failed: {0}
+    Crypto(signal_crypto::Error),
+}
+
+impl From<signal_crypto::Error> for Error {
+    fn from(e: signal_crypto::Error) -> Self {
+        Error::Crypto(e)
+    }
+}
@@ -0,0 +1,8 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+pub mod backup {
+    include!(concat!(env!("OUT_DIR"), "/signal.proto.backup.rs"));
+}
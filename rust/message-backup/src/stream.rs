@@ -0,0 +1,129 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use crate::frame::{
+    BackupDecryptor, BackupEncryptor, MAGIC, MAX_RECORD_LEN, RECORD_LENGTH_PREFIX_LEN,
+};
+use crate::key::BackupSecret;
+use crate::proto::backup::Frame;
+use crate::{Error, Result};
+
+use rand::{CryptoRng, Rng};
+use std::io::{self, Read, Write};
+
+/// Writes an encrypted archive to an underlying [`Write`].
+///
+/// The archive is not complete until [`BackupWriter::finish`] is called.
+pub struct BackupWriter<W: Write> {
+    output: W,
+    encryptor: BackupEncryptor,
+}
+
+impl<W: Write> BackupWriter<W> {
+    /// Starts a new archive, writing its header immediately.
+    pub fn new<R: Rng + CryptoRng>(
+        mut output: W,
+        secret: &BackupSecret,
+        rng: &mut R,
+    ) -> Result<Self> {
+        let encryptor = BackupEncryptor::new(secret, rng)?;
+        output.write_all(&encryptor.archive_prefix())?;
+        Ok(Self { output, encryptor })
+    }
+
+    pub fn write_frame(&mut self, frame: &Frame) -> Result<()> {
+        let record = self.encryptor.encrypt_frame(frame)?;
+        self.output.write_all(&record)?;
+        Ok(())
+    }
+
+    /// Writes the end-of-archive marker and returns the underlying output.
+    pub fn finish(mut self) -> Result<W> {
+        let record = self.encryptor.finish()?;
+        self.output.write_all(&record)?;
+        self.output.flush()?;
+        Ok(self.output)
+    }
+}
+
+/// Reads and authenticates an encrypted archive from an underlying [`Read`].
+///
+/// Frames are returned as they are read. An archive that ends without its end-of-archive marker
+/// produces [`Error::Truncated`], so callers should not treat the restored data as complete until
+/// [`BackupReader::read_frame`] has returned `Ok(None)`.
+pub struct BackupReader<R: Read> {
+    input: R,
+    decryptor: BackupDecryptor,
+}
+
+impl<R: Read> BackupReader<R> {
+    /// Reads and validates the archive header.
+    pub fn new(mut input: R, secret: &BackupSecret) -> Result<Self> {
+        let mut magic = [0u8; MAGIC.len()];
+        read_exact_or_truncated(&mut input, &mut magic)?;
+        if &magic != MAGIC {
+            return Err(Error::NotABackup);
+        }
+
+        let serialized_header = read_record(&mut input)?.ok_or(Error::Truncated)?;
+        let decryptor = BackupDecryptor::new(secret, &serialized_header)?;
+        Ok(Self { input, decryptor })
+    }
+
+    /// Reads the next frame, or returns `None` once the end of the archive has been verified.
+    pub fn read_frame(&mut self) -> Result<Option<Frame>> {
+        if self.decryptor.is_finished() {
+            return Ok(None);
+        }
+
+        let record = read_record(&mut self.input)?.ok_or(Error::Truncated)?;
+        let frame = self.decryptor.decrypt_frame(&record)?;
+        if frame.is_none() {
+            let mut trailing = [0u8; 1];
+            if self.input.read(&mut trailing)? != 0 {
+                return Err(Error::TrailingData);
+            }
+        }
+        Ok(frame)
+    }
+}
+
+impl<R: Read> Iterator for BackupReader<R> {
+    type Item = Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_frame().transpose()
+    }
+}
+
+fn read_exact_or_truncated(input: &mut impl Read, buf: &mut [u8]) -> Result<()> {
+    input.read_exact(buf).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => Error::Truncated,
+        _ => Error::Io(e),
+    })
+}
+
+/// Reads a length-prefixed record, returning `None` on a clean end of input.
+fn read_record(input: &mut impl Read) -> Result<Option<Vec<u8>>> {
+    let mut len_bytes = [0u8; RECORD_LENGTH_PREFIX_LEN];
+    let first_read = loop {
+        match input.read(&mut len_bytes) {
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            result => break result?,
+        }
+    };
+    if first_read == 0 {
+        return Ok(None);
+    }
+    read_exact_or_truncated(input, &mut len_bytes[first_read..])?;
+
+    let len = u32::from_be_bytes(len_bytes) as usize;
+    if len > MAX_RECORD_LEN {
+        return Err(Error::RecordTooLarge(len));
+    }
+    let mut record = vec![0u8; len];
+    read_exact_or_truncated(input, &mut record)?;
+    Ok(Some(record))
+}
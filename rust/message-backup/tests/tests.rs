@@ -0,0 +1,201 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use message_backup::*;
+use rand::rngs::OsRng;
+use std::convert::TryInto;
+
+fn sample_frames() -> Vec<Frame> {
+    vec![
+        Frame {
+            item: Some(Item::Key(KeyEntry {
+                name: "identity".to_string(),
+                value: vec![0x05; 33],
+            })),
+        },
+        Frame {
+            item: Some(Item::Message(Message {
+                conversation_id: "+14155550100".to_string(),
+                timestamp: 1_638_316_800_000,
+                content: b"hello".to_vec(),
+            })),
+        },
+        Frame {
+            item: Some(Item::Attachment(Attachment {
+                attachment_id: 7,
+                content_type: "image/jpeg".to_string(),
+                length: 3,
+            })),
+        },
+        Frame {
+            item: Some(Item::AttachmentData(AttachmentData {
+                attachment_id: 7,
+                data: vec![0xff, 0xd8, 0xff],
+            })),
+        },
+    ]
+}
+
+fn write_archive(secret: &BackupSecret, frames: &[Frame]) -> Result<Vec<u8>> {
+    let mut writer = BackupWriter::new(vec![], secret, &mut OsRng)?;
+    for frame in frames {
+        writer.write_frame(frame)?;
+    }
+    writer.finish()
+}
+
+fn read_archive(secret: &BackupSecret, archive: &[u8]) -> Result<Vec<Frame>> {
+    BackupReader::new(archive, secret)?.collect()
+}
+
+#[test]
+fn round_trip_recovery_key() -> Result<()> {
+    let secret = BackupSecret::RecoveryKey([0x42; RECOVERY_KEY_LEN]);
+    let archive = write_archive(&secret, &sample_frames())?;
+    assert!(archive.starts_with(MAGIC));
+    assert_eq!(read_archive(&secret, &archive)?, sample_frames());
+
+    // A fresh salt means a fresh archive, even with the same key and contents.
+    assert_ne!(write_archive(&secret, &sample_frames())?, archive);
+    Ok(())
+}
+
+#[test]
+fn round_trip_passphrase() -> Result<()> {
+    let secret = BackupSecret::Passphrase("correct horse battery staple".to_string());
+    let archive = write_archive(&secret, &sample_frames())?;
+    assert_eq!(read_archive(&secret, &archive)?, sample_frames());
+
+    let wrong = BackupSecret::Passphrase("incorrect horse battery staple".to_string());
+    assert!(matches!(
+        read_archive(&wrong, &archive),
+        Err(Error::BadMac { frame_index: 0 })
+    ));
+
+    let recovery_key = BackupSecret::RecoveryKey([0; RECOVERY_KEY_LEN]);
+    assert!(matches!(
+        read_archive(&recovery_key, &archive),
+        Err(Error::WrongSecretKind)
+    ));
+    Ok(())
+}
+
+#[test]
+fn empty_archive() -> Result<()> {
+    let secret = BackupSecret::RecoveryKey([0x42; RECOVERY_KEY_LEN]);
+    let archive = write_archive(&secret, &[])?;
+    assert_eq!(read_archive(&secret, &archive)?, vec![]);
+    Ok(())
+}
+
+#[test]
+fn truncation_is_detected() -> Result<()> {
+    let secret = BackupSecret::RecoveryKey([0x42; RECOVERY_KEY_LEN]);
+    let archive = write_archive(&secret, &sample_frames())?;
+
+    for len in 0..archive.len() {
+        let result = read_archive(&secret, &archive[..len]);
+        assert!(
+            matches!(result, Err(Error::Truncated) | Err(Error::NotABackup)),
+            "truncated at {} of {}: {:?}",
+            len,
+            archive.len(),
+            result
+        );
+    }
+
+    let mut extended = archive;
+    extended.push(0);
+    assert!(matches!(
+        read_archive(&secret, &extended),
+        Err(Error::TrailingData)
+    ));
+    Ok(())
+}
+
+#[test]
+fn tampering_is_detected() -> Result<()> {
+    let secret = BackupSecret::RecoveryKey([0x42; RECOVERY_KEY_LEN]);
+    let archive = write_archive(&secret, &sample_frames())?;
+
+    // Flip a bit in the header's salt: every frame key changes.
+    let header_len = u32::from_be_bytes(archive[8..12].try_into().unwrap()) as usize;
+    let mut modified = archive.clone();
+    modified[12 + header_len - 1] ^= 1;
+    assert!(matches!(
+        read_archive(&secret, &modified),
+        Err(Error::BadMac { frame_index: 0 })
+    ));
+
+    // Flip a bit in the last byte of the last data frame's MAC.
+    let final_record_len = RECORD_LENGTH_PREFIX_LEN + 1 + 32;
+    let mut modified = archive.clone();
+    let index = modified.len() - final_record_len - 1;
+    modified[index] ^= 1;
+    assert!(matches!(
+        read_archive(&secret, &modified),
+        Err(Error::BadMac { frame_index: 3 })
+    ));
+
+    // Drop the last data frame entirely.
+    let mut decryptor_input = archive;
+    let first_record = 12 + header_len;
+    let record_len = |at: usize| {
+        RECORD_LENGTH_PREFIX_LEN
+            + u32::from_be_bytes(decryptor_input[at..at + 4].try_into().unwrap()) as usize
+    };
+    let mut at = first_record;
+    for _ in 0..3 {
+        at += record_len(at);
+    }
+    let len = record_len(at);
+    decryptor_input.drain(at..at + len);
+    assert!(matches!(
+        read_archive(&secret, &decryptor_input),
+        Err(Error::BadMac { frame_index: 3 })
+    ));
+    Ok(())
+}
+
+#[test]
+fn unsupported_version() -> Result<()> {
+    let secret = BackupSecret::RecoveryKey([0x42; RECOVERY_KEY_LEN]);
+    // Header { version: 2, key_derivation: RECOVERY_KEY }
+    assert!(matches!(
+        BackupDecryptor::new(&secret, &[0x08, 0x02, 0x10, 0x01]),
+        Err(Error::UnsupportedVersion(2))
+    ));
+    Ok(())
+}
+
+#[test]
+fn serialized_frames() -> Result<()> {
+    use prost::Message as _;
+
+    let secret = BackupSecret::RecoveryKey([0x42; RECOVERY_KEY_LEN]);
+    let frame = sample_frames().remove(1).encode_to_vec();
+
+    let mut encryptor = BackupEncryptor::new(&secret, &mut OsRng)?;
+    assert!(matches!(
+        encryptor.encrypt_serialized_frame(&[0xff]),
+        Err(Error::InvalidRecord(_))
+    ));
+    let record = encryptor.encrypt_serialized_frame(&frame)?;
+    let final_record = encryptor.finish()?;
+
+    let prefix = encryptor.archive_prefix();
+    let mut decryptor = BackupDecryptor::new(&secret, &prefix[12..])?;
+    assert_eq!(
+        decryptor.decrypt_serialized_frame(&record[RECORD_LENGTH_PREFIX_LEN..])?,
+        Some(frame)
+    );
+    assert!(!decryptor.is_finished());
+    assert_eq!(
+        decryptor.decrypt_serialized_frame(&final_record[RECORD_LENGTH_PREFIX_LEN..])?,
+        None
+    );
+    assert!(decryptor.is_finished());
+    Ok(())
+}
@@ -10,6 +10,18 @@ pub enum PokshoError {
     BadArgsWrongNumberOfPointArgs,    // Bad arguments were passed to the function
     BadArgsMissingScalarArg,          // Bad arguments were passed to the function
     BadArgsMissingPointArg,           // Bad arguments were passed to the function
+    BadArgsUnusedScalarArg,           // A scalar argument isn't in the statement
+    BadArgsUnusedPointArg,            // A point argument isn't in the statement
+    BadStatementEmptyName,            // A scalar or point name in an equation was empty
+    BadStatementNoTerms,              // An equation had no terms on its right-hand side
+    BadStatementTooManyTerms,         // An equation had more than MAX_TERMS terms
+    BadStatementTooManyEquations,     // A statement would have more than MAX_EQUATIONS equations
+    BadStatementTooManyScalars,       // A statement would have more than MAX_SCALARS scalars
+    BadStatementTooManyPoints,        // A statement would have more than MAX_POINTS points
+    BadStatementTooManyBranches,      // An OrStatement would have more than 255 branches
+    BadStatementNoEquations,          // A statement had no equations
+    BadStatementMismatchedTranscript, // An OrStatement branch uses a different Transcript
+    BadStatementNoCommitmentEquation, // A statement doesn't prove the opening of a range proof's commitment
     VerificationFailure,              // Proof verification failed
     ProofCreationVerificationFailure, // Proof verification failed during proof creation, indicating bad inputs or faulty computation
 }
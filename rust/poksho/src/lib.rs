@@ -7,21 +7,32 @@
 
 pub mod args;
 pub mod errors;
+pub mod or_statement;
 pub mod proof;
+pub mod range_proof;
 pub mod scalar;
 pub mod shoapi;
 pub mod shohmacsha256;
 pub mod shosha256;
+pub mod shoshake256;
 pub mod sign;
 mod simple_types;
 pub mod statement;
+pub mod static_statement;
 
 pub use args::{PointArgs, ScalarArgs};
 pub use errors::PokshoError;
+pub use or_statement::OrStatement;
 pub use proof::Proof;
+pub use range_proof::{
+    prove_range, prove_range_with_transcript, prove_statement_and_range, range_proof_len,
+    verify_range_proof, verify_range_proof_with_transcript, verify_statement_and_range,
+};
 pub use scalar::{scalar_from_slice_canonical, scalar_from_slice_wide};
 pub use shoapi::ShoApi;
 pub use shohmacsha256::ShoHmacSha256;
 pub use shosha256::ShoSha256;
+pub use shoshake256::ShoShake256;
 pub use sign::{sign, verify_signature};
-pub use statement::Statement;
+pub use statement::{Statement, Transcript};
+pub use static_statement::StaticStatement;
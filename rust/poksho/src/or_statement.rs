@@ -0,0 +1,425 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+// OrStatement proves that the prover knows a witness for at least one of several statements
+// ("branches"), without revealing which one.  This is the disjunctive composition of Sigma
+// protocols from Cramer, Damgård and Schoenmakers, "Proofs of Partial Knowledge and Simplified
+// Design of Witness Hiding Protocols" (CRYPTO '94).
+//
+// For every branch except the real one, the prover picks a challenge c_i and response s_i at
+// random and solves for the commitment R_i = F_i(s_i) - c_i*A_i, which is how a simulator
+// would produce a transcript.  For the real branch it commits to a nonce as usual.  The overall
+// challenge h is then derived from all the commitments, and the real branch's challenge is
+// whatever makes the c_i sum to h.  The verifier can't tell simulated branches from the real one.
+//
+// The hash inputs follow the ones for Statement (see statement.rs), with a distinct label L
+// ("POKSHO_Ristretto_Or_SHOHMACSHA256" or "POKSHO_Ristretto_Or_SHOSHAKE256", for the
+// statement's Transcript), and with the descriptions and point values of every branch absorbed
+// in branch order:
+//
+//  sho = SHO(L)
+//  sho.AbsorbAndRatchet(Nb || D_1 || ... || D_Nb || A_1 || ... || A_Nb)
+//         sho2 = sho.Clone()
+//         sho2.AbsorbAndRatchet(Z || k || a_k)
+//         sho2.AbsorbAndRatchet(M)
+//         for i=1..Nb: (c_i, s_i) = sho2.Squeeze(64 + 64 * num_scalars_i)
+//         r = s_k
+//  sho.AbsorbAndRatchet(R_1 || ... || R_Nb || M)
+//  h = Squeeze(64)
+//
+// Each D_i already encodes how many points A_i contains, so the concatenation is unambiguous.
+//
+//  Proof format
+//  ---
+//  for i=1..Nb:
+//    c_i : RistrettoScalar
+//    for j=1..num_scalars_i:
+//      s_i[j] : RistrettoScalar
+
+use crate::args::*;
+use crate::errors::*;
+use crate::scalar::*;
+use crate::simple_types::*;
+use crate::statement::{Statement, Transcript, TranscriptSho};
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+
+use PokshoError::*;
+
+/// The most branches an OrStatement can have.
+pub const MAX_BRANCHES: usize = 255;
+
+pub struct OrStatement {
+    branches: Vec<Statement>,
+    transcript: Transcript,
+}
+
+impl OrStatement {
+    pub fn new() -> Self {
+        Self::with_transcript(Transcript::default())
+    }
+
+    /// An OrStatement whose branches must all use `transcript`.
+    pub fn with_transcript(transcript: Transcript) -> Self {
+        Self {
+            branches: Vec::new(),
+            transcript,
+        }
+    }
+
+    pub fn transcript(&self) -> Transcript {
+        self.transcript
+    }
+
+    #[deprecated(note = "panics on invalid input; use try_add() instead")]
+    pub fn add(&mut self, branch: Statement) {
+        if let Err(e) = self.try_add(branch) {
+            panic!("Invalid branch for statement: {:?}", e);
+        }
+    }
+
+    pub fn try_add(&mut self, branch: Statement) -> Result<(), PokshoError> {
+        if branch.num_equations() == 0 {
+            return Err(BadStatementNoEquations);
+        }
+        if branch.transcript() != self.transcript {
+            return Err(BadStatementMismatchedTranscript);
+        }
+        if self.branches.len() >= MAX_BRANCHES {
+            return Err(BadStatementTooManyBranches);
+        }
+        self.branches.push(branch);
+        Ok(())
+    }
+
+    pub fn num_branches(&self) -> usize {
+        self.branches.len()
+    }
+
+    /// The size of every proof of this statement.
+    pub fn proof_len(&self) -> usize {
+        self.branches
+            .iter()
+            .map(|branch| 32 * (1 + branch.num_scalars()))
+            .sum()
+    }
+
+    /// Proves knowledge of `scalar_args` for the branch at `branch_index`.
+    ///
+    /// `point_args` holds the points for every branch, in the order the branches were added.
+    /// Every branch takes the same work to prove, but `branch_index` isn't handled in constant
+    /// time.
+    pub fn prove(
+        &self,
+        branch_index: usize,
+        scalar_args: &ScalarArgs,
+        point_args: &[PointArgs],
+        message: &[u8],
+        randomness: &[u8], // must be 32 bytes
+    ) -> Result<Vec<u8>, PokshoError> {
+        if randomness.len() != 32 || branch_index >= self.branches.len() {
+            return Err(BadArgs);
+        }
+        let witness = self.branches[branch_index].sort_scalars(scalar_args)?;
+        let all_points = self.sort_points(point_args)?;
+
+        let sho = self.absorb_statement(&all_points);
+
+        // "Synthetic" randomness for the nonce and the simulated branches, based on hashing
+        // randomness, the real branch and its witness, and the message
+        let mut sho2 = sho.clone();
+        sho2.absorb(randomness); // Z
+        sho2.absorb(&[branch_index as u8]); // k
+        for scalar in &witness {
+            sho2.absorb(&scalar.to_bytes()); // a_k
+        }
+        sho2.ratchet();
+        sho2.absorb_and_ratchet(message); // M
+        let random_bytes = sho2.squeeze_and_ratchet(2 * self.proof_len());
+        let mut random_scalars = random_bytes.chunks_exact(64).map(scalar_from_slice_wide);
+
+        let mut challenges = Vec::<Scalar>::with_capacity(self.branches.len());
+        let mut responses = Vec::<G1>::with_capacity(self.branches.len());
+        for (i, branch) in self.branches.iter().enumerate() {
+            let challenge = random_scalars.next().expect("enough randomness");
+            let response: G1 = random_scalars.by_ref().take(branch.num_scalars()).collect();
+            // The real branch's "response" is its nonce, with no challenge to subtract
+            let challenge = if i == branch_index {
+                Scalar::zero()
+            } else {
+                challenge
+            };
+            challenges.push(challenge);
+            responses.push(response);
+        }
+
+        let commitments = self.commitments(&challenges, &responses, &all_points);
+        let challenge = self.challenge(sho, &commitments, message);
+
+        let simulated_challenges: Scalar = challenges.iter().sum();
+        challenges[branch_index] = challenge - simulated_challenges;
+        for (response, scalar) in responses[branch_index].iter_mut().zip(&witness) {
+            *response += scalar * challenges[branch_index];
+        }
+
+        let proof_bytes = Self::encode_proof(&challenges, &responses);
+
+        // Verify before returning, since a bad proof could indicate
+        // a glitched/faulty response that leaks private keys, or incorrect inputs
+        match self.verify_proof(&proof_bytes, point_args, message) {
+            Err(VerificationFailure) => Err(ProofCreationVerificationFailure),
+            Err(e) => Err(e),
+            Ok(_) => Ok(proof_bytes),
+        }
+    }
+
+    pub fn verify_proof(
+        &self,
+        proof_bytes: &[u8],
+        point_args: &[PointArgs],
+        message: &[u8],
+    ) -> Result<(), PokshoError> {
+        let all_points = self.sort_points(point_args)?;
+        let (challenges, responses) = self.decode_proof(proof_bytes)?;
+
+        let sho = self.absorb_statement(&all_points);
+        let commitments = self.commitments(&challenges, &responses, &all_points);
+        let challenge = self.challenge(sho, &commitments, message);
+
+        // Check challenge (const time)
+        if challenge == challenges.iter().sum() {
+            Ok(())
+        } else {
+            Err(VerificationFailure)
+        }
+    }
+
+    fn sort_points(&self, point_args: &[PointArgs]) -> Result<Vec<G2>, PokshoError> {
+        if self.branches.is_empty() {
+            return Err(BadStatementNoEquations);
+        }
+        if point_args.len() != self.branches.len() {
+            return Err(BadArgs);
+        }
+        self.branches
+            .iter()
+            .zip(point_args)
+            .map(|(branch, point_args)| branch.sort_points(point_args))
+            .collect()
+    }
+
+    // Absorbs the label L, the number of branches, and every description D_i and point value A_i
+    fn absorb_statement(&self, all_points: &[G2]) -> TranscriptSho {
+        let mut sho = self.transcript.start_with_label(
+            b"POKSHO_Ristretto_Or_SHOHMACSHA256",
+            b"POKSHO_Ristretto_Or_SHOSHAKE256",
+        ); // L
+        sho.absorb(&[self.branches.len() as u8]); // Nb
+        for branch in &self.branches {
+            sho.absorb(branch.description()); // D_i
+        }
+        for points in all_points {
+            for point in points {
+                sho.absorb(&point.compress().to_bytes()); // A_i
+            }
+        }
+        sho.ratchet();
+        sho
+    }
+
+    // R_i = F_i(s_i) - c_i*A_i
+    fn commitments(
+        &self,
+        challenges: &[Scalar],
+        responses: &[G1],
+        all_points: &[G2],
+    ) -> Vec<RistrettoPoint> {
+        let mut commitments = Vec::new();
+        for (i, branch) in self.branches.iter().enumerate() {
+            commitments.extend(branch.homomorphism(
+                &responses[i],
+                &all_points[i],
+                Some(challenges[i]),
+            ));
+        }
+        commitments
+    }
+
+    fn challenge(
+        &self,
+        mut sho: TranscriptSho,
+        commitments: &[RistrettoPoint],
+        message: &[u8],
+    ) -> Scalar {
+        for point in commitments {
+            sho.absorb(&point.compress().to_bytes()); // R
+        }
+        sho.absorb_and_ratchet(message); // M
+        scalar_from_slice_wide(&sho.squeeze_and_ratchet(64))
+    }
+
+    fn encode_proof(challenges: &[Scalar], responses: &[G1]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for (challenge, response) in challenges.iter().zip(responses) {
+            bytes.extend_from_slice(challenge.as_bytes());
+            for scalar in response {
+                bytes.extend_from_slice(scalar.as_bytes());
+            }
+        }
+        bytes
+    }
+
+    fn decode_proof(&self, proof_bytes: &[u8]) -> Result<(Vec<Scalar>, Vec<G1>), PokshoError> {
+        if proof_bytes.len() != self.proof_len() {
+            return Err(VerificationFailure);
+        }
+        let mut scalars = proof_bytes
+            .chunks_exact(32)
+            .map(|chunk| scalar_from_slice_canonical(chunk).ok_or(VerificationFailure));
+        let mut challenges = Vec::with_capacity(self.branches.len());
+        let mut responses = Vec::with_capacity(self.branches.len());
+        for branch in &self.branches {
+            challenges.push(scalars.next().expect("length checked")?);
+            responses.push(
+                scalars
+                    .by_ref()
+                    .take(branch.num_scalars())
+                    .collect::<Result<G1, _>>()?,
+            );
+        }
+        Ok((challenges, responses))
+    }
+}
+
+impl Default for OrStatement {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case, clippy::unwrap_used)]
+
+    use super::*;
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+
+    // Proves knowledge of the discrete log of A with respect to one of several generators
+    fn membership_statement(num_branches: usize) -> OrStatement {
+        let mut st = OrStatement::new();
+        for _ in 0..num_branches {
+            let mut branch = Statement::new();
+            branch.try_add("A", &[("a", "H")]).unwrap();
+            st.try_add(branch).unwrap();
+        }
+        st
+    }
+
+    #[test]
+    fn test_or_proof() {
+        let generators: Vec<RistrettoPoint> = (1..=3u64)
+            .map(|i| Scalar::from(i * 1000) * RISTRETTO_BASEPOINT_POINT)
+            .collect();
+        let a = Scalar::from(42u64);
+        let st = membership_statement(generators.len());
+        let message = b"message";
+
+        for real_branch in 0..generators.len() {
+            let A = a * generators[real_branch];
+            let point_args: Vec<PointArgs> = generators
+                .iter()
+                .map(|H| {
+                    let mut point_args = PointArgs::new();
+                    point_args.add("A", A);
+                    point_args.add("H", *H);
+                    point_args
+                })
+                .collect();
+            let mut scalar_args = ScalarArgs::new();
+            scalar_args.add("a", a);
+
+            let proof = st
+                .prove(real_branch, &scalar_args, &point_args, message, &[1u8; 32])
+                .unwrap();
+            assert_eq!(proof.len(), st.proof_len());
+            assert_eq!(proof.len(), 3 * 64);
+            st.verify_proof(&proof, &point_args, message).unwrap();
+
+            // Bad message
+            assert!(matches!(
+                st.verify_proof(&proof, &point_args, b"other"),
+                Err(VerificationFailure)
+            ));
+
+            // Bad proofs
+            let mut proof2 = proof.clone();
+            proof2[0] ^= 1;
+            assert!(matches!(
+                st.verify_proof(&proof2, &point_args, message),
+                Err(VerificationFailure)
+            ));
+            assert!(matches!(
+                st.verify_proof(&proof[..proof.len() - 32], &point_args, message),
+                Err(VerificationFailure)
+            ));
+
+            // Wrong number of branches
+            assert!(matches!(
+                st.verify_proof(&proof, &point_args[1..], message),
+                Err(BadArgs)
+            ));
+
+            // The witness doesn't satisfy any other branch
+            let wrong_branch = (real_branch + 1) % generators.len();
+            assert!(matches!(
+                st.prove(wrong_branch, &scalar_args, &point_args, message, &[1u8; 32]),
+                Err(ProofCreationVerificationFailure)
+            ));
+        }
+    }
+    #[test]
+    fn test_transcript() {
+        let H = Scalar::from(1000u64) * RISTRETTO_BASEPOINT_POINT;
+        let a = Scalar::from(42u64);
+        let mut point_args = PointArgs::new();
+        point_args.add("A", a * H);
+        point_args.add("H", H);
+        let point_args = [point_args.clone(), point_args];
+        let mut scalar_args = ScalarArgs::new();
+        scalar_args.add("a", a);
+        let message = b"message";
+
+        let mut st = OrStatement::with_transcript(Transcript::Shake256);
+        for _ in 0..2 {
+            let mut branch = Statement::with_transcript(Transcript::Shake256);
+            branch.try_add("A", &[("a", "H")]).unwrap();
+            st.try_add(branch).unwrap();
+        }
+        let proof = st
+            .prove(0, &scalar_args, &point_args, message, &[1u8; 32])
+            .unwrap();
+        st.verify_proof(&proof, &point_args, message).unwrap();
+
+        // The same statement with the default transcript gives a different proof
+        let hmac_st = membership_statement(2);
+        let hmac_proof = hmac_st
+            .prove(0, &scalar_args, &point_args, message, &[1u8; 32])
+            .unwrap();
+        assert_ne!(proof, hmac_proof);
+        assert!(matches!(
+            hmac_st.verify_proof(&proof, &point_args, message),
+            Err(VerificationFailure)
+        ));
+
+        // Every branch has to use the statement's transcript
+        let mut branch = Statement::new();
+        branch.try_add("A", &[("a", "H")]).unwrap();
+        assert!(matches!(
+            st.try_add(branch),
+            Err(BadStatementMismatchedTranscript)
+        ));
+        assert_eq!(st.num_branches(), 2);
+    }
+}
@@ -0,0 +1,625 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+// Range proofs show that a Pedersen commitment V = v*G + r*H opens to a value v in [0, 2^n),
+// without revealing v.
+//
+// The commitment only binds v if nobody knows the discrete log of H with respect to G: anyone
+// who knows x with H = x*G can open V = v*G + r*H to any other value v' with blinding
+// r + (v - v')/x.  H should therefore be derived by hashing to the curve (for instance with
+// RistrettoPoint::from_uniform_bytes), never as a known multiple of G.  G itself and the
+// identity are rejected as BadArgs, but other bad choices of H can't be detected.
+//
+// The value is decomposed into bits b_j, each committed as V_j = b_j*G + r_j*H with the r_j
+// chosen so that V = sum(2^j * V_j).  An OrStatement then proves, for each bit, that V_j is a
+// commitment to either 0 or 1 (V_j = r_j*H, or V_j - G = r_j*H).  V_0 isn't sent; the verifier
+// computes it from V and the other bit commitments.
+//
+// Other ranges reduce to this one by shifting the commitment, which keeps the same blinding:
+//  - v >= N:           prove v - N in range for V - N*G
+//  - v <= N:           prove N - v in range for N*G - V (with blinding -r)
+//  - N <= v <= M:      do both, choosing n so that M - N < 2^n
+//
+// A proof is bound to the statement (n, V, H, and the bit commitments) and the message through
+// a separate transcript, whose output is used as the message for each bit's OrStatement.  The
+// bit statements use the same Transcript as the range proof, whose label L is
+// "POKSHO_Ristretto_Range_SHOHMACSHA256" or "POKSHO_Ristretto_Range_SHOSHAKE256":
+//
+//  sho = SHO(L)
+//  sho.AbsorbAndRatchet(n || V || H)
+//         sho2 = sho.Clone()
+//         sho2.AbsorbAndRatchet(Z || v || r)
+//         sho2.AbsorbAndRatchet(M)
+//         r_1..r_(n-1) = sho2.Squeeze(64 * (n-1))
+//         Z_0..Z_(n-1) = sho2.Squeeze(32 * n)
+//  sho.AbsorbAndRatchet(V_1 || ... || V_(n-1) || M)
+//  C = sho.Squeeze(32)
+//  for j=0..n-1: proof_j = OrStatement(V_j = r_j*H or V_j - G = r_j*H).Prove(message = C || j,
+//                                                                        randomness = Z_j)
+//
+//  Proof format
+//  ---
+//  for j=1..n-1:
+//    V_j : RistrettoPoint
+//  for j=0..n-1:
+//    proof_j : 128 bytes
+//
+// A range proof can be combined with a Statement proof about the same commitment, so that the
+// committed value can also be used in the Statement's equations (for instance, to show that it
+// matches an attribute in a credential).  Both proofs use the Statement's Transcript and the
+// same message M, and the combined proof is the Statement proof followed by the range proof.
+// The Statement must include the equation V = v*G + r*H, which ties the scalar v in its other
+// equations to the value in range; statements without it are rejected by both prover and
+// verifier.
+
+use crate::args::*;
+use crate::errors::*;
+use crate::or_statement::OrStatement;
+use crate::scalar::*;
+use crate::statement::{Statement, Transcript, TranscriptSho};
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+
+use PokshoError::*;
+
+const BIT_PROOF_LEN: usize = 128;
+
+/// The size of a range proof for `num_bits` bits.
+pub fn range_proof_len(num_bits: usize) -> usize {
+    32 * (num_bits - 1) + BIT_PROOF_LEN * num_bits
+}
+
+/// Proves that `commitment = value*G + blinding*H` for some `value` less than `2^num_bits`.
+///
+/// `H` must be a generator whose discrete log with respect to `G` is unknown, or the proof
+/// shows nothing about `value`; `G` itself and the identity are rejected.
+///
+/// `num_bits` must be between 1 and 64. Which bits of `value` are set can affect timing.
+#[allow(non_snake_case)]
+pub fn prove_range(
+    value: u64,
+    blinding: Scalar,
+    H: RistrettoPoint,
+    num_bits: usize,
+    message: &[u8],
+    randomness: &[u8], // must be 32 bytes
+) -> Result<Vec<u8>, PokshoError> {
+    prove_range_with_transcript(
+        Transcript::default(),
+        value,
+        blinding,
+        H,
+        num_bits,
+        message,
+        randomness,
+    )
+}
+
+/// Like [`prove_range`], with the given transcript for the proof and every bit's OrStatement.
+#[allow(non_snake_case)]
+pub fn prove_range_with_transcript(
+    transcript: Transcript,
+    value: u64,
+    blinding: Scalar,
+    H: RistrettoPoint,
+    num_bits: usize,
+    message: &[u8],
+    randomness: &[u8], // must be 32 bytes
+) -> Result<Vec<u8>, PokshoError> {
+    if randomness.len() != 32 || !(1..=64).contains(&num_bits) {
+        return Err(BadArgs);
+    }
+    if num_bits < 64 && value >> num_bits != 0 {
+        return Err(BadArgs);
+    }
+    check_generator(H)?;
+    let commitment = Scalar::from(value) * RISTRETTO_BASEPOINT_POINT + blinding * H;
+    let sho = absorb_statement(transcript, num_bits, commitment, H);
+
+    // "Synthetic" blindings and randomness, based on hashing randomness, the opening, and the
+    // message
+    let mut sho2 = sho.clone();
+    sho2.absorb(randomness); // Z
+    sho2.absorb(&value.to_be_bytes()); // v
+    sho2.absorb(blinding.as_bytes()); // r
+    sho2.ratchet();
+    sho2.absorb_and_ratchet(message); // M
+    let blinding_bytes = sho2.squeeze_and_ratchet(64 * (num_bits - 1));
+    let bit_randomness = sho2.squeeze_and_ratchet(32 * num_bits);
+
+    let mut bit_blindings = vec![Scalar::zero(); num_bits];
+    let mut remaining_blinding = blinding;
+    for (j, chunk) in blinding_bytes.chunks_exact(64).enumerate() {
+        bit_blindings[j + 1] = scalar_from_slice_wide(chunk);
+        remaining_blinding -= power_of_two(j + 1) * bit_blindings[j + 1];
+    }
+    bit_blindings[0] = remaining_blinding;
+
+    let bit_commitments: Vec<RistrettoPoint> = bit_blindings
+        .iter()
+        .enumerate()
+        .map(|(j, r_j)| Scalar::from((value >> j) & 1) * RISTRETTO_BASEPOINT_POINT + r_j * H)
+        .collect();
+
+    let mut proof = Vec::with_capacity(range_proof_len(num_bits));
+    for V_j in &bit_commitments[1..] {
+        proof.extend_from_slice(V_j.compress().as_bytes());
+    }
+    let context = bit_context(sho, &bit_commitments[1..], message);
+
+    let st = bit_statement(transcript)?;
+    for (j, (V_j, r_j)) in bit_commitments.iter().zip(&bit_blindings).enumerate() {
+        let mut scalar_args = ScalarArgs::new();
+        scalar_args.add("r", *r_j);
+        proof.extend(st.prove(
+            ((value >> j) & 1) as usize,
+            &scalar_args,
+            &bit_point_args(*V_j, H),
+            &bit_message(&context, j),
+            &bit_randomness[32 * j..32 * (j + 1)],
+        )?);
+    }
+    Ok(proof)
+}
+
+/// Checks a proof that `commitment` opens to a value less than `2^num_bits`, where `commitment
+/// = value*G + blinding*H`.
+///
+/// As for [`prove_range`], `H` must have an unknown discrete log with respect to `G`.
+#[allow(non_snake_case)]
+pub fn verify_range_proof(
+    proof_bytes: &[u8],
+    commitment: RistrettoPoint,
+    H: RistrettoPoint,
+    num_bits: usize,
+    message: &[u8],
+) -> Result<(), PokshoError> {
+    verify_range_proof_with_transcript(
+        Transcript::default(),
+        proof_bytes,
+        commitment,
+        H,
+        num_bits,
+        message,
+    )
+}
+
+/// Like [`verify_range_proof`], for a proof made with [`prove_range_with_transcript`].
+#[allow(non_snake_case)]
+pub fn verify_range_proof_with_transcript(
+    transcript: Transcript,
+    proof_bytes: &[u8],
+    commitment: RistrettoPoint,
+    H: RistrettoPoint,
+    num_bits: usize,
+    message: &[u8],
+) -> Result<(), PokshoError> {
+    if !(1..=64).contains(&num_bits) {
+        return Err(BadArgs);
+    }
+    check_generator(H)?;
+    if proof_bytes.len() != range_proof_len(num_bits) {
+        return Err(VerificationFailure);
+    }
+    let (commitment_bytes, bit_proofs) = proof_bytes.split_at(32 * (num_bits - 1));
+
+    let mut bit_commitments = Vec::with_capacity(num_bits);
+    bit_commitments.push(commitment);
+    for (j, chunk) in commitment_bytes.chunks_exact(32).enumerate() {
+        let V_j = CompressedRistretto::from_slice(chunk)
+            .decompress()
+            .ok_or(VerificationFailure)?;
+        // V_0 = V - sum(2^j * V_j)
+        bit_commitments[0] -= power_of_two(j + 1) * V_j;
+        bit_commitments.push(V_j);
+    }
+
+    let sho = absorb_statement(transcript, num_bits, commitment, H);
+    let context = bit_context(sho, &bit_commitments[1..], message);
+
+    let st = bit_statement(transcript)?;
+    for (j, (V_j, bit_proof)) in bit_commitments
+        .iter()
+        .zip(bit_proofs.chunks_exact(BIT_PROOF_LEN))
+        .enumerate()
+    {
+        st.verify_proof(
+            bit_proof,
+            &bit_point_args(*V_j, H),
+            &bit_message(&context, j),
+        )?;
+    }
+    Ok(())
+}
+
+/// Proves `st` together with a range proof for one of its points.
+///
+/// `commitment` and `generator` name the points V and H in `point_args`, where V = value*G +
+/// blinding*H. `st` must include that equation, with any two scalar names, so that its proof and
+/// the range proof are about the same value; otherwise this returns
+/// [`PokshoError::BadStatementNoCommitmentEquation`].
+#[allow(non_snake_case, clippy::too_many_arguments)]
+pub fn prove_statement_and_range(
+    st: &Statement,
+    scalar_args: &ScalarArgs,
+    point_args: &PointArgs,
+    commitment: &str,
+    generator: &str,
+    value: u64,
+    blinding: Scalar,
+    num_bits: usize,
+    message: &[u8],
+    randomness: &[u8], // must be 32 bytes
+) -> Result<Vec<u8>, PokshoError> {
+    if !st.has_commitment_equation(commitment, generator) {
+        return Err(BadStatementNoCommitmentEquation);
+    }
+    let H = *point_args.0.get(generator).ok_or(BadArgsMissingPointArg)?;
+    let mut proof = st.prove(scalar_args, point_args, message, randomness)?;
+    proof.extend(prove_range_with_transcript(
+        st.transcript(),
+        value,
+        blinding,
+        H,
+        num_bits,
+        message,
+        randomness,
+    )?);
+
+    // Verify before returning, since the commitment in point_args might not match the opening
+    match verify_statement_and_range(
+        st, &proof, point_args, commitment, generator, num_bits, message,
+    ) {
+        Err(VerificationFailure) => Err(ProofCreationVerificationFailure),
+        Err(e) => Err(e),
+        Ok(_) => Ok(proof),
+    }
+}
+
+/// Checks a proof from [`prove_statement_and_range`].
+#[allow(non_snake_case)]
+pub fn verify_statement_and_range(
+    st: &Statement,
+    proof_bytes: &[u8],
+    point_args: &PointArgs,
+    commitment: &str,
+    generator: &str,
+    num_bits: usize,
+    message: &[u8],
+) -> Result<(), PokshoError> {
+    if !st.has_commitment_equation(commitment, generator) {
+        return Err(BadStatementNoCommitmentEquation);
+    }
+    let V = *point_args.0.get(commitment).ok_or(BadArgsMissingPointArg)?;
+    let H = *point_args.0.get(generator).ok_or(BadArgsMissingPointArg)?;
+    let statement_proof_len = 32 * (1 + st.num_scalars());
+    if proof_bytes.len() < statement_proof_len {
+        return Err(VerificationFailure);
+    }
+    let (statement_proof, range_proof) = proof_bytes.split_at(statement_proof_len);
+    st.verify_proof(statement_proof, point_args, message)?;
+    verify_range_proof_with_transcript(st.transcript(), range_proof, V, H, num_bits, message)
+}
+
+// Rejects generators whose discrete log with respect to G is obviously known
+#[allow(non_snake_case)]
+fn check_generator(H: RistrettoPoint) -> Result<(), PokshoError> {
+    if H == RISTRETTO_BASEPOINT_POINT || H == RistrettoPoint::identity() {
+        return Err(BadArgs);
+    }
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+fn absorb_statement(
+    transcript: Transcript,
+    num_bits: usize,
+    V: RistrettoPoint,
+    H: RistrettoPoint,
+) -> TranscriptSho {
+    let mut sho = transcript.start_with_label(
+        b"POKSHO_Ristretto_Range_SHOHMACSHA256",
+        b"POKSHO_Ristretto_Range_SHOSHAKE256",
+    ); // L
+    sho.absorb(&[num_bits as u8]); // n
+    sho.absorb(V.compress().as_bytes()); // V
+    sho.absorb(H.compress().as_bytes()); // H
+    sho.ratchet();
+    sho
+}
+
+fn bit_context(
+    mut sho: TranscriptSho,
+    bit_commitments: &[RistrettoPoint],
+    message: &[u8],
+) -> Vec<u8> {
+    for point in bit_commitments {
+        sho.absorb(point.compress().as_bytes()); // V_j
+    }
+    sho.absorb_and_ratchet(message); // M
+    sho.squeeze_and_ratchet(32)
+}
+
+fn bit_message(context: &[u8], j: usize) -> Vec<u8> {
+    let mut message = context.to_vec();
+    message.push(j as u8);
+    message
+}
+
+// V = r*H, for either V = V_j (the bit is 0) or V = V_j - G (the bit is 1)
+fn bit_statement(transcript: Transcript) -> Result<OrStatement, PokshoError> {
+    let mut st = OrStatement::with_transcript(transcript);
+    for _ in 0..2 {
+        let mut branch = Statement::with_transcript(transcript);
+        branch.try_add("V", &[("r", "H")])?;
+        st.try_add(branch)?;
+    }
+    Ok(st)
+}
+
+#[allow(non_snake_case)]
+fn bit_point_args(V_j: RistrettoPoint, H: RistrettoPoint) -> [PointArgs; 2] {
+    let mut zero = PointArgs::new();
+    zero.add("V", V_j);
+    zero.add("H", H);
+    let mut one = PointArgs::new();
+    one.add("V", V_j - RISTRETTO_BASEPOINT_POINT);
+    one.add("H", H);
+    [zero, one]
+}
+
+fn power_of_two(j: usize) -> Scalar {
+    Scalar::from(1u128 << j)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case, clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn test_range_proof() {
+        let H = Scalar::from(1234u64) * RISTRETTO_BASEPOINT_POINT;
+        let blinding = Scalar::from(5678u64);
+        let commit = |value: u64| Scalar::from(value) * RISTRETTO_BASEPOINT_POINT + blinding * H;
+        let randomness = [3u8; 32];
+
+        for (value, num_bits) in [(0, 1), (1, 1), (5, 3), (200, 8), (u64::MAX, 64)] {
+            let proof = prove_range(value, blinding, H, num_bits, b"message", &randomness).unwrap();
+            assert_eq!(proof.len(), range_proof_len(num_bits));
+            verify_range_proof(&proof, commit(value), H, num_bits, b"message").unwrap();
+
+            assert!(matches!(
+                verify_range_proof(&proof, commit(value), H, num_bits, b"other"),
+                Err(VerificationFailure)
+            ));
+            assert!(matches!(
+                verify_range_proof(&proof, commit(value ^ 1), H, num_bits, b"message"),
+                Err(VerificationFailure)
+            ));
+            let mut proof2 = proof.clone();
+            let last = proof2.len() - 1;
+            proof2[last] ^= 1;
+            assert!(matches!(
+                verify_range_proof(&proof2, commit(value), H, num_bits, b"message"),
+                Err(VerificationFailure)
+            ));
+        }
+
+        // Values that don't fit
+        assert!(matches!(
+            prove_range(8, blinding, H, 3, b"message", &randomness),
+            Err(BadArgs)
+        ));
+        assert!(matches!(
+            prove_range(0, blinding, H, 0, b"message", &randomness),
+            Err(BadArgs)
+        ));
+
+        // A proof for 8 bits doesn't verify as a proof for 4 bits
+        let proof = prove_range(5, blinding, H, 8, b"message", &randomness).unwrap();
+        assert!(matches!(
+            verify_range_proof(&proof, commit(5), H, 4, b"message"),
+            Err(VerificationFailure)
+        ));
+    }
+
+    #[test]
+    fn test_window() {
+        // Show that 1000 <= v <= 1100 by proving v - 1000 and 1100 - v are both less than 2^7
+        let H = Scalar::from(1234u64) * RISTRETTO_BASEPOINT_POINT;
+        let blinding = Scalar::from(5678u64);
+        let value = 1050u64;
+        let V = Scalar::from(value) * RISTRETTO_BASEPOINT_POINT + blinding * H;
+        let [lower, upper] = [1000u64, 1100u64];
+
+        let lower_proof = prove_range(value - lower, blinding, H, 7, b"lower", &[1u8; 32]).unwrap();
+        let upper_proof =
+            prove_range(upper - value, -blinding, H, 7, b"upper", &[2u8; 32]).unwrap();
+
+        let G = RISTRETTO_BASEPOINT_POINT;
+        verify_range_proof(&lower_proof, V - Scalar::from(lower) * G, H, 7, b"lower").unwrap();
+        verify_range_proof(&upper_proof, Scalar::from(upper) * G - V, H, 7, b"upper").unwrap();
+
+        // The same proofs don't work for a different window
+        assert!(
+            verify_range_proof(&lower_proof, V - Scalar::from(1001u64) * G, H, 7, b"lower")
+                .is_err()
+        );
+    }
+    #[test]
+    fn test_transcript() {
+        let H = Scalar::from(1234u64) * RISTRETTO_BASEPOINT_POINT;
+        let blinding = Scalar::from(5678u64);
+        let V = Scalar::from(5u64) * RISTRETTO_BASEPOINT_POINT + blinding * H;
+        let randomness = [3u8; 32];
+
+        let proof = prove_range_with_transcript(
+            Transcript::Shake256,
+            5,
+            blinding,
+            H,
+            8,
+            b"message",
+            &randomness,
+        )
+        .unwrap();
+        verify_range_proof_with_transcript(Transcript::Shake256, &proof, V, H, 8, b"message")
+            .unwrap();
+
+        // Proofs for one transcript don't verify with the other
+        assert_ne!(
+            proof,
+            prove_range(5, blinding, H, 8, b"message", &randomness).unwrap()
+        );
+        assert!(matches!(
+            verify_range_proof(&proof, V, H, 8, b"message"),
+            Err(VerificationFailure)
+        ));
+    }
+    #[test]
+    fn test_bad_generator() {
+        let G = RISTRETTO_BASEPOINT_POINT;
+        let blinding = Scalar::from(5678u64);
+        for H in [G, RistrettoPoint::identity()] {
+            assert!(matches!(
+                prove_range(5, blinding, H, 8, b"message", &[3u8; 32]),
+                Err(BadArgs)
+            ));
+            let V = Scalar::from(5u64) * G + blinding * H;
+            assert!(matches!(
+                verify_range_proof(&[0u8; 32 * 7 + 128 * 8], V, H, 8, b"message"),
+                Err(BadArgs)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_statement_and_range() {
+        // Shows that V commits to the same value as W, using another generator I, and that the
+        // value is less than 2^16
+        let G = RISTRETTO_BASEPOINT_POINT;
+        let H = Scalar::from(1234u64) * G;
+        let I = Scalar::from(4321u64) * G;
+        let value = 40000u64;
+        let v = Scalar::from(value);
+        let r = Scalar::from(5678u64);
+        let s = Scalar::from(8765u64);
+
+        let mut st = Statement::new();
+        st.try_add("V", &[("v", "G"), ("r", "H")]).unwrap();
+        st.try_add("W", &[("v", "G"), ("s", "I")]).unwrap();
+        let mut scalar_args = ScalarArgs::new();
+        scalar_args.add("v", v);
+        scalar_args.add("r", r);
+        scalar_args.add("s", s);
+        let mut point_args = PointArgs::new();
+        point_args.add("V", v * G + r * H);
+        point_args.add("W", v * G + s * I);
+        point_args.add("H", H);
+        point_args.add("I", I);
+
+        let proof = prove_statement_and_range(
+            &st,
+            &scalar_args,
+            &point_args,
+            "V",
+            "H",
+            value,
+            r,
+            16,
+            b"message",
+            &[5u8; 32],
+        )
+        .unwrap();
+        assert_eq!(proof.len(), 32 * 4 + range_proof_len(16));
+        verify_statement_and_range(&st, &proof, &point_args, "V", "H", 16, b"message").unwrap();
+
+        assert!(matches!(
+            verify_statement_and_range(&st, &proof, &point_args, "V", "H", 16, b"other"),
+            Err(VerificationFailure)
+        ));
+        assert!(matches!(
+            verify_statement_and_range(&st, &proof, &point_args, "V", "H", 15, b"message"),
+            Err(VerificationFailure)
+        ));
+        assert!(matches!(
+            verify_statement_and_range(&st, &proof[..64], &point_args, "V", "H", 16, b"message"),
+            Err(VerificationFailure)
+        ));
+
+        // The value doesn't fit, or doesn't match the commitment
+        assert!(matches!(
+            prove_statement_and_range(
+                &st,
+                &scalar_args,
+                &point_args,
+                "V",
+                "H",
+                value,
+                r,
+                15,
+                b"message",
+                &[5u8; 32],
+            ),
+            Err(BadArgs)
+        ));
+        assert!(matches!(
+            prove_statement_and_range(
+                &st,
+                &scalar_args,
+                &point_args,
+                "V",
+                "H",
+                value + 1,
+                r,
+                16,
+                b"message",
+                &[5u8; 32],
+            ),
+            Err(ProofCreationVerificationFailure)
+        ));
+
+        // The statement has to open V with G and H, or the range says nothing about v
+        let mut unrelated_st = Statement::new();
+        unrelated_st
+            .try_add("V", &[("v", "I"), ("r", "H")])
+            .unwrap();
+        unrelated_st
+            .try_add("W", &[("v", "G"), ("s", "I")])
+            .unwrap();
+        let mut unrelated_point_args = PointArgs::new();
+        unrelated_point_args.add("V", v * I + r * H);
+        unrelated_point_args.add("W", v * G + s * I);
+        unrelated_point_args.add("H", H);
+        unrelated_point_args.add("I", I);
+        assert!(matches!(
+            prove_statement_and_range(
+                &unrelated_st,
+                &scalar_args,
+                &unrelated_point_args,
+                "V",
+                "H",
+                value,
+                r,
+                16,
+                b"message",
+                &[5u8; 32],
+            ),
+            Err(BadStatementNoCommitmentEquation)
+        ));
+        assert!(matches!(
+            verify_statement_and_range(&st, &proof, &point_args, "W", "I", 16, b"message"),
+            Err(VerificationFailure)
+        ));
+        assert!(matches!(
+            verify_statement_and_range(&st, &proof, &point_args, "V", "I", 16, b"message"),
+            Err(BadStatementNoCommitmentEquation)
+        ));
+    }
+}
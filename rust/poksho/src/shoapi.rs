@@ -24,8 +24,7 @@ where
 
     fn ratchet(&mut self);
 
-    // unimplemented; make this more generic later
-    // pub fn squeeze(&mut self, _outlen: usize) -> Vec<u8>;
+    // Squeezing without ratcheting is only supported by ShoShake256::squeeze()
 
     fn squeeze_and_ratchet(&mut self, outlen: usize) -> Vec<u8>;
 }
@@ -0,0 +1,221 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+// A SHO built as a duplex sponge on Keccak-f[1600], with the same rate and padding as SHAKE256.
+//
+// Input is XORed into the rate portion of the state, and output is read from it, applying the
+// permutation whenever a block is used up.  Ratcheting pads the current phase with a domain
+// separator byte and applies the permutation:
+//
+//  - after absorbing, the padding is SHAKE256's (0x1F ... 0x80), so a new SHO followed by
+//    squeeze() produces exactly SHAKE256(label)
+//  - after squeezing, the separator byte is 0x04 instead
+//
+// Unlike the other SHOs, output can be squeezed incrementally: squeeze(a) followed by squeeze(b)
+// gives the same bytes as squeeze(a + b).
+
+use crate::shoapi::ShoApi;
+
+pub const RATE: usize = 136;
+
+const ABSORB_SEPARATOR: u8 = 0x1F;
+const SQUEEZE_SEPARATOR: u8 = 0x04;
+
+#[derive(Clone)]
+#[allow(clippy::upper_case_acronyms)]
+enum Mode {
+    ABSORBING,
+    SQUEEZING,
+    RATCHETED,
+}
+
+#[derive(Clone)]
+pub struct ShoShake256 {
+    state: [u64; 25],
+    position: usize,
+    mode: Mode,
+}
+
+impl ShoShake256 {
+    /// Squeezes `outlen` bytes without ratcheting, so that more output can follow.
+    pub fn squeeze(&mut self, outlen: usize) -> Vec<u8> {
+        if let Mode::ABSORBING = self.mode {
+            panic!("squeeze() called after absorb() without a ratchet()");
+        }
+        let mut output = Vec::<u8>::with_capacity(outlen);
+        for _ in 0..outlen {
+            if self.position == RATE {
+                keccak::f1600(&mut self.state);
+                self.position = 0;
+            }
+            output.push(self.byte(self.position));
+            self.position += 1;
+        }
+        self.mode = Mode::SQUEEZING;
+        output
+    }
+
+    fn byte(&self, index: usize) -> u8 {
+        (self.state[index / 8] >> (8 * (index % 8))) as u8
+    }
+
+    fn xor_byte(&mut self, index: usize, byte: u8) {
+        self.state[index / 8] ^= (byte as u64) << (8 * (index % 8));
+    }
+
+    fn pad_and_permute(&mut self, separator: u8) {
+        // A squeeze that ended on a block boundary leaves no room in the rate for the separator
+        if self.position == RATE {
+            keccak::f1600(&mut self.state);
+            self.position = 0;
+        }
+        self.xor_byte(self.position, separator);
+        self.xor_byte(RATE - 1, 0x80);
+        keccak::f1600(&mut self.state);
+        self.position = 0;
+    }
+}
+
+impl ShoApi for ShoShake256 {
+    fn new(label: &[u8]) -> ShoShake256 {
+        let mut sho = ShoShake256 {
+            state: [0; 25],
+            position: 0,
+            mode: Mode::RATCHETED,
+        };
+        sho.absorb_and_ratchet(label);
+        sho
+    }
+
+    fn absorb(&mut self, input: &[u8]) {
+        match self.mode {
+            Mode::SQUEEZING => self.pad_and_permute(SQUEEZE_SEPARATOR),
+            Mode::RATCHETED | Mode::ABSORBING => {}
+        }
+        self.mode = Mode::ABSORBING;
+        for byte in input {
+            self.xor_byte(self.position, *byte);
+            self.position += 1;
+            if self.position == RATE {
+                keccak::f1600(&mut self.state);
+                self.position = 0;
+            }
+        }
+    }
+
+    // called after absorb() or squeeze() only
+    fn ratchet(&mut self) {
+        match self.mode {
+            Mode::ABSORBING => self.pad_and_permute(ABSORB_SEPARATOR),
+            Mode::SQUEEZING => self.pad_and_permute(SQUEEZE_SEPARATOR),
+            Mode::RATCHETED => panic!(),
+        }
+        self.mode = Mode::RATCHETED;
+    }
+
+    fn squeeze_and_ratchet(&mut self, outlen: usize) -> Vec<u8> {
+        let output = self.squeeze(outlen);
+        self.ratchet();
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha3::digest::{ExtendableOutput, Update, XofReader};
+    use sha3::Shake256;
+
+    fn shake256(input: &[u8], outlen: usize) -> Vec<u8> {
+        let mut hasher = Shake256::default();
+        hasher.update(input);
+        let mut output = vec![0u8; outlen];
+        hasher.finalize_xof().read(&mut output);
+        output
+    }
+
+    #[test]
+    fn test_matches_shake256() {
+        for len in [0, 1, RATE - 1, RATE, RATE + 1, 3 * RATE] {
+            let label = vec![0x5a; len];
+            let mut sho = ShoShake256::new(&label);
+            assert_eq!(sho.squeeze(2 * RATE + 7), shake256(&label, 2 * RATE + 7));
+        }
+    }
+
+    #[test]
+    fn test_incremental_squeeze() {
+        let mut sho = ShoShake256::new(b"asd");
+        sho.absorb_and_ratchet(b"asdasd");
+        let mut sho2 = sho.clone();
+
+        let out = sho.squeeze_and_ratchet(300);
+        let mut out2 = sho2.squeeze(100);
+        out2.extend(sho2.squeeze(0));
+        out2.extend(sho2.squeeze(200));
+        sho2.ratchet();
+        assert_eq!(out, out2);
+        assert_eq!(sho.squeeze(32), sho2.squeeze(32));
+    }
+
+    #[test]
+    fn test_ratchet_after_full_block() {
+        let mut sho = ShoShake256::new(b"asd");
+        sho.squeeze(RATE);
+        let mut expected = sho.state;
+        sho.ratchet();
+
+        // The separator goes in a fresh block, leaving the capacity untouched
+        keccak::f1600(&mut expected);
+        expected[0] ^= SQUEEZE_SEPARATOR as u64;
+        expected[RATE / 8 - 1] ^= 0x80 << 56;
+        keccak::f1600(&mut expected);
+        assert_eq!(sho.state, expected);
+
+        let mut sho2 = ShoShake256::new(b"asd");
+        sho2.squeeze(RATE);
+        sho2.absorb(b"asdasd");
+        sho2.ratchet();
+        let mut sho3 = sho.clone();
+        sho3.absorb(b"asdasd");
+        sho3.ratchet();
+        assert_eq!(sho2.state, sho3.state);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_vectors() {
+        let mut sho = ShoShake256::new(b"asd");
+        sho.absorb_and_ratchet(b"asdasd");
+        let out = sho.squeeze_and_ratchet(64);
+        /*
+        println!("{}", hex::encode(&out));
+        */
+        assert_eq!(
+            out,
+            hex::decode(concat!(
+                "27a4037032789cee4779b119ad96916394df0dc7069ff7e66b9cfb41b153e875",
+                "980c158b17c07af00c8caf7e5ec68694f431d8a338a49348ccd93da397328f4e"
+            ))
+            .unwrap()
+        );
+
+        sho.absorb(b"asd");
+        sho.absorb(b"asd");
+        sho.ratchet();
+        let out = sho.squeeze_and_ratchet(65);
+        /*
+        println!("{}", hex::encode(&out));
+        */
+        assert_eq!(
+            out,
+            hex::decode(concat!(
+                "1abc11ad79c535dd455d00459fb85f8dedd4bb3040502442b9c51192243aa8b2",
+                "90a29f518e77b524c1060ec5f2c06db54db025fc7fe9415e06ebb597c985573d37"
+            ))
+            .unwrap()
+        );
+    }
+}
@@ -17,7 +17,7 @@ pub fn sign(
     randomness: &[u8],
 ) -> Result<Vec<u8>, PokshoError> {
     let mut st = Statement::new();
-    st.add("public_key", &[("private_key", "G")]);
+    st.try_add("public_key", &[("private_key", "G")])?;
 
     let mut scalar_args = ScalarArgs::new();
     scalar_args.add("private_key", private_key);
@@ -34,7 +34,7 @@ pub fn verify_signature(
     message: &[u8],
 ) -> Result<(), PokshoError> {
     let mut st = Statement::new();
-    st.add("public_key", &[("private_key", "G")]);
+    st.try_add("public_key", &[("private_key", "G")])?;
 
     let mut point_args = PointArgs::new();
     point_args.add("public_key", public_key);
@@ -9,12 +9,13 @@ use crate::proof::*;
 use crate::scalar::*;
 use crate::shoapi::ShoApi;
 use crate::shohmacsha256::ShoHmacSha256;
+use crate::shoshake256::ShoShake256;
 use crate::simple_types::*;
 use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
-use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
-use curve25519_dalek::traits::MultiscalarMul;
-use std::collections::HashMap;
+use curve25519_dalek::traits::{IsIdentity, MultiscalarMul, VartimeMultiscalarMul};
+use std::collections::{HashMap, HashSet};
 
 // POKSHO implements the "Sigma protocol for arbitrary linear relations" described in section
 // 19.5.3 of https://crypto.stanford.edu/~dabo/cryptobook/BonehShoup_0_4.pdf
@@ -59,6 +60,7 @@ use std::collections::HashMap;
 // Below we describe the hash inputs to SHO/HMAC-SHA256:
 //
 //  L : bytes, label = "POKSHO_Ristretto_SHOHMACSHA256"
+//      (or "POKSHO_Ristretto_SHOSHAKE256" with SHO/SHAKE256 in place of SHO/HMAC-SHA256)
 //  D : bytes, description of statement - see below
 //  a : G1, witness scalars for statement
 //  A : G2, point values for statement = homomorphism(a)
@@ -96,15 +98,135 @@ use std::collections::HashMap;
 //  ---
 //  for index=0..total number of scalars:
 //   RistrettoScalar
+//
+//  Proof format
+//  ---
+//  h : RistrettoScalar
+//  for index=0..total number of scalars:
+//    s[index] : RistrettoScalar, Schnorr response = r + h*a
+//
+// Proofs can also be made in a "batchable" format that carries the commitment R instead of the
+// challenge h, which the verifier recomputes with the hashing above.  The two formats prove the
+// same thing, and each can be computed from the other given the statement and points:
+//
+//  Batchable proof format
+//  ---
+//  for i=1..Ne:
+//    R[i] : RistrettoPoint
+//  for index=0..total number of scalars:
+//    s[index] : RistrettoScalar
+//
+// A compact proof has to be checked by recomputing R and hashing it, but with R in hand, a
+// batch of N batchable proofs can be checked at once.  Each proof k satisfies R_k = F(s_k) -
+// h_k*A_k, one point per equation, so the verifier checks a random linear combination of all of
+// them with a single multiscalar multiplication:
+//
+//  sum(rho_kj * (F_j(s_k) - h_k*A_kj - R_kj)) = 0   for k=1..N, j=1..Ne
+//
+// If any equation doesn't hold, the sum is zero only with negligible probability over the
+// weights rho, which are squeezed from a separate SHO:
+//
+//  sho = SHO("POKSHO_Ristretto_Batch_SHOHMACSHA256")
+//      (or "POKSHO_Ristretto_Batch_SHOSHAKE256" with SHO/SHAKE256)
+//  sho.AbsorbAndRatchet(D || h_1 || s_1 || ... || h_N || s_N)
+//  rho = sho.Squeeze(64 * Ne * N)
+//
+// Each h_k already depends on A_k, R_k and M_k, so the weights depend on everything in the
+// batch and can't be predicted by whoever made the proofs.
 
 use PokshoError::*;
 
-type ScalarIndex = u8;
-type PointIndex = u8;
+/// The most equations a statement can have.
+pub const MAX_EQUATIONS: usize = 255;
+/// The most terms the right-hand side of an equation can have.
+pub const MAX_TERMS: usize = 255;
+/// The most distinct scalars a statement can have.
+pub const MAX_SCALARS: usize = 256;
+/// The most distinct points a statement can have, including the base point "G".
+pub const MAX_POINTS: usize = 256;
+
+/// The SHO used for a statement's Fiat-Shamir transcript.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Transcript {
+    /// [`ShoHmacSha256`], used by all existing proofs.
+    HmacSha256,
+    /// [`ShoShake256`], a duplex sponge that's faster for large statements.
+    Shake256,
+}
+
+impl Default for Transcript {
+    fn default() -> Self {
+        Transcript::HmacSha256
+    }
+}
+
+impl Transcript {
+    // Starts a transcript by absorbing the protocol label L
+    pub(crate) fn start(self) -> TranscriptSho {
+        self.start_with_label(
+            b"POKSHO_Ristretto_SHOHMACSHA256",
+            b"POKSHO_Ristretto_SHOSHAKE256",
+        )
+    }
+
+    // Starts a transcript with whichever of the labels matches this SHO, for proofs that
+    // build on Statement with their own labels
+    pub(crate) fn start_with_label(
+        self,
+        hmac_sha256_label: &[u8],
+        shake256_label: &[u8],
+    ) -> TranscriptSho {
+        match self {
+            Transcript::HmacSha256 => {
+                TranscriptSho::HmacSha256(ShoHmacSha256::new(hmac_sha256_label))
+            }
+            Transcript::Shake256 => TranscriptSho::Shake256(ShoShake256::new(shake256_label)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) enum TranscriptSho {
+    HmacSha256(ShoHmacSha256),
+    Shake256(ShoShake256),
+}
+
+impl TranscriptSho {
+    pub(crate) fn absorb(&mut self, input: &[u8]) {
+        match self {
+            TranscriptSho::HmacSha256(sho) => sho.absorb(input),
+            TranscriptSho::Shake256(sho) => sho.absorb(input),
+        }
+    }
+
+    pub(crate) fn ratchet(&mut self) {
+        match self {
+            TranscriptSho::HmacSha256(sho) => sho.ratchet(),
+            TranscriptSho::Shake256(sho) => sho.ratchet(),
+        }
+    }
+
+    pub(crate) fn absorb_and_ratchet(&mut self, input: &[u8]) {
+        self.absorb(input);
+        self.ratchet();
+    }
+
+    pub(crate) fn squeeze_and_ratchet(&mut self, outlen: usize) -> Vec<u8> {
+        match self {
+            TranscriptSho::HmacSha256(sho) => sho.squeeze_and_ratchet(outlen),
+            TranscriptSho::Shake256(sho) => sho.squeeze_and_ratchet(outlen),
+        }
+    }
+}
 
-struct Term {
-    scalar: ScalarIndex,
-    point: PointIndex,
+pub(crate) type ScalarIndex = u8;
+pub(crate) type PointIndex = u8;
+
+#[doc(hidden)]
+#[derive(Copy, Clone)]
+pub struct Term {
+    pub scalar: ScalarIndex,
+    pub point: PointIndex,
 }
 
 struct Equation {
@@ -112,6 +234,21 @@ struct Equation {
     rhs: Vec<Term>,
 }
 
+/// Read access to an equation, whether it was built at runtime or at compile time.
+pub(crate) trait EquationTerms {
+    fn lhs(&self) -> PointIndex;
+    fn rhs(&self) -> &[Term];
+}
+
+impl EquationTerms for Equation {
+    fn lhs(&self) -> PointIndex {
+        self.lhs
+    }
+    fn rhs(&self) -> &[Term] {
+        &self.rhs
+    }
+}
+
 pub struct Statement {
     // We store the Schnorr ZKP equations using scalar and point indices
     // which are numbered from zero, and are assigned sequentially based
@@ -127,10 +264,17 @@ pub struct Statement {
     scalar_vec: Vec<String>,
     point_map: HashMap<String, PointIndex>,
     point_vec: Vec<String>,
+    // The description D, kept up to date by try_add() so it isn't re-encoded for every proof.
+    description: Vec<u8>,
+    transcript: Transcript,
 }
 
 impl Statement {
     pub fn new() -> Self {
+        Self::with_transcript(Transcript::default())
+    }
+
+    pub fn with_transcript(transcript: Transcript) -> Self {
         let mut point_map = HashMap::new();
         point_map.insert("G".to_string(), 0); // G is base point
         let point_vec = vec!["G".to_string()];
@@ -140,35 +284,134 @@ impl Statement {
             scalar_vec: Vec::new(),
             point_map,
             point_vec,
+            description: vec![0],
+            transcript,
         }
     }
 
-    // panics on invalid input
+    pub fn transcript(&self) -> Transcript {
+        self.transcript
+    }
+
+    #[deprecated(note = "panics on invalid input; use try_add() instead")]
     pub fn add(&mut self, lhs_str: &str, rhs_pairs: &[(&str, &str)]) {
-        if (lhs_str.is_empty())
-            || (rhs_pairs.is_empty())
-            || (rhs_pairs.len() > 255)
-            || (self.equations.len() >= 255)
+        if let Err(e) = self.try_add(lhs_str, rhs_pairs) {
+            panic!("Invalid equation for statement: {:?}", e);
+        }
+    }
+
+    /// Adds the equation `lhs_str = sum(scalar * point)` for the pairs in `rhs_pairs`.
+    ///
+    /// On error the statement is left unchanged.
+    pub fn try_add(
+        &mut self,
+        lhs_str: &str,
+        rhs_pairs: &[(&str, &str)],
+    ) -> Result<(), PokshoError> {
+        if rhs_pairs.is_empty() {
+            return Err(BadStatementNoTerms);
+        }
+        if rhs_pairs.len() > MAX_TERMS {
+            return Err(BadStatementTooManyTerms);
+        }
+        if self.equations.len() >= MAX_EQUATIONS {
+            return Err(BadStatementTooManyEquations);
+        }
+        if lhs_str.is_empty()
+            || rhs_pairs
+                .iter()
+                .any(|(scalar, point)| scalar.is_empty() || point.is_empty())
         {
-            panic!("Unexpected input sizes to add");
+            return Err(BadStatementEmptyName);
         }
-        let lhs = self
-            .add_point(lhs_str.to_string())
-            .expect("add_point succeeds");
-        let mut rhs = Vec::<Term>::with_capacity(rhs_pairs.len());
-        for pair in rhs_pairs {
-            if pair.0.is_empty() || pair.1.is_empty() {
-                panic!("Unexpected pair size");
+
+        // Check the new names fit before assigning any indices
+        let mut new_scalars = HashSet::new();
+        let mut new_points = HashSet::new();
+        for point in std::iter::once(lhs_str).chain(rhs_pairs.iter().map(|(_, point)| *point)) {
+            if !self.point_map.contains_key(point) {
+                new_points.insert(point);
             }
-            let scalar = self
-                .add_scalar(pair.0.to_string())
-                .expect("add_scalar succeeds");
-            let point = self
-                .add_point(pair.1.to_string())
-                .expect("add_point succeeds");
+        }
+        for (scalar, _) in rhs_pairs {
+            if !self.scalar_map.contains_key(*scalar) {
+                new_scalars.insert(*scalar);
+            }
+        }
+        if self.scalar_vec.len() + new_scalars.len() > MAX_SCALARS {
+            return Err(BadStatementTooManyScalars);
+        }
+        if self.point_vec.len() + new_points.len() > MAX_POINTS {
+            return Err(BadStatementTooManyPoints);
+        }
+
+        let lhs = self.add_point(lhs_str);
+        let mut rhs = Vec::<Term>::with_capacity(rhs_pairs.len());
+        for (scalar, point) in rhs_pairs {
+            let scalar = self.add_scalar(scalar);
+            let point = self.add_point(point);
             rhs.push(Term { scalar, point });
         }
+        self.description[0] += 1;
+        self.description.push(lhs);
+        self.description.push(rhs.len() as u8);
+        for Term { scalar, point } in &rhs {
+            self.description.push(*scalar);
+            self.description.push(*point);
+        }
         self.equations.push(Equation { lhs, rhs });
+        Ok(())
+    }
+
+    pub fn num_equations(&self) -> usize {
+        self.equations.len()
+    }
+
+    pub fn num_scalars(&self) -> usize {
+        self.scalar_vec.len()
+    }
+
+    /// The number of points, including the base point "G".
+    pub fn num_points(&self) -> usize {
+        self.point_vec.len()
+    }
+
+    /// Checks that the arguments match this statement exactly, before proving or verifying.
+    ///
+    /// Unlike [`Self::prove`], this reports which kind of argument is missing or extra. Pass `None`
+    /// for `scalar_args` to check only the points, as a verifier would.
+    pub fn validate(
+        &self,
+        scalar_args: Option<&ScalarArgs>,
+        point_args: &PointArgs,
+    ) -> Result<(), PokshoError> {
+        if self.equations.is_empty() {
+            return Err(BadStatementNoEquations);
+        }
+        if let Some(scalar_args) = scalar_args {
+            if scalar_args
+                .0
+                .keys()
+                .any(|name| !self.scalar_map.contains_key(name))
+            {
+                return Err(BadArgsUnusedScalarArg);
+            }
+            if scalar_args.0.len() != self.scalar_vec.len() {
+                return Err(BadArgsMissingScalarArg);
+            }
+        }
+        // "G" is always the base point, so it's never an argument
+        if point_args
+            .0
+            .keys()
+            .any(|name| name == "G" || !self.point_map.contains_key(name))
+        {
+            return Err(BadArgsUnusedPointArg);
+        }
+        if point_args.0.len() != self.point_vec.len() - 1 {
+            return Err(BadArgsMissingPointArg);
+        }
+        Ok(())
     }
 
     pub fn prove(
@@ -178,226 +421,273 @@ impl Statement {
         message: &[u8],
         randomness: &[u8], // must be 32 bytes
     ) -> Result<Vec<u8>, PokshoError> {
-        if randomness.len() != 32 {
-            return Err(PokshoError::BadArgs);
+        if self.equations.is_empty() {
+            return Err(BadStatementNoEquations);
         }
         let g1 = self.sort_scalars(scalar_args)?;
         let all_points = self.sort_points(point_args)?;
+        prove_with_sorted_args(
+            self.transcript,
+            &self.description,
+            &self.equations,
+            &g1,
+            &all_points,
+            message,
+            randomness,
+        )
+    }
 
-        // Absorb the protocol label L, description of statement D, and point values A
-        let mut sho = ShoHmacSha256::new(b"POKSHO_Ristretto_SHOHMACSHA256"); // L
-        sho.absorb(&self.to_bytes()); // D
-        for point in &all_points {
-            // A
-            sho.absorb(&point.compress().to_bytes());
+    pub fn verify_proof(
+        &self,
+        proof_bytes: &[u8],
+        point_args: &PointArgs,
+        message: &[u8],
+    ) -> Result<(), PokshoError> {
+        if self.equations.is_empty() {
+            return Err(BadStatementNoEquations);
         }
+        let all_points = self.sort_points(point_args)?;
+        verify_proof_with_sorted_args(
+            self.transcript,
+            &self.description,
+            &self.equations,
+            self.scalar_vec.len(),
+            proof_bytes,
+            &all_points,
+            message,
+        )
+    }
 
-        sho.ratchet(); // Ratchet
+    /// Like [`prove`](Self::prove), but the proof carries the commitment rather than the
+    /// challenge, so that it can be checked with [`verify_proofs_batch`](Self::verify_proofs_batch).
+    pub fn prove_batchable(
+        &self,
+        scalar_args: &ScalarArgs,
+        point_args: &PointArgs,
+        message: &[u8],
+        randomness: &[u8], // must be 32 bytes
+    ) -> Result<Vec<u8>, PokshoError> {
+        let proof_bytes = self.prove(scalar_args, point_args, message, randomness)?;
+        let proof = Proof::from_slice(&proof_bytes).expect("valid proof");
+        let all_points = self.sort_points(point_args)?;
+        // The prover's points might be secret, so this isn't vartime
+        let commitment = self.homomorphism(&proof.response, &all_points, Some(proof.challenge));
 
-        // Random nonce
-        // "Synthetic" nonce based on hashing randomness, witness (private scalars) and message
-        let mut sho2 = sho.clone();
-        sho2.absorb(randomness); // Z
-        for scalar in &g1 {
-            sho2.absorb(&scalar.to_bytes()); // a
+        let mut bytes = Vec::with_capacity(self.batchable_proof_len());
+        for point in &commitment {
+            bytes.extend_from_slice(point.compress().as_bytes());
         }
-        sho2.ratchet(); // Ratchet
-        sho2.absorb_and_ratchet(message); // M
-        let blinding_scalar_bytes = sho2.squeeze_and_ratchet(g1.len() * 64);
-
-        let mut nonce = self.g1_new();
-        for i in 0..g1.len() {
-            nonce.push(scalar_from_slice_wide(
-                &blinding_scalar_bytes[i * 64..(i + 1) * 64],
-            ))
+        for scalar in &proof.response {
+            bytes.extend_from_slice(scalar.as_bytes());
         }
+        Ok(bytes)
+    }
 
-        // Commitment from nonce by applying homomorphism F: commitment = F(nonce)
-        let commitment = self.homomorphism_with_subtraction(&nonce, &all_points, None);
-
-        // Challenge from commitment and message
-        for point in &commitment {
-            sho.absorb(&point.compress().to_bytes());
-        }
-        sho.absorb_and_ratchet(message);
-        let challenge = scalar_from_slice_wide(&sho.squeeze_and_ratchet(64));
+    /// The size of every proof from [`prove_batchable`](Self::prove_batchable).
+    pub fn batchable_proof_len(&self) -> usize {
+        32 * (self.equations.len() + self.scalar_vec.len())
+    }
 
-        // Response
-        let mut response = self.g1_new();
-        for i in 0..g1.len() {
-            response.push(nonce[i] + (g1[i] * challenge));
+    /// Checks several proofs from [`prove_batchable`](Self::prove_batchable), each with its own
+    /// points and message.
+    ///
+    /// The proofs are checked together with a single multiscalar multiplication. If that fails,
+    /// they're checked one at a time, and the index of the first proof that didn't verify is
+    /// returned along with its error.
+    pub fn verify_proofs_batch(
+        &self,
+        proofs: &[(&[u8], &PointArgs, &[u8])],
+    ) -> Result<(), (usize, PokshoError)> {
+        let decoded: Vec<Result<BatchableProof, PokshoError>> = proofs
+            .iter()
+            .map(|(proof_bytes, point_args, message)| {
+                self.decode_batchable_proof(proof_bytes, point_args, message)
+            })
+            .collect();
+        if decoded.iter().all(Result::is_ok) {
+            let decoded: Vec<&BatchableProof> = decoded.iter().flatten().collect();
+            if self.check_batch(&decoded) {
+                return Ok(());
+            }
         }
 
-        let proof = Proof {
-            challenge,
-            response,
-        };
-
-        // Verify before returning, since a bad proof could indicate
-        // a glitched/faulty response that leaks private keys, or incorrect inputs
-        let proof_bytes = proof.to_bytes();
-        match self.verify_proof(&proof_bytes, point_args, message) {
-            Err(VerificationFailure) => Err(ProofCreationVerificationFailure),
-            Err(e) => Err(e),
-            Ok(_) => Ok(proof_bytes),
+        for (i, proof) in decoded.into_iter().enumerate() {
+            let proof = proof.map_err(|e| (i, e))?;
+            let commitment = homomorphism_with_subtraction(
+                &self.equations,
+                &proof.response,
+                &proof.all_points,
+                Some(proof.challenge),
+                true,
+            );
+            if commitment != proof.commitment {
+                return Err((i, VerificationFailure));
+            }
         }
+        Ok(())
     }
 
-    pub fn verify_proof(
+    // Decodes a batchable proof and recomputes its challenge, just as for a compact proof
+    fn decode_batchable_proof(
         &self,
         proof_bytes: &[u8],
         point_args: &PointArgs,
         message: &[u8],
-    ) -> Result<(), PokshoError> {
-        let proof = Proof::from_slice(proof_bytes).ok_or(VerificationFailure)?;
-        if proof.response.len() != self.scalar_vec.len() {
-            return Err(VerificationFailure);
+    ) -> Result<BatchableProof, PokshoError> {
+        if self.equations.is_empty() {
+            return Err(BadStatementNoEquations);
         }
         let all_points = self.sort_points(point_args)?;
+        if proof_bytes.len() != self.batchable_proof_len() {
+            return Err(VerificationFailure);
+        }
+        let (commitment_bytes, response_bytes) = proof_bytes.split_at(32 * self.equations.len());
+        let commitment = commitment_bytes
+            .chunks_exact(32)
+            .map(|chunk| {
+                CompressedRistretto::from_slice(chunk)
+                    .decompress()
+                    .ok_or(VerificationFailure)
+            })
+            .collect::<Result<G2, _>>()?;
+        let response = response_bytes
+            .chunks_exact(32)
+            .map(|chunk| scalar_from_slice_canonical(chunk).ok_or(VerificationFailure))
+            .collect::<Result<G1, _>>()?;
 
-        // Absorb the protocol label L, statement description D, and point values A
-        let mut sho = ShoHmacSha256::new(b"POKSHO_Ristretto_SHOHMACSHA256"); // L
-        sho.absorb(&self.to_bytes()); // D
+        let mut sho = self.transcript.start(); // L
+        sho.absorb(&self.description); // D
         for point in &all_points {
-            // A
-            sho.absorb(&point.compress().to_bytes());
+            sho.absorb(&point.compress().to_bytes()); // A
         }
         sho.ratchet();
-
-        // Reconstruct commitment
-        //
-        // commitment R = F(s) - h*A
-        //
-        // F: homomorphism
-        // s: response element in G1
-        // h: challenge scalar
-        // A: element in G2 whose preimage we are proving knowledge of (i.e. LHS of Schnorr eqns)
-        let commitment =
-            self.homomorphism_with_subtraction(&proof.response, &all_points, Some(proof.challenge));
-
-        // Reconstruct challenge from commitment and message
-        for point in &commitment {
-            // R
-            sho.absorb(&point.compress().to_bytes());
-        }
+        sho.absorb(commitment_bytes); // R
         sho.absorb_and_ratchet(message); // M
         let challenge = scalar_from_slice_wide(&sho.squeeze_and_ratchet(64));
 
-        // Check challenge (const time)
-        if challenge == proof.challenge {
-            Ok(())
-        } else {
-            Err(VerificationFailure)
+        Ok(BatchableProof {
+            all_points,
+            commitment,
+            response,
+            challenge,
+        })
+    }
+
+    // Checks that the random linear combination of every equation R = F(s) - h*A in the batch
+    // holds, merging the terms for the base point
+    fn check_batch(&self, proofs: &[&BatchableProof]) -> bool {
+        let mut sho = self.transcript.start_with_label(
+            b"POKSHO_Ristretto_Batch_SHOHMACSHA256",
+            b"POKSHO_Ristretto_Batch_SHOSHAKE256",
+        );
+        sho.absorb(&self.description); // D
+        for proof in proofs {
+            sho.absorb(proof.challenge.as_bytes()); // h
+            for scalar in &proof.response {
+                sho.absorb(scalar.as_bytes()); // s
+            }
+        }
+        sho.ratchet();
+        let weight_bytes = sho.squeeze_and_ratchet(64 * self.equations.len() * proofs.len());
+        let mut weights = weight_bytes.chunks_exact(64).map(scalar_from_slice_wide);
+
+        let mut scalars = Vec::new();
+        let mut points = Vec::new();
+        let mut base_point_scalar = Scalar::zero();
+        for proof in proofs {
+            let mut point_scalars = vec![Scalar::zero(); proof.all_points.len()];
+            for (equation, commitment) in self.equations.iter().zip(&proof.commitment) {
+                let weight = weights.next().expect("enough weights");
+                for term in &equation.rhs {
+                    point_scalars[term.point as usize] +=
+                        weight * proof.response[term.scalar as usize];
+                }
+                point_scalars[equation.lhs as usize] -= weight * proof.challenge;
+                scalars.push(-weight);
+                points.push(*commitment);
+            }
+            base_point_scalar += point_scalars[0];
+            scalars.extend_from_slice(&point_scalars[1..]);
+            points.extend_from_slice(&proof.all_points[1..]);
         }
+        scalars.push(base_point_scalar);
+        points.push(RISTRETTO_BASEPOINT_POINT);
+
+        // Everything a verifier handles is public
+        RistrettoPoint::vartime_multiscalar_mul(scalars, points).is_identity()
     }
 
-    fn add_scalar(&mut self, scalar_name: String) -> Result<ScalarIndex, PokshoError> {
-        match self.scalar_map.get(&scalar_name) {
-            Some(index) => Ok(*index),
+    // The caller must have checked there's room for a new scalar
+    fn add_scalar(&mut self, scalar_name: &str) -> ScalarIndex {
+        match self.scalar_map.get(scalar_name) {
+            Some(index) => *index,
             None => {
                 assert!(self.scalar_map.len() == self.scalar_vec.len());
-                let new_index = self.scalar_map.len();
-                if new_index > 255 {
-                    return Err(BadArgs);
-                }
-                let new_index = new_index as u8;
-                self.scalar_map.insert(scalar_name.clone(), new_index);
-                self.scalar_vec.push(scalar_name.clone());
-                Ok(new_index)
+                let new_index = self.scalar_map.len() as u8;
+                self.scalar_map.insert(scalar_name.to_string(), new_index);
+                self.scalar_vec.push(scalar_name.to_string());
+                new_index
             }
         }
     }
 
-    fn add_point(&mut self, point_name: String) -> Result<PointIndex, PokshoError> {
-        match self.point_map.get(&point_name) {
-            Some(index) => Ok(*index),
+    // The caller must have checked there's room for a new point
+    fn add_point(&mut self, point_name: &str) -> PointIndex {
+        match self.point_map.get(point_name) {
+            Some(index) => *index,
             None => {
                 assert!(self.point_map.len() == self.point_vec.len());
-                let new_index = self.point_map.len();
-                if new_index > 255 {
-                    return Err(BadArgs);
-                }
-                let new_index = new_index as u8;
-                self.point_map.insert(point_name.clone(), new_index);
-                self.point_vec.push(point_name.clone());
-                Ok(new_index)
+                let new_index = self.point_map.len() as u8;
+                self.point_map.insert(point_name.to_string(), new_index);
+                self.point_vec.push(point_name.to_string());
+                new_index
             }
         }
     }
 
-    fn to_bytes(&self) -> Vec<u8> {
-        assert!(
-            self.equations.len() <= 255
-                && self.scalar_map.len() <= 256
-                && self.point_map.len() <= 256
-        );
-        let mut v = vec![self.equations.len() as u8];
-        for Equation { lhs, rhs } in &self.equations {
-            assert!(*lhs as usize <= self.point_map.len());
-            assert!(rhs.len() <= 255);
-            v.push(*lhs);
-            v.push(rhs.len() as u8);
-            for Term { scalar, point } in rhs {
-                assert!((*scalar as usize) < self.scalar_map.len());
-                assert!((*point as usize) < self.point_map.len());
-                v.push(*scalar);
-                v.push(*point);
-            }
-        }
-        v
+    // Whether some equation is exactly lhs = a*G + b*generator, for two different scalars a and b
+    pub(crate) fn has_commitment_equation(&self, lhs: &str, generator: &str) -> bool {
+        let (lhs, generator) = match (self.point_map.get(lhs), self.point_map.get(generator)) {
+            (Some(lhs), Some(generator)) if *generator != 0 => (*lhs, *generator),
+            _ => return false,
+        };
+        self.equations.iter().any(|equation| {
+            equation.lhs == lhs
+                && match equation.rhs[..] {
+                    [a, b] => {
+                        a.scalar != b.scalar
+                            && ((a.point == 0 && b.point == generator)
+                                || (a.point == generator && b.point == 0))
+                    }
+                    _ => false,
+                }
+        })
     }
 
-    fn g1_new(&self) -> G1 {
-        G1::with_capacity(self.scalar_vec.len())
+    #[cfg(test)]
+    fn to_bytes(&self) -> Vec<u8> {
+        self.description.clone()
     }
 
-    fn g2_new(&self) -> G2 {
-        G2::with_capacity(self.equations.len())
+    pub(crate) fn description(&self) -> &[u8] {
+        &self.description
     }
 
-    // Applies the homomorphism from G1 -> G2
-    // If given a challenge h, also subtracts h*A for efficient recovery of
-    // the Schnorr commitment
-    fn homomorphism_with_subtraction(
+    // Applies the homomorphism to g1, subtracting challenge*A if given a challenge
+    pub(crate) fn homomorphism(
         &self,
         g1: &[Scalar],
         all_points: &[RistrettoPoint],
         challenge: Option<Scalar>,
     ) -> G2 {
-        let mut g2 = self.g2_new();
-        for e in &self.equations {
-            let scalar_iter = e
-                .rhs
-                .iter()
-                .map(|Term { scalar, point: _ }| g1[*scalar as usize]);
-            let point_iter = e
-                .rhs
-                .iter()
-                .map(|Term { scalar: _, point }| all_points[*point as usize]);
-
-            // Can this be done without a vector?
-            let mut v_scalar = Vec::<Scalar>::with_capacity(1);
-            let mut v_point = Vec::<RistrettoPoint>::with_capacity(1);
-            if let Some(h) = challenge {
-                v_scalar.push(-h);
-                v_point.push(all_points[e.lhs as usize]);
-            };
-
-            let scalar_iter = scalar_iter.chain(v_scalar);
-            let point_iter = point_iter.chain(v_point);
-
-            // Could use vartime_multiscalar_mul in some cases, but in the
-            // general case points might be secret (not just scalars!)
-            g2.push(RistrettoPoint::multiscalar_mul(scalar_iter, point_iter));
-        }
-        g2
+        homomorphism_with_subtraction(&self.equations, g1, all_points, challenge, false)
     }
 
-    fn sort_scalars(&self, scalar_args: &ScalarArgs) -> Result<G1, PokshoError> {
+    pub(crate) fn sort_scalars(&self, scalar_args: &ScalarArgs) -> Result<G1, PokshoError> {
         if scalar_args.0.len() != self.scalar_vec.len() {
             return Err(BadArgsWrongNumberOfScalarArgs);
         }
-        let mut g1 = self.g1_new();
+        let mut g1 = G1::with_capacity(self.scalar_vec.len());
         for scalar_name in &self.scalar_vec {
             g1.push(
                 *scalar_args
@@ -409,7 +699,10 @@ impl Statement {
         Ok(g1)
     }
 
-    fn sort_points(&self, point_args: &PointArgs) -> Result<Vec<RistrettoPoint>, PokshoError> {
+    pub(crate) fn sort_points(
+        &self,
+        point_args: &PointArgs,
+    ) -> Result<Vec<RistrettoPoint>, PokshoError> {
         if point_args.0.len() != self.point_vec.len() - 1 {
             return Err(BadArgsWrongNumberOfPointArgs);
         }
@@ -421,12 +714,208 @@ impl Statement {
     }
 }
 
+// A proof from Statement::prove_batchable, decoded, with its challenge recomputed
+struct BatchableProof {
+    all_points: G2,
+    commitment: G2,
+    response: G1,
+    challenge: Scalar,
+}
+
 impl Default for Statement {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// Proves knowledge of `g1` for the statement with the given description and equations.
+///
+/// `g1` and `all_points` must already be in index order, with the base point first in
+/// `all_points`.
+pub(crate) fn prove_with_sorted_args<E: EquationTerms>(
+    transcript: Transcript,
+    description: &[u8],
+    equations: &[E],
+    g1: &[Scalar],
+    all_points: &[RistrettoPoint],
+    message: &[u8],
+    randomness: &[u8], // must be 32 bytes
+) -> Result<Vec<u8>, PokshoError> {
+    if randomness.len() != 32 {
+        return Err(PokshoError::BadArgs);
+    }
+
+    // Absorb the protocol label L, description of statement D, and point values A
+    let mut sho = transcript.start(); // L
+    sho.absorb(description); // D
+    for point in all_points {
+        // A
+        sho.absorb(&point.compress().to_bytes());
+    }
+
+    sho.ratchet(); // Ratchet
+
+    // Random nonce
+    // "Synthetic" nonce based on hashing randomness, witness (private scalars) and message
+    let mut sho2 = sho.clone();
+    sho2.absorb(randomness); // Z
+    for scalar in g1 {
+        sho2.absorb(&scalar.to_bytes()); // a
+    }
+    sho2.ratchet(); // Ratchet
+    sho2.absorb_and_ratchet(message); // M
+    let blinding_scalar_bytes = sho2.squeeze_and_ratchet(g1.len() * 64);
+
+    let mut nonce = G1::with_capacity(g1.len());
+    for i in 0..g1.len() {
+        nonce.push(scalar_from_slice_wide(
+            &blinding_scalar_bytes[i * 64..(i + 1) * 64],
+        ))
+    }
+
+    // Commitment from nonce by applying homomorphism F: commitment = F(nonce)
+    let commitment = homomorphism_with_subtraction(equations, &nonce, all_points, None, false);
+
+    // Challenge from commitment and message
+    for point in &commitment {
+        sho.absorb(&point.compress().to_bytes());
+    }
+    sho.absorb_and_ratchet(message);
+    let challenge = scalar_from_slice_wide(&sho.squeeze_and_ratchet(64));
+
+    // Response
+    let mut response = G1::with_capacity(g1.len());
+    for i in 0..g1.len() {
+        response.push(nonce[i] + (g1[i] * challenge));
+    }
+
+    let proof = Proof {
+        challenge,
+        response,
+    };
+
+    // Verify before returning, since a bad proof could indicate
+    // a glitched/faulty response that leaks private keys, or incorrect inputs
+    let proof_bytes = proof.to_bytes();
+    match verify_proof_with_sorted_args(
+        transcript,
+        description,
+        equations,
+        g1.len(),
+        &proof_bytes,
+        all_points,
+        message,
+    ) {
+        Err(VerificationFailure) => Err(ProofCreationVerificationFailure),
+        Err(e) => Err(e),
+        Ok(_) => Ok(proof_bytes),
+    }
+}
+
+/// Checks a proof for the statement with the given description and equations.
+///
+/// `all_points` must already be in index order, with the base point first.
+pub(crate) fn verify_proof_with_sorted_args<E: EquationTerms>(
+    transcript: Transcript,
+    description: &[u8],
+    equations: &[E],
+    num_scalars: usize,
+    proof_bytes: &[u8],
+    all_points: &[RistrettoPoint],
+    message: &[u8],
+) -> Result<(), PokshoError> {
+    let proof = Proof::from_slice(proof_bytes).ok_or(VerificationFailure)?;
+    if proof.response.len() != num_scalars {
+        return Err(VerificationFailure);
+    }
+
+    // Absorb the protocol label L, statement description D, and point values A
+    let mut sho = transcript.start(); // L
+    sho.absorb(description); // D
+    for point in all_points {
+        // A
+        sho.absorb(&point.compress().to_bytes());
+    }
+    sho.ratchet();
+
+    // Reconstruct commitment
+    //
+    // commitment R = F(s) - h*A
+    //
+    // F: homomorphism
+    // s: response element in G1
+    // h: challenge scalar
+    // A: element in G2 whose preimage we are proving knowledge of (i.e. LHS of Schnorr eqns)
+    let commitment = homomorphism_with_subtraction(
+        equations,
+        &proof.response,
+        all_points,
+        Some(proof.challenge),
+        false,
+    );
+
+    // Reconstruct challenge from commitment and message
+    for point in &commitment {
+        // R
+        sho.absorb(&point.compress().to_bytes());
+    }
+    sho.absorb_and_ratchet(message); // M
+    let challenge = scalar_from_slice_wide(&sho.squeeze_and_ratchet(64));
+
+    // Check challenge (const time)
+    if challenge == proof.challenge {
+        Ok(())
+    } else {
+        Err(VerificationFailure)
+    }
+}
+
+// Applies the homomorphism from G1 -> G2
+// If given a challenge h, also subtracts h*A for efficient recovery of
+// the Schnorr commitment
+// Only use vartime if all the scalars and points are public
+fn homomorphism_with_subtraction<E: EquationTerms>(
+    equations: &[E],
+    g1: &[Scalar],
+    all_points: &[RistrettoPoint],
+    challenge: Option<Scalar>,
+    vartime: bool,
+) -> G2 {
+    let mut g2 = G2::with_capacity(equations.len());
+    for e in equations {
+        let scalar_iter = e
+            .rhs()
+            .iter()
+            .map(|Term { scalar, point: _ }| g1[*scalar as usize]);
+        let point_iter = e
+            .rhs()
+            .iter()
+            .map(|Term { scalar: _, point }| all_points[*point as usize]);
+
+        // Can this be done without a vector?
+        let mut v_scalar = Vec::<Scalar>::with_capacity(1);
+        let mut v_point = Vec::<RistrettoPoint>::with_capacity(1);
+        if let Some(h) = challenge {
+            v_scalar.push(-h);
+            v_point.push(all_points[e.lhs() as usize]);
+        };
+
+        let scalar_iter = scalar_iter.chain(v_scalar);
+        let point_iter = point_iter.chain(v_point);
+
+        // In the general case points might be secret (not just scalars!)
+        if vartime {
+            g2.push(RistrettoPoint::vartime_multiscalar_mul(
+                scalar_iter,
+                point_iter,
+            ));
+        } else {
+            g2.push(RistrettoPoint::multiscalar_mul(scalar_iter, point_iter));
+        }
+    }
+    g2
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(non_snake_case)]
@@ -434,18 +923,19 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::unwrap_used)]
     fn test_statement_encoding() {
         let mut s = Statement::new();
-        s.add("A", &[("a", "G")]);
+        s.try_add("A", &[("a", "G")]).unwrap();
         assert!(s.to_bytes() == vec![1, 1, 1, 0, 0]);
 
         let mut s = Statement::new();
-        s.add("A", &[("a", "G")]);
-        s.add("B", &[("a", "H")]);
+        s.try_add("A", &[("a", "G")]).unwrap();
+        s.try_add("B", &[("a", "H")]).unwrap();
         assert!(s.to_bytes() == vec![2, 1, 1, 0, 0, 2, 1, 0, 3]);
 
         let mut s = Statement::new();
-        s.add("A", &[("a", "G"), ("b", "H")]);
+        s.try_add("A", &[("a", "G"), ("b", "H")]).unwrap();
         assert!(s.to_bytes() == vec![1, 1, 2, 0, 0, 1, 2]);
     }
 
@@ -510,8 +1000,9 @@ mod tests {
         let B = c * H + d * I;
 
         let mut st = Statement::new();
-        st.add("A", &[("a", "G"), ("b", "H"), ("c", "I")]);
-        st.add("B", &[("c", "H"), ("d", "I")]);
+        st.try_add("A", &[("a", "G"), ("b", "H"), ("c", "I")])
+            .unwrap();
+        st.try_add("B", &[("c", "H"), ("d", "I")]).unwrap();
         assert!(st.to_bytes() == vec![2, 1, 3, 0, 0, 1, 2, 2, 3, 4, 2, 2, 2, 3, 3]);
 
         let mut scalar_args = ScalarArgs::new();
@@ -610,4 +1101,240 @@ mod tests {
             Err(VerificationFailure)
         ));
     }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_verify_proofs_batch() {
+        let mut st = Statement::new();
+        st.try_add("A", &[("a", "G")]).unwrap();
+        st.try_add("B", &[("a", "H")]).unwrap();
+
+        let H = Scalar::from(1000u64) * RISTRETTO_BASEPOINT_POINT;
+        let messages: Vec<[u8; 1]> = (0..4u8).map(|i| [i]).collect();
+        let mut point_args_list = Vec::new();
+        let mut proofs = Vec::new();
+        for (i, message) in messages.iter().enumerate() {
+            let a = Scalar::from(i as u64 + 1);
+            let mut scalar_args = ScalarArgs::new();
+            scalar_args.add("a", a);
+            let mut point_args = PointArgs::new();
+            point_args.add("A", a * RISTRETTO_BASEPOINT_POINT);
+            point_args.add("B", a * H);
+            point_args.add("H", H);
+            let proof = st
+                .prove_batchable(&scalar_args, &point_args, message, &[i as u8; 32])
+                .unwrap();
+            assert_eq!(proof.len(), st.batchable_proof_len());
+
+            // Same response as the compact proof
+            let compact_proof = st
+                .prove(&scalar_args, &point_args, message, &[i as u8; 32])
+                .unwrap();
+            assert_eq!(proof[64..], compact_proof[32..]);
+
+            proofs.push(proof);
+            point_args_list.push(point_args);
+        }
+
+        let mut batch: Vec<(&[u8], &PointArgs, &[u8])> = proofs
+            .iter()
+            .zip(&point_args_list)
+            .zip(&messages)
+            .map(|((proof, point_args), message)| (&proof[..], point_args, &message[..]))
+            .collect();
+        st.verify_proofs_batch(&batch).unwrap();
+        st.verify_proofs_batch(&[]).unwrap();
+
+        // Swapping two messages breaks both proofs; the first is reported
+        batch[1].2 = &messages[2];
+        batch[2].2 = &messages[1];
+        assert!(matches!(
+            st.verify_proofs_batch(&batch),
+            Err((1, VerificationFailure))
+        ));
+        batch[1].2 = &messages[1];
+        batch[2].2 = &messages[2];
+
+        // A commitment that doesn't match the response
+        let mut bad_proof = proofs[2].clone();
+        bad_proof[..32].copy_from_slice(RISTRETTO_BASEPOINT_POINT.compress().as_bytes());
+        batch[2].0 = &bad_proof;
+        assert!(matches!(
+            st.verify_proofs_batch(&batch),
+            Err((2, VerificationFailure))
+        ));
+        batch[2].0 = &proofs[2];
+
+        // Compact proofs aren't accepted
+        let mut scalar_args = ScalarArgs::new();
+        scalar_args.add("a", Scalar::from(1u64));
+        let compact_proof = st
+            .prove(&scalar_args, &point_args_list[0], &messages[0], &[0u8; 32])
+            .unwrap();
+        batch[0].0 = &compact_proof;
+        assert!(matches!(
+            st.verify_proofs_batch(&batch),
+            Err((0, VerificationFailure))
+        ));
+        batch[0].0 = &proofs[0];
+
+        let empty_point_args = PointArgs::new();
+        batch[3].1 = &empty_point_args;
+        assert!(matches!(
+            st.verify_proofs_batch(&batch),
+            Err((3, PokshoError::BadArgsWrongNumberOfPointArgs))
+        ));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_try_add() {
+        let mut st = Statement::new();
+        assert!(matches!(st.try_add("A", &[]), Err(BadStatementNoTerms)));
+        assert!(matches!(
+            st.try_add("", &[("a", "G")]),
+            Err(BadStatementEmptyName)
+        ));
+        assert!(matches!(
+            st.try_add("A", &[("a", "G"), ("", "H")]),
+            Err(BadStatementEmptyName)
+        ));
+        let too_many_terms = vec![("a", "G"); MAX_TERMS + 1];
+        assert!(matches!(
+            st.try_add("A", &too_many_terms),
+            Err(BadStatementTooManyTerms)
+        ));
+        assert_eq!(st.num_equations(), 0);
+        assert_eq!(st.to_bytes(), vec![0]);
+
+        // Scalars and points are counted across equations, and a failed add changes nothing
+        let names: Vec<String> = (0..MAX_SCALARS).map(|i| format!("x{}", i)).collect();
+        let pairs: Vec<(&str, &str)> = names.iter().map(|name| (&name[..], "G")).collect();
+        st.try_add("A", &pairs[..MAX_TERMS]).unwrap();
+        let description = st.to_bytes();
+        assert!(matches!(
+            st.try_add("B", &[(&names[MAX_TERMS], "G"), ("extra", "G")]),
+            Err(BadStatementTooManyScalars)
+        ));
+        assert_eq!(st.to_bytes(), description);
+        assert_eq!(
+            (st.num_equations(), st.num_scalars(), st.num_points()),
+            (1, 255, 2)
+        );
+        st.try_add("B", &[(&names[MAX_TERMS], "G")]).unwrap();
+        assert_eq!(st.num_scalars(), MAX_SCALARS);
+
+        let mut st = Statement::new();
+        for name in &names[..MAX_POINTS - 2] {
+            st.try_add(name, &[("a", "G")]).unwrap();
+        }
+        assert!(matches!(
+            st.try_add("P", &[("a", "Q")]),
+            Err(BadStatementTooManyPoints)
+        ));
+        st.try_add("P", &[("a", "G")]).unwrap();
+        assert_eq!(st.num_points(), MAX_POINTS);
+        assert!(matches!(
+            st.try_add("P", &[("a", "G")]),
+            Err(BadStatementTooManyEquations)
+        ));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_validate() {
+        let mut st = Statement::new();
+        let mut scalar_args = ScalarArgs::new();
+        let mut point_args = PointArgs::new();
+        assert!(matches!(
+            st.validate(Some(&scalar_args), &point_args),
+            Err(BadStatementNoEquations)
+        ));
+        assert!(matches!(
+            st.prove(&scalar_args, &point_args, b"", &[0u8; 32]),
+            Err(BadStatementNoEquations)
+        ));
+
+        st.try_add("A", &[("a", "G"), ("b", "H")]).unwrap();
+        scalar_args.add("a", Scalar::one());
+        point_args.add("A", RISTRETTO_BASEPOINT_POINT);
+        assert!(matches!(
+            st.validate(Some(&scalar_args), &point_args),
+            Err(BadArgsMissingScalarArg)
+        ));
+        scalar_args.add("b", Scalar::one());
+        assert!(matches!(
+            st.validate(None, &point_args),
+            Err(BadArgsMissingPointArg)
+        ));
+        point_args.add("H", RISTRETTO_BASEPOINT_POINT);
+        st.validate(Some(&scalar_args), &point_args).unwrap();
+        st.validate(None, &point_args).unwrap();
+
+        let mut extra_scalar_args = scalar_args.clone();
+        extra_scalar_args.add("c", Scalar::one());
+        assert!(matches!(
+            st.validate(Some(&extra_scalar_args), &point_args),
+            Err(BadArgsUnusedScalarArg)
+        ));
+        let mut extra_point_args = point_args.clone();
+        extra_point_args.add("G", RISTRETTO_BASEPOINT_POINT);
+        assert!(matches!(
+            st.validate(None, &extra_point_args),
+            Err(BadArgsUnusedPointArg)
+        ));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_shake256_transcript() {
+        let a = Scalar::from(1234u64);
+        let H = Scalar::from(5678u64) * RISTRETTO_BASEPOINT_POINT;
+        let mut scalar_args = ScalarArgs::new();
+        scalar_args.add("a", a);
+        let mut point_args = PointArgs::new();
+        point_args.add("A", a * RISTRETTO_BASEPOINT_POINT);
+        point_args.add("B", a * H);
+        point_args.add("H", H);
+
+        let statement = |transcript| {
+            let mut st = Statement::with_transcript(transcript);
+            st.try_add("A", &[("a", "G")]).unwrap();
+            st.try_add("B", &[("a", "H")]).unwrap();
+            st
+        };
+        let hmac_st = statement(Transcript::HmacSha256);
+        let shake_st = statement(Transcript::Shake256);
+        assert_eq!(hmac_st.to_bytes(), shake_st.to_bytes());
+
+        let proof = shake_st
+            .prove(&scalar_args, &point_args, b"message", &[0u8; 32])
+            .unwrap();
+        shake_st
+            .verify_proof(&proof, &point_args, b"message")
+            .unwrap();
+        let batchable_proof = shake_st
+            .prove_batchable(&scalar_args, &point_args, b"message", &[0u8; 32])
+            .unwrap();
+        shake_st
+            .verify_proofs_batch(&[(&batchable_proof, &point_args, b"message")])
+            .unwrap();
+        assert!(matches!(
+            hmac_st.verify_proofs_batch(&[(&batchable_proof, &point_args, b"message")]),
+            Err((0, VerificationFailure))
+        ));
+        assert!(matches!(
+            hmac_st.verify_proof(&proof, &point_args, b"message"),
+            Err(VerificationFailure)
+        ));
+
+        let hmac_proof = hmac_st
+            .prove(&scalar_args, &point_args, b"message", &[0u8; 32])
+            .unwrap();
+        assert_ne!(proof, hmac_proof);
+        assert!(matches!(
+            shake_st.verify_proof(&hmac_proof, &point_args, b"message"),
+            Err(VerificationFailure)
+        ));
+    }
 }
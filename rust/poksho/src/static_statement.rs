@@ -0,0 +1,416 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+// Statements declared at compile time with the statement! macro.
+//
+// A Statement built at runtime looks up every scalar and point by name, and panics if it was
+// built badly.  A StaticStatement instead gets its indices from enums generated by the macro, so
+// a misspelled name fails to compile, and its description D is encoded once during constant
+// evaluation.  Arguments are passed as structs with one field per scalar or point.
+//
+// Scalars and points are indexed in the order they're declared (after "G" for points).  Declaring
+// them in the order they first appear in the equations gives the same description, and
+// therefore the same proofs, as the equivalent runtime Statement with the same Transcript.
+
+use crate::errors::*;
+use crate::statement::*;
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use std::marker::PhantomData;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use curve25519_dalek::ristretto::RistrettoPoint;
+    pub use curve25519_dalek::scalar::Scalar;
+}
+
+#[doc(hidden)]
+pub struct StaticEquation {
+    pub lhs: u8,
+    pub rhs: &'static [Term],
+}
+
+impl EquationTerms for StaticEquation {
+    fn lhs(&self) -> PointIndex {
+        self.lhs
+    }
+    fn rhs(&self) -> &[Term] {
+        self.rhs
+    }
+}
+
+/// The scalars or points for a [`StaticStatement`], generated by [`statement!`](crate::statement).
+pub trait StatementArgs {
+    type Item;
+
+    /// The number of values.
+    const LEN: usize;
+
+    /// The values in index order.
+    fn to_sorted_vec(&self) -> Vec<Self::Item>;
+}
+
+pub struct StaticStatement<S, P> {
+    description: &'static [u8],
+    equations: &'static [StaticEquation],
+    transcript: Transcript,
+    args: PhantomData<(S, P)>,
+}
+
+impl<S, P> StaticStatement<S, P> {
+    #[doc(hidden)]
+    pub const fn new(
+        description: &'static [u8],
+        equations: &'static [StaticEquation],
+        transcript: Transcript,
+    ) -> Self {
+        Self {
+            description,
+            equations,
+            transcript,
+            args: PhantomData,
+        }
+    }
+
+    /// The encoded statement, as absorbed into every proof.
+    pub fn description(&self) -> &'static [u8] {
+        self.description
+    }
+
+    pub fn transcript(&self) -> Transcript {
+        self.transcript
+    }
+}
+
+impl<S, P> StaticStatement<S, P>
+where
+    S: StatementArgs<Item = Scalar>,
+    P: StatementArgs<Item = RistrettoPoint>,
+{
+    pub fn prove(
+        &self,
+        scalars: &S,
+        points: &P,
+        message: &[u8],
+        randomness: &[u8], // must be 32 bytes
+    ) -> Result<Vec<u8>, PokshoError> {
+        prove_with_sorted_args(
+            self.transcript,
+            self.description,
+            self.equations,
+            &scalars.to_sorted_vec(),
+            &Self::all_points(points),
+            message,
+            randomness,
+        )
+    }
+
+    pub fn verify_proof(
+        &self,
+        proof_bytes: &[u8],
+        points: &P,
+        message: &[u8],
+    ) -> Result<(), PokshoError> {
+        verify_proof_with_sorted_args(
+            self.transcript,
+            self.description,
+            self.equations,
+            S::LEN,
+            proof_bytes,
+            &Self::all_points(points),
+            message,
+        )
+    }
+
+    fn all_points(points: &P) -> Vec<RistrettoPoint> {
+        let mut all_points = Vec::with_capacity(P::LEN + 1);
+        all_points.push(RISTRETTO_BASEPOINT_POINT);
+        all_points.extend(points.to_sorted_vec());
+        all_points
+    }
+}
+
+/// Fails constant evaluation if `condition` is false.
+///
+/// (Panicking in a const fn isn't stable yet, but indexing out of bounds is still caught.)
+#[allow(clippy::no_effect)]
+const fn const_assert(condition: bool) {
+    [()][!condition as usize];
+}
+
+/// Encodes the description D for `equations`, checking during constant evaluation that the
+/// statement has a valid shape and uses every declared scalar and point.
+///
+/// `num_points` includes the base point.
+#[doc(hidden)]
+pub const fn encode_description<const N: usize>(
+    equations: &[StaticEquation],
+    num_scalars: usize,
+    num_points: usize,
+) -> [u8; N] {
+    const_assert(!equations.is_empty() && equations.len() <= 255);
+    const_assert(num_scalars <= 256 && num_points <= 256);
+
+    let mut scalar_used = [false; 256];
+    let mut point_used = [false; 256];
+    point_used[0] = true; // G needn't appear
+
+    let mut description = [0u8; N];
+    description[0] = equations.len() as u8;
+    let mut offset = 1;
+    let mut i = 0;
+    while i < equations.len() {
+        let StaticEquation { lhs, rhs } = &equations[i];
+        const_assert(!rhs.is_empty() && rhs.len() <= 255);
+        point_used[*lhs as usize] = true;
+        description[offset] = *lhs;
+        description[offset + 1] = rhs.len() as u8;
+        offset += 2;
+        let mut j = 0;
+        while j < rhs.len() {
+            scalar_used[rhs[j].scalar as usize] = true;
+            point_used[rhs[j].point as usize] = true;
+            description[offset] = rhs[j].scalar;
+            description[offset + 1] = rhs[j].point;
+            offset += 2;
+            j += 1;
+        }
+        i += 1;
+    }
+    const_assert(offset == N);
+
+    let mut k = 0;
+    while k < num_scalars {
+        const_assert(scalar_used[k]);
+        k += 1;
+    }
+    let mut k = 0;
+    while k < num_points {
+        const_assert(point_used[k]);
+        k += 1;
+    }
+    description
+}
+
+/// Declares a [`StaticStatement`] along with structs for its scalar and point arguments.
+///
+/// ```
+/// poksho::statement! {
+///     /// Knowledge of the discrete log of `A` and of `B` with respect to `H`.
+///     pub static DLEQ;
+///     pub struct DleqScalars { a }
+///     pub struct DleqPoints { A, B, H }
+///     A = a * G;
+///     B = a * H;
+/// }
+/// ```
+///
+/// `G` is always the Ristretto base point, and must not be declared. Every declared scalar and
+/// point must appear in some equation, and every name in an equation must be declared;
+/// otherwise the statement fails to compile.
+///
+/// Proofs use [`Transcript::HmacSha256`](crate::Transcript::HmacSha256) unless another
+/// transcript is named after the statement, as in `pub static DLEQ with Shake256;`.
+#[macro_export]
+macro_rules! statement {
+    (
+        $(#[$meta:meta])*
+        $vis:vis static $name:ident $(with $transcript:ident)?;
+        $scalars_vis:vis struct $scalars:ident { $($scalar:ident),+ $(,)? }
+        $points_vis:vis struct $points:ident { $($point:ident),+ $(,)? }
+        $($lhs:ident = $s0:ident * $p0:ident $(+ $s:ident * $p:ident)*;)+
+    ) => {
+        #[allow(non_snake_case)]
+        #[derive(Copy, Clone)]
+        $scalars_vis struct $scalars {
+            $(pub $scalar: $crate::static_statement::__private::Scalar),+
+        }
+
+        impl $crate::static_statement::StatementArgs for $scalars {
+            type Item = $crate::static_statement::__private::Scalar;
+            const LEN: usize = [$(stringify!($scalar)),+].len();
+            fn to_sorted_vec(&self) -> Vec<Self::Item> {
+                vec![$(self.$scalar),+]
+            }
+        }
+
+        #[allow(non_snake_case)]
+        #[derive(Copy, Clone)]
+        $points_vis struct $points {
+            $(pub $point: $crate::static_statement::__private::RistrettoPoint),+
+        }
+
+        impl $crate::static_statement::StatementArgs for $points {
+            type Item = $crate::static_statement::__private::RistrettoPoint;
+            const LEN: usize = [$(stringify!($point)),+].len();
+            fn to_sorted_vec(&self) -> Vec<Self::Item> {
+                vec![$(self.$point),+]
+            }
+        }
+
+        $(#[$meta])*
+        $vis static $name: $crate::StaticStatement<$scalars, $points> = {
+            #[allow(non_camel_case_types, dead_code, clippy::upper_case_acronyms)]
+            enum ScalarIndex {
+                $($scalar),+
+            }
+            #[allow(non_camel_case_types, dead_code, clippy::upper_case_acronyms)]
+            enum PointIndex {
+                G,
+                $($point),+
+            }
+            const EQUATIONS: &[$crate::static_statement::StaticEquation] = &[$(
+                $crate::static_statement::StaticEquation {
+                    lhs: PointIndex::$lhs as u8,
+                    rhs: &[
+                        $crate::statement::Term {
+                            scalar: ScalarIndex::$s0 as u8,
+                            point: PointIndex::$p0 as u8,
+                        },
+                        $($crate::statement::Term {
+                            scalar: ScalarIndex::$s as u8,
+                            point: PointIndex::$p as u8,
+                        }),*
+                    ],
+                }
+            ),+];
+            const DESCRIPTION_LEN: usize =
+                1 $(+ 2 + 2 * [stringify!($s0) $(, stringify!($s))*].len())+;
+            const DESCRIPTION: [u8; DESCRIPTION_LEN] =
+                $crate::static_statement::encode_description(
+                    EQUATIONS,
+                    [$(stringify!($scalar)),+].len(),
+                    1 + [$(stringify!($point)),+].len(),
+                );
+            // The named transcript, if any, otherwise the default
+            const TRANSCRIPTS: &[$crate::Transcript] = &[
+                $crate::Transcript::HmacSha256
+                $(, $crate::Transcript::$transcript)?
+            ];
+            const TRANSCRIPT: $crate::Transcript = TRANSCRIPTS[TRANSCRIPTS.len() - 1];
+            $crate::StaticStatement::new(&DESCRIPTION, EQUATIONS, TRANSCRIPT)
+        };
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case, clippy::unwrap_used)]
+
+    use crate::args::*;
+    use crate::statement::{Statement, Transcript};
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+    use curve25519_dalek::scalar::Scalar;
+
+    crate::statement! {
+        static COMPLEX;
+        struct ComplexScalars { a, b, c, d }
+        struct ComplexPoints { A, H, I, B }
+        A = a * G + b * H + c * I;
+        B = c * H + d * I;
+    }
+
+    crate::statement! {
+        static DLEQ with Shake256;
+        struct DleqScalars { a }
+        struct DleqPoints { A, B, H }
+        A = a * G;
+        B = a * H;
+    }
+
+    #[test]
+    fn test_matches_runtime_statement() {
+        let [a, b, c, d] = [101u64, 102, 103, 104].map(Scalar::from);
+        let H = Scalar::from(105u64) * RISTRETTO_BASEPOINT_POINT;
+        let I = Scalar::from(106u64) * RISTRETTO_BASEPOINT_POINT;
+        let A = a * RISTRETTO_BASEPOINT_POINT + b * H + c * I;
+        let B = c * H + d * I;
+
+        let mut st = Statement::new();
+        st.try_add("A", &[("a", "G"), ("b", "H"), ("c", "I")])
+            .unwrap();
+        st.try_add("B", &[("c", "H"), ("d", "I")]).unwrap();
+        assert_eq!(
+            COMPLEX.description(),
+            &[2, 1, 3, 0, 0, 1, 2, 2, 3, 4, 2, 2, 2, 3, 3]
+        );
+
+        let mut scalar_args = ScalarArgs::new();
+        scalar_args.add("a", a);
+        scalar_args.add("b", b);
+        scalar_args.add("c", c);
+        scalar_args.add("d", d);
+        let mut point_args = PointArgs::new();
+        point_args.add("A", A);
+        point_args.add("B", B);
+        point_args.add("H", H);
+        point_args.add("I", I);
+
+        let scalars = ComplexScalars { a, b, c, d };
+        let points = ComplexPoints { A, H, I, B };
+        let randomness = [7u8; 32];
+
+        let proof = COMPLEX
+            .prove(&scalars, &points, b"message", &randomness)
+            .unwrap();
+        assert_eq!(
+            proof,
+            st.prove(&scalar_args, &point_args, b"message", &randomness)
+                .unwrap()
+        );
+        COMPLEX.verify_proof(&proof, &points, b"message").unwrap();
+        st.verify_proof(&proof, &point_args, b"message").unwrap();
+
+        assert!(COMPLEX.verify_proof(&proof, &points, b"other").is_err());
+        let wrong_points = ComplexPoints { B: A, ..points };
+        assert!(COMPLEX
+            .verify_proof(&proof, &wrong_points, b"message")
+            .is_err());
+        let wrong_scalars = ComplexScalars { d: a, ..scalars };
+        assert!(COMPLEX
+            .prove(&wrong_scalars, &points, b"message", &randomness)
+            .is_err());
+    }
+    #[test]
+    fn test_transcript() {
+        let a = Scalar::from(101u64);
+        let H = Scalar::from(105u64) * RISTRETTO_BASEPOINT_POINT;
+        let A = a * RISTRETTO_BASEPOINT_POINT;
+        let B = a * H;
+
+        let mut st = Statement::with_transcript(Transcript::Shake256);
+        st.try_add("A", &[("a", "G")]).unwrap();
+        st.try_add("B", &[("a", "H")]).unwrap();
+        assert_eq!(DLEQ.transcript(), Transcript::Shake256);
+        assert_eq!(COMPLEX.transcript(), Transcript::HmacSha256);
+
+        let mut scalar_args = ScalarArgs::new();
+        scalar_args.add("a", a);
+        let mut point_args = PointArgs::new();
+        point_args.add("A", A);
+        point_args.add("B", B);
+        point_args.add("H", H);
+
+        let points = DleqPoints { A, B, H };
+        let randomness = [7u8; 32];
+        let proof = DLEQ
+            .prove(&DleqScalars { a }, &points, b"message", &randomness)
+            .unwrap();
+        assert_eq!(
+            proof,
+            st.prove(&scalar_args, &point_args, b"message", &randomness)
+                .unwrap()
+        );
+        DLEQ.verify_proof(&proof, &points, b"message").unwrap();
+
+        let mut hmac_st = Statement::new();
+        hmac_st.try_add("A", &[("a", "G")]).unwrap();
+        hmac_st.try_add("B", &[("a", "H")]).unwrap();
+        assert!(hmac_st
+            .verify_proof(&proof, &point_args, b"message")
+            .is_err());
+    }
+}
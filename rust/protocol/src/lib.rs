@@ -33,6 +33,7 @@ mod identity_key;
 mod proto;
 mod protocol;
 mod ratchet;
+mod retry;
 mod sealed_sender;
 mod sender_keys;
 mod session;
@@ -62,16 +63,22 @@ pub use {
         initialize_alice_session_record, initialize_bob_session_record,
         AliceSignalProtocolParameters, BobSignalProtocolParameters,
     },
+    retry::{process_decryption_error_message, RetryAction, SentContent, SentContentLog},
     sealed_sender::{
-        sealed_sender_decrypt, sealed_sender_decrypt_to_usmc, sealed_sender_encrypt,
-        sealed_sender_encrypt_from_usmc, sealed_sender_multi_recipient_encrypt,
-        sealed_sender_multi_recipient_fan_out, ContentHint, SealedSenderDecryptionResult,
-        SenderCertificate, ServerCertificate, UnidentifiedSenderMessageContent,
+        sealed_sender_decrypt, sealed_sender_decrypt_to_usmc,
+        sealed_sender_decrypt_to_usmc_with_versions, sealed_sender_decrypt_with_options,
+        sealed_sender_encrypt, sealed_sender_encrypt_from_usmc,
+        sealed_sender_multi_recipient_encrypt, sealed_sender_multi_recipient_fan_out,
+        sealed_sender_v2_encrypt, sealed_sender_v2_encrypt_from_usmc, ContentHint,
+        SealedSenderDecryptionOptions, SealedSenderDecryptionResult, SealedSenderVersion,
+        SenderCertificate, SenderCertificateCache, ServerCertificate, TrustRoot,
+        UnidentifiedSenderMessageContent,
     },
     sender_keys::SenderKeyRecord,
     session::{process_prekey, process_prekey_bundle},
     session_cipher::{
         message_decrypt, message_decrypt_prekey, message_decrypt_signal, message_encrypt,
+        message_encrypt_for_devices, DeviceMessage, MultiDeviceEncryptResult,
     },
     state::{PreKeyBundle, PreKeyRecord, SessionRecord, SignedPreKeyRecord},
     storage::{
@@ -0,0 +1,197 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Handling for [`DecryptionErrorMessage`]s, which a recipient sends when it fails to decrypt a
+//! message.
+//!
+//! The sender records each message it sends in a [`SentContentLog`]. When a decryption error
+//! arrives, [`process_decryption_error_message`] works out how to recover, and returns a list of
+//! [`RetryAction`]s for the app to carry out.
+
+use crate::{
+    ContentHint, Context, DecryptionErrorMessage, DeviceId, ProtocolAddress, Result, SessionStore,
+};
+
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Content that was sent, kept so that it can be resent if a recipient fails to decrypt it.
+#[derive(Clone, Debug)]
+pub struct SentContent {
+    /// The serialized content, before encryption.
+    pub content: Box<[u8]>,
+    pub content_hint: ContentHint,
+    pub group_id: Option<Box<[u8]>>,
+    /// The sender key distribution the content was encrypted with, if any.
+    pub distribution_id: Option<Uuid>,
+    /// Every device the content was sent to.
+    pub recipients: Vec<ProtocolAddress>,
+}
+
+/// A bounded log of recently sent content, keyed by the timestamp it was sent with.
+///
+/// When the log is full, the entry with the oldest timestamp is dropped.
+#[derive(Clone, Debug)]
+pub struct SentContentLog {
+    capacity: usize,
+    entries: BTreeMap<u64, SentContent>,
+}
+
+impl SentContentLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Records content sent at `timestamp`, replacing any earlier entry with the same timestamp.
+    pub fn record(&mut self, timestamp: u64, content: SentContent) {
+        self.entries.insert(timestamp, content);
+        while self.entries.len() > self.capacity {
+            let oldest = *self.entries.keys().next().expect("log is not empty");
+            self.entries.remove(&oldest);
+        }
+    }
+
+    pub fn get(&self, timestamp: u64) -> Option<&SentContent> {
+        self.entries.get(&timestamp)
+    }
+
+    pub fn remove(&mut self, timestamp: u64) -> Option<SentContent> {
+        self.entries.remove(&timestamp)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// A step to take in response to a [`DecryptionErrorMessage`].
+///
+/// Actions are returned in the order they should be carried out.
+#[derive(Clone, Debug)]
+pub enum RetryAction {
+    /// Archive the current session with the device, so that the next message sent to it starts
+    /// a new one.
+    ArchiveSession(ProtocolAddress),
+    /// Send the device a new sender key distribution message for `distribution_id` before the
+    /// next group message that uses it.
+    ResetSenderKey {
+        recipient: ProtocolAddress,
+        distribution_id: Uuid,
+    },
+    /// Resend the original content to the device, as a 1:1 message.
+    Resend {
+        recipient: ProtocolAddress,
+        timestamp: u64,
+        content: SentContent,
+    },
+    /// Send the device an empty message to establish a new session, because the original content
+    /// can't be resent.
+    SendNullMessage(ProtocolAddress),
+}
+
+/// Works out how to recover from a [`DecryptionErrorMessage`] sent by `requester`.
+///
+/// Errors about messages sent by a device other than `local_device_id` are ignored, since they
+/// are for another of this user's devices to handle. Content is only resent if it was
+/// [`ContentHint::Resendable`] and `requester` was one of its recipients.
+///
+/// This doesn't modify any stores; the app is responsible for carrying out the returned actions.
+pub async fn process_decryption_error_message(
+    message: &DecryptionErrorMessage,
+    requester: &ProtocolAddress,
+    local_device_id: DeviceId,
+    sent_log: &SentContentLog,
+    session_store: &dyn SessionStore,
+    ctx: Context,
+) -> Result<Vec<RetryAction>> {
+    let mut actions = vec![];
+    if message.device_id() != local_device_id {
+        return Ok(actions);
+    }
+
+    // Only archive the session if it's still the one the requester failed to decrypt with;
+    // otherwise a new session has already been established since.
+    let mut archived_session = false;
+    if let Some(ratchet_key) = message.ratchet_key() {
+        if let Some(session_record) = session_store.load_session(requester, ctx).await? {
+            if session_record.current_ratchet_key_matches(ratchet_key)? {
+                actions.push(RetryAction::ArchiveSession(requester.clone()));
+                archived_session = true;
+            }
+        }
+    }
+
+    let sent_content = sent_log
+        .get(message.timestamp())
+        .filter(|content| content.recipients.contains(requester));
+
+    if let Some(sent_content) = sent_content {
+        // A message without a ratchet key was encrypted with a sender key.
+        if message.ratchet_key().is_none() {
+            if let Some(distribution_id) = sent_content.distribution_id {
+                actions.push(RetryAction::ResetSenderKey {
+                    recipient: requester.clone(),
+                    distribution_id,
+                });
+            }
+        }
+        if sent_content.content_hint == ContentHint::Resendable {
+            actions.push(RetryAction::Resend {
+                recipient: requester.clone(),
+                timestamp: message.timestamp(),
+                content: sent_content.clone(),
+            });
+            return Ok(actions);
+        }
+    }
+
+    if archived_session {
+        actions.push(RetryAction::SendNullMessage(requester.clone()));
+    }
+    Ok(actions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sent_content(byte: u8) -> SentContent {
+        SentContent {
+            content: vec![byte].into_boxed_slice(),
+            content_hint: ContentHint::Resendable,
+            group_id: None,
+            distribution_id: None,
+            recipients: vec![ProtocolAddress::new("+14151111111".to_owned(), 1)],
+        }
+    }
+
+    #[test]
+    fn test_sent_content_log_eviction() {
+        let mut log = SentContentLog::new(2);
+        assert!(log.is_empty());
+
+        log.record(20, sent_content(20));
+        log.record(10, sent_content(10));
+        log.record(30, sent_content(30));
+        assert_eq!(log.len(), 2);
+        assert!(log.get(10).is_none());
+        assert_eq!(&*log.get(20).expect("present").content, &[20]);
+
+        log.record(30, sent_content(31));
+        assert_eq!(log.len(), 2);
+        assert_eq!(&*log.get(30).expect("present").content, &[31]);
+
+        assert!(log.remove(20).is_some());
+        assert!(log.get(20).is_none());
+        assert_eq!(log.len(), 1);
+    }
+}
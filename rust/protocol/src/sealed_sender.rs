@@ -26,7 +26,9 @@ use uuid::Uuid;
 
 use proto::sealed_sender::unidentified_sender_message::message::Type as ProtoMessageType;
 
+use std::collections::HashMap;
 use std::convert::{TryFrom, TryInto};
+use std::sync::Mutex;
 
 #[derive(Debug, Clone)]
 pub struct ServerCertificate {
@@ -152,6 +154,58 @@ impl ServerCertificate {
     }
 }
 
+/// A key that [`ServerCertificate`]s may be signed with, optionally only trusted within a window of
+/// time.
+///
+/// Accepting more than one trust root allows clients to keep accepting certificates chained to an
+/// old root while a new one is rolled out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrustRoot {
+    key: PublicKey,
+    not_before: Option<u64>,
+    not_after: Option<u64>,
+}
+
+impl TrustRoot {
+    /// A trust root that is always valid.
+    pub fn new(key: PublicKey) -> Self {
+        Self::with_validity(key, None, None)
+    }
+
+    /// A trust root that is only valid from `not_before` through `not_after`, inclusive, if given.
+    pub fn with_validity(key: PublicKey, not_before: Option<u64>, not_after: Option<u64>) -> Self {
+        Self {
+            key,
+            not_before,
+            not_after,
+        }
+    }
+
+    pub fn key(&self) -> PublicKey {
+        self.key
+    }
+
+    pub fn not_before(&self) -> Option<u64> {
+        self.not_before
+    }
+
+    pub fn not_after(&self) -> Option<u64> {
+        self.not_after
+    }
+
+    pub fn is_valid_at(&self, time: u64) -> bool {
+        self.not_before
+            .map_or(true, |not_before| not_before <= time)
+            && self.not_after.map_or(true, |not_after| time <= not_after)
+    }
+}
+
+impl From<PublicKey> for TrustRoot {
+    fn from(key: PublicKey) -> Self {
+        Self::new(key)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SenderCertificate {
     signer: ServerCertificate,
@@ -258,9 +312,31 @@ impl SenderCertificate {
     }
 
     pub fn validate(&self, trust_root: &PublicKey, validation_time: u64) -> Result<bool> {
-        if !self.signer.validate(trust_root)? {
+        Ok(self
+            .validate_with_trust_roots(&[TrustRoot::new(*trust_root)], validation_time)?
+            .is_some())
+    }
+
+    /// Validates the certificate against each of `trust_roots` that is valid at
+    /// `validation_time`, in order.
+    ///
+    /// Returns the key of the trust root the certificate chains to, or `None` if the certificate
+    /// isn't valid.
+    pub fn validate_with_trust_roots(
+        &self,
+        trust_roots: &[TrustRoot],
+        validation_time: u64,
+    ) -> Result<Option<PublicKey>> {
+        let mut validated_by = None;
+        for trust_root in trust_roots {
+            if trust_root.is_valid_at(validation_time) && self.signer.validate(&trust_root.key)? {
+                validated_by = Some(trust_root.key);
+                break;
+            }
+        }
+        if validated_by.is_none() {
             log::error!("received server certificate not signed by trust root");
-            return Ok(false);
+            return Ok(None);
         }
 
         if !self
@@ -269,7 +345,7 @@ impl SenderCertificate {
             .verify_signature(&self.certificate, &self.signature)?
         {
             log::error!("received sender certificate not signed by server");
-            return Ok(false);
+            return Ok(None);
         }
 
         if validation_time > self.expiration {
@@ -278,10 +354,10 @@ impl SenderCertificate {
                 self.expiration,
                 validation_time
             );
-            return Ok(false);
+            return Ok(None);
         }
 
-        Ok(true)
+        Ok(validated_by)
     }
 
     pub fn signer(&self) -> Result<&ServerCertificate> {
@@ -321,6 +397,143 @@ impl SenderCertificate {
     }
 }
 
+/// A cache of [`SenderCertificate`]s that have already been validated, so that the signatures in
+/// a certificate and its [`ServerCertificate`] only need to be checked once.
+///
+/// Certificates are keyed by their serialized bytes, and remember the trust root they were
+/// validated against. A cached certificate is still checked against the validation time and the
+/// current set of trust roots on every use, and expired certificates are the first to be evicted
+/// once the cache is full.
+///
+/// The cache can be shared across threads.
+#[derive(Debug)]
+pub struct SenderCertificateCache {
+    capacity: usize,
+    entries: Mutex<HashMap<Vec<u8>, ValidatedSenderCertificate>>,
+}
+
+#[derive(Debug)]
+struct ValidatedSenderCertificate {
+    trust_root: PublicKey,
+    expiration: u64,
+}
+
+impl SenderCertificateCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Equivalent to [`SenderCertificate::validate`], but skips checking the signatures if
+    /// `certificate` has already been validated against `trust_root`.
+    ///
+    /// Only successful validations are cached.
+    pub fn validate(
+        &self,
+        certificate: &SenderCertificate,
+        trust_root: &PublicKey,
+        validation_time: u64,
+    ) -> Result<bool> {
+        Ok(self
+            .validate_with_trust_roots(
+                certificate,
+                &[TrustRoot::new(*trust_root)],
+                validation_time,
+            )?
+            .is_some())
+    }
+
+    /// Equivalent to [`SenderCertificate::validate_with_trust_roots`], but skips checking the
+    /// signatures if `certificate` has already been validated against one of `trust_roots` that
+    /// is valid at `validation_time`.
+    ///
+    /// Only successful validations are cached.
+    pub fn validate_with_trust_roots(
+        &self,
+        certificate: &SenderCertificate,
+        trust_roots: &[TrustRoot],
+        validation_time: u64,
+    ) -> Result<Option<PublicKey>> {
+        if let Some(cached_trust_root) = self.cached_trust_root(certificate) {
+            let still_trusted = trust_roots.iter().any(|trust_root| {
+                trust_root.key == cached_trust_root && trust_root.is_valid_at(validation_time)
+            });
+            if still_trusted {
+                if validation_time > certificate.expiration {
+                    log::error!(
+                        "received expired sender certificate (expiration: {}, validation_time: {})",
+                        certificate.expiration,
+                        validation_time
+                    );
+                    return Ok(None);
+                }
+                return Ok(Some(cached_trust_root));
+            }
+        }
+
+        let validated_by = certificate.validate_with_trust_roots(trust_roots, validation_time)?;
+        if let Some(trust_root) = &validated_by {
+            self.insert(certificate, trust_root, validation_time);
+        }
+        Ok(validated_by)
+    }
+
+    /// The number of certificates currently in the cache.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Vec<u8>, ValidatedSenderCertificate>> {
+        // The map is never left in an inconsistent state, so it's fine to keep using it after a
+        // panic on another thread.
+        self.entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn cached_trust_root(&self, certificate: &SenderCertificate) -> Option<PublicKey> {
+        self.lock()
+            .get(&certificate.serialized)
+            .map(|entry| entry.trust_root)
+    }
+
+    fn insert(&self, certificate: &SenderCertificate, trust_root: &PublicKey, now: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.lock();
+        if entries.len() >= self.capacity && !entries.contains_key(&certificate.serialized) {
+            entries.retain(|_, entry| entry.expiration >= now);
+        }
+        if entries.len() >= self.capacity && !entries.contains_key(&certificate.serialized) {
+            // Evict whichever certificate will expire first.
+            let soonest_expiring = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.expiration)
+                .map(|(serialized, _)| serialized.clone())
+                .expect("cache is not empty");
+            entries.remove(&soonest_expiring);
+        }
+        entries.insert(
+            certificate.serialized.clone(),
+            ValidatedSenderCertificate {
+                trust_root: *trust_root,
+                expiration: certificate.expiration,
+            },
+        );
+    }
+}
+
 impl From<ProtoMessageType> for CiphertextMessageType {
     fn from(message_type: ProtoMessageType) -> Self {
         let result = match message_type {
@@ -587,6 +800,13 @@ impl UnidentifiedSenderMessage {
             _ => Err(SignalProtocolError::UnknownSealedSenderVersion(version)),
         }
     }
+
+    fn version(&self) -> SealedSenderVersion {
+        match self {
+            Self::V1 { .. } => SealedSenderVersion::V1,
+            Self::V2 { .. } => SealedSenderVersion::V2,
+        }
+    }
 }
 
 mod sealed_sender_v1 {
@@ -768,6 +988,24 @@ mod sealed_sender_v1 {
     }
 }
 
+/// A Sealed Sender message format.
+///
+/// [`sealed_sender_decrypt_to_usmc`] accepts both; use
+/// [`sealed_sender_decrypt_to_usmc_with_versions`] or
+/// [`SealedSenderDecryptionOptions::accepted_versions`] to stop accepting v1 messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SealedSenderVersion {
+    /// See [`sealed_sender_encrypt_from_usmc`].
+    V1,
+    /// See [`sealed_sender_v2_encrypt_from_usmc`].
+    V2,
+}
+
+impl SealedSenderVersion {
+    /// Every version, for decrypting any Sealed Sender message.
+    pub const ALL: &'static [Self] = &[Self::V1, Self::V2];
+}
+
 /// Encrypt the plaintext message `ptext`, generate an [`UnidentifiedSenderMessageContent`], then
 /// pass the result to [`sealed_sender_encrypt_from_usmc`].
 ///
@@ -783,15 +1021,61 @@ pub async fn sealed_sender_encrypt<R: Rng + CryptoRng>(
     ctx: Context,
     rng: &mut R,
 ) -> Result<Vec<u8>> {
+    let usmc = encrypt_to_usmc(
+        destination,
+        sender_cert,
+        ptext,
+        session_store,
+        identity_store,
+        ctx,
+    )
+    .await?;
+    sealed_sender_encrypt_from_usmc(destination, &usmc, identity_store, ctx, rng).await
+}
+
+/// Encrypt the plaintext message `ptext`, generate an [`UnidentifiedSenderMessageContent`], then
+/// pass the result to [`sealed_sender_v2_encrypt_from_usmc`].
+///
+/// This is a simple way to encrypt a message in a 1:1 using [Sealed Sender v2].
+///
+/// [Sealed Sender v2]: sealed_sender_v2_encrypt_from_usmc
+pub async fn sealed_sender_v2_encrypt<R: Rng + CryptoRng>(
+    destination: &ProtocolAddress,
+    sender_cert: &SenderCertificate,
+    ptext: &[u8],
+    session_store: &mut (dyn SessionStore + Send + Sync),
+    identity_store: &mut (dyn IdentityKeyStore + Send + Sync),
+    ctx: Context,
+    rng: &mut R,
+) -> Result<Vec<u8>> {
+    let usmc = encrypt_to_usmc(
+        destination,
+        sender_cert,
+        ptext,
+        session_store,
+        identity_store,
+        ctx,
+    )
+    .await?;
+    sealed_sender_v2_encrypt_from_usmc(destination, &usmc, identity_store, ctx, rng).await
+}
+
+async fn encrypt_to_usmc(
+    destination: &ProtocolAddress,
+    sender_cert: &SenderCertificate,
+    ptext: &[u8],
+    session_store: &mut (dyn SessionStore + Send + Sync),
+    identity_store: &mut (dyn IdentityKeyStore + Send + Sync),
+    ctx: Context,
+) -> Result<UnidentifiedSenderMessageContent> {
     let message = message_encrypt(ptext, destination, session_store, identity_store, ctx).await?;
-    let usmc = UnidentifiedSenderMessageContent::new(
+    UnidentifiedSenderMessageContent::new(
         message.message_type(),
         sender_cert.clone(),
         message.serialize().to_vec(),
         ContentHint::Default,
         None,
-    )?;
-    sealed_sender_encrypt_from_usmc(destination, &usmc, identity_store, ctx, rng).await
+    )
 }
 
 /// This method implements the single-key single-recipient [KEM] described in [this Signal blog
@@ -934,6 +1218,30 @@ mod sealed_sender_v2 {
         }
     }
 
+    /// Symmetrically encrypt the serialized [`UnidentifiedSenderMessageContent`] `usmc` with the
+    /// key `k` derived from the random bytes, appending the authentication tag.
+    pub(super) fn encrypt_message(
+        keys: &DerivedKeys,
+        usmc: &UnidentifiedSenderMessageContent,
+    ) -> Result<Vec<u8>> {
+        let mut ciphertext = usmc.serialized()?.to_vec();
+        let symmetric_authentication_tag = Aes256GcmSiv::new_from_slice(&keys.k)
+            .and_then(|aes_gcm_siv| {
+                aes_gcm_siv.encrypt_in_place_detached(
+                    // There's no nonce because the key is already one-use.
+                    &aes_gcm_siv::Nonce::default(),
+                    // And there's no associated data.
+                    &[],
+                    &mut ciphertext,
+                )
+            })
+            .expect("AES-GCM-SIV encryption should not fail with a just-computed key");
+        // AES-GCM-SIV expects the authentication tag to be at the end of the ciphertext
+        // when decrypting.
+        ciphertext.extend_from_slice(&symmetric_authentication_tag);
+        Ok(ciphertext)
+    }
+
     /// Encrypt or decrypt a slice of random bytes `input` using a shared secret derived from
     /// `our_keys` and `their_key`.
     ///
@@ -1234,24 +1542,7 @@ pub async fn sealed_sender_multi_recipient_encrypt<R: Rng + CryptoRng>(
     let keys = sealed_sender_v2::DerivedKeys::calculate(&m);
     let e_pub = &keys.e.public_key;
 
-    let ciphertext = {
-        let mut ciphertext = usmc.serialized()?.to_vec();
-        let symmetric_authentication_tag = Aes256GcmSiv::new_from_slice(&keys.k)
-            .and_then(|aes_gcm_siv| {
-                aes_gcm_siv.encrypt_in_place_detached(
-                    // There's no nonce because the key is already one-use.
-                    &aes_gcm_siv::Nonce::default(),
-                    // And there's no associated data.
-                    &[],
-                    &mut ciphertext,
-                )
-            })
-            .expect("AES-GCM-SIV encryption should not fail with a just-computed key");
-        // AES-GCM-SIV expects the authentication tag to be at the end of the ciphertext
-        // when decrypting.
-        ciphertext.extend_from_slice(&symmetric_authentication_tag);
-        ciphertext
-    };
+    let ciphertext = sealed_sender_v2::encrypt_message(&keys, usmc)?;
 
     // Uses a flat representation: count || UUID_i || deviceId_i || registrationId_i || C_i || AT_i || ... || E.pub || ciphertext
     let version = SEALED_SENDER_V2_VERSION;
@@ -1349,6 +1640,62 @@ pub async fn sealed_sender_multi_recipient_encrypt<R: Rng + CryptoRng>(
     Ok(serialized)
 }
 
+/// Encrypt an [`UnidentifiedSenderMessageContent`] for a single recipient using Sealed Sender v2.
+///
+/// This uses the same construction as [`sealed_sender_multi_recipient_encrypt`] with only one
+/// recipient, which is cheaper than [Sealed Sender v1] and authenticates the message with
+/// AES-GCM-SIV. Since there's only one recipient, there's no recipient list to route on: the
+/// output is already in the [received message] format, and is decrypted by
+/// [`sealed_sender_decrypt_to_usmc`] like any other Sealed Sender message.
+///
+/// Like [Sealed Sender v1], this only requires the recipient's identity key to be known, not an
+/// existing session.
+///
+/// [Sealed Sender v1]: sealed_sender_encrypt_from_usmc
+/// [received message]: sealed_sender_multi_recipient_encrypt#received-messages
+pub async fn sealed_sender_v2_encrypt_from_usmc<R: Rng + CryptoRng>(
+    destination: &ProtocolAddress,
+    usmc: &UnidentifiedSenderMessageContent,
+    identity_store: &mut dyn IdentityKeyStore,
+    ctx: Context,
+    rng: &mut R,
+) -> Result<Vec<u8>> {
+    let our_identity = identity_store.get_identity_key_pair(ctx).await?;
+    let their_identity = identity_store
+        .get_identity(destination, ctx)
+        .await?
+        .ok_or_else(|| SignalProtocolError::SessionNotFound(destination.clone()))?;
+
+    let m: [u8; sealed_sender_v2::MESSAGE_KEY_LEN] = rng.gen();
+    let keys = sealed_sender_v2::DerivedKeys::calculate(&m);
+    let e_pub = &keys.e.public_key;
+
+    let c = sealed_sender_v2::apply_agreement_xor(
+        &keys.e,
+        their_identity.public_key(),
+        Direction::Sending,
+        &m,
+    )?;
+    let at = sealed_sender_v2::compute_authentication_tag(
+        &our_identity,
+        &their_identity,
+        Direction::Sending,
+        e_pub,
+        &c,
+    )?;
+    let ciphertext = sealed_sender_v2::encrypt_message(&keys, usmc)?;
+
+    // Uses the flat received-message representation: C || AT || E.pub || ciphertext
+    let version = SEALED_SENDER_V2_VERSION;
+    let mut serialized = vec![version | (version << 4)];
+    serialized.extend_from_slice(&c);
+    serialized.extend_from_slice(&at);
+    serialized.extend_from_slice(e_pub.public_key_bytes()?);
+    serialized.extend_from_slice(&ciphertext);
+
+    Ok(serialized)
+}
+
 /// Split out the encoded message from [`sealed_sender_multi_recipient_encrypt`] into a sequence of
 /// individual encrypted [`UnidentifiedSenderMessageContent`]s. **Note: this method is only used in
 /// testing.**
@@ -1423,9 +1770,34 @@ pub async fn sealed_sender_decrypt_to_usmc(
     identity_store: &mut dyn IdentityKeyStore,
     ctx: Context,
 ) -> Result<UnidentifiedSenderMessageContent> {
+    sealed_sender_decrypt_to_usmc_with_versions(
+        ciphertext,
+        SealedSenderVersion::ALL,
+        identity_store,
+        ctx,
+    )
+    .await
+}
+
+/// Equivalent to [`sealed_sender_decrypt_to_usmc`], but rejects messages whose format is not one of
+/// `accepted_versions`.
+pub async fn sealed_sender_decrypt_to_usmc_with_versions(
+    ciphertext: &[u8],
+    accepted_versions: &[SealedSenderVersion],
+    identity_store: &mut dyn IdentityKeyStore,
+    ctx: Context,
+) -> Result<UnidentifiedSenderMessageContent> {
+    let message = UnidentifiedSenderMessage::deserialize(ciphertext)?;
+    if !accepted_versions.contains(&message.version()) {
+        return Err(SignalProtocolError::InvalidSealedSenderMessage(format!(
+            "Sealed Sender {:?} messages are not accepted",
+            message.version()
+        )));
+    }
+
     let our_identity = identity_store.get_identity_key_pair(ctx).await?;
 
-    match UnidentifiedSenderMessage::deserialize(ciphertext)? {
+    match message {
         UnidentifiedSenderMessage::V1 {
             ephemeral_public,
             encrypted_static,
@@ -1565,6 +1937,8 @@ pub struct SealedSenderDecryptionResult {
     pub sender_e164: Option<String>,
     pub device_id: u32,
     pub message: Vec<u8>,
+    /// The trust root that the sender certificate was validated against.
+    pub trust_root: PublicKey,
 }
 
 impl SealedSenderDecryptionResult {
@@ -1583,6 +1957,10 @@ impl SealedSenderDecryptionResult {
     pub fn message(&self) -> Result<&[u8]> {
         Ok(self.message.as_ref())
     }
+
+    pub fn trust_root(&self) -> Result<PublicKey> {
+        Ok(self.trust_root)
+    }
 }
 
 /// Decrypt a Sealed Sender message `ciphertext` in either the v1 or v2 format, validate its sender
@@ -1606,13 +1984,79 @@ pub async fn sealed_sender_decrypt(
     signed_pre_key_store: &mut (dyn SignedPreKeyStore + Send + Sync),
     ctx: Context,
 ) -> Result<SealedSenderDecryptionResult> {
-    let usmc = sealed_sender_decrypt_to_usmc(ciphertext, identity_store, ctx).await?;
+    sealed_sender_decrypt_with_options(
+        ciphertext,
+        SealedSenderDecryptionOptions {
+            trust_roots: &[TrustRoot::new(*trust_root)],
+            certificate_cache: None,
+            accepted_versions: SealedSenderVersion::ALL,
+        },
+        timestamp,
+        local_e164,
+        local_uuid,
+        local_device_id,
+        identity_store,
+        session_store,
+        pre_key_store,
+        signed_pre_key_store,
+        ctx,
+    )
+    .await
+}
 
-    if !usmc.sender()?.validate(trust_root, timestamp)? {
-        return Err(SignalProtocolError::InvalidSealedSenderMessage(
-            "trust root validation failed".to_string(),
-        ));
-    }
+/// Which messages [`sealed_sender_decrypt_with_options`] accepts, and how it validates their
+/// sender certificates.
+#[derive(Clone, Copy)]
+pub struct SealedSenderDecryptionOptions<'a> {
+    /// The trust roots to validate sender certificates against; the first one that is valid at the
+    /// message's timestamp and signed the certificate is recorded in the result.
+    pub trust_roots: &'a [TrustRoot],
+    /// If provided, certificates that have already been validated are looked up here instead of
+    /// checking their signatures again.
+    pub certificate_cache: Option<&'a SenderCertificateCache>,
+    /// The message formats to accept, as in [`sealed_sender_decrypt_to_usmc_with_versions`]; use
+    /// [`SealedSenderVersion::ALL`] to accept any message.
+    pub accepted_versions: &'a [SealedSenderVersion],
+}
+
+/// Equivalent to [`sealed_sender_decrypt`], but checks the message format and validates the sender
+/// certificate according to `options`.
+#[allow(clippy::too_many_arguments)]
+pub async fn sealed_sender_decrypt_with_options(
+    ciphertext: &[u8],
+    options: SealedSenderDecryptionOptions<'_>,
+    timestamp: u64,
+    local_e164: Option<String>,
+    local_uuid: String,
+    local_device_id: u32,
+    identity_store: &mut (dyn IdentityKeyStore + Send + Sync),
+    session_store: &mut (dyn SessionStore + Send + Sync),
+    pre_key_store: &mut (dyn PreKeyStore + Send + Sync),
+    signed_pre_key_store: &mut (dyn SignedPreKeyStore + Send + Sync),
+    ctx: Context,
+) -> Result<SealedSenderDecryptionResult> {
+    let SealedSenderDecryptionOptions {
+        trust_roots,
+        certificate_cache,
+        accepted_versions,
+    } = options;
+
+    let usmc = sealed_sender_decrypt_to_usmc_with_versions(
+        ciphertext,
+        accepted_versions,
+        identity_store,
+        ctx,
+    )
+    .await?;
+    let validated_by = match certificate_cache {
+        Some(cache) => cache.validate_with_trust_roots(usmc.sender()?, trust_roots, timestamp)?,
+        None => usmc
+            .sender()?
+            .validate_with_trust_roots(trust_roots, timestamp)?,
+    };
+    let trust_root = validated_by.ok_or_else(|| {
+        SignalProtocolError::InvalidSealedSenderMessage("trust root validation failed".to_string())
+    })?;
 
     let is_local_uuid = local_uuid == usmc.sender()?.sender_uuid()?;
 
@@ -1672,6 +2116,7 @@ pub async fn sealed_sender_decrypt(
         sender_e164: usmc.sender()?.sender_e164()?.map(|s| s.to_string()),
         device_id: usmc.sender()?.sender_device_id()?,
         message,
+        trust_root,
     })
 }
 
@@ -4,8 +4,8 @@
 //
 
 use crate::{
-    CiphertextMessage, CiphertextMessageType, Context, Direction, IdentityKeyStore, KeyPair,
-    PreKeySignalMessage, PreKeyStore, ProtocolAddress, PublicKey, Result, SessionRecord,
+    CiphertextMessage, CiphertextMessageType, Context, DeviceId, Direction, IdentityKeyStore,
+    KeyPair, PreKeySignalMessage, PreKeyStore, ProtocolAddress, PublicKey, Result, SessionRecord,
     SessionStore, SignalMessage, SignalProtocolError, SignedPreKeyStore,
 };
 
@@ -28,6 +28,141 @@ pub async fn message_encrypt(
         .load_session(remote_address, ctx)
         .await?
         .ok_or_else(|| SignalProtocolError::SessionNotFound(remote_address.clone()))?;
+    let message = encrypt_for_session(
+        ptext,
+        remote_address,
+        &mut session_record,
+        identity_store,
+        ctx,
+    )
+    .await?;
+    session_store
+        .store_session(remote_address, &session_record, ctx)
+        .await?;
+    Ok(message)
+}
+
+/// A message encrypted for one device by [`message_encrypt_for_devices`].
+pub struct DeviceMessage {
+    pub device_id: DeviceId,
+    /// The registration ID of the device's session, which servers expect alongside the message.
+    pub registration_id: u32,
+    pub message: CiphertextMessage,
+}
+
+/// The result of [`message_encrypt_for_devices`].
+///
+/// Every requested device appears in exactly one of these lists.
+#[derive(Default)]
+pub struct MultiDeviceEncryptResult {
+    pub messages: Vec<DeviceMessage>,
+    /// Devices with no current session or no saved identity key; a new session must be
+    /// established from a prekey bundle before sending to them.
+    pub missing_devices: Vec<DeviceId>,
+    /// Devices whose session has a different registration ID than expected, which means the
+    /// device was re-registered and its session should be replaced.
+    pub stale_devices: Vec<DeviceId>,
+    /// Devices whose identity key isn't trusted for sending.
+    pub untrusted_devices: Vec<DeviceId>,
+}
+
+impl MultiDeviceEncryptResult {
+    /// Returns true if a message was encrypted for every requested device.
+    pub fn is_complete(&self) -> bool {
+        self.missing_devices.is_empty()
+            && self.stale_devices.is_empty()
+            && self.untrusted_devices.is_empty()
+    }
+}
+
+/// Encrypts `ptext` for each of a user's devices.
+///
+/// Each device is given along with the registration ID the server currently lists for it. A
+/// device that can't be encrypted for is reported in the result instead of failing the whole
+/// operation; other errors, such as storage failures, are still returned. Sessions are only
+/// updated for devices that a message was encrypted for.
+pub async fn message_encrypt_for_devices(
+    ptext: &[u8],
+    name: &str,
+    devices: &[(DeviceId, u32)],
+    session_store: &mut (dyn SessionStore + Send + Sync),
+    identity_store: &mut (dyn IdentityKeyStore + Send + Sync),
+    ctx: Context,
+) -> Result<MultiDeviceEncryptResult> {
+    let mut result = MultiDeviceEncryptResult::default();
+
+    for &(device_id, expected_registration_id) in devices {
+        let remote_address = ProtocolAddress::new(name.to_owned(), device_id);
+
+        let mut session_record = match session_store.load_session(&remote_address, ctx).await? {
+            Some(session_record) if session_record.has_current_session_state() => session_record,
+            _ => {
+                result.missing_devices.push(device_id);
+                continue;
+            }
+        };
+
+        let registration_id = session_record.remote_registration_id()?;
+        if registration_id != expected_registration_id {
+            log::info!(
+                "registration ID for {} is {}, not {}",
+                remote_address,
+                registration_id,
+                expected_registration_id
+            );
+            result.stale_devices.push(device_id);
+            continue;
+        }
+
+        // Like sealed_sender_multi_recipient_encrypt, require an identity key to already be
+        // saved, since re-establishing the session will fetch it.
+        if identity_store
+            .get_identity(&remote_address, ctx)
+            .await?
+            .is_none()
+        {
+            log::warn!("missing identity key for {}", remote_address);
+            result.missing_devices.push(device_id);
+            continue;
+        }
+
+        match encrypt_for_session(
+            ptext,
+            &remote_address,
+            &mut session_record,
+            identity_store,
+            ctx,
+        )
+        .await
+        {
+            Ok(message) => {
+                session_store
+                    .store_session(&remote_address, &session_record, ctx)
+                    .await?;
+                result.messages.push(DeviceMessage {
+                    device_id,
+                    registration_id,
+                    message,
+                });
+            }
+            Err(SignalProtocolError::UntrustedIdentity(_)) => {
+                result.untrusted_devices.push(device_id);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(result)
+}
+
+// Encrypts with the current session in session_record, advancing it without storing it.
+async fn encrypt_for_session(
+    ptext: &[u8],
+    remote_address: &ProtocolAddress,
+    session_record: &mut SessionRecord,
+    identity_store: &mut (dyn IdentityKeyStore + Send + Sync),
+    ctx: Context,
+) -> Result<CiphertextMessage> {
     let session_state = session_record
         .session_state_mut()
         .ok_or_else(|| SignalProtocolError::SessionNotFound(remote_address.clone()))?;
@@ -123,9 +258,6 @@ pub async fn message_encrypt(
         .save_identity(remote_address, &their_identity_key, ctx)
         .await?;
 
-    session_store
-        .store_session(remote_address, &session_record, ctx)
-        .await?;
     Ok(message)
 }
 
@@ -132,6 +132,137 @@ fn test_sender_cert() -> Result<(), SignalProtocolError> {
     Ok(())
 }
 
+#[test]
+fn test_sender_cert_cache() -> Result<(), SignalProtocolError> {
+    let mut rng = OsRng;
+    let trust_root = KeyPair::generate(&mut rng);
+    let server_key = KeyPair::generate(&mut rng);
+    let key = KeyPair::generate(&mut rng);
+
+    let server_cert =
+        ServerCertificate::new(1, server_key.public_key, &trust_root.private_key, &mut rng)?;
+
+    let expires = 1605722925;
+    let mut new_sender_cert = |device_id, expires| {
+        SenderCertificate::new(
+            "9d0652a3-dcc3-4d11-975f-74d61598733f".to_string(),
+            None,
+            key.public_key,
+            device_id,
+            expires,
+            server_cert.clone(),
+            &server_key.private_key,
+            &mut rng,
+        )
+    };
+    let sender_cert = new_sender_cert(1, expires)?;
+    let later_sender_cert = new_sender_cert(2, expires + 100)?;
+    let latest_sender_cert = new_sender_cert(3, expires + 200)?;
+
+    let cache = std::sync::Arc::new(SenderCertificateCache::new(2));
+    assert!(cache.validate(&sender_cert, &trust_root.public_key, expires)?);
+    assert_eq!(cache.len(), 1);
+
+    // Cached certificates are still checked for expiration and against the trust root.
+    assert!(cache.validate(&sender_cert, &trust_root.public_key, expires - 1)?);
+    assert!(!cache.validate(&sender_cert, &trust_root.public_key, expires + 1)?);
+    let wrong_trust_root = KeyPair::generate(&mut OsRng);
+    assert!(!cache.validate(&sender_cert, &wrong_trust_root.public_key, expires)?);
+    assert_eq!(cache.len(), 1);
+
+    // The cache can be shared across threads.
+    let thread_cache = cache.clone();
+    let thread_trust_root = trust_root.public_key;
+    assert!(std::thread::spawn(move || {
+        thread_cache.validate(&later_sender_cert, &thread_trust_root, expires)
+    })
+    .join()
+    .expect("thread succeeded")?);
+    assert_eq!(cache.len(), 2);
+
+    // Once full, the certificate that expires first is evicted.
+    assert!(cache.validate(&latest_sender_cert, &trust_root.public_key, expires)?);
+    assert_eq!(cache.len(), 2);
+    assert!(cache.validate(&sender_cert, &trust_root.public_key, expires)?);
+    assert_eq!(cache.len(), 2);
+
+    cache.clear();
+    assert!(cache.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_sender_cert_multiple_trust_roots() -> Result<(), SignalProtocolError> {
+    let mut rng = OsRng;
+    let old_trust_root = KeyPair::generate(&mut rng);
+    let new_trust_root = KeyPair::generate(&mut rng);
+    let server_key = KeyPair::generate(&mut rng);
+    let key = KeyPair::generate(&mut rng);
+
+    let expires = 1605722925;
+    let rotation_start = expires - 1000;
+    let rotation_end = expires - 100;
+    let trust_roots = [
+        TrustRoot::with_validity(old_trust_root.public_key, None, Some(rotation_end)),
+        TrustRoot::with_validity(new_trust_root.public_key, Some(rotation_start), None),
+    ];
+
+    let mut new_sender_cert = |trust_root: &KeyPair| {
+        let server_cert =
+            ServerCertificate::new(1, server_key.public_key, &trust_root.private_key, &mut rng)?;
+        SenderCertificate::new(
+            "9d0652a3-dcc3-4d11-975f-74d61598733f".to_string(),
+            None,
+            key.public_key,
+            42,
+            expires,
+            server_cert,
+            &server_key.private_key,
+            &mut rng,
+        )
+    };
+    let old_sender_cert = new_sender_cert(&old_trust_root)?;
+    let new_sender_cert = new_sender_cert(&new_trust_root)?;
+
+    // Before the rotation, only the old root is trusted.
+    assert_eq!(
+        old_sender_cert.validate_with_trust_roots(&trust_roots, rotation_start - 1)?,
+        Some(old_trust_root.public_key)
+    );
+    assert_eq!(
+        new_sender_cert.validate_with_trust_roots(&trust_roots, rotation_start - 1)?,
+        None
+    );
+
+    // During the rotation, either is.
+    assert_eq!(
+        old_sender_cert.validate_with_trust_roots(&trust_roots, rotation_end)?,
+        Some(old_trust_root.public_key)
+    );
+    assert_eq!(
+        new_sender_cert.validate_with_trust_roots(&trust_roots, rotation_start)?,
+        Some(new_trust_root.public_key)
+    );
+
+    // Afterwards, only the new root is, even for certificates cached earlier.
+    let cache = SenderCertificateCache::new(10);
+    assert_eq!(
+        cache.validate_with_trust_roots(&old_sender_cert, &trust_roots, rotation_end)?,
+        Some(old_trust_root.public_key)
+    );
+    assert_eq!(
+        cache.validate_with_trust_roots(&old_sender_cert, &trust_roots, rotation_end + 1)?,
+        None
+    );
+    assert_eq!(
+        cache.validate_with_trust_roots(&new_sender_cert, &trust_roots, rotation_end + 1)?,
+        Some(new_trust_root.public_key)
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_sealed_sender() -> Result<(), SignalProtocolError> {
     async {
@@ -215,6 +346,7 @@ fn test_sealed_sender() -> Result<(), SignalProtocolError> {
         assert_eq!(bob_ptext.sender_uuid, alice_uuid);
         assert_eq!(bob_ptext.sender_e164, Some(alice_e164));
         assert_eq!(bob_ptext.device_id, alice_device_id);
+        assert_eq!(bob_ptext.trust_root, trust_root.public_key);
 
         // Now test but with an expired cert:
 
@@ -300,6 +432,187 @@ fn test_sealed_sender() -> Result<(), SignalProtocolError> {
     .expect("sync")
 }
 
+#[test]
+fn test_sealed_sender_v2_single_recipient() -> Result<(), SignalProtocolError> {
+    async {
+        let mut rng = OsRng;
+
+        let alice_device_id = 23;
+        let bob_device_id = 42;
+
+        let alice_uuid = "9d0652a3-dcc3-4d11-975f-74d61598733f".to_string();
+        let bob_uuid = "796abedb-ca4e-4f18-8803-1fde5b921f9f".to_string();
+
+        let bob_uuid_address = ProtocolAddress::new(bob_uuid.clone(), bob_device_id);
+
+        let mut alice_store = support::test_in_memory_protocol_store()?;
+        let mut bob_store = support::test_in_memory_protocol_store()?;
+
+        let alice_pubkey = *alice_store.get_identity_key_pair(None).await?.public_key();
+
+        let bob_pre_key_bundle = create_pre_key_bundle(&mut bob_store, &mut rng).await?;
+
+        process_prekey_bundle(
+            &bob_uuid_address,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            &bob_pre_key_bundle,
+            &mut rng,
+            None,
+        )
+        .await?;
+
+        let trust_root = KeyPair::generate(&mut rng);
+        let server_key = KeyPair::generate(&mut rng);
+
+        let server_cert =
+            ServerCertificate::new(1, server_key.public_key, &trust_root.private_key, &mut rng)?;
+
+        let expires = 1605722925;
+
+        let sender_cert = SenderCertificate::new(
+            alice_uuid.clone(),
+            None,
+            alice_pubkey,
+            alice_device_id,
+            expires,
+            server_cert,
+            &server_key.private_key,
+            &mut rng,
+        )?;
+
+        let alice_ptext = vec![1, 2, 3, 23, 99];
+        let alice_ctext = sealed_sender_v2_encrypt(
+            &bob_uuid_address,
+            &sender_cert,
+            &alice_ptext,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            None,
+            &mut rng,
+        )
+        .await?;
+        assert_eq!(alice_ctext[0], 0x22);
+
+        let bob_ptext = sealed_sender_decrypt_with_options(
+            &alice_ctext,
+            SealedSenderDecryptionOptions {
+                trust_roots: &[trust_root.public_key.into()],
+                certificate_cache: Some(&SenderCertificateCache::new(10)),
+                accepted_versions: SealedSenderVersion::ALL,
+            },
+            expires - 1,
+            None,
+            bob_uuid.clone(),
+            bob_device_id,
+            &mut bob_store.identity_store,
+            &mut bob_store.session_store,
+            &mut bob_store.pre_key_store,
+            &mut bob_store.signed_pre_key_store,
+            None,
+        )
+        .await?;
+
+        assert_eq!(bob_ptext.message, alice_ptext);
+        assert_eq!(bob_ptext.sender_uuid, alice_uuid);
+        assert_eq!(bob_ptext.device_id, alice_device_id);
+
+        // A v2 message for Bob can't be decrypted by anyone else.
+        let usmc = UnidentifiedSenderMessageContent::new(
+            CiphertextMessageType::Plaintext,
+            sender_cert.clone(),
+            vec![],
+            ContentHint::Default,
+            None,
+        )?;
+        let alice_ctext = sealed_sender_v2_encrypt_from_usmc(
+            &bob_uuid_address,
+            &usmc,
+            &mut alice_store.identity_store,
+            None,
+            &mut rng,
+        )
+        .await?;
+        let bob_usmc =
+            sealed_sender_decrypt_to_usmc(&alice_ctext, &mut bob_store.identity_store, None)
+                .await?;
+        assert_eq!(bob_usmc.serialized()?, usmc.serialized()?);
+
+        let mut carol_store = support::test_in_memory_protocol_store()?;
+        assert!(matches!(
+            sealed_sender_decrypt_to_usmc(&alice_ctext, &mut carol_store.identity_store, None)
+                .await,
+            Err(SignalProtocolError::InvalidSealedSenderMessage(_))
+        ));
+
+        // Bob can choose to stop accepting v1 messages.
+        let v2_only = [SealedSenderVersion::V2];
+        let bob_usmc = sealed_sender_decrypt_to_usmc_with_versions(
+            &alice_ctext,
+            &v2_only,
+            &mut bob_store.identity_store,
+            None,
+        )
+        .await?;
+        assert_eq!(bob_usmc.serialized()?, usmc.serialized()?);
+
+        let alice_v1_ctext = sealed_sender_encrypt_from_usmc(
+            &bob_uuid_address,
+            &usmc,
+            &mut alice_store.identity_store,
+            None,
+            &mut rng,
+        )
+        .await?;
+        assert!(matches!(
+            sealed_sender_decrypt_to_usmc_with_versions(
+                &alice_v1_ctext,
+                &v2_only,
+                &mut bob_store.identity_store,
+                None,
+            )
+            .await,
+            Err(SignalProtocolError::InvalidSealedSenderMessage(_))
+        ));
+
+        let alice_v1_ctext = sealed_sender_encrypt(
+            &bob_uuid_address,
+            &sender_cert,
+            &alice_ptext,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            None,
+            &mut rng,
+        )
+        .await?;
+        assert!(matches!(
+            sealed_sender_decrypt_with_options(
+                &alice_v1_ctext,
+                SealedSenderDecryptionOptions {
+                    trust_roots: &[trust_root.public_key.into()],
+                    certificate_cache: None,
+                    accepted_versions: &v2_only,
+                },
+                expires - 1,
+                None,
+                bob_uuid.clone(),
+                bob_device_id,
+                &mut bob_store.identity_store,
+                &mut bob_store.session_store,
+                &mut bob_store.pre_key_store,
+                &mut bob_store.signed_pre_key_store,
+                None,
+            )
+            .await,
+            Err(SignalProtocolError::InvalidSealedSenderMessage(_))
+        ));
+
+        Ok(())
+    }
+    .now_or_never()
+    .expect("sync")
+}
+
 #[test]
 fn test_sender_key_in_sealed_sender() -> Result<(), SignalProtocolError> {
     async {
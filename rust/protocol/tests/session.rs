@@ -887,6 +887,242 @@ fn optional_one_time_prekey() -> Result<(), SignalProtocolError> {
     .expect("sync")
 }
 
+#[test]
+fn encrypt_for_devices() -> Result<(), SignalProtocolError> {
+    async {
+        let mut csprng = OsRng;
+
+        let alice_address = ProtocolAddress::new("+14151111111".to_owned(), 1);
+        let bob_name = "+14151111112";
+
+        let mut alice_store = support::test_in_memory_protocol_store()?;
+        let mut bob_stores = vec![
+            support::test_in_memory_protocol_store()?,
+            support::test_in_memory_protocol_store()?,
+            support::test_in_memory_protocol_store()?,
+        ];
+
+        // Devices 1, 2, and 4 have sessions; device 3 doesn't
+        let mut registration_ids = vec![];
+        for (bob_store, device_id) in bob_stores.iter_mut().zip([1, 2, 4]) {
+            let bob_pre_key_bundle = create_pre_key_bundle(bob_store, &mut csprng).await?;
+            process_prekey_bundle(
+                &ProtocolAddress::new(bob_name.to_owned(), device_id),
+                &mut alice_store.session_store,
+                &mut alice_store.identity_store,
+                &bob_pre_key_bundle,
+                &mut csprng,
+                None,
+            )
+            .await?;
+            registration_ids.push(bob_pre_key_bundle.registration_id()?);
+        }
+
+        // Device 4's identity key has changed since its session was created
+        alice_store
+            .save_identity(
+                &ProtocolAddress::new(bob_name.to_owned(), 4),
+                IdentityKeyPair::generate(&mut csprng).identity_key(),
+                None,
+            )
+            .await?;
+
+        let original_message = "L'enfer, c'est les autres";
+        let result = message_encrypt_for_devices(
+            original_message.as_bytes(),
+            bob_name,
+            &[
+                (1, registration_ids[0]),
+                (2, registration_ids[1].wrapping_add(1)),
+                (3, 1234),
+                (4, registration_ids[2]),
+            ],
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            None,
+        )
+        .await?;
+
+        assert!(!result.is_complete());
+        assert_eq!(result.missing_devices, vec![3]);
+        assert_eq!(result.stale_devices, vec![2]);
+        assert_eq!(result.untrusted_devices, vec![4]);
+        assert_eq!(result.messages.len(), 1);
+
+        let device_message = &result.messages[0];
+        assert_eq!(device_message.device_id, 1);
+        assert_eq!(device_message.registration_id, registration_ids[0]);
+        assert_eq!(
+            device_message.message.message_type(),
+            CiphertextMessageType::PreKey
+        );
+
+        let incoming_message = CiphertextMessage::PreKeySignalMessage(
+            PreKeySignalMessage::try_from(device_message.message.serialize())?,
+        );
+        let ptext = decrypt(&mut bob_stores[0], &alice_address, &incoming_message).await?;
+        assert_eq!(
+            String::from_utf8(ptext).expect("valid utf8"),
+            original_message
+        );
+
+        Ok(())
+    }
+    .now_or_never()
+    .expect("sync")
+}
+
+#[test]
+fn retry_after_decryption_error() -> Result<(), SignalProtocolError> {
+    async {
+        let mut csprng = OsRng;
+
+        let alice_address = ProtocolAddress::new("+14151111111".to_owned(), 1);
+        let bob_address = ProtocolAddress::new("+14151111112".to_owned(), 1);
+
+        let mut alice_store = support::test_in_memory_protocol_store()?;
+        let mut bob_store = support::test_in_memory_protocol_store()?;
+
+        let bob_pre_key_bundle = create_pre_key_bundle(&mut bob_store, &mut csprng).await?;
+        process_prekey_bundle(
+            &bob_address,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            &bob_pre_key_bundle,
+            &mut csprng,
+            None,
+        )
+        .await?;
+
+        let distribution_id = uuid::Uuid::from_u128(0xd1d1d1d1_7000_11eb_b32a_33b8a8a487a6);
+        let mut sent_log = SentContentLog::new(10);
+        sent_log.record(
+            1000,
+            SentContent {
+                content: b"resendable".to_vec().into_boxed_slice(),
+                content_hint: ContentHint::Resendable,
+                group_id: None,
+                distribution_id: None,
+                recipients: vec![bob_address.clone()],
+            },
+        );
+        sent_log.record(
+            2000,
+            SentContent {
+                content: b"group".to_vec().into_boxed_slice(),
+                content_hint: ContentHint::Implicit,
+                group_id: Some(b"group id".to_vec().into_boxed_slice()),
+                distribution_id: Some(distribution_id),
+                recipients: vec![bob_address.clone()],
+            },
+        );
+
+        let outgoing_message = encrypt(&mut alice_store, &bob_address, "resendable").await?;
+        let error_message = DecryptionErrorMessage::for_original(
+            outgoing_message.serialize(),
+            outgoing_message.message_type(),
+            1000,
+            alice_address.device_id(),
+        )?;
+
+        // The session Bob failed to decrypt with is archived, then the content is resent.
+        let actions = process_decryption_error_message(
+            &error_message,
+            &bob_address,
+            alice_address.device_id(),
+            &sent_log,
+            &alice_store.session_store,
+            None,
+        )
+        .await?;
+        assert_eq!(actions.len(), 2);
+        assert!(
+            matches!(&actions[0], RetryAction::ArchiveSession(address) if address == &bob_address)
+        );
+        assert!(matches!(
+            &actions[1],
+            RetryAction::Resend { recipient, timestamp: 1000, content }
+                if recipient == &bob_address && &*content.content == b"resendable"
+        ));
+
+        // Errors for another of Alice's devices are ignored.
+        let actions = process_decryption_error_message(
+            &error_message,
+            &bob_address,
+            alice_address.device_id() + 1,
+            &sent_log,
+            &alice_store.session_store,
+            None,
+        )
+        .await?;
+        assert!(actions.is_empty());
+
+        // Content that has been forgotten is replaced by a null message on the new session.
+        sent_log.remove(1000);
+        let actions = process_decryption_error_message(
+            &error_message,
+            &bob_address,
+            alice_address.device_id(),
+            &sent_log,
+            &alice_store.session_store,
+            None,
+        )
+        .await?;
+        assert_eq!(actions.len(), 2);
+        assert!(
+            matches!(&actions[1], RetryAction::SendNullMessage(address) if address == &bob_address)
+        );
+
+        // Once the session has been replaced, there's nothing left to do.
+        let mut session_record = alice_store
+            .load_session(&bob_address, None)
+            .await?
+            .expect("session found");
+        session_record.archive_current_state()?;
+        alice_store
+            .store_session(&bob_address, &session_record, None)
+            .await?;
+
+        let actions = process_decryption_error_message(
+            &error_message,
+            &bob_address,
+            alice_address.device_id(),
+            &sent_log,
+            &alice_store.session_store,
+            None,
+        )
+        .await?;
+        assert!(actions.is_empty());
+
+        // Sender key failures reset the sender key; implicit content is not resent.
+        let error_message = DecryptionErrorMessage::for_original(
+            &[],
+            CiphertextMessageType::SenderKey,
+            2000,
+            alice_address.device_id(),
+        )?;
+        let actions = process_decryption_error_message(
+            &error_message,
+            &bob_address,
+            alice_address.device_id(),
+            &sent_log,
+            &alice_store.session_store,
+            None,
+        )
+        .await?;
+        assert_eq!(actions.len(), 1);
+        assert!(matches!(
+            &actions[0],
+            RetryAction::ResetSenderKey { recipient, distribution_id: id }
+                if recipient == &bob_address && *id == distribution_id
+        ));
+
+        Ok(())
+    }
+    .now_or_never()
+    .expect("sync")
+}
+
 #[test]
 fn basic_session_v3() -> Result<(), SignalProtocolError> {
     let (alice_session, bob_session) = initialize_sessions_v3()?;
@@ -0,0 +1,229 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Compares batch issuance and batch verification against one-at-a-time calls.
+//!
+//! Batch verification checks batchable presentations with a single multiscalar multiplication.
+//! Run with `--features rayon` to measure parallel issuance.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+extern crate zkgroup;
+
+const BATCH_SIZE: usize = 64;
+
+fn randomness(i: usize) -> zkgroup::RandomnessBytes {
+    let mut randomness = zkgroup::TEST_ARRAY_32;
+    randomness[..8].copy_from_slice(&(i as u64).to_be_bytes());
+    randomness
+}
+
+fn uid(i: usize) -> zkgroup::UidBytes {
+    let mut uid = zkgroup::TEST_ARRAY_16;
+    uid[..8].copy_from_slice(&(i as u64).to_be_bytes());
+    uid
+}
+
+fn benchmark_auth_batch(c: &mut Criterion) {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let master_key = zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1);
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::derive_from_master_key(master_key);
+    let group_public_params = group_secret_params.get_public_params();
+
+    let redemption_time = 123456u32;
+    let requests: Vec<_> = (0..BATCH_SIZE).map(|i| (uid(i), redemption_time)).collect();
+
+    let mut group = c.benchmark_group("auth_credential_batch");
+    group.throughput(Throughput::Elements(BATCH_SIZE as u64));
+
+    group.bench_function("issue_auth_credential", |b| {
+        b.iter(|| {
+            requests
+                .iter()
+                .enumerate()
+                .map(|(i, (uid, redemption_time))| {
+                    server_secret_params.issue_auth_credential(
+                        randomness(i),
+                        *uid,
+                        *redemption_time,
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("issue_auth_credentials", |b| {
+        b.iter(|| server_secret_params.issue_auth_credentials(zkgroup::TEST_ARRAY_32_2, &requests))
+    });
+
+    let credentials: Vec<_> = server_secret_params
+        .issue_auth_credentials(zkgroup::TEST_ARRAY_32_2, &requests)
+        .iter()
+        .zip(&requests)
+        .map(|(response, (uid, redemption_time))| {
+            server_public_params
+                .receive_auth_credential(*uid, *redemption_time, response)
+                .unwrap()
+        })
+        .collect();
+    let presentations: Vec<_> = credentials
+        .iter()
+        .enumerate()
+        .map(|(i, credential)| {
+            server_public_params.create_auth_credential_presentation(
+                randomness(i),
+                group_secret_params,
+                *credential,
+            )
+        })
+        .collect();
+    let batchable_presentations: Vec<_> = credentials
+        .iter()
+        .enumerate()
+        .map(|(i, credential)| {
+            server_public_params.create_batchable_auth_credential_presentation(
+                randomness(i),
+                group_secret_params,
+                *credential,
+            )
+        })
+        .collect();
+    let batch: Vec<_> = batchable_presentations
+        .iter()
+        .map(|presentation| (group_public_params, presentation))
+        .collect();
+
+    group.bench_function("verify_auth_credential_presentation", |b| {
+        b.iter(|| {
+            for presentation in &presentations {
+                server_secret_params
+                    .verify_auth_credential_presentation(group_public_params, presentation)
+                    .unwrap();
+            }
+        })
+    });
+    group.bench_function("verify_auth_credential_presentations", |b| {
+        b.iter(|| {
+            server_secret_params
+                .verify_auth_credential_presentations(&batch)
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn benchmark_profile_key_batch(c: &mut Criterion) {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let master_key = zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1);
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::derive_from_master_key(master_key);
+    let group_public_params = group_secret_params.get_public_params();
+
+    let profile_key =
+        zkgroup::profiles::ProfileKey::create(zkgroup::common::constants::TEST_ARRAY_32_1);
+    let contexts: Vec<_> = (0..BATCH_SIZE)
+        .map(|i| {
+            server_public_params.create_profile_key_credential_request_context(
+                randomness(i),
+                uid(i),
+                profile_key,
+            )
+        })
+        .collect();
+    let requests: Vec<_> = contexts
+        .iter()
+        .map(|context| context.get_request())
+        .collect();
+    let issue_batch: Vec<_> = requests
+        .iter()
+        .enumerate()
+        .map(|(i, request)| (request, uid(i), profile_key.get_commitment(uid(i))))
+        .collect();
+
+    let mut group = c.benchmark_group("profile_key_credential_batch");
+    group.throughput(Throughput::Elements(BATCH_SIZE as u64));
+
+    group.bench_function("issue_profile_key_credential", |b| {
+        b.iter(|| {
+            issue_batch
+                .iter()
+                .enumerate()
+                .map(|(i, (request, uid, commitment))| {
+                    server_secret_params
+                        .issue_profile_key_credential(randomness(i), request, *uid, *commitment)
+                        .unwrap()
+                })
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("issue_profile_key_credentials", |b| {
+        b.iter(|| {
+            server_secret_params
+                .issue_profile_key_credentials(zkgroup::TEST_ARRAY_32_2, &issue_batch)
+        })
+    });
+
+    let credentials: Vec<_> = server_secret_params
+        .issue_profile_key_credentials(zkgroup::TEST_ARRAY_32_2, &issue_batch)
+        .into_iter()
+        .zip(&contexts)
+        .map(|(response, context)| {
+            server_public_params
+                .receive_profile_key_credential(context, &response.unwrap())
+                .unwrap()
+        })
+        .collect();
+    let presentations: Vec<_> = credentials
+        .iter()
+        .enumerate()
+        .map(|(i, credential)| {
+            server_public_params.create_profile_key_credential_presentation(
+                randomness(i),
+                group_secret_params,
+                *credential,
+            )
+        })
+        .collect();
+    let batchable_presentations: Vec<_> = credentials
+        .iter()
+        .enumerate()
+        .map(|(i, credential)| {
+            server_public_params.create_batchable_profile_key_credential_presentation(
+                randomness(i),
+                group_secret_params,
+                *credential,
+            )
+        })
+        .collect();
+    let batch: Vec<_> = batchable_presentations
+        .iter()
+        .map(|presentation| (group_public_params, presentation))
+        .collect();
+
+    group.bench_function("verify_profile_key_credential_presentation", |b| {
+        b.iter(|| {
+            for presentation in &presentations {
+                server_secret_params
+                    .verify_profile_key_credential_presentation(group_public_params, presentation)
+                    .unwrap();
+            }
+        })
+    });
+    group.bench_function("verify_profile_key_credential_presentations", |b| {
+        b.iter(|| {
+            server_secret_params
+                .verify_profile_key_credential_presentations(&batch)
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_auth_batch, benchmark_profile_key_batch);
+criterion_main!(benches);
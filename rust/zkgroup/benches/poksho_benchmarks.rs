@@ -0,0 +1,105 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Compares verifying poksho proofs one at a time against `Statement::verify_proofs_batch`.
+//!
+//! Both verify the same proofs: the batch takes them in the batchable encoding, which carries
+//! the commitments instead of the challenge.
+
+#![allow(non_snake_case)]
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::scalar::Scalar;
+use poksho::{PointArgs, ScalarArgs, Statement};
+
+const BATCH_SIZE: usize = 64;
+
+fn benchmark_verify_proofs_batch(c: &mut Criterion) {
+    // Shaped like a credential presentation: a few equations sharing scalars across generators.
+    let mut st = Statement::new();
+    st.try_add("Z", &[("z", "I")]).unwrap();
+    st.try_add("C_x1", &[("t", "C_x0"), ("z0", "G_x0"), ("z", "G_x1")])
+        .unwrap();
+    st.try_add("A", &[("a1", "G_a1"), ("a2", "G_a2")]).unwrap();
+    st.try_add("C_y2-E_A2", &[("z", "G_y2"), ("z1", "-E_A1")])
+        .unwrap();
+    st.try_add("E_A1", &[("a1", "C_y1"), ("z1", "G_y1")])
+        .unwrap();
+
+    let point = |n: u64| Scalar::from(n) * RISTRETTO_BASEPOINT_POINT;
+    let message = [0u8; 32];
+
+    let mut point_args_list = Vec::with_capacity(BATCH_SIZE);
+    let mut proofs = Vec::with_capacity(BATCH_SIZE);
+    let mut batchable_proofs = Vec::with_capacity(BATCH_SIZE);
+    for i in 0..BATCH_SIZE as u64 {
+        let [z, t, a1, a2] = [10 * i + 1, 10 * i + 2, 10 * i + 3, 10 * i + 4].map(Scalar::from);
+        let z0 = -(z * t);
+        let z1 = -(z * a2);
+        let [I, C_x0, G_x0, G_x1, G_a1, G_a2, G_y1, G_y2] = [2, 3, 5, 7, 11, 13, 17, 19].map(point);
+        let C_y1 = a2 * G_a1;
+        let E_A1 = a1 * C_y1 + z1 * G_y1;
+
+        let mut scalar_args = ScalarArgs::new();
+        scalar_args.add("z", z);
+        scalar_args.add("t", t);
+        scalar_args.add("z0", z0);
+        scalar_args.add("a1", a1);
+        scalar_args.add("a2", a2);
+        scalar_args.add("z1", z1);
+
+        let mut point_args = PointArgs::new();
+        point_args.add("Z", z * I);
+        point_args.add("I", I);
+        point_args.add("C_x1", t * C_x0 + z0 * G_x0 + z * G_x1);
+        point_args.add("C_x0", C_x0);
+        point_args.add("G_x0", G_x0);
+        point_args.add("G_x1", G_x1);
+        point_args.add("A", a1 * G_a1 + a2 * G_a2);
+        point_args.add("G_a1", G_a1);
+        point_args.add("G_a2", G_a2);
+        point_args.add("C_y2-E_A2", z * G_y2 - z1 * E_A1);
+        point_args.add("G_y2", G_y2);
+        point_args.add("-E_A1", -E_A1);
+        point_args.add("E_A1", E_A1);
+        point_args.add("C_y1", C_y1);
+        point_args.add("G_y1", G_y1);
+
+        proofs.push(
+            st.prove(&scalar_args, &point_args, &message, &[i as u8; 32])
+                .unwrap(),
+        );
+        batchable_proofs.push(
+            st.prove_batchable(&scalar_args, &point_args, &message, &[i as u8; 32])
+                .unwrap(),
+        );
+        point_args_list.push(point_args);
+    }
+
+    let batch: Vec<(&[u8], &PointArgs, &[u8])> = batchable_proofs
+        .iter()
+        .zip(&point_args_list)
+        .map(|(proof, point_args)| (&proof[..], point_args, &message[..]))
+        .collect();
+
+    let mut group = c.benchmark_group("poksho_batch");
+    group.throughput(Throughput::Elements(BATCH_SIZE as u64));
+
+    group.bench_function("verify_proof", |b| {
+        b.iter(|| {
+            for (proof, point_args) in proofs.iter().zip(&point_args_list) {
+                st.verify_proof(proof, point_args, &message).unwrap();
+            }
+        })
+    });
+    group.bench_function("verify_proofs_batch", |b| {
+        b.iter(|| st.verify_proofs_batch(&batch).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_verify_proofs_batch);
+criterion_main!(benches);
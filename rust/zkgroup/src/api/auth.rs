@@ -6,7 +6,13 @@
 pub mod auth_credential;
 pub mod auth_credential_presentation;
 pub mod auth_credential_response;
+pub mod auth_credential_with_pni;
+pub mod auth_credential_with_pni_presentation;
+pub mod auth_credential_with_pni_response;
 
 pub use auth_credential::AuthCredential;
 pub use auth_credential_presentation::AuthCredentialPresentation;
 pub use auth_credential_response::AuthCredentialResponse;
+pub use auth_credential_with_pni::AuthCredentialWithPni;
+pub use auth_credential_with_pni_presentation::AuthCredentialWithPniPresentation;
+pub use auth_credential_with_pni_response::AuthCredentialWithPniResponse;
@@ -4,8 +4,12 @@
 //
 
 use crate::api;
+use crate::common::constants::*;
+use crate::common::errors::DeserializationError;
+use crate::common::serialization::{deserialize_layout, VersionedSerializable};
 use crate::common::simple_types::*;
 use crate::crypto;
+use crate::crypto::proofs::ProofFormat;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
@@ -16,7 +20,34 @@ pub struct AuthCredentialPresentation {
     pub(crate) redemption_time: RedemptionTime,
 }
 
+/// Version 1 carries a [`ProofFormat::Batchable`] proof; see
+/// [`ServerPublicParams::create_batchable_auth_credential_presentation`](crate::ServerPublicParams::create_batchable_auth_credential_presentation).
+impl VersionedSerializable for AuthCredentialPresentation {
+    const NAME: &'static str = "AuthCredentialPresentation";
+    const VERSION: u8 = 0;
+    const LEN: usize = AUTH_CREDENTIAL_PRESENTATION_LEN;
+
+    fn serialized_len(version: u8) -> Option<usize> {
+        match version {
+            0 => Some(Self::LEN),
+            1 => Some(AUTH_CREDENTIAL_PRESENTATION_V1_LEN),
+            _ => None,
+        }
+    }
+
+    fn deserialize_version(_version: u8, bytes: &[u8]) -> Result<Self, DeserializationError> {
+        deserialize_layout(bytes, Self::NAME)
+    }
+}
+
 impl AuthCredentialPresentation {
+    pub(crate) fn proof_format(&self) -> ProofFormat {
+        match self.reserved {
+            [0] => ProofFormat::Compact,
+            _ => ProofFormat::Batchable,
+        }
+    }
+
     pub fn get_uuid_ciphertext(&self) -> api::groups::UuidCiphertext {
         api::groups::UuidCiphertext {
             reserved: Default::default(),
@@ -0,0 +1,19 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::simple_types::*;
+use crate::crypto;
+
+/// An auth credential that can be presented as either the account's ACI or PNI.
+///
+/// Unlike [`AuthCredential`](super::AuthCredential), the redemption time is in seconds.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AuthCredentialWithPni {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) credential: crypto::kvac::Credential<crypto::credentials::AuthCredentialWithPni>,
+    pub(crate) redemption_time: Timestamp,
+}
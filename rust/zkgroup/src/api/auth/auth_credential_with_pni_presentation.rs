@@ -0,0 +1,36 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use crate::api;
+use crate::common::simple_types::*;
+use crate::crypto;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct AuthCredentialWithPniPresentation {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) presentation: crypto::kvac::Presentation<crypto::credentials::AuthCredentialWithPni>,
+    pub(crate) redemption_time: Timestamp,
+}
+
+impl AuthCredentialWithPniPresentation {
+    pub fn get_aci_ciphertext(&self) -> api::groups::UuidCiphertext {
+        api::groups::UuidCiphertext {
+            reserved: Default::default(),
+            ciphertext: self.presentation.get_ciphertexts()[0],
+        }
+    }
+
+    pub fn get_pni_ciphertext(&self) -> api::groups::PniCiphertext {
+        api::groups::PniCiphertext {
+            reserved: Default::default(),
+            ciphertext: self.presentation.get_ciphertexts()[1],
+        }
+    }
+
+    pub fn get_redemption_time(&self) -> Timestamp {
+        self.redemption_time
+    }
+}
@@ -0,0 +1,15 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use crate::common::simple_types::*;
+use crate::crypto;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct AuthCredentialWithPniResponse {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) response:
+        crypto::kvac::UnblindedIssuanceResponse<crypto::credentials::AuthCredentialWithPni>,
+}
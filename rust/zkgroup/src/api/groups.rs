@@ -3,12 +3,24 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+pub mod attribute_ciphertext;
+pub mod blob_stream;
 pub mod group_params;
+pub mod invite_link;
 pub mod profile_key_ciphertext;
 pub mod uuid_ciphertext;
 
+pub use attribute_ciphertext::AttributeCiphertextPresentation;
+pub use attribute_ciphertext::E164Ciphertext;
+pub use attribute_ciphertext::PniCiphertext;
+pub use attribute_ciphertext::UsernameCiphertext;
+pub use blob_stream::BlobDecryptReader;
+pub use blob_stream::BlobEncryptWriter;
 pub use group_params::GroupMasterKey;
 pub use group_params::GroupPublicParams;
 pub use group_params::GroupSecretParams;
+pub use invite_link::GroupInviteLink;
+pub use invite_link::InviteLinkPresentation;
+pub use invite_link::InviteLinkPublicKey;
 pub use profile_key_ciphertext::ProfileKeyCiphertext;
 pub use uuid_ciphertext::UuidCiphertext;
@@ -0,0 +1,34 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use crate::common::simple_types::*;
+use crate::crypto;
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PniCiphertext {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) ciphertext: crypto::uid_encryption::Ciphertext,
+}
+
+#[derive(Copy, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct E164Ciphertext {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) ciphertext: crypto::uid_encryption::Ciphertext,
+}
+
+#[derive(Copy, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct UsernameCiphertext {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) ciphertext: crypto::uid_encryption::Ciphertext,
+}
+
+/// Shows a server that already knows a member's attribute that one of the ciphertexts above
+/// encrypts it under a particular group's key.
+#[derive(Serialize, Deserialize)]
+pub struct AttributeCiphertextPresentation {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) proof: crypto::proofs::AttributeEncryptionProof,
+}
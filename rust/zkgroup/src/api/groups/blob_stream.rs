@@ -0,0 +1,374 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Chunked blob encryption for group avatars and other large group-state blobs.
+//!
+//! A streamed blob is laid out as:
+//!
+//! ```text
+//! header || chunk_0 || ... || chunk_n || BLOB_STREAM_VERSION
+//! ```
+//!
+//! The header is the version byte, a random nonce prefix, and the plaintext chunk length as a
+//! big-endian `u32`. Every chunk but the last holds exactly that much plaintext; the last holds at
+//! most that much, and may be empty. Each chunk is sealed with AES-GCM-SIV under the group's blob
+//! key, with the header as associated data and a nonce of `prefix || index || is_last`, so
+//! reordering, dropping, or truncating chunks all cause an authentication failure.
+//!
+//! Single-shot blobs from [`GroupSecretParams::encrypt_blob`] end in a zero reserved byte where
+//! streamed blobs end in [`BLOB_STREAM_VERSION`], which is how
+//! [`GroupSecretParams::decrypt_blob`] tells them apart.
+//!
+//! [`GroupSecretParams::encrypt_blob`]: crate::api::groups::GroupSecretParams::encrypt_blob
+//! [`GroupSecretParams::decrypt_blob`]: crate::api::groups::GroupSecretParams::decrypt_blob
+
+use std::convert::TryInto;
+use std::io::{self, Read, Write};
+
+use crate::common::constants::*;
+use crate::common::errors::*;
+use crate::common::simple_types::*;
+use aead::{generic_array::GenericArray, Aead, NewAead, Payload};
+use aes_gcm_siv::Aes256GcmSiv;
+
+const HEADER_LEN: usize = 1 + BLOB_STREAM_NONCE_PREFIX_LEN + 4;
+
+type Header = [u8; HEADER_LEN];
+
+fn chunk_nonce(header: &Header, index: u32, is_last: bool) -> [u8; AESGCM_NONCE_LEN] {
+    let mut nonce = [0u8; AESGCM_NONCE_LEN];
+    nonce[..BLOB_STREAM_NONCE_PREFIX_LEN]
+        .copy_from_slice(&header[1..1 + BLOB_STREAM_NONCE_PREFIX_LEN]);
+    nonce[BLOB_STREAM_NONCE_PREFIX_LEN..AESGCM_NONCE_LEN - 1].copy_from_slice(&index.to_be_bytes());
+    nonce[AESGCM_NONCE_LEN - 1] = is_last as u8;
+    nonce
+}
+
+fn invalid_data(e: impl Into<ZkGroupError>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.into())
+}
+
+/// Encrypts a blob as it is written, passing the ciphertext on to an inner writer.
+///
+/// The blob is not complete until [`finish`](Self::finish) is called; dropping the writer without
+/// finishing leaves a blob that will fail to decrypt.
+pub struct BlobEncryptWriter<W: Write> {
+    inner: W,
+    cipher: Aes256GcmSiv,
+    header: Header,
+    chunk_len: usize,
+    index: u32,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> BlobEncryptWriter<W> {
+    pub(crate) fn new(
+        blob_key: &AesKeyBytes,
+        nonce_prefix: &[u8],
+        chunk_len: usize,
+        mut inner: W,
+    ) -> io::Result<Self> {
+        if chunk_len == 0 || chunk_len > MAX_BLOB_STREAM_CHUNK_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                ZkGroupError::BadArgs,
+            ));
+        }
+        let mut header: Header = Default::default();
+        header[0] = BLOB_STREAM_VERSION;
+        header[1..1 + BLOB_STREAM_NONCE_PREFIX_LEN].copy_from_slice(nonce_prefix);
+        header[1 + BLOB_STREAM_NONCE_PREFIX_LEN..]
+            .copy_from_slice(&(chunk_len as u32).to_be_bytes());
+        inner.write_all(&header)?;
+
+        Ok(Self {
+            inner,
+            cipher: Aes256GcmSiv::new(GenericArray::from_slice(blob_key)),
+            header,
+            chunk_len,
+            index: 0,
+            pending: Vec::with_capacity(chunk_len),
+        })
+    }
+
+    fn write_chunk(&mut self, is_last: bool) -> io::Result<()> {
+        let nonce = chunk_nonce(&self.header, self.index, is_last);
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                GenericArray::from_slice(&nonce),
+                Payload {
+                    msg: &self.pending,
+                    aad: &self.header,
+                },
+            )
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, ZkGroupError::BadArgs))?;
+        self.inner.write_all(&ciphertext)?;
+        self.pending.clear();
+        self.index = self
+            .index
+            .checked_add(1)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, ZkGroupError::BadArgs))?;
+        Ok(())
+    }
+
+    /// Writes the final chunk and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_chunk(true)?;
+        self.inner.write_all(&[BLOB_STREAM_VERSION])?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for BlobEncryptWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // A full chunk is only sealed once more data arrives, since the last chunk must be
+        // marked as such and we can't know it's the last until `finish`.
+        if self.pending.len() == self.chunk_len && !buf.is_empty() {
+            self.write_chunk(false)?;
+        }
+        let len = buf.len().min(self.chunk_len - self.pending.len());
+        self.pending.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Decrypts a streamed blob as it is read from an inner reader.
+///
+/// Only plaintext from chunks that have been authenticated is ever returned. Reads fail with
+/// [`io::ErrorKind::InvalidData`] wrapping a [`ZkGroupError`] if the blob has been modified or
+/// truncated, so a caller must read to the end before trusting that it has the whole blob.
+pub struct BlobDecryptReader<R: Read> {
+    inner: R,
+    cipher: Aes256GcmSiv,
+    header: Option<Header>,
+    index: u32,
+    ciphertext: Vec<u8>,
+    plaintext: Vec<u8>,
+    plaintext_offset: usize,
+    done: bool,
+}
+
+impl<R: Read> BlobDecryptReader<R> {
+    pub(crate) fn new(blob_key: &AesKeyBytes, inner: R) -> Self {
+        Self {
+            inner,
+            cipher: Aes256GcmSiv::new(GenericArray::from_slice(blob_key)),
+            header: None,
+            index: 0,
+            ciphertext: Vec::new(),
+            plaintext: Vec::new(),
+            plaintext_offset: 0,
+            done: false,
+        }
+    }
+
+    /// Reads until `self.ciphertext` holds `len` bytes, returning false on a clean EOF.
+    fn fill_to(&mut self, len: usize) -> io::Result<bool> {
+        while self.ciphertext.len() < len {
+            let start = self.ciphertext.len();
+            self.ciphertext.resize(len, 0);
+            match self.inner.read(&mut self.ciphertext[start..]) {
+                Ok(0) => {
+                    self.ciphertext.truncate(start);
+                    return Ok(false);
+                }
+                Ok(n) => self.ciphertext.truncate(start + n),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => self.ciphertext.truncate(start),
+                Err(e) => {
+                    self.ciphertext.truncate(start);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    fn read_header(&mut self) -> io::Result<Header> {
+        if let Some(header) = self.header {
+            return Ok(header);
+        }
+        if !self.fill_to(HEADER_LEN)? {
+            return Err(invalid_data(DecryptionError::BlobTruncated));
+        }
+        let header: Header = self.ciphertext[..HEADER_LEN]
+            .try_into()
+            .expect("correct size");
+        let chunk_len = u32::from_be_bytes(
+            header[1 + BLOB_STREAM_NONCE_PREFIX_LEN..]
+                .try_into()
+                .unwrap(),
+        );
+        if header[0] != BLOB_STREAM_VERSION
+            || chunk_len == 0
+            || chunk_len as usize > MAX_BLOB_STREAM_CHUNK_LEN
+        {
+            return Err(invalid_data(DecryptionError::BlobStreamHeader));
+        }
+        self.ciphertext.drain(..HEADER_LEN);
+        self.header = Some(header);
+        Ok(header)
+    }
+
+    fn read_chunk(&mut self) -> io::Result<()> {
+        let header = self.read_header()?;
+        let chunk_len = u32::from_be_bytes(
+            header[1 + BLOB_STREAM_NONCE_PREFIX_LEN..]
+                .try_into()
+                .unwrap(),
+        );
+        let sealed_chunk_len = chunk_len as usize + AESGCM_TAG_LEN;
+
+        // Look one byte past a full chunk plus trailer: if it's there, this chunk isn't the last.
+        let is_last = !self.fill_to(sealed_chunk_len + 2)?;
+        let sealed = if is_last {
+            match self.ciphertext.split_last() {
+                Some((&BLOB_STREAM_VERSION, rest)) if rest.len() >= AESGCM_TAG_LEN => rest,
+                _ => return Err(invalid_data(DecryptionError::BlobTruncated)),
+            }
+        } else {
+            &self.ciphertext[..sealed_chunk_len]
+        };
+
+        let nonce = chunk_nonce(&header, self.index, is_last);
+        self.plaintext = self
+            .cipher
+            .decrypt(
+                GenericArray::from_slice(&nonce),
+                Payload {
+                    msg: sealed,
+                    aad: &header,
+                },
+            )
+            .map_err(|_| invalid_data(DecryptionError::BlobAuthentication))?;
+        self.plaintext_offset = 0;
+
+        if is_last {
+            self.ciphertext.clear();
+            self.done = true;
+        } else {
+            self.ciphertext.drain(..sealed_chunk_len);
+            self.index = self
+                .index
+                .checked_add(1)
+                .ok_or_else(|| invalid_data(DecryptionError::BlobStreamHeader))?;
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for BlobDecryptReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.plaintext_offset == self.plaintext.len() {
+            if self.done || buf.is_empty() {
+                return Ok(0);
+            }
+            self.read_chunk()?;
+        }
+        let available = &self.plaintext[self.plaintext_offset..];
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.plaintext_offset += len;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: AesKeyBytes = TEST_ARRAY_32;
+    const PREFIX: [u8; BLOB_STREAM_NONCE_PREFIX_LEN] = [7u8; BLOB_STREAM_NONCE_PREFIX_LEN];
+
+    fn encrypt(plaintext: &[u8], chunk_len: usize) -> Vec<u8> {
+        let mut writer = BlobEncryptWriter::new(&KEY, &PREFIX, chunk_len, Vec::new()).unwrap();
+        // Write in uneven pieces to exercise chunk boundaries.
+        for piece in plaintext.chunks(3) {
+            writer.write_all(piece).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    fn decrypt(ciphertext: &[u8]) -> io::Result<Vec<u8>> {
+        let mut plaintext = Vec::new();
+        BlobDecryptReader::new(&KEY, ciphertext).read_to_end(&mut plaintext)?;
+        Ok(plaintext)
+    }
+
+    fn decryption_error(ciphertext: &[u8]) -> ZkGroupError {
+        let err = decrypt(ciphertext).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        *err.into_inner()
+            .unwrap()
+            .downcast::<ZkGroupError>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let plaintext: Vec<u8> = (0..100).collect();
+        for &len in &[0, 1, 9, 10, 11, 99, 100] {
+            let ciphertext = encrypt(&plaintext[..len], 10);
+            let chunks = ((len + 9) / 10).max(1);
+            assert_eq!(
+                ciphertext.len(),
+                HEADER_LEN + len + chunks * AESGCM_TAG_LEN + 1
+            );
+            assert_eq!(decrypt(&ciphertext).unwrap(), &plaintext[..len]);
+        }
+    }
+
+    #[test]
+    fn test_truncation_is_detected() {
+        let ciphertext = encrypt(&[1u8; 30], 10);
+        let sealed_chunk_len = 10 + AESGCM_TAG_LEN;
+
+        // Cut at a chunk boundary, with and without a forged trailer.
+        let at_boundary = &ciphertext[..HEADER_LEN + 2 * sealed_chunk_len];
+        assert_eq!(
+            decryption_error(at_boundary),
+            DecryptionError::BlobTruncated.into()
+        );
+        let mut forged = at_boundary.to_vec();
+        forged.push(BLOB_STREAM_VERSION);
+        assert_eq!(
+            decryption_error(&forged),
+            DecryptionError::BlobAuthentication.into()
+        );
+
+        assert_eq!(
+            decryption_error(&ciphertext[..HEADER_LEN - 1]),
+            DecryptionError::BlobTruncated.into()
+        );
+    }
+
+    #[test]
+    fn test_reordering_is_detected() {
+        let mut ciphertext = encrypt(&[1u8; 30], 10);
+        let sealed_chunk_len = 10 + AESGCM_TAG_LEN;
+        let (first, second) = ciphertext[HEADER_LEN..].split_at_mut(sealed_chunk_len);
+        first.swap_with_slice(&mut second[..sealed_chunk_len]);
+        assert_eq!(
+            decryption_error(&ciphertext),
+            DecryptionError::BlobAuthentication.into()
+        );
+    }
+
+    #[test]
+    fn test_bad_header() {
+        let mut ciphertext = encrypt(b"hello", 10);
+        ciphertext[HEADER_LEN - 1] = 0;
+        ciphertext[HEADER_LEN - 2] = 0;
+        assert_eq!(
+            decryption_error(&ciphertext),
+            DecryptionError::BlobStreamHeader.into()
+        );
+    }
+}
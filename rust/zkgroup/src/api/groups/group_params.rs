@@ -4,6 +4,7 @@
 //
 
 use std::convert::TryInto;
+use std::io::{Read, Write};
 
 use crate::api;
 use crate::common::constants::*;
@@ -119,10 +120,124 @@ impl GroupSecretParams {
         &self,
         ciphertext: api::groups::UuidCiphertext,
     ) -> Result<UidBytes, ZkGroupError> {
-        let uid = self.uid_enc_key_pair.decrypt(ciphertext.ciphertext)?;
+        let uid: crypto::uid_struct::UidStruct =
+            self.uid_enc_key_pair.decrypt(ciphertext.ciphertext)?;
         Ok(uid.to_bytes())
     }
 
+    pub fn encrypt_pni(&self, pni_bytes: UidBytes) -> api::groups::PniCiphertext {
+        let pni = crypto::pni_struct::PniStruct::new(pni_bytes);
+        self.encrypt_pni_struct(pni)
+    }
+
+    pub fn encrypt_pni_struct(
+        &self,
+        pni: crypto::pni_struct::PniStruct,
+    ) -> api::groups::PniCiphertext {
+        api::groups::PniCiphertext {
+            reserved: Default::default(),
+            ciphertext: self.uid_enc_key_pair.encrypt(pni),
+        }
+    }
+
+    pub fn decrypt_pni(
+        &self,
+        ciphertext: api::groups::PniCiphertext,
+    ) -> Result<UidBytes, ZkGroupError> {
+        let pni: crypto::pni_struct::PniStruct =
+            self.uid_enc_key_pair.decrypt(ciphertext.ciphertext)?;
+        Ok(pni.to_bytes())
+    }
+
+    /// Fails with [`ZkGroupError::InvalidAttribute`] unless `e164` looks like `+14155550100`.
+    pub fn encrypt_e164(&self, e164: &str) -> Result<api::groups::E164Ciphertext, ZkGroupError> {
+        let e164 = e164_struct(e164)?;
+        Ok(api::groups::E164Ciphertext {
+            reserved: Default::default(),
+            ciphertext: self.uid_enc_key_pair.encrypt(e164),
+        })
+    }
+
+    pub fn decrypt_e164(
+        &self,
+        ciphertext: api::groups::E164Ciphertext,
+    ) -> Result<String, ZkGroupError> {
+        let e164: crypto::attribute_struct::E164Struct =
+            self.uid_enc_key_pair.decrypt(ciphertext.ciphertext)?;
+        Ok(e164.to_e164())
+    }
+
+    /// Fails with [`ZkGroupError::InvalidAttribute`] if `username` is empty, contains a NUL, or
+    /// is longer than [`ATTRIBUTE_LEN`] bytes.
+    pub fn encrypt_username(
+        &self,
+        username: &str,
+    ) -> Result<api::groups::UsernameCiphertext, ZkGroupError> {
+        let username = username_struct(username)?;
+        Ok(api::groups::UsernameCiphertext {
+            reserved: Default::default(),
+            ciphertext: self.uid_enc_key_pair.encrypt(username),
+        })
+    }
+
+    pub fn decrypt_username(
+        &self,
+        ciphertext: api::groups::UsernameCiphertext,
+    ) -> Result<String, ZkGroupError> {
+        let username: crypto::attribute_struct::UsernameStruct =
+            self.uid_enc_key_pair.decrypt(ciphertext.ciphertext)?;
+        Ok(username.to_username())
+    }
+
+    pub fn create_pni_ciphertext_presentation(
+        &self,
+        randomness: RandomnessBytes,
+        pni_bytes: UidBytes,
+    ) -> api::groups::AttributeCiphertextPresentation {
+        let pni = crypto::pni_struct::PniStruct::new(pni_bytes);
+        self.create_attribute_ciphertext_presentation(randomness, pni)
+    }
+
+    pub fn create_e164_ciphertext_presentation(
+        &self,
+        randomness: RandomnessBytes,
+        e164: &str,
+    ) -> Result<api::groups::AttributeCiphertextPresentation, ZkGroupError> {
+        let e164 = e164_struct(e164)?;
+        Ok(self.create_attribute_ciphertext_presentation(randomness, e164))
+    }
+
+    pub fn create_username_ciphertext_presentation(
+        &self,
+        randomness: RandomnessBytes,
+        username: &str,
+    ) -> Result<api::groups::AttributeCiphertextPresentation, ZkGroupError> {
+        let username = username_struct(username)?;
+        Ok(self.create_attribute_ciphertext_presentation(randomness, username))
+    }
+
+    fn create_attribute_ciphertext_presentation<A: crypto::uid_encryption::Attribute>(
+        &self,
+        randomness: RandomnessBytes,
+        attr: A,
+    ) -> api::groups::AttributeCiphertextPresentation {
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20211201_Random_GroupSecretParams_CreateAttributeCiphertextPresentation",
+            &randomness,
+        );
+        let ciphertext = self.uid_enc_key_pair.encrypt(attr);
+        let proof = crypto::proofs::AttributeEncryptionProof::new(
+            self.uid_enc_key_pair,
+            attr,
+            ciphertext,
+            &mut sho,
+        );
+        api::groups::AttributeCiphertextPresentation {
+            reserved: Default::default(),
+            proof,
+        }
+    }
+
     pub fn encrypt_profile_key(
         &self,
         profile_key: api::profiles::ProfileKey,
@@ -193,10 +308,58 @@ impl GroupSecretParams {
         self.encrypt_blob(randomness, &padded_plaintext)
     }
 
+    /// Starts encrypting a blob in chunks of [`BLOB_STREAM_CHUNK_LEN`], writing the ciphertext to
+    /// `writer`.
+    ///
+    /// Call [`BlobEncryptWriter::finish`](api::groups::BlobEncryptWriter::finish) once all of the
+    /// plaintext has been written. The result can be decrypted with
+    /// [`decrypt_blob_stream`](Self::decrypt_blob_stream) or, if it fits in memory,
+    /// [`decrypt_blob`](Self::decrypt_blob).
+    pub fn encrypt_blob_stream<W: Write>(
+        &self,
+        randomness: RandomnessBytes,
+        writer: W,
+    ) -> std::io::Result<api::groups::BlobEncryptWriter<W>> {
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20211201_Random_GroupSecretParams_EncryptBlobStream",
+            &randomness,
+        );
+        api::groups::BlobEncryptWriter::new(
+            &self.blob_key,
+            &sho.squeeze(BLOB_STREAM_NONCE_PREFIX_LEN),
+            BLOB_STREAM_CHUNK_LEN,
+            writer,
+        )
+    }
+
+    /// Decrypts a blob produced by [`encrypt_blob_stream`](Self::encrypt_blob_stream) as it is
+    /// read from `reader`.
+    pub fn decrypt_blob_stream<R: Read>(&self, reader: R) -> api::groups::BlobDecryptReader<R> {
+        api::groups::BlobDecryptReader::new(&self.blob_key, reader)
+    }
+
+    /// Decrypts a blob from [`encrypt_blob`](Self::encrypt_blob) or
+    /// [`encrypt_blob_stream`](Self::encrypt_blob_stream).
     pub fn decrypt_blob(&self, ciphertext: &[u8]) -> Result<Vec<u8>, ZkGroupError> {
-        if ciphertext.len() < AESGCM_NONCE_LEN + 1 {
-            // AESGCM_NONCE_LEN = 12 bytes for IV
-            return Err(ZkGroupError::DecryptionFailure);
+        if ciphertext.last() == Some(&BLOB_STREAM_VERSION) {
+            let mut plaintext = Vec::new();
+            return match self
+                .decrypt_blob_stream(ciphertext)
+                .read_to_end(&mut plaintext)
+            {
+                Ok(_) => Ok(plaintext),
+                Err(e) => Err(e
+                    .into_inner()
+                    .and_then(|e| e.downcast::<ZkGroupError>().ok())
+                    .map_or(DecryptionError::BlobAuthentication.into(), |e| *e)),
+            };
+        }
+        if ciphertext.len() < AESGCM_NONCE_LEN + AESGCM_TAG_LEN + 1 {
+            // AESGCM_NONCE_LEN = 12 bytes for IV, AESGCM_TAG_LEN = 16 bytes for tag
+            return Err(DecryptionError::BlobTooShort {
+                len: ciphertext.len(),
+            }
+            .into());
         }
         let unreserved_len = ciphertext.len() - 1;
         let nonce = &ciphertext[unreserved_len - AESGCM_NONCE_LEN..unreserved_len];
@@ -208,14 +371,14 @@ impl GroupSecretParams {
         let mut decrypted = self.decrypt_blob(ciphertext)?;
 
         if decrypted.len() < ENCRYPTED_BLOB_PADDING_LENGTH_SIZE {
-            return Err(ZkGroupError::DecryptionFailure);
+            return Err(DecryptionError::BlobPadding.into());
         }
         let (padding_len_bytes, plaintext_plus_padding) =
             decrypted.split_at(ENCRYPTED_BLOB_PADDING_LENGTH_SIZE);
 
         let padding_len = u32::from_be_bytes(padding_len_bytes.try_into().expect("correct size"));
         if plaintext_plus_padding.len() < padding_len as usize {
-            return Err(ZkGroupError::DecryptionFailure);
+            return Err(DecryptionError::BlobPadding.into());
         }
 
         decrypted.truncate(decrypted.len() - padding_len as usize);
@@ -246,14 +409,17 @@ impl GroupSecretParams {
     ) -> Result<Vec<u8>, ZkGroupError> {
         if ciphertext.len() < AESGCM_TAG_LEN {
             // AESGCM_TAG_LEN = 16 bytes for tag
-            return Err(ZkGroupError::DecryptionFailure);
+            return Err(DecryptionError::BlobTooShort {
+                len: ciphertext.len(),
+            }
+            .into());
         }
         let key = GenericArray::from_slice(key);
         let aead_cipher = Aes256GcmSiv::new(&*key);
         let nonce = GenericArray::from_slice(nonce);
         match aead_cipher.decrypt(nonce, ciphertext) {
             Ok(plaintext_vec) => Ok(plaintext_vec),
-            Err(_) => Err(ZkGroupError::DecryptionFailure),
+            Err(_) => Err(DecryptionError::BlobAuthentication.into()),
         }
     }
 }
@@ -262,6 +428,54 @@ impl GroupPublicParams {
     pub fn get_group_identifier(&self) -> GroupIdentifierBytes {
         self.group_id
     }
+
+    pub fn verify_pni_ciphertext_presentation(
+        &self,
+        ciphertext: api::groups::PniCiphertext,
+        pni_bytes: UidBytes,
+        presentation: &api::groups::AttributeCiphertextPresentation,
+    ) -> Result<(), ZkGroupError> {
+        let pni = crypto::pni_struct::PniStruct::new(pni_bytes);
+        presentation
+            .proof
+            .verify(self.uid_enc_public_key, pni, ciphertext.ciphertext)
+    }
+
+    pub fn verify_e164_ciphertext_presentation(
+        &self,
+        ciphertext: api::groups::E164Ciphertext,
+        e164: &str,
+        presentation: &api::groups::AttributeCiphertextPresentation,
+    ) -> Result<(), ZkGroupError> {
+        let e164 = e164_struct(e164)?;
+        presentation
+            .proof
+            .verify(self.uid_enc_public_key, e164, ciphertext.ciphertext)
+    }
+
+    pub fn verify_username_ciphertext_presentation(
+        &self,
+        ciphertext: api::groups::UsernameCiphertext,
+        username: &str,
+        presentation: &api::groups::AttributeCiphertextPresentation,
+    ) -> Result<(), ZkGroupError> {
+        let username = username_struct(username)?;
+        presentation
+            .proof
+            .verify(self.uid_enc_public_key, username, ciphertext.ciphertext)
+    }
+}
+
+fn e164_struct(e164: &str) -> Result<crypto::attribute_struct::E164Struct, ZkGroupError> {
+    crypto::attribute_struct::E164Struct::from_e164(e164)
+        .ok_or(ZkGroupError::InvalidAttribute { kind: "E.164" })
+}
+
+fn username_struct(
+    username: &str,
+) -> Result<crypto::attribute_struct::UsernameStruct, ZkGroupError> {
+    crypto::attribute_struct::UsernameStruct::from_username(username)
+        .ok_or(ZkGroupError::InvalidAttribute { kind: "username" })
 }
 
 #[cfg(test)]
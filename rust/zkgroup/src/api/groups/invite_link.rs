@@ -0,0 +1,162 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use crate::api;
+use crate::common::constants::*;
+use crate::common::errors::*;
+use crate::common::serialization::{self, VersionedSerializable};
+use crate::common::sho::*;
+use crate::common::simple_types::*;
+use crate::crypto;
+use serde::{Deserialize, Serialize};
+
+/// The contents of a group invite link: everything needed to decrypt the group, plus a password
+/// that the server requires before letting someone join through the link.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct GroupInviteLink {
+    reserved: ReservedBytes,
+    master_key: api::groups::GroupMasterKey,
+    password: InviteLinkPasswordBytes,
+}
+
+/// Registered with the server by a group admin when enabling an invite link.
+#[derive(Copy, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InviteLinkPublicKey {
+    reserved: ReservedBytes,
+    pub(crate) public_key: crypto::invite_link::PublicKey,
+}
+
+/// Sent by a member joining through an invite link, alongside their
+/// [`AuthCredentialPresentation`](api::auth::AuthCredentialPresentation).
+///
+/// Proves knowledge of the link password without revealing it, and is bound to the joining
+/// member's encrypted UID.
+#[derive(Serialize, Deserialize)]
+pub struct InviteLinkPresentation {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) proof: crypto::proofs::InviteLinkPresentationProof,
+    pub(crate) ciphertext: crypto::uid_encryption::Ciphertext,
+}
+
+impl GroupInviteLink {
+    pub fn new(master_key: api::groups::GroupMasterKey, password: InviteLinkPasswordBytes) -> Self {
+        Self {
+            reserved: [<Self as VersionedSerializable>::VERSION],
+            master_key,
+            password,
+        }
+    }
+
+    /// Creates an invite link with a fresh password.
+    pub fn generate(master_key: api::groups::GroupMasterKey, randomness: RandomnessBytes) -> Self {
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20211201_Random_GroupInviteLink_Generate",
+            &randomness,
+        );
+        let mut password: InviteLinkPasswordBytes = Default::default();
+        password.copy_from_slice(&sho.squeeze(INVITE_LINK_PASSWORD_LEN)[..]);
+        Self::new(master_key, password)
+    }
+
+    /// Decodes the payload of an invite link, as produced by [`GroupInviteLink::serialize`].
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, DeserializationError> {
+        serialization::deserialize(bytes)
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        serialization::serialize(self)
+    }
+
+    pub fn get_master_key(&self) -> api::groups::GroupMasterKey {
+        self.master_key
+    }
+
+    pub fn get_password(&self) -> InviteLinkPasswordBytes {
+        self.password
+    }
+
+    pub fn get_group_secret_params(&self) -> api::groups::GroupSecretParams {
+        api::groups::GroupSecretParams::derive_from_master_key(self.master_key)
+    }
+
+    fn derive_key_pair(&self) -> crypto::invite_link::KeyPair {
+        let group_id = self.get_group_secret_params().get_group_identifier();
+        let mut input = Vec::with_capacity(GROUP_IDENTIFIER_LEN + INVITE_LINK_PASSWORD_LEN);
+        input.extend_from_slice(&group_id);
+        input.extend_from_slice(&self.password);
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20211201_GroupInviteLink_DeriveKeyPair",
+            &input,
+        );
+        crypto::invite_link::KeyPair::derive_from(&mut sho)
+    }
+
+    pub fn get_public_key(&self) -> InviteLinkPublicKey {
+        InviteLinkPublicKey {
+            reserved: Default::default(),
+            public_key: self.derive_key_pair().get_public_key(),
+        }
+    }
+
+    pub fn create_presentation(
+        &self,
+        randomness: RandomnessBytes,
+        uid_bytes: UidBytes,
+    ) -> InviteLinkPresentation {
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20211201_Random_GroupInviteLink_CreatePresentation",
+            &randomness,
+        );
+        let group_secret_params = self.get_group_secret_params();
+        let ciphertext = group_secret_params.encrypt_uuid(uid_bytes).ciphertext;
+        let message = presentation_message(group_secret_params.get_group_identifier(), &ciphertext);
+        let proof = crypto::proofs::InviteLinkPresentationProof::new(
+            self.derive_key_pair(),
+            &message,
+            &mut sho,
+        );
+        InviteLinkPresentation {
+            reserved: Default::default(),
+            proof,
+            ciphertext,
+        }
+    }
+}
+
+impl InviteLinkPublicKey {
+    /// Checks that `presentation` was made by someone holding the invite link for this group.
+    ///
+    /// The caller should also check that the presentation's UUID ciphertext matches the one in
+    /// the joining member's auth credential presentation.
+    pub fn verify_presentation(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &InviteLinkPresentation,
+    ) -> Result<(), ZkGroupError> {
+        let message = presentation_message(
+            group_public_params.get_group_identifier(),
+            &presentation.ciphertext,
+        );
+        presentation.proof.verify(self.public_key, &message)
+    }
+}
+
+impl InviteLinkPresentation {
+    pub fn get_uuid_ciphertext(&self) -> api::groups::UuidCiphertext {
+        api::groups::UuidCiphertext {
+            reserved: Default::default(),
+            ciphertext: self.ciphertext,
+        }
+    }
+}
+
+fn presentation_message(
+    group_id: GroupIdentifierBytes,
+    ciphertext: &crypto::uid_encryption::Ciphertext,
+) -> Vec<u8> {
+    let mut message = group_id.to_vec();
+    message.extend(bincode::serialize(ciphertext).expect("can serialize"));
+    message
+}
@@ -3,14 +3,27 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+pub mod extended_receipt_credential;
+pub mod extended_receipt_credential_presentation;
+pub mod extended_receipt_credential_request;
+pub mod extended_receipt_credential_request_context;
+pub mod extended_receipt_credential_response;
 pub mod receipt_credential;
 pub mod receipt_credential_presentation;
 pub mod receipt_credential_request;
 pub mod receipt_credential_request_context;
 pub mod receipt_credential_response;
+pub mod redemption_ledger;
 
+pub use crate::crypto::receipt_struct::ReceiptDisclosure;
+pub use extended_receipt_credential::ExtendedReceiptCredential;
+pub use extended_receipt_credential_presentation::ExtendedReceiptCredentialPresentation;
+pub use extended_receipt_credential_request::ExtendedReceiptCredentialRequest;
+pub use extended_receipt_credential_request_context::ExtendedReceiptCredentialRequestContext;
+pub use extended_receipt_credential_response::ExtendedReceiptCredentialResponse;
 pub use receipt_credential::ReceiptCredential;
 pub use receipt_credential_presentation::ReceiptCredentialPresentation;
 pub use receipt_credential_request::ReceiptCredentialRequest;
 pub use receipt_credential_request_context::ReceiptCredentialRequestContext;
 pub use receipt_credential_response::ReceiptCredentialResponse;
+pub use redemption_ledger::{InMemoryReceiptRedemptionLedger, ReceiptRedemptionLedger};
@@ -0,0 +1,38 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::simple_types::*;
+use crate::crypto;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExtendedReceiptCredential {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) credential: crypto::kvac::Credential<crypto::credentials::ExtendedReceiptCredential>,
+    pub(crate) receipt_expiration_time: ReceiptExpirationTime,
+    pub(crate) receipt_level: ReceiptLevel,
+    pub(crate) receipt_serial_bytes: ReceiptSerialBytes,
+    pub(crate) payment_type: ReceiptPaymentType,
+    pub(crate) validity_start_time: ReceiptValidityStartTime,
+}
+
+impl ExtendedReceiptCredential {
+    pub fn get_receipt_expiration_time(&self) -> ReceiptExpirationTime {
+        self.receipt_expiration_time
+    }
+
+    pub fn get_receipt_level(&self) -> ReceiptLevel {
+        self.receipt_level
+    }
+
+    pub fn get_payment_type(&self) -> ReceiptPaymentType {
+        self.payment_type
+    }
+
+    pub fn get_validity_start_time(&self) -> ReceiptValidityStartTime {
+        self.validity_start_time
+    }
+}
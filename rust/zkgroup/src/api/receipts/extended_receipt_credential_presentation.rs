@@ -0,0 +1,68 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::simple_types::*;
+use crate::crypto;
+use crate::crypto::receipt_struct::{ReceiptDisclosure, ReceiptStruct};
+
+/// Hidden attributes are serialized as zero, so every presentation has the same length.
+#[derive(Serialize, Deserialize)]
+pub struct ExtendedReceiptCredentialPresentation {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) presentation:
+        crypto::kvac::Presentation<crypto::credentials::ExtendedReceiptCredential>,
+    pub(crate) receipt_expiration_time: ReceiptExpirationTime,
+    pub(crate) receipt_level: ReceiptLevel,
+    pub(crate) receipt_serial_bytes: ReceiptSerialBytes,
+    pub(crate) disclosure: ReceiptDisclosure,
+    pub(crate) payment_type: ReceiptPaymentType,
+    pub(crate) validity_start_time: ReceiptValidityStartTime,
+}
+
+impl ExtendedReceiptCredentialPresentation {
+    pub fn get_receipt_struct(&self) -> ReceiptStruct {
+        ReceiptStruct {
+            receipt_serial_bytes: self.receipt_serial_bytes,
+            receipt_expiration_time: self.receipt_expiration_time,
+            receipt_level: self.receipt_level,
+        }
+    }
+
+    pub fn get_receipt_expiration_time(&self) -> ReceiptExpirationTime {
+        self.receipt_expiration_time
+    }
+
+    pub fn get_receipt_level(&self) -> ReceiptLevel {
+        self.receipt_level
+    }
+
+    pub fn get_receipt_serial_bytes(&self) -> ReceiptSerialBytes {
+        self.receipt_serial_bytes
+    }
+
+    pub fn get_disclosure(&self) -> ReceiptDisclosure {
+        self.disclosure
+    }
+
+    /// `None` if the client chose not to disclose the payment type.
+    pub fn get_payment_type(&self) -> Option<ReceiptPaymentType> {
+        if self.disclosure.payment_type {
+            Some(self.payment_type)
+        } else {
+            None
+        }
+    }
+
+    /// `None` if the client chose not to disclose the validity start time.
+    pub fn get_validity_start_time(&self) -> Option<ReceiptValidityStartTime> {
+        if self.disclosure.validity_start_time {
+            Some(self.validity_start_time)
+        } else {
+            None
+        }
+    }
+}
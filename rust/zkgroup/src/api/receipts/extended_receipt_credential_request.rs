@@ -0,0 +1,16 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::simple_types::*;
+use crate::crypto;
+
+#[derive(Serialize, Deserialize)]
+pub struct ExtendedReceiptCredentialRequest {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) request:
+        crypto::kvac::IssuanceRequest<crypto::credentials::ExtendedReceiptCredential>,
+}
@@ -0,0 +1,27 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use serde::{Deserialize, Serialize};
+
+use crate::api;
+use crate::common::simple_types::*;
+use crate::crypto;
+
+#[derive(Serialize, Deserialize)]
+pub struct ExtendedReceiptCredentialRequestContext {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) receipt_serial_bytes: ReceiptSerialBytes,
+    pub(crate) context:
+        crypto::kvac::RequestContext<crypto::credentials::ExtendedReceiptCredential>,
+}
+
+impl ExtendedReceiptCredentialRequestContext {
+    pub fn get_request(&self) -> api::receipts::ExtendedReceiptCredentialRequest {
+        api::receipts::ExtendedReceiptCredentialRequest {
+            reserved: Default::default(),
+            request: self.context.get_request(),
+        }
+    }
+}
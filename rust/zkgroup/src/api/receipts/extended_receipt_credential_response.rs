@@ -0,0 +1,20 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::simple_types::*;
+use crate::crypto;
+
+#[derive(Serialize, Deserialize)]
+pub struct ExtendedReceiptCredentialResponse {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) receipt_expiration_time: ReceiptExpirationTime,
+    pub(crate) receipt_level: ReceiptLevel,
+    pub(crate) payment_type: ReceiptPaymentType,
+    pub(crate) validity_start_time: ReceiptValidityStartTime,
+    pub(crate) response:
+        crypto::kvac::IssuanceResponse<crypto::credentials::ExtendedReceiptCredential>,
+}
@@ -0,0 +1,96 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::common::simple_types::*;
+
+/// Remembers which receipts have already been redeemed, so that the same receipt serial can't be
+/// presented twice.
+///
+/// Used by [`ServerSecretParams::redeem_receipt_credential_presentation`][redeem] after the
+/// presentation has been verified and checked for expiration.
+///
+/// [redeem]: crate::api::ServerSecretParams::redeem_receipt_credential_presentation
+pub trait ReceiptRedemptionLedger {
+    /// Marks `receipt_serial_bytes` as redeemed, returning `false` if it already was.
+    ///
+    /// This must be atomic: if it is called concurrently with the same serial, only one call may
+    /// return `true`. `receipt_expiration_time` is provided so that implementations can forget
+    /// serials once they would be rejected as expired anyway.
+    fn record_redemption(
+        &self,
+        receipt_serial_bytes: ReceiptSerialBytes,
+        receipt_expiration_time: ReceiptExpirationTime,
+    ) -> bool;
+}
+
+/// A [`ReceiptRedemptionLedger`] for a single process, which forgets everything when dropped.
+#[derive(Default)]
+pub struct InMemoryReceiptRedemptionLedger {
+    redeemed: Mutex<HashMap<ReceiptSerialBytes, ReceiptExpirationTime>>,
+}
+
+impl InMemoryReceiptRedemptionLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of serials currently remembered.
+    pub fn len(&self) -> usize {
+        self.redeemed.lock().expect("not poisoned").len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forgets every serial whose receipt has expired as of `current_time`.
+    ///
+    /// Those receipts can no longer be redeemed, so remembering them is unnecessary.
+    pub fn remove_expired(&self, current_time: Timestamp) {
+        self.redeemed
+            .lock()
+            .expect("not poisoned")
+            .retain(|_, expiration_time| *expiration_time > current_time);
+    }
+}
+
+impl ReceiptRedemptionLedger for InMemoryReceiptRedemptionLedger {
+    fn record_redemption(
+        &self,
+        receipt_serial_bytes: ReceiptSerialBytes,
+        receipt_expiration_time: ReceiptExpirationTime,
+    ) -> bool {
+        let mut redeemed = self.redeemed.lock().expect("not poisoned");
+        if redeemed.contains_key(&receipt_serial_bytes) {
+            return false;
+        }
+        redeemed.insert(receipt_serial_bytes, receipt_expiration_time);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_memory_ledger() {
+        let ledger = InMemoryReceiptRedemptionLedger::new();
+        assert!(ledger.record_redemption([1; 16], 100));
+        assert!(!ledger.record_redemption([1; 16], 100));
+        assert!(ledger.record_redemption([2; 16], 200));
+        assert_eq!(ledger.len(), 2);
+
+        ledger.remove_expired(100);
+        assert_eq!(ledger.len(), 1);
+        assert!(!ledger.record_redemption([2; 16], 200));
+
+        ledger.remove_expired(200);
+        assert!(ledger.is_empty());
+    }
+}
@@ -3,17 +3,23 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
-use serde::{Deserialize, Serialize};
+use std::iter;
+
+use serde::{Deserialize, Serialize, Serializer};
 
 use crate::api;
+use crate::common::batch;
+use crate::common::constants::*;
 use crate::common::errors::*;
+use crate::common::serialization::{deserialize_layout, VersionedSerializable};
 use crate::common::sho::*;
 use crate::common::simple_types::*;
 use crate::crypto;
+use crate::crypto::proofs::ProofFormat;
 
-#[derive(Copy, Clone, Serialize, Deserialize)]
+#[derive(Clone, Deserialize)]
+#[serde(from = "ServerSecretParamsV1")]
 pub struct ServerSecretParams {
-    pub(crate) reserved: ReservedBytes,
     pub(crate) auth_credentials_key_pair:
         crypto::credentials::KeyPair<crypto::credentials::AuthCredential>,
     pub(crate) profile_key_credentials_key_pair:
@@ -22,16 +28,212 @@ pub struct ServerSecretParams {
     receipt_credentials_key_pair:
         crypto::credentials::KeyPair<crypto::credentials::ReceiptCredential>,
     pni_credentials_key_pair: crypto::credentials::KeyPair<crypto::credentials::PniCredential>,
+    /// Absent in params decoded from version 0.
+    v1_key_pairs: Option<ServerSecretParamsV1KeyPairs>,
 }
 
-#[derive(Copy, Clone, Serialize, Deserialize)]
+#[derive(Copy, Clone, Deserialize)]
+#[serde(from = "ServerPublicParamsV1")]
 pub struct ServerPublicParams {
-    pub(crate) reserved: ReservedBytes,
     pub(crate) auth_credentials_public_key: crypto::credentials::PublicKey,
     pub(crate) profile_key_credentials_public_key: crypto::credentials::PublicKey,
     sig_public_key: crypto::signature::PublicKey,
     receipt_credentials_public_key: crypto::credentials::PublicKey,
     pni_credentials_public_key: crypto::credentials::PublicKey,
+    /// Absent in params decoded from version 0.
+    v1_public_keys: Option<ServerPublicParamsV1PublicKeys>,
+}
+
+/// The original encoding of [`ServerSecretParams`].
+#[derive(Serialize, Deserialize)]
+struct ServerSecretParamsV0 {
+    reserved: ReservedBytes,
+    auth_credentials_key_pair: crypto::credentials::KeyPair<crypto::credentials::AuthCredential>,
+    profile_key_credentials_key_pair:
+        crypto::credentials::KeyPair<crypto::credentials::ProfileKeyCredential>,
+    sig_key_pair: crypto::signature::KeyPair,
+    receipt_credentials_key_pair:
+        crypto::credentials::KeyPair<crypto::credentials::ReceiptCredential>,
+    pni_credentials_key_pair: crypto::credentials::KeyPair<crypto::credentials::PniCredential>,
+}
+
+/// The key pairs added in version 1 of [`ServerSecretParams`].
+#[derive(Clone, Serialize, Deserialize)]
+struct ServerSecretParamsV1KeyPairs {
+    auth_credentials_with_pni_key_pair:
+        crypto::kvac::KeyPair<crypto::credentials::AuthCredentialWithPni>,
+    extended_receipt_credentials_key_pair:
+        crypto::kvac::KeyPair<crypto::credentials::ExtendedReceiptCredential>,
+}
+
+/// The current encoding of [`ServerSecretParams`]: version 0 followed by the key pairs added in
+/// version 1.
+#[derive(Serialize, Deserialize)]
+struct ServerSecretParamsV1 {
+    v0: ServerSecretParamsV0,
+    v1_key_pairs: ServerSecretParamsV1KeyPairs,
+}
+
+/// The original encoding of [`ServerPublicParams`].
+#[derive(Serialize, Deserialize)]
+struct ServerPublicParamsV0 {
+    reserved: ReservedBytes,
+    auth_credentials_public_key: crypto::credentials::PublicKey,
+    profile_key_credentials_public_key: crypto::credentials::PublicKey,
+    sig_public_key: crypto::signature::PublicKey,
+    receipt_credentials_public_key: crypto::credentials::PublicKey,
+    pni_credentials_public_key: crypto::credentials::PublicKey,
+}
+
+/// The public keys added in version 1 of [`ServerPublicParams`].
+#[derive(Copy, Clone, Serialize, Deserialize)]
+struct ServerPublicParamsV1PublicKeys {
+    auth_credentials_with_pni_public_key:
+        crypto::kvac::PublicKey<crypto::credentials::AuthCredentialWithPni>,
+    extended_receipt_credentials_public_key:
+        crypto::kvac::PublicKey<crypto::credentials::ExtendedReceiptCredential>,
+}
+
+/// The current encoding of [`ServerPublicParams`]: version 0 followed by the public keys added
+/// in version 1.
+#[derive(Serialize, Deserialize)]
+struct ServerPublicParamsV1 {
+    v0: ServerPublicParamsV0,
+    v1_public_keys: ServerPublicParamsV1PublicKeys,
+}
+
+impl From<ServerSecretParamsV0> for ServerSecretParams {
+    fn from(v0: ServerSecretParamsV0) -> Self {
+        Self {
+            auth_credentials_key_pair: v0.auth_credentials_key_pair,
+            profile_key_credentials_key_pair: v0.profile_key_credentials_key_pair,
+            sig_key_pair: v0.sig_key_pair,
+            receipt_credentials_key_pair: v0.receipt_credentials_key_pair,
+            pni_credentials_key_pair: v0.pni_credentials_key_pair,
+            v1_key_pairs: None,
+        }
+    }
+}
+
+impl From<ServerSecretParamsV1> for ServerSecretParams {
+    fn from(v1: ServerSecretParamsV1) -> Self {
+        Self {
+            v1_key_pairs: Some(v1.v1_key_pairs),
+            ..v1.v0.into()
+        }
+    }
+}
+
+impl Serialize for ServerSecretParams {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let v0 = ServerSecretParamsV0 {
+            reserved: [0],
+            auth_credentials_key_pair: self.auth_credentials_key_pair,
+            profile_key_credentials_key_pair: self.profile_key_credentials_key_pair,
+            sig_key_pair: self.sig_key_pair,
+            receipt_credentials_key_pair: self.receipt_credentials_key_pair,
+            pni_credentials_key_pair: self.pni_credentials_key_pair,
+        };
+        match &self.v1_key_pairs {
+            None => v0.serialize(serializer),
+            Some(v1_key_pairs) => ServerSecretParamsV1 {
+                v0: ServerSecretParamsV0 {
+                    reserved: [1],
+                    ..v0
+                },
+                v1_key_pairs: v1_key_pairs.clone(),
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+impl VersionedSerializable for ServerSecretParams {
+    const NAME: &'static str = "ServerSecretParams";
+    const VERSION: u8 = 1;
+    const LEN: usize = SERVER_SECRET_PARAMS_LEN;
+
+    fn serialized_len(version: u8) -> Option<usize> {
+        match version {
+            0 => Some(SERVER_SECRET_PARAMS_V0_LEN),
+            1 => Some(Self::LEN),
+            _ => None,
+        }
+    }
+
+    fn deserialize_version(version: u8, bytes: &[u8]) -> Result<Self, DeserializationError> {
+        match version {
+            0 => deserialize_layout::<ServerSecretParamsV0>(bytes, Self::NAME).map(Into::into),
+            _ => deserialize_layout::<ServerSecretParamsV1>(bytes, Self::NAME).map(Into::into),
+        }
+    }
+}
+
+impl From<ServerPublicParamsV0> for ServerPublicParams {
+    fn from(v0: ServerPublicParamsV0) -> Self {
+        Self {
+            auth_credentials_public_key: v0.auth_credentials_public_key,
+            profile_key_credentials_public_key: v0.profile_key_credentials_public_key,
+            sig_public_key: v0.sig_public_key,
+            receipt_credentials_public_key: v0.receipt_credentials_public_key,
+            pni_credentials_public_key: v0.pni_credentials_public_key,
+            v1_public_keys: None,
+        }
+    }
+}
+
+impl From<ServerPublicParamsV1> for ServerPublicParams {
+    fn from(v1: ServerPublicParamsV1) -> Self {
+        Self {
+            v1_public_keys: Some(v1.v1_public_keys),
+            ..v1.v0.into()
+        }
+    }
+}
+
+impl Serialize for ServerPublicParams {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let v0 = ServerPublicParamsV0 {
+            reserved: [0],
+            auth_credentials_public_key: self.auth_credentials_public_key,
+            profile_key_credentials_public_key: self.profile_key_credentials_public_key,
+            sig_public_key: self.sig_public_key,
+            receipt_credentials_public_key: self.receipt_credentials_public_key,
+            pni_credentials_public_key: self.pni_credentials_public_key,
+        };
+        match self.v1_public_keys {
+            None => v0.serialize(serializer),
+            Some(v1_public_keys) => ServerPublicParamsV1 {
+                v0: ServerPublicParamsV0 {
+                    reserved: [1],
+                    ..v0
+                },
+                v1_public_keys,
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+impl VersionedSerializable for ServerPublicParams {
+    const NAME: &'static str = "ServerPublicParams";
+    const VERSION: u8 = 1;
+    const LEN: usize = SERVER_PUBLIC_PARAMS_LEN;
+
+    fn serialized_len(version: u8) -> Option<usize> {
+        match version {
+            0 => Some(SERVER_PUBLIC_PARAMS_V0_LEN),
+            1 => Some(Self::LEN),
+            _ => None,
+        }
+    }
+
+    fn deserialize_version(version: u8, bytes: &[u8]) -> Result<Self, DeserializationError> {
+        match version {
+            0 => deserialize_layout::<ServerPublicParamsV0>(bytes, Self::NAME).map(Into::into),
+            _ => deserialize_layout::<ServerPublicParamsV1>(bytes, Self::NAME).map(Into::into),
+        }
+    }
 }
 
 impl ServerSecretParams {
@@ -46,20 +248,24 @@ impl ServerSecretParams {
         let sig_key_pair = crypto::signature::KeyPair::generate(&mut sho);
         let receipt_credentials_key_pair = crypto::credentials::KeyPair::generate(&mut sho);
         let pni_credentials_key_pair = crypto::credentials::KeyPair::generate(&mut sho);
+        let auth_credentials_with_pni_key_pair = crypto::kvac::KeyPair::generate(&mut sho);
+        let extended_receipt_credentials_key_pair = crypto::kvac::KeyPair::generate(&mut sho);
 
         Self {
-            reserved: Default::default(),
             auth_credentials_key_pair,
             profile_key_credentials_key_pair,
             sig_key_pair,
             receipt_credentials_key_pair,
             pni_credentials_key_pair,
+            v1_key_pairs: Some(ServerSecretParamsV1KeyPairs {
+                auth_credentials_with_pni_key_pair,
+                extended_receipt_credentials_key_pair,
+            }),
         }
     }
 
     pub fn get_public_params(&self) -> ServerPublicParams {
         ServerPublicParams {
-            reserved: Default::default(),
             auth_credentials_public_key: self.auth_credentials_key_pair.get_public_key(),
             profile_key_credentials_public_key: self
                 .profile_key_credentials_key_pair
@@ -67,6 +273,16 @@ impl ServerSecretParams {
             sig_public_key: self.sig_key_pair.get_public_key(),
             receipt_credentials_public_key: self.receipt_credentials_key_pair.get_public_key(),
             pni_credentials_public_key: self.pni_credentials_key_pair.get_public_key(),
+            v1_public_keys: self.v1_key_pairs.as_ref().map(|key_pairs| {
+                ServerPublicParamsV1PublicKeys {
+                    auth_credentials_with_pni_public_key: key_pairs
+                        .auth_credentials_with_pni_key_pair
+                        .get_public_key(),
+                    extended_receipt_credentials_public_key: key_pairs
+                        .extended_receipt_credentials_key_pair
+                        .get_public_key(),
+                }
+            }),
         }
     }
 
@@ -111,16 +327,164 @@ impl ServerSecretParams {
         }
     }
 
+    /// Issues one auth credential per `(uid_bytes, redemption_time)` pair, in order.
+    ///
+    /// Each credential gets its own randomness derived from `randomness`, so the results are the
+    /// same whether or not the `rayon` feature is enabled.
+    pub fn issue_auth_credentials(
+        &self,
+        randomness: RandomnessBytes,
+        requests: &[(UidBytes, RedemptionTime)],
+    ) -> Vec<api::auth::AuthCredentialResponse> {
+        let randomness = batch::split_randomness(
+            b"Signal_ZKGroup_20211201_Random_ServerSecretParams_IssueAuthCredentials",
+            randomness,
+            requests.len(),
+        );
+        let items: Vec<_> = randomness.into_iter().zip(requests).collect();
+        batch::map(&items, |(randomness, (uid_bytes, redemption_time))| {
+            self.issue_auth_credential(*randomness, *uid_bytes, *redemption_time)
+        })
+    }
+
     pub fn verify_auth_credential_presentation(
         &self,
         group_public_params: api::groups::GroupPublicParams,
         presentation: &api::auth::AuthCredentialPresentation,
     ) -> Result<(), ZkGroupError> {
-        presentation.proof.verify(
+        match presentation.proof_format() {
+            ProofFormat::Compact => presentation.proof.verify(
+                self.auth_credentials_key_pair,
+                group_public_params.uid_enc_public_key,
+                presentation.ciphertext,
+                presentation.redemption_time,
+            ),
+            ProofFormat::Batchable => self
+                .verify_auth_credential_presentations(&[(group_public_params, presentation)])
+                .map_err(|(_, e)| e),
+        }
+    }
+
+    /// Like [`Self::verify_auth_credential_presentation`], but if the presentation doesn't verify
+    /// for `group_public_params`, checks whether it was made for one of
+    /// `other_group_public_params` instead.
+    ///
+    /// A presentation made for another group fails with [`ZkGroupError::ProofForOtherGroup`],
+    /// giving its index in `other_group_public_params`; one that doesn't verify for any of them
+    /// fails with [`ZkGroupError::ProofVerificationFailure`]. Each extra group costs another
+    /// verification, and only when the presentation fails.
+    pub fn verify_auth_credential_presentation_among_groups(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        other_group_public_params: &[api::groups::GroupPublicParams],
+        presentation: &api::auth::AuthCredentialPresentation,
+    ) -> Result<(), ZkGroupError> {
+        verify_among_groups(group_public_params, other_group_public_params, |params| {
+            self.verify_auth_credential_presentation(params, presentation)
+        })
+    }
+
+    /// Verifies each of `presentations`, failing with the index of the first one that doesn't
+    /// verify.
+    ///
+    /// Presentations from [`ServerPublicParams::create_batchable_auth_credential_presentation`]
+    /// are verified together, with a single multiscalar multiplication. Any others are verified
+    /// one at a time, in parallel if the `rayon` feature is enabled.
+    pub fn verify_auth_credential_presentations(
+        &self,
+        presentations: &[(
+            api::groups::GroupPublicParams,
+            &api::auth::AuthCredentialPresentation,
+        )],
+    ) -> Result<(), (usize, ZkGroupError)> {
+        let (batchable, compact): (Vec<_>, Vec<_>) =
+            presentations
+                .iter()
+                .enumerate()
+                .partition(|(_, (_, presentation))| {
+                    presentation.proof_format() == ProofFormat::Batchable
+                });
+
+        let compact_results = batch::map(&compact, |(i, (group_public_params, presentation))| {
+            self.verify_auth_credential_presentation(*group_public_params, presentation)
+                .map_err(|e| (*i, e))
+        });
+        let proofs: Vec<_> = batchable
+            .iter()
+            .map(|(_, (group_public_params, presentation))| {
+                (
+                    &presentation.proof,
+                    group_public_params.uid_enc_public_key,
+                    presentation.ciphertext,
+                    presentation.redemption_time,
+                )
+            })
+            .collect();
+        let batch_result = crypto::proofs::AuthCredentialPresentationProof::verify_batch(
             self.auth_credentials_key_pair,
-            group_public_params.uid_enc_public_key,
-            presentation.ciphertext,
-            presentation.redemption_time,
+            &proofs,
+        )
+        .map_err(|(i, e)| (batchable[i].0, e));
+
+        first_failure(compact_results.into_iter().chain(iter::once(batch_result)))
+    }
+
+    /// Issues a credential that can authenticate as either `aci_bytes` or `pni_bytes`.
+    ///
+    /// `redemption_time` should be the start of a day (a multiple of [`SECONDS_PER_DAY`]);
+    /// clients reject any other value, since an unusual time could be used to track them.
+    pub fn issue_auth_credential_with_pni(
+        &self,
+        randomness: RandomnessBytes,
+        aci_bytes: UidBytes,
+        pni_bytes: UidBytes,
+        redemption_time: Timestamp,
+    ) -> Result<api::auth::AuthCredentialWithPniResponse, ZkGroupError> {
+        let key_pair = self.auth_credentials_with_pni_key_pair()?;
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20211201_Random_ServerSecretParams_IssueAuthCredentialWithPni",
+            &randomness,
+        );
+
+        let attributes = auth_credential_with_pni_attributes(aci_bytes, pni_bytes, redemption_time);
+        let response = key_pair.issue_unblinded(&attributes, &mut sho)?;
+        Ok(api::auth::AuthCredentialWithPniResponse {
+            reserved: Default::default(),
+            response,
+        })
+    }
+
+    /// Checks an [`AuthCredentialWithPniPresentation`](api::auth::AuthCredentialWithPniPresentation).
+    ///
+    /// Besides the proof, this checks that `current_time` (in seconds) is no earlier than a day
+    /// before the credential's redemption time and no later than two days after it, to allow for
+    /// clock skew between the client and server.
+    pub fn verify_auth_credential_with_pni_presentation(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AuthCredentialWithPniPresentation,
+        current_time: Timestamp,
+    ) -> Result<(), ZkGroupError> {
+        let acceptable_start_time = presentation.redemption_time.saturating_sub(SECONDS_PER_DAY);
+        let acceptable_end_time = presentation
+            .redemption_time
+            .saturating_add(2 * SECONDS_PER_DAY);
+        if !(acceptable_start_time..=acceptable_end_time).contains(&current_time) {
+            return Err(ZkGroupError::RedemptionTimeOutOfRange {
+                redemption_time: presentation.redemption_time,
+                current_time,
+            });
+        }
+
+        self.auth_credentials_with_pni_key_pair()?.verify(
+            &presentation.presentation,
+            &[
+                None,
+                None,
+                Some(encode_timestamp(presentation.redemption_time)),
+            ],
+            Some(group_public_params.uid_enc_public_key),
+            &[],
         )
     }
 
@@ -133,13 +497,77 @@ impl ServerSecretParams {
         let uid_enc_public_key = group_public_params.uid_enc_public_key;
         let profile_key_enc_public_key = group_public_params.profile_key_enc_public_key;
 
-        presentation.proof.verify(
-            credentials_key_pair,
-            presentation.uid_enc_ciphertext,
-            uid_enc_public_key,
-            presentation.profile_key_enc_ciphertext,
-            profile_key_enc_public_key,
+        match presentation.proof_format() {
+            ProofFormat::Compact => presentation.proof.verify(
+                credentials_key_pair,
+                presentation.uid_enc_ciphertext,
+                uid_enc_public_key,
+                presentation.profile_key_enc_ciphertext,
+                profile_key_enc_public_key,
+            ),
+            ProofFormat::Batchable => self
+                .verify_profile_key_credential_presentations(&[(group_public_params, presentation)])
+                .map_err(|(_, e)| e),
+        }
+    }
+
+    /// Like [`Self::verify_profile_key_credential_presentation`], but distinguishes presentations
+    /// made for one of `other_group_public_params`.
+    ///
+    /// See [`Self::verify_auth_credential_presentation_among_groups`].
+    pub fn verify_profile_key_credential_presentation_among_groups(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        other_group_public_params: &[api::groups::GroupPublicParams],
+        presentation: &api::profiles::ProfileKeyCredentialPresentation,
+    ) -> Result<(), ZkGroupError> {
+        verify_among_groups(group_public_params, other_group_public_params, |params| {
+            self.verify_profile_key_credential_presentation(params, presentation)
+        })
+    }
+
+    /// Verifies each of `presentations`, failing with the index of the first one that doesn't
+    /// verify.
+    ///
+    /// See [`Self::verify_auth_credential_presentations`].
+    pub fn verify_profile_key_credential_presentations(
+        &self,
+        presentations: &[(
+            api::groups::GroupPublicParams,
+            &api::profiles::ProfileKeyCredentialPresentation,
+        )],
+    ) -> Result<(), (usize, ZkGroupError)> {
+        let (batchable, compact): (Vec<_>, Vec<_>) =
+            presentations
+                .iter()
+                .enumerate()
+                .partition(|(_, (_, presentation))| {
+                    presentation.proof_format() == ProofFormat::Batchable
+                });
+
+        let compact_results = batch::map(&compact, |(i, (group_public_params, presentation))| {
+            self.verify_profile_key_credential_presentation(*group_public_params, presentation)
+                .map_err(|e| (*i, e))
+        });
+        let proofs: Vec<_> = batchable
+            .iter()
+            .map(|(_, (group_public_params, presentation))| {
+                (
+                    &presentation.proof,
+                    presentation.uid_enc_ciphertext,
+                    group_public_params.uid_enc_public_key,
+                    presentation.profile_key_enc_ciphertext,
+                    group_public_params.profile_key_enc_public_key,
+                )
+            })
+            .collect();
+        let batch_result = crypto::proofs::ProfileKeyCredentialPresentationProof::verify_batch(
+            self.profile_key_credentials_key_pair,
+            &proofs,
         )
+        .map_err(|(i, e)| (batchable[i].0, e));
+
+        first_failure(compact_results.into_iter().chain(iter::once(batch_result)))
     }
 
     pub fn verify_pni_credential_presentation(
@@ -206,6 +634,30 @@ impl ServerSecretParams {
         })
     }
 
+    /// Issues one profile key credential per `(request, uid_bytes, commitment)` triple, returning
+    /// one result per input, in order.
+    ///
+    /// See [`Self::issue_auth_credentials`].
+    pub fn issue_profile_key_credentials(
+        &self,
+        randomness: RandomnessBytes,
+        requests: &[(
+            &api::profiles::ProfileKeyCredentialRequest,
+            UidBytes,
+            api::profiles::ProfileKeyCommitment,
+        )],
+    ) -> Vec<Result<api::profiles::ProfileKeyCredentialResponse, ZkGroupError>> {
+        let randomness = batch::split_randomness(
+            b"Signal_ZKGroup_20211201_Random_ServerSecretParams_IssueProfileKeyCredentials",
+            randomness,
+            requests.len(),
+        );
+        let items: Vec<_> = randomness.into_iter().zip(requests).collect();
+        batch::map(&items, |(randomness, (request, uid_bytes, commitment))| {
+            self.issue_profile_key_credential(*randomness, request, *uid_bytes, *commitment)
+        })
+    }
+
     pub fn issue_pni_credential(
         &self,
         randomness: RandomnessBytes,
@@ -304,6 +756,230 @@ impl ServerSecretParams {
             presentation.get_receipt_struct(),
         )
     }
+
+    /// Verifies `presentation` and then records its serial in `ledger`, so that it can only be
+    /// redeemed once.
+    ///
+    /// A receipt is expired once `current_time` reaches its expiration time. Nothing is recorded
+    /// if the presentation is invalid or expired.
+    pub fn redeem_receipt_credential_presentation(
+        &self,
+        presentation: &api::receipts::ReceiptCredentialPresentation,
+        current_time: Timestamp,
+        ledger: &impl api::receipts::ReceiptRedemptionLedger,
+    ) -> Result<(), ZkGroupError> {
+        self.verify_receipt_credential_presentation(presentation)?;
+        Self::record_receipt_redemption(presentation.get_receipt_struct(), current_time, ledger)
+    }
+
+    /// Like [`Self::redeem_receipt_credential_presentation`], for extended receipts.
+    ///
+    /// The presentation must disclose at least `required_disclosure`, and always its validity
+    /// start time, so that the receipt can be rejected while `current_time` is before it. Plain
+    /// and extended receipts share a serial space, so the same ledger may be used for both.
+    pub fn redeem_extended_receipt_credential_presentation(
+        &self,
+        presentation: &api::receipts::ExtendedReceiptCredentialPresentation,
+        required_disclosure: api::receipts::ReceiptDisclosure,
+        current_time: Timestamp,
+        ledger: &impl api::receipts::ReceiptRedemptionLedger,
+    ) -> Result<(), ZkGroupError> {
+        let required_disclosure = api::receipts::ReceiptDisclosure {
+            validity_start_time: true,
+            ..required_disclosure
+        };
+        self.verify_extended_receipt_credential_presentation(presentation, required_disclosure)?;
+        let validity_start_time = presentation.validity_start_time;
+        if current_time < validity_start_time {
+            return Err(ZkGroupError::ReceiptNotYetValid {
+                validity_start_time,
+                current_time,
+            });
+        }
+        Self::record_receipt_redemption(presentation.get_receipt_struct(), current_time, ledger)
+    }
+
+    fn record_receipt_redemption(
+        receipt: crypto::receipt_struct::ReceiptStruct,
+        current_time: Timestamp,
+        ledger: &impl api::receipts::ReceiptRedemptionLedger,
+    ) -> Result<(), ZkGroupError> {
+        if current_time >= receipt.receipt_expiration_time {
+            return Err(ZkGroupError::ReceiptExpired {
+                expiration_time: receipt.receipt_expiration_time,
+                current_time,
+            });
+        }
+        if !ledger.record_redemption(
+            receipt.receipt_serial_bytes,
+            receipt.receipt_expiration_time,
+        ) {
+            return Err(ZkGroupError::ReceiptAlreadyRedeemed);
+        }
+        Ok(())
+    }
+
+    /// Like [`Self::issue_receipt_credential`], but also certifies a payment type and a time
+    /// before which the receipt shouldn't be redeemed.
+    pub fn issue_extended_receipt_credential(
+        &self,
+        randomness: RandomnessBytes,
+        request: &api::receipts::ExtendedReceiptCredentialRequest,
+        receipt_expiration_time: ReceiptExpirationTime,
+        receipt_level: ReceiptLevel,
+        payment_type: ReceiptPaymentType,
+        validity_start_time: ReceiptValidityStartTime,
+    ) -> Result<api::receipts::ExtendedReceiptCredentialResponse, ZkGroupError> {
+        let key_pair = self.extended_receipt_credentials_key_pair()?;
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20211201_Random_ServerSecretParams_IssueExtendedReceiptCredential",
+            &randomness,
+        );
+
+        let attributes = extended_receipt_known_attributes(
+            receipt_expiration_time,
+            receipt_level,
+            payment_type,
+            validity_start_time,
+        );
+        let response = key_pair.issue(&request.request, &attributes, &mut sho)?;
+
+        Ok(api::receipts::ExtendedReceiptCredentialResponse {
+            reserved: Default::default(),
+            receipt_expiration_time,
+            receipt_level,
+            payment_type,
+            validity_start_time,
+            response,
+        })
+    }
+
+    /// Checks the presentation against whichever attributes it discloses.
+    ///
+    /// Fails with [`ZkGroupError::ReceiptAttributeNotDisclosed`] if the presentation hides an
+    /// attribute that `required_disclosure` asks for.
+    pub fn verify_extended_receipt_credential_presentation(
+        &self,
+        presentation: &api::receipts::ExtendedReceiptCredentialPresentation,
+        required_disclosure: api::receipts::ReceiptDisclosure,
+    ) -> Result<(), ZkGroupError> {
+        if let Some(attribute) = presentation.disclosure.first_missing(required_disclosure) {
+            return Err(ZkGroupError::ReceiptAttributeNotDisclosed { attribute });
+        }
+        let receipt = presentation.get_receipt_struct();
+        let attributes = [
+            Some(encode_receipt_serial_bytes(receipt.receipt_serial_bytes)),
+            Some(receipt.calc_m1()),
+            presentation
+                .get_payment_type()
+                .map(crypto::receipt_struct::ExtendedReceiptStruct::calc_m3_from),
+            presentation
+                .get_validity_start_time()
+                .map(crypto::receipt_struct::ExtendedReceiptStruct::calc_m4_from),
+        ];
+        self.extended_receipt_credentials_key_pair()?.verify(
+            &presentation.presentation,
+            &attributes,
+            None,
+            &[],
+        )
+    }
+
+    fn auth_credentials_with_pni_key_pair(
+        &self,
+    ) -> Result<&crypto::kvac::KeyPair<crypto::credentials::AuthCredentialWithPni>, ZkGroupError>
+    {
+        self.v1_key_pairs
+            .as_ref()
+            .map(|key_pairs| &key_pairs.auth_credentials_with_pni_key_pair)
+            .ok_or(ZkGroupError::MissingCredentialKey {
+                credential: "auth with PNI",
+            })
+    }
+
+    fn extended_receipt_credentials_key_pair(
+        &self,
+    ) -> Result<&crypto::kvac::KeyPair<crypto::credentials::ExtendedReceiptCredential>, ZkGroupError>
+    {
+        self.v1_key_pairs
+            .as_ref()
+            .map(|key_pairs| &key_pairs.extended_receipt_credentials_key_pair)
+            .ok_or(ZkGroupError::MissingCredentialKey {
+                credential: "extended receipt",
+            })
+    }
+}
+
+/// The attributes of an auth credential with a PNI, in the order
+/// [`AuthCredentialWithPni`](crypto::credentials::AuthCredentialWithPni) declares them.
+fn auth_credential_with_pni_attributes(
+    aci_bytes: UidBytes,
+    pni_bytes: UidBytes,
+    redemption_time: Timestamp,
+) -> [crypto::kvac::AttributeValue; 3] {
+    let aci = crypto::uid_struct::UidStruct::new(aci_bytes);
+    let pni = crypto::pni_struct::PniStruct::new(pni_bytes);
+    [
+        crypto::kvac::AttributeValue::Points(aci.M1, aci.M2),
+        crypto::kvac::AttributeValue::Points(pni.M1, pni.M2),
+        crypto::kvac::AttributeValue::Scalar(encode_timestamp(redemption_time)),
+    ]
+}
+
+/// The attributes of an extended receipt credential that the issuer knows, which are all but the
+/// serial.
+fn extended_receipt_known_attributes(
+    receipt_expiration_time: ReceiptExpirationTime,
+    receipt_level: ReceiptLevel,
+    payment_type: ReceiptPaymentType,
+    validity_start_time: ReceiptValidityStartTime,
+) -> [crypto::kvac::AttributeValue; 3] {
+    [
+        crypto::receipt_struct::ReceiptStruct::calc_m1_from(receipt_expiration_time, receipt_level),
+        crypto::receipt_struct::ExtendedReceiptStruct::calc_m3_from(payment_type),
+        crypto::receipt_struct::ExtendedReceiptStruct::calc_m4_from(validity_start_time),
+    ]
+    .map(crypto::kvac::AttributeValue::Scalar)
+}
+
+// Runs `verify` for `group_public_params`, and if the proof fails, for each of
+// `other_group_public_params` until one succeeds
+fn verify_among_groups(
+    group_public_params: api::groups::GroupPublicParams,
+    other_group_public_params: &[api::groups::GroupPublicParams],
+    verify: impl Fn(api::groups::GroupPublicParams) -> Result<(), ZkGroupError>,
+) -> Result<(), ZkGroupError> {
+    match verify(group_public_params) {
+        Err(ZkGroupError::ProofVerificationFailure { proof }) => {
+            match other_group_public_params
+                .iter()
+                .position(|params| verify(*params).is_ok())
+            {
+                Some(index) => Err(ZkGroupError::ProofForOtherGroup { proof, index }),
+                None => Err(ZkGroupError::ProofVerificationFailure { proof }),
+            }
+        }
+        result => result,
+    }
+}
+
+// The version of a presentation whose proof is in `format`
+fn presentation_version(format: ProofFormat) -> ReservedBytes {
+    match format {
+        ProofFormat::Compact => [0],
+        ProofFormat::Batchable => [1],
+    }
+}
+
+// Picks the failure with the lowest index, so that a batch fails the same way however its checks
+// were split up
+fn first_failure(
+    results: impl Iterator<Item = Result<(), (usize, ZkGroupError)>>,
+) -> Result<(), (usize, ZkGroupError)> {
+    results
+        .filter_map(Result::err)
+        .min_by_key(|(index, _)| *index)
+        .map_or(Ok(()), Err)
 }
 
 impl ServerPublicParams {
@@ -347,7 +1023,41 @@ impl ServerPublicParams {
             b"Signal_ZKGroup_20200424_Random_ServerPublicParams_CreateAuthCredentialPresentation",
             &randomness,
         );
+        self.create_auth_credential_presentation_with_format(
+            group_secret_params,
+            auth_credential,
+            ProofFormat::Compact,
+            &mut sho,
+        )
+    }
 
+    /// Like [`Self::create_auth_credential_presentation`], but with a larger proof that the server
+    /// can verify together with others in [`ServerSecretParams::verify_auth_credential_presentations`].
+    pub fn create_batchable_auth_credential_presentation(
+        &self,
+        randomness: RandomnessBytes,
+        group_secret_params: api::groups::GroupSecretParams,
+        auth_credential: api::auth::AuthCredential,
+    ) -> api::auth::AuthCredentialPresentation {
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20211201_Random_ServerPublicParams_CreateBatchableAuthCredentialPresentation",
+            &randomness,
+        );
+        self.create_auth_credential_presentation_with_format(
+            group_secret_params,
+            auth_credential,
+            ProofFormat::Batchable,
+            &mut sho,
+        )
+    }
+
+    fn create_auth_credential_presentation_with_format(
+        &self,
+        group_secret_params: api::groups::GroupSecretParams,
+        auth_credential: api::auth::AuthCredential,
+        format: ProofFormat,
+        sho: &mut Sho,
+    ) -> api::auth::AuthCredentialPresentation {
         let uuid_ciphertext = group_secret_params.encrypt_uid_struct(auth_credential.uid);
 
         let proof = crypto::proofs::AuthCredentialPresentationProof::new(
@@ -357,17 +1067,72 @@ impl ServerPublicParams {
             auth_credential.uid,
             uuid_ciphertext.ciphertext,
             auth_credential.redemption_time,
-            &mut sho,
+            format,
+            sho,
         );
 
         api::auth::AuthCredentialPresentation {
-            reserved: Default::default(),
+            reserved: presentation_version(format),
             proof,
             ciphertext: uuid_ciphertext.ciphertext,
             redemption_time: auth_credential.redemption_time,
         }
     }
 
+    /// Checks the server's proof and unpacks an auth credential with a PNI.
+    ///
+    /// Fails with [`ZkGroupError::RedemptionTimeNotDayAligned`] if `redemption_time` is not the
+    /// start of a day.
+    pub fn receive_auth_credential_with_pni(
+        &self,
+        aci_bytes: UidBytes,
+        pni_bytes: UidBytes,
+        redemption_time: Timestamp,
+        response: &api::auth::AuthCredentialWithPniResponse,
+    ) -> Result<api::auth::AuthCredentialWithPni, ZkGroupError> {
+        if redemption_time % SECONDS_PER_DAY != 0 {
+            return Err(ZkGroupError::RedemptionTimeNotDayAligned { redemption_time });
+        }
+
+        let attributes = auth_credential_with_pni_attributes(aci_bytes, pni_bytes, redemption_time);
+        let credential = response
+            .response
+            .receive(&self.auth_credentials_with_pni_public_key()?, &attributes)?;
+
+        Ok(api::auth::AuthCredentialWithPni {
+            reserved: Default::default(),
+            credential,
+            redemption_time,
+        })
+    }
+
+    pub fn create_auth_credential_with_pni_presentation(
+        &self,
+        randomness: RandomnessBytes,
+        group_secret_params: api::groups::GroupSecretParams,
+        auth_credential: &api::auth::AuthCredentialWithPni,
+    ) -> Result<api::auth::AuthCredentialWithPniPresentation, ZkGroupError> {
+        let public_key = self.auth_credentials_with_pni_public_key()?;
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20211201_Random_ServerPublicParams_CreateAuthCredentialWithPniPresentation",
+            &randomness,
+        );
+
+        let presentation = auth_credential.credential.present(
+            &public_key,
+            &[],
+            Some(group_secret_params.uid_enc_key_pair),
+            &[],
+            &mut sho,
+        )?;
+
+        Ok(api::auth::AuthCredentialWithPniPresentation {
+            reserved: Default::default(),
+            presentation,
+            redemption_time: auth_credential.redemption_time,
+        })
+    }
+
     pub fn create_profile_key_credential_request_context(
         &self,
         randomness: RandomnessBytes,
@@ -492,7 +1257,42 @@ impl ServerPublicParams {
             b"Signal_ZKGroup_20200424_Random_ServerPublicParams_CreateProfileKeyCredentialPresentation",
             &randomness,
         );
+        self.create_profile_key_credential_presentation_with_format(
+            group_secret_params,
+            profile_key_credential,
+            ProofFormat::Compact,
+            &mut sho,
+        )
+    }
+
+    /// Like [`Self::create_profile_key_credential_presentation`], but with a larger proof that the
+    /// server can verify together with others in
+    /// [`ServerSecretParams::verify_profile_key_credential_presentations`].
+    pub fn create_batchable_profile_key_credential_presentation(
+        &self,
+        randomness: RandomnessBytes,
+        group_secret_params: api::groups::GroupSecretParams,
+        profile_key_credential: api::profiles::ProfileKeyCredential,
+    ) -> api::profiles::ProfileKeyCredentialPresentation {
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20211201_Random_ServerPublicParams_CreateBatchableProfileKeyCredentialPresentation",
+            &randomness,
+        );
+        self.create_profile_key_credential_presentation_with_format(
+            group_secret_params,
+            profile_key_credential,
+            ProofFormat::Batchable,
+            &mut sho,
+        )
+    }
 
+    fn create_profile_key_credential_presentation_with_format(
+        &self,
+        group_secret_params: api::groups::GroupSecretParams,
+        profile_key_credential: api::profiles::ProfileKeyCredential,
+        format: ProofFormat,
+        sho: &mut Sho,
+    ) -> api::profiles::ProfileKeyCredentialPresentation {
         let uid_enc_key_pair = group_secret_params.uid_enc_key_pair;
         let profile_key_enc_key_pair = group_secret_params.profile_key_enc_key_pair;
         let credentials_public_key = self.profile_key_credentials_public_key;
@@ -512,11 +1312,12 @@ impl ServerPublicParams {
             profile_key_ciphertext.ciphertext,
             profile_key_credential.uid_bytes,
             profile_key_credential.profile_key_bytes,
-            &mut sho,
+            format,
+            sho,
         );
 
         api::profiles::ProfileKeyCredentialPresentation {
-            reserved: Default::default(),
+            reserved: presentation_version(format),
             proof,
             uid_enc_ciphertext: uuid_ciphertext.ciphertext,
             profile_key_enc_ciphertext: profile_key_ciphertext.ciphertext,
@@ -638,4 +1439,118 @@ impl ServerPublicParams {
             receipt_serial_bytes: receipt_credential.receipt_serial_bytes,
         }
     }
+
+    /// Starts a request for an extended receipt credential with the given serial.
+    pub fn create_extended_receipt_credential_request_context(
+        &self,
+        randomness: RandomnessBytes,
+        receipt_serial_bytes: ReceiptSerialBytes,
+    ) -> Result<api::receipts::ExtendedReceiptCredentialRequestContext, ZkGroupError> {
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20211201_Random_ServerPublicParams_CreateExtendedReceiptCredentialRequestContext",
+            &randomness,
+        );
+        let context = crypto::kvac::RequestContext::new(
+            &[encode_receipt_serial_bytes(receipt_serial_bytes)],
+            &mut sho,
+        )?;
+        Ok(api::receipts::ExtendedReceiptCredentialRequestContext {
+            reserved: Default::default(),
+            receipt_serial_bytes,
+            context,
+        })
+    }
+
+    pub fn receive_extended_receipt_credential(
+        &self,
+        context: &api::receipts::ExtendedReceiptCredentialRequestContext,
+        response: &api::receipts::ExtendedReceiptCredentialResponse,
+    ) -> Result<api::receipts::ExtendedReceiptCredential, ZkGroupError> {
+        let attributes = extended_receipt_known_attributes(
+            response.receipt_expiration_time,
+            response.receipt_level,
+            response.payment_type,
+            response.validity_start_time,
+        );
+        let credential = context.context.receive(
+            &self.extended_receipt_credentials_public_key()?,
+            &response.response,
+            &attributes,
+        )?;
+        Ok(api::receipts::ExtendedReceiptCredential {
+            reserved: Default::default(),
+            credential,
+            receipt_expiration_time: response.receipt_expiration_time,
+            receipt_level: response.receipt_level,
+            receipt_serial_bytes: context.receipt_serial_bytes,
+            payment_type: response.payment_type,
+            validity_start_time: response.validity_start_time,
+        })
+    }
+
+    /// Presents `receipt_credential`, revealing only the optional attributes selected by
+    /// `disclosure`.
+    pub fn create_extended_receipt_credential_presentation(
+        &self,
+        randomness: RandomnessBytes,
+        receipt_credential: &api::receipts::ExtendedReceiptCredential,
+        disclosure: api::receipts::ReceiptDisclosure,
+    ) -> Result<api::receipts::ExtendedReceiptCredentialPresentation, ZkGroupError> {
+        let public_key = self.extended_receipt_credentials_public_key()?;
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20211201_Random_ServerPublicParams_CreateExtendedReceiptCredentialPresentation",
+            &randomness,
+        );
+        let mut disclosed = vec![crypto::credentials::ExtendedReceiptCredential::SERIAL];
+        if disclosure.payment_type {
+            disclosed.push(crypto::credentials::ExtendedReceiptCredential::PAYMENT_TYPE);
+        }
+        if disclosure.validity_start_time {
+            disclosed.push(crypto::credentials::ExtendedReceiptCredential::VALIDITY_START_TIME);
+        }
+        let presentation =
+            receipt_credential
+                .credential
+                .present(&public_key, &disclosed, None, &[], &mut sho)?;
+        Ok(api::receipts::ExtendedReceiptCredentialPresentation {
+            reserved: Default::default(),
+            presentation,
+            receipt_expiration_time: receipt_credential.receipt_expiration_time,
+            receipt_level: receipt_credential.receipt_level,
+            receipt_serial_bytes: receipt_credential.receipt_serial_bytes,
+            disclosure,
+            payment_type: if disclosure.payment_type {
+                receipt_credential.payment_type
+            } else {
+                0
+            },
+            validity_start_time: if disclosure.validity_start_time {
+                receipt_credential.validity_start_time
+            } else {
+                0
+            },
+        })
+    }
+
+    fn auth_credentials_with_pni_public_key(
+        &self,
+    ) -> Result<crypto::kvac::PublicKey<crypto::credentials::AuthCredentialWithPni>, ZkGroupError>
+    {
+        self.v1_public_keys
+            .map(|public_keys| public_keys.auth_credentials_with_pni_public_key)
+            .ok_or(ZkGroupError::MissingCredentialKey {
+                credential: "auth with PNI",
+            })
+    }
+
+    fn extended_receipt_credentials_public_key(
+        &self,
+    ) -> Result<crypto::kvac::PublicKey<crypto::credentials::ExtendedReceiptCredential>, ZkGroupError>
+    {
+        self.v1_public_keys
+            .map(|public_keys| public_keys.extended_receipt_credentials_public_key)
+            .ok_or(ZkGroupError::MissingCredentialKey {
+                credential: "extended receipt",
+            })
+    }
 }
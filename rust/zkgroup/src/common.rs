@@ -4,7 +4,9 @@
 //
 
 pub mod array_utils;
+pub(crate) mod batch;
 pub mod constants;
 pub mod errors;
+pub mod serialization;
 pub mod sho;
 pub mod simple_types;
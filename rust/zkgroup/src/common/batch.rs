@@ -0,0 +1,54 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Helpers for the batch issuance and parallel verification APIs on
+//! [`ServerSecretParams`](crate::ServerSecretParams).
+//!
+//! With the `rayon` feature enabled, batches are processed on rayon's global thread pool;
+//! otherwise they are processed sequentially on the calling thread. Either way, results come back
+//! in the same order as the inputs.
+
+use crate::common::constants::*;
+use crate::common::sho::*;
+use crate::common::simple_types::*;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+#[cfg(feature = "rayon")]
+pub(crate) fn map<T, R>(items: &[T], f: impl Fn(&T) -> R + Send + Sync) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+{
+    items.par_iter().map(f).collect()
+}
+
+#[cfg(not(feature = "rayon"))]
+pub(crate) fn map<T, R>(items: &[T], f: impl Fn(&T) -> R + Send + Sync) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+{
+    items.iter().map(f).collect()
+}
+
+/// Expands one caller-supplied `randomness` into a separate value for each of `count` operations.
+///
+/// This is done up front so that the outputs do not depend on how the batch is scheduled.
+pub(crate) fn split_randomness(
+    label: &[u8],
+    randomness: RandomnessBytes,
+    count: usize,
+) -> Vec<RandomnessBytes> {
+    let mut sho = Sho::new(label, &randomness);
+    (0..count)
+        .map(|_| {
+            let mut item_randomness = [0u8; RANDOMNESS_LEN];
+            item_randomness.copy_from_slice(&sho.squeeze(RANDOMNESS_LEN));
+            item_randomness
+        })
+        .collect()
+}
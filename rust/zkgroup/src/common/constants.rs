@@ -8,15 +8,37 @@ pub const NUM_PROFILE_KEY_CRED_ATTRIBUTES: usize = 4;
 pub const NUM_RECEIPT_CRED_ATTRIBUTES: usize = 2;
 
 pub const AES_KEY_LEN: usize = 32;
+pub const ATTRIBUTE_LEN: usize = 16;
+pub const ATTRIBUTE_CIPHERTEXT_PRESENTATION_LEN: usize = 105;
+pub const BLOB_STREAM_VERSION: u8 = 1;
+pub const BLOB_STREAM_CHUNK_LEN: usize = 64 * 1024;
+pub const BLOB_STREAM_NONCE_PREFIX_LEN: usize = 7;
+pub const MAX_BLOB_STREAM_CHUNK_LEN: usize = 1024 * 1024;
 pub const AESGCM_NONCE_LEN: usize = 12;
 pub const AESGCM_TAG_LEN: usize = 16;
+pub const E164_CIPHERTEXT_LEN: usize = 65;
+pub const EXTENDED_RECEIPT_CREDENTIAL_LEN: usize = 297;
+pub const EXTENDED_RECEIPT_CREDENTIAL_PRESENTATION_LEN: usize = 555;
+pub const EXTENDED_RECEIPT_CREDENTIAL_REQUEST_LEN: usize = 241;
+pub const EXTENDED_RECEIPT_CREDENTIAL_REQUEST_CONTEXT_LEN: usize = 329;
+pub const EXTENDED_RECEIPT_CREDENTIAL_RESPONSE_LEN: usize = 489;
 pub const GROUP_MASTER_KEY_LEN: usize = 32;
 pub const GROUP_SECRET_PARAMS_LEN: usize = 289;
 pub const GROUP_PUBLIC_PARAMS_LEN: usize = 97;
 pub const GROUP_IDENTIFIER_LEN: usize = 32;
+pub const GROUP_INVITE_LINK_LEN: usize = 49;
+pub const INVITE_LINK_PASSWORD_LEN: usize = 16;
+pub const INVITE_LINK_PRESENTATION_LEN: usize = 137;
+pub const INVITE_LINK_PUBLIC_KEY_LEN: usize = 33;
 pub const AUTH_CREDENTIAL_LEN: usize = 181;
 pub const AUTH_CREDENTIAL_PRESENTATION_LEN: usize = 493;
+pub const AUTH_CREDENTIAL_PRESENTATION_V1_LEN: usize = 653;
 pub const AUTH_CREDENTIAL_RESPONSE_LEN: usize = 361;
+pub const AUTH_CREDENTIAL_WITH_PNI_LEN: usize = 285;
+pub const AUTH_CREDENTIAL_WITH_PNI_PRESENTATION_LEN: usize = 673;
+pub const AUTH_CREDENTIAL_WITH_PNI_RESPONSE_LEN: usize = 425;
+pub const MAX_E164_DIGITS: usize = 15;
+pub const PNI_CIPHERTEXT_LEN: usize = 65;
 pub const PNI_CREDENTIAL_LEN: usize = 161;
 pub const PNI_CREDENTIAL_PRESENTATION_LEN: usize = 841;
 pub const PNI_CREDENTIAL_REQUEST_CONTEXT_LEN: usize = 489;
@@ -26,6 +48,7 @@ pub const PROFILE_KEY_CIPHERTEXT_LEN: usize = 65;
 pub const PROFILE_KEY_COMMITMENT_LEN: usize = 97;
 pub const PROFILE_KEY_CREDENTIAL_LEN: usize = 145;
 pub const PROFILE_KEY_CREDENTIAL_PRESENTATION_LEN: usize = 713;
+pub const PROFILE_KEY_CREDENTIAL_PRESENTATION_V1_LEN: usize = 969;
 pub const PROFILE_KEY_CREDENTIAL_REQUEST_LEN: usize = 329;
 pub const PROFILE_KEY_CREDENTIAL_REQUEST_CONTEXT_LEN: usize = 473;
 pub const PROFILE_KEY_CREDENTIAL_RESPONSE_LEN: usize = 457;
@@ -38,13 +61,18 @@ pub const RECEIPT_CREDENTIAL_REQUEST_CONTEXT_LEN: usize = 177;
 pub const RECEIPT_CREDENTIAL_RESPONSE_LEN: usize = 409;
 pub const RECEIPT_SERIAL_LEN: usize = 16;
 pub const RESERVED_LEN: usize = 1;
-pub const SERVER_SECRET_PARAMS_LEN: usize = 1537;
-pub const SERVER_PUBLIC_PARAMS_LEN: usize = 289;
+pub const SERVER_SECRET_PARAMS_LEN: usize = 2289;
+pub const SERVER_PUBLIC_PARAMS_LEN: usize = 417;
+pub const SERVER_SECRET_PARAMS_V0_LEN: usize = 1537;
+pub const SERVER_PUBLIC_PARAMS_V0_LEN: usize = 289;
+pub const USERNAME_CIPHERTEXT_LEN: usize = 65;
 pub const UUID_CIPHERTEXT_LEN: usize = 65;
 pub const RANDOMNESS_LEN: usize = 32;
 pub const SIGNATURE_LEN: usize = 64;
 pub const UUID_LEN: usize = 16;
 
+pub const SECONDS_PER_DAY: u64 = 86400;
+
 pub const TEST_ARRAY_16: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
 
 pub const TEST_ARRAY_16_1: [u8; 16] = [
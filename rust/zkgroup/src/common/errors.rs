@@ -3,16 +3,114 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
-#[derive(Debug, displaydoc::Display)]
+use crate::common::simple_types::Timestamp;
+
+#[derive(Debug, displaydoc::Display, PartialEq, Eq)]
 pub enum ZkGroupError {
     /// Bad arguments were passed to the function
     BadArgs,
-    /// Decryption failed
-    DecryptionFailure,
+    /// {0}
+    Deserialization(DeserializationError),
+    /// Decryption failed: {0}
+    DecryptionFailure(DecryptionError),
     /// MAC verification failed
     MacVerificationFailure,
-    /// Proof verification failed
-    ProofVerificationFailure,
+    /// {proof} verification failed
+    ProofVerificationFailure { proof: &'static str },
+    /// {proof} is for a different group, whose public params are at index {index}
+    ProofForOtherGroup { proof: &'static str, index: usize },
     /// Signature verification failed
     SignatureVerificationFailure,
+    /// Value is not a valid {kind}
+    InvalidAttribute { kind: &'static str },
+    /// Redemption time {redemption_time} is not the start of a day
+    RedemptionTimeNotDayAligned { redemption_time: Timestamp },
+    /// Redemption time {redemption_time} is outside the acceptable window around {current_time}
+    RedemptionTimeOutOfRange {
+        redemption_time: Timestamp,
+        current_time: Timestamp,
+    },
+    /// These server params have no key for {credential} credentials
+    MissingCredentialKey { credential: &'static str },
+    /// Receipt has already been redeemed
+    ReceiptAlreadyRedeemed,
+    /// Receipt expired at {expiration_time}, which is not after {current_time}
+    ReceiptExpired {
+        expiration_time: Timestamp,
+        current_time: Timestamp,
+    },
+    /// Receipt is not valid until {validity_start_time}, which is after {current_time}
+    ReceiptNotYetValid {
+        validity_start_time: Timestamp,
+        current_time: Timestamp,
+    },
+    /// Receipt presentation does not disclose its {attribute}
+    ReceiptAttributeNotDisclosed { attribute: &'static str },
+}
+
+impl std::error::Error for ZkGroupError {}
+
+/// A proof that reports failures by name.
+pub(crate) trait ProofName {
+    /// The name of the proof in [`ZkGroupError::ProofVerificationFailure`].
+    const NAME: &'static str;
+}
+
+/// The [`ZkGroupError::ProofVerificationFailure`] for a proof of type `T`.
+pub(crate) fn proof_failure<T: ProofName>() -> ZkGroupError {
+    ZkGroupError::ProofVerificationFailure { proof: T::NAME }
+}
+
+/// Which ciphertext could not be decrypted, and why.
+///
+/// None of these carry any part of the plaintext or key material.
+#[derive(Debug, displaydoc::Display, PartialEq, Eq, Clone, Copy)]
+pub enum DecryptionError {
+    /// {kind} ciphertext does not decrypt to a valid {kind}
+    AttributeCiphertext { kind: &'static str },
+    /// profile key ciphertext does not decrypt to a profile key for this UID
+    ProfileKeyCiphertext,
+    /// blob ciphertext of {len} bytes is too short
+    BlobTooShort { len: usize },
+    /// blob ciphertext failed authentication
+    BlobAuthentication,
+    /// blob plaintext has an invalid padding header
+    BlobPadding,
+    /// streamed blob has an invalid header
+    BlobStreamHeader,
+    /// streamed blob ends before its final chunk
+    BlobTruncated,
+}
+
+/// Why a serialized zkgroup value could not be decoded.
+#[derive(Debug, displaydoc::Display, PartialEq, Eq)]
+pub enum DeserializationError {
+    /// {type_name} cannot be decoded from an empty buffer
+    Empty { type_name: &'static str },
+    /// {type_name} has unknown version {version}
+    UnknownVersion {
+        type_name: &'static str,
+        version: u8,
+    },
+    /// {type_name} version {version} must be {expected} bytes, but got {actual}
+    WrongLength {
+        type_name: &'static str,
+        version: u8,
+        expected: usize,
+        actual: usize,
+    },
+    /// {type_name} has the right length but is not validly encoded
+    Malformed { type_name: &'static str },
+}
+
+impl From<DeserializationError> for ZkGroupError {
+    fn from(e: DeserializationError) -> Self {
+        ZkGroupError::Deserialization(e)
+    }
+}
+
+impl From<DecryptionError> for ZkGroupError {
+    fn from(e: DecryptionError) -> Self {
+        ZkGroupError::DecryptionFailure(e)
+    }
 }
@@ -0,0 +1,200 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Versioned serialization for zkgroup's API types.
+//!
+//! Every serialized API type starts with a single `reserved` byte, which holds the version of the
+//! encoding that follows. [`deserialize`] reads that byte first, checks the length expected for
+//! that version, and only then decodes the rest, so that a type can gain a new encoding without
+//! becoming a new type.
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::auth::*;
+use crate::api::groups::*;
+use crate::api::profiles::*;
+use crate::api::receipts::*;
+use crate::common::constants::*;
+use crate::common::errors::DeserializationError;
+
+/// A zkgroup type whose serialized form starts with a version byte.
+pub trait VersionedSerializable: Serialize + for<'a> Deserialize<'a> {
+    /// The name of the type in [`DeserializationError`]s.
+    const NAME: &'static str;
+
+    /// The version written by [`serialize`].
+    const VERSION: u8;
+
+    /// The serialized length of [`Self::VERSION`].
+    const LEN: usize;
+
+    /// The serialized length for `version`, or `None` if that version can't be read.
+    ///
+    /// Defaults to accepting only [`Self::VERSION`].
+    fn serialized_len(version: u8) -> Option<usize> {
+        if version == Self::VERSION {
+            Some(Self::LEN)
+        } else {
+            None
+        }
+    }
+
+    /// Decodes `bytes`, which are known to start with `version` and to have the length for it.
+    ///
+    /// Types that can read more than one version should override this to dispatch on `version`.
+    fn deserialize_version(version: u8, bytes: &[u8]) -> Result<Self, DeserializationError> {
+        debug_assert_eq!(version, Self::VERSION);
+        deserialize_layout(bytes, Self::NAME)
+    }
+}
+
+/// Decodes `bytes` as `L`, reporting failures against `type_name`.
+///
+/// For use by [`VersionedSerializable::deserialize_version`] when an older version is decoded
+/// through a different type.
+pub(crate) fn deserialize_layout<L: for<'a> Deserialize<'a>>(
+    bytes: &[u8],
+    type_name: &'static str,
+) -> Result<L, DeserializationError> {
+    bincode::deserialize(bytes).map_err(|_| DeserializationError::Malformed { type_name })
+}
+
+/// Decodes a value produced by [`serialize`], checking its version and length.
+pub fn deserialize<T: VersionedSerializable>(bytes: &[u8]) -> Result<T, DeserializationError> {
+    let type_name = T::NAME;
+    let version = *bytes
+        .first()
+        .ok_or(DeserializationError::Empty { type_name })?;
+    let expected = T::serialized_len(version)
+        .ok_or(DeserializationError::UnknownVersion { type_name, version })?;
+    if bytes.len() != expected {
+        return Err(DeserializationError::WrongLength {
+            type_name,
+            version,
+            expected,
+            actual: bytes.len(),
+        });
+    }
+    T::deserialize_version(version, bytes)
+}
+
+/// Encodes a value.
+///
+/// This is the current version unless the value was decoded from an older one that it can still
+/// be written in, so that re-encoding never changes a value's bytes.
+pub fn serialize<T: VersionedSerializable>(value: &T) -> Vec<u8> {
+    let bytes = bincode::serialize(value).expect("can always serialize a value");
+    debug_assert!(bytes
+        .first()
+        .map_or(false, |&version| T::serialized_len(version)
+            == Some(bytes.len())));
+    bytes
+}
+
+macro_rules! versioned_serializable {
+    ($($typ:ty => $len:ident),* $(,)?) => {
+        $(
+            impl VersionedSerializable for $typ {
+                const NAME: &'static str = stringify!($typ);
+                const VERSION: u8 = 0;
+                const LEN: usize = $len;
+            }
+        )*
+    };
+}
+
+versioned_serializable! {
+    AttributeCiphertextPresentation => ATTRIBUTE_CIPHERTEXT_PRESENTATION_LEN,
+    AuthCredential => AUTH_CREDENTIAL_LEN,
+    AuthCredentialResponse => AUTH_CREDENTIAL_RESPONSE_LEN,
+    AuthCredentialWithPni => AUTH_CREDENTIAL_WITH_PNI_LEN,
+    AuthCredentialWithPniPresentation => AUTH_CREDENTIAL_WITH_PNI_PRESENTATION_LEN,
+    AuthCredentialWithPniResponse => AUTH_CREDENTIAL_WITH_PNI_RESPONSE_LEN,
+    E164Ciphertext => E164_CIPHERTEXT_LEN,
+    ExtendedReceiptCredential => EXTENDED_RECEIPT_CREDENTIAL_LEN,
+    ExtendedReceiptCredentialPresentation => EXTENDED_RECEIPT_CREDENTIAL_PRESENTATION_LEN,
+    ExtendedReceiptCredentialRequest => EXTENDED_RECEIPT_CREDENTIAL_REQUEST_LEN,
+    ExtendedReceiptCredentialRequestContext => EXTENDED_RECEIPT_CREDENTIAL_REQUEST_CONTEXT_LEN,
+    ExtendedReceiptCredentialResponse => EXTENDED_RECEIPT_CREDENTIAL_RESPONSE_LEN,
+    GroupInviteLink => GROUP_INVITE_LINK_LEN,
+    GroupPublicParams => GROUP_PUBLIC_PARAMS_LEN,
+    GroupSecretParams => GROUP_SECRET_PARAMS_LEN,
+    InviteLinkPresentation => INVITE_LINK_PRESENTATION_LEN,
+    InviteLinkPublicKey => INVITE_LINK_PUBLIC_KEY_LEN,
+    PniCiphertext => PNI_CIPHERTEXT_LEN,
+    PniCredential => PNI_CREDENTIAL_LEN,
+    PniCredentialPresentation => PNI_CREDENTIAL_PRESENTATION_LEN,
+    PniCredentialRequestContext => PNI_CREDENTIAL_REQUEST_CONTEXT_LEN,
+    PniCredentialResponse => PNI_CREDENTIAL_RESPONSE_LEN,
+    ProfileKeyCiphertext => PROFILE_KEY_CIPHERTEXT_LEN,
+    ProfileKeyCommitment => PROFILE_KEY_COMMITMENT_LEN,
+    ProfileKeyCredential => PROFILE_KEY_CREDENTIAL_LEN,
+    ProfileKeyCredentialRequest => PROFILE_KEY_CREDENTIAL_REQUEST_LEN,
+    ProfileKeyCredentialRequestContext => PROFILE_KEY_CREDENTIAL_REQUEST_CONTEXT_LEN,
+    ProfileKeyCredentialResponse => PROFILE_KEY_CREDENTIAL_RESPONSE_LEN,
+    ReceiptCredential => RECEIPT_CREDENTIAL_LEN,
+    ReceiptCredentialPresentation => RECEIPT_CREDENTIAL_PRESENTATION_LEN,
+    ReceiptCredentialRequest => RECEIPT_CREDENTIAL_REQUEST_LEN,
+    ReceiptCredentialRequestContext => RECEIPT_CREDENTIAL_REQUEST_CONTEXT_LEN,
+    ReceiptCredentialResponse => RECEIPT_CREDENTIAL_RESPONSE_LEN,
+    UsernameCiphertext => USERNAME_CIPHERTEXT_LEN,
+    UuidCiphertext => UUID_CIPHERTEXT_LEN,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let uid_ciphertext = GroupSecretParams::generate(TEST_ARRAY_32).encrypt_uuid(TEST_ARRAY_16);
+        let bytes = serialize(&uid_ciphertext);
+        assert_eq!(bytes.len(), UUID_CIPHERTEXT_LEN);
+        assert_eq!(bytes[0], UuidCiphertext::VERSION);
+        let decoded: UuidCiphertext = deserialize(&bytes).unwrap();
+        assert_eq!(serialize(&decoded), bytes);
+    }
+
+    #[test]
+    fn test_errors() {
+        let bytes = serialize(&GroupSecretParams::generate(TEST_ARRAY_32));
+
+        assert_eq!(
+            deserialize::<GroupSecretParams>(&[]).err(),
+            Some(DeserializationError::Empty {
+                type_name: "GroupSecretParams"
+            })
+        );
+        assert_eq!(
+            deserialize::<GroupSecretParams>(&bytes[..bytes.len() - 1]).err(),
+            Some(DeserializationError::WrongLength {
+                type_name: "GroupSecretParams",
+                version: 0,
+                expected: GROUP_SECRET_PARAMS_LEN,
+                actual: GROUP_SECRET_PARAMS_LEN - 1,
+            })
+        );
+
+        let mut future_version = bytes;
+        future_version[0] = 1;
+        assert_eq!(
+            deserialize::<GroupSecretParams>(&future_version).err(),
+            Some(DeserializationError::UnknownVersion {
+                type_name: "GroupSecretParams",
+                version: 1,
+            })
+        );
+
+        // A UUID ciphertext whose points aren't valid encodings.
+        let mut malformed = [0xFFu8; UUID_CIPHERTEXT_LEN];
+        malformed[0] = 0;
+        assert_eq!(
+            deserialize::<UuidCiphertext>(&malformed).err(),
+            Some(DeserializationError::Malformed {
+                type_name: "UuidCiphertext",
+            })
+        );
+    }
+}
@@ -15,10 +15,14 @@ pub type ReservedBytes = [u8; RESERVED_LEN];
 pub type SignatureBytes = [u8; SIGNATURE_LEN];
 pub type NotarySignatureBytes = [u8; SIGNATURE_LEN];
 pub type GroupIdentifierBytes = [u8; GROUP_IDENTIFIER_LEN];
+pub type InviteLinkPasswordBytes = [u8; INVITE_LINK_PASSWORD_LEN];
 pub type ProfileKeyVersionBytes = [u8; PROFILE_KEY_VERSION_LEN];
 pub type ProfileKeyVersionEncodedBytes = [u8; PROFILE_KEY_VERSION_ENCODED_LEN];
 pub type RedemptionTime = u32;
 
+// Seconds past the epoch. Used where day granularity (as in `RedemptionTime`) is too coarse.
+pub type Timestamp = u64;
+
 // A random UUID that the receipt issuing server will blind authorize to redeem a given receipt
 // level within a certain time frame.
 pub type ReceiptSerialBytes = [u8; RECEIPT_SERIAL_LEN];
@@ -31,12 +35,25 @@ pub type ReceiptExpirationTime = u64;
 // should validate this matches their expectations.
 pub type ReceiptLevel = u64;
 
+// Identifies how a receipt was paid for (e.g. one-time or recurring). Its meaning is up to the
+// issuing server; it is only carried by extended receipt credentials.
+pub type ReceiptPaymentType = u64;
+
+// Seconds past the epoch before which an extended receipt should not be redeemed.
+pub type ReceiptValidityStartTime = u64;
+
 pub fn encode_redemption_time(redemption_time: u32) -> Scalar {
     let mut scalar_bytes: [u8; 32] = Default::default();
     scalar_bytes[0..4].copy_from_slice(&redemption_time.to_be_bytes());
     Scalar::from_bytes_mod_order(scalar_bytes)
 }
 
+pub fn encode_timestamp(timestamp: Timestamp) -> Scalar {
+    let mut scalar_bytes: [u8; 32] = Default::default();
+    scalar_bytes[0..8].copy_from_slice(&timestamp.to_be_bytes());
+    Scalar::from_bytes_mod_order(scalar_bytes)
+}
+
 pub fn encode_receipt_serial_bytes(receipt_serial_bytes: ReceiptSerialBytes) -> Scalar {
     let mut scalar_bytes: [u8; 32] = Default::default();
     scalar_bytes[0..16].copy_from_slice(&receipt_serial_bytes[..]);
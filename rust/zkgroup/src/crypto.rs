@@ -3,7 +3,11 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+pub mod attribute_struct;
 pub mod credentials;
+pub mod invite_link;
+pub mod kvac;
+pub mod pni_struct;
 pub mod profile_key_commitment;
 pub mod profile_key_credential_request;
 pub mod profile_key_encryption;
@@ -0,0 +1,199 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+#![allow(non_snake_case)]
+
+use std::marker::PhantomData;
+
+use crate::common::constants::*;
+use crate::common::sho::*;
+use crate::crypto::uid_encryption::Attribute;
+use crate::crypto::uid_struct::PointDecodeFailure;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use sha2::Sha256;
+
+pub type AttributeBytes = [u8; ATTRIBUTE_LEN];
+
+/// Distinguishes one kind of group member attribute from another.
+///
+/// Each kind has its own `M1` label, so the same bytes encrypt differently per kind.
+pub trait AttributeKind: Copy {
+    const NAME: &'static str;
+    const M1_LABEL: &'static [u8];
+
+    /// Whether `bytes` is a canonical encoding of an attribute of this kind.
+    fn is_valid(bytes: &AttributeBytes) -> bool;
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum E164Kind {}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum UsernameKind {}
+
+impl AttributeKind for E164Kind {
+    const NAME: &'static str = "E.164";
+    const M1_LABEL: &'static [u8] = b"Signal_ZKGroup_20211201_E164_CalcM1";
+
+    fn is_valid(bytes: &AttributeBytes) -> bool {
+        let digits = unpadded(bytes);
+        (1..=MAX_E164_DIGITS).contains(&digits.len())
+            && digits[0] != b'0'
+            && digits.iter().all(u8::is_ascii_digit)
+    }
+}
+
+impl AttributeKind for UsernameKind {
+    const NAME: &'static str = "username";
+    const M1_LABEL: &'static [u8] = b"Signal_ZKGroup_20211201_Username_CalcM1";
+
+    fn is_valid(bytes: &AttributeBytes) -> bool {
+        let username = unpadded(bytes);
+        !username.is_empty() && !username.contains(&0) && std::str::from_utf8(username).is_ok()
+    }
+}
+
+/// Strips the zero padding from the end of an encoded attribute.
+fn unpadded(bytes: &AttributeBytes) -> &[u8] {
+    let len = bytes.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+    &bytes[..len]
+}
+
+fn padded(value: &[u8]) -> AttributeBytes {
+    let mut bytes: AttributeBytes = Default::default();
+    bytes[..value.len()].copy_from_slice(value);
+    bytes
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct AttributeStruct<K> {
+    pub(crate) bytes: AttributeBytes,
+    pub(crate) M1: RistrettoPoint,
+    pub(crate) M2: RistrettoPoint,
+    kind: PhantomData<K>,
+}
+
+pub type E164Struct = AttributeStruct<E164Kind>;
+pub type UsernameStruct = AttributeStruct<UsernameKind>;
+
+impl<K: AttributeKind> AttributeStruct<K> {
+    /// Returns `None` if `bytes` is not a valid attribute of kind `K`.
+    pub fn new(bytes: AttributeBytes) -> Option<Self> {
+        if !K::is_valid(&bytes) {
+            return None;
+        }
+        let mut sho = Sho::new(K::M1_LABEL, &bytes);
+        let M1 = sho.get_point();
+        let M2 = RistrettoPoint::lizard_encode::<Sha256>(&bytes);
+        Some(AttributeStruct {
+            bytes,
+            M1,
+            M2,
+            kind: PhantomData,
+        })
+    }
+
+    pub fn to_bytes(&self) -> AttributeBytes {
+        self.bytes
+    }
+}
+
+impl E164Struct {
+    /// Accepts a `+`-prefixed E.164 number with no separators, like `+14155550100`.
+    pub fn from_e164(e164: &str) -> Option<Self> {
+        let digits = e164.strip_prefix('+')?.as_bytes();
+        if digits.len() > ATTRIBUTE_LEN {
+            return None;
+        }
+        Self::new(padded(digits))
+    }
+
+    pub fn to_e164(&self) -> String {
+        let digits = std::str::from_utf8(unpadded(&self.bytes)).expect("validated on creation");
+        format!("+{}", digits)
+    }
+}
+
+impl UsernameStruct {
+    /// Accepts any non-empty UTF-8 username of at most [`ATTRIBUTE_LEN`] bytes without NULs.
+    pub fn from_username(username: &str) -> Option<Self> {
+        // Checked before padding, since a trailing NUL would be indistinguishable from padding.
+        if username.len() > ATTRIBUTE_LEN || username.contains('\0') {
+            return None;
+        }
+        Self::new(padded(username.as_bytes()))
+    }
+
+    pub fn to_username(&self) -> String {
+        std::str::from_utf8(unpadded(&self.bytes))
+            .expect("validated on creation")
+            .to_string()
+    }
+}
+
+impl<K: AttributeKind> Attribute for AttributeStruct<K> {
+    const KIND: &'static str = K::NAME;
+
+    fn M1(&self) -> RistrettoPoint {
+        self.M1
+    }
+
+    fn M2(&self) -> RistrettoPoint {
+        self.M2
+    }
+
+    fn from_M2(M2: RistrettoPoint) -> Result<Self, PointDecodeFailure> {
+        match M2.lizard_decode::<Sha256>() {
+            None => Err(PointDecodeFailure),
+            Some(bytes) => Self::new(bytes).ok_or(PointDecodeFailure),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_e164_encoding() {
+        let e164 = E164Struct::from_e164("+14155550100").unwrap();
+        assert_eq!(&e164.to_bytes()[..12], b"14155550100\0");
+        assert_eq!(e164.to_e164(), "+14155550100");
+
+        for invalid in [
+            "14155550100",
+            "+",
+            "+04155550100",
+            "+1415555010a",
+            "+1234567890123456",
+        ] {
+            assert!(E164Struct::from_e164(invalid).is_none(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_username_encoding() {
+        let username = UsernameStruct::from_username("séance.42").unwrap();
+        assert_eq!(username.to_username(), "séance.42");
+        assert!(UsernameStruct::from_username("sixteen.bytes.ok").is_some());
+
+        for invalid in ["", "seventeen.bytes.x", "nul\0inside", "abc\0", "\0"] {
+            assert!(
+                UsernameStruct::from_username(invalid).is_none(),
+                "{:?}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_kinds_are_separated() {
+        let e164 = E164Struct::from_e164("+1555").unwrap();
+        let username = UsernameStruct::from_username("1555").unwrap();
+        assert!(e164.to_bytes() == username.to_bytes());
+        assert!(e164.M2 == username.M2);
+        assert!(e164.M1 != username.M1);
+    }
+}
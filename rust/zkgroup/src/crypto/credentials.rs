@@ -13,6 +13,7 @@ use serde::{Deserialize, Serialize};
 use crate::common::array_utils::{ArrayLike, OneBased};
 use crate::common::sho::*;
 use crate::common::simple_types::*;
+use crate::crypto::kvac::{self, Visibility};
 use crate::crypto::receipt_struct::ReceiptStruct;
 use crate::crypto::uid_struct;
 use crate::crypto::{profile_key_credential_request, receipt_credential_request, receipt_struct};
@@ -192,6 +193,46 @@ pub struct BlindedReceiptCredential {
     pub(crate) S2: RistrettoPoint,
 }
 
+/// An auth credential for both an ACI and a PNI, declared through [`kvac`].
+///
+/// The attributes are the ACI and PNI, which presentations encrypt under the group's key, and the
+/// redemption time in seconds.
+#[derive(Clone)]
+pub enum AuthCredentialWithPni {}
+
+impl kvac::CredentialType for AuthCredentialWithPni {
+    const LABEL: &'static [u8] = b"Signal_ZKGroup_20211201_AuthCredentialWithPni";
+    const ATTRIBUTES: &'static [Visibility] = &[
+        Visibility::Encrypted,
+        Visibility::Encrypted,
+        Visibility::Revealed,
+    ];
+}
+
+/// A receipt credential with a payment type and validity start time, declared through [`kvac`].
+///
+/// The serial is blinded to the issuer, but like the expiration time and level, always disclosed
+/// in presentations; the other two attributes are disclosed at the client's choice.
+#[derive(Clone)]
+pub enum ExtendedReceiptCredential {}
+
+impl ExtendedReceiptCredential {
+    pub const SERIAL: usize = 0;
+    pub const EXPIRATION_TIME_AND_LEVEL: usize = 1;
+    pub const PAYMENT_TYPE: usize = 2;
+    pub const VALIDITY_START_TIME: usize = 3;
+}
+
+impl kvac::CredentialType for ExtendedReceiptCredential {
+    const LABEL: &'static [u8] = b"Signal_ZKGroup_20211201_ExtendedReceiptCredential";
+    const ATTRIBUTES: &'static [Visibility] = &[
+        Visibility::Blinded,
+        Visibility::Revealed,
+        Visibility::Hidden,
+        Visibility::Hidden,
+    ];
+}
+
 pub(crate) fn convert_to_points_uid_struct(
     uid: uid_struct::UidStruct,
     redemption_time: RedemptionTime,
@@ -0,0 +1,39 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+#![allow(non_snake_case)]
+
+use crate::common::sho::*;
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use serde::{Deserialize, Serialize};
+
+/// The key pair derived from an invite link's group and password.
+///
+/// Only the public half is given to the server, so it can check that a joining member knows the
+/// password without learning it.
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyPair {
+    pub(crate) x: Scalar,
+    pub(crate) X: RistrettoPoint,
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublicKey {
+    pub(crate) X: RistrettoPoint,
+}
+
+impl KeyPair {
+    pub fn derive_from(sho: &mut Sho) -> Self {
+        let x = sho.get_scalar();
+        let X = x * RISTRETTO_BASEPOINT_POINT;
+        KeyPair { x, X }
+    }
+
+    pub fn get_public_key(&self) -> PublicKey {
+        PublicKey { X: self.X }
+    }
+}
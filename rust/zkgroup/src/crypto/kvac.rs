@@ -0,0 +1,1424 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! A generic keyed-verification anonymous credential (KVAC) scheme.
+//!
+//! This is the same MAC-based construction as the hand-written credentials in
+//! [`credentials`](super::credentials) and [`proofs`](super::proofs), but the attribute list comes
+//! from a [`CredentialType`] instead of being spelled out for each credential. The credentials
+//! that predate this module keep their own implementations, since their encodings are fixed by
+//! existing clients.
+//!
+//! Each attribute has a [`Visibility`], and an [`AttributeValue`] to match:
+//!
+//! - [`Visibility::Revealed`] attributes are known to the issuer, and disclosed by every
+//!   presentation.
+//! - [`Visibility::Hidden`] attributes are known to the issuer, and disclosed only when the client
+//!   chooses to.
+//! - [`Visibility::Blinded`] attributes are encrypted to the issuer under a key only the client
+//!   knows, and disclosed only when the client chooses to.
+//! - [`Visibility::Encrypted`] attributes are point pairs, like a UID's, that are known to the
+//!   issuer. Presentations encrypt them under a group's [`uid_encryption`] key instead.
+//!
+//! A scalar attribute `m_i` enters the MAC as the point `m_i * G_m_i`; an encrypted attribute
+//! enters it as its two points directly.
+//!
+//! Credentials with blinded attributes are issued in three steps:
+//!
+//! 1. The client creates a [`RequestContext`] from the blinded attribute values, and sends its
+//!    [`IssuanceRequest`] to the issuer.
+//! 2. The issuer calls [`KeyPair::issue`] with the values of the other attributes.
+//! 3. The client checks the [`IssuanceResponse`] with [`RequestContext::receive`], which yields a
+//!    [`Credential`].
+//!
+//! Credentials without blinded attributes are issued directly, with [`KeyPair::issue_unblinded`]
+//! and [`UnblindedIssuanceResponse::receive`]. Either way, the client then calls
+//! [`Credential::present`] for each use, and the issuer checks the [`Presentation`] with
+//! [`KeyPair::verify`].
+//!
+//! Declaring a credential only takes a marker type:
+//!
+//! ```
+//! use zkgroup::crypto::kvac::{CredentialType, Visibility};
+//!
+//! #[derive(Clone)]
+//! enum MembershipCredential {}
+//!
+//! impl CredentialType for MembershipCredential {
+//!     const LABEL: &'static [u8] = b"Example_MembershipCredential";
+//!     const ATTRIBUTES: &'static [Visibility] =
+//!         &[Visibility::Revealed, Visibility::Hidden, Visibility::Blinded];
+//! }
+//! ```
+
+#![allow(non_snake_case)]
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::marker::PhantomData;
+use std::sync::RwLock;
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use lazy_static::lazy_static;
+use poksho::PokshoError;
+use serde::{Deserialize, Serialize};
+
+use crate::common::constants::*;
+use crate::common::errors::*;
+use crate::common::sho::*;
+use crate::crypto::uid_encryption;
+
+/// How much of an attribute the issuer and verifier learn.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Visibility {
+    /// Known to the issuer and to every verifier.
+    Revealed,
+    /// Known to the issuer, and to verifiers the client discloses it to.
+    Hidden,
+    /// Encrypted to the issuer, and known to verifiers the client discloses it to.
+    Blinded,
+    /// Known to the issuer, and encrypted to verifiers under a group's key.
+    Encrypted,
+}
+
+/// The value of one attribute.
+// Credentials only hold a few of these, so the size of the point pair doesn't matter.
+#[allow(clippy::large_enum_variant)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AttributeValue {
+    /// The value of a revealed, hidden, or blinded attribute.
+    Scalar(Scalar),
+    /// The points `M1` and `M2` of an encrypted attribute, as in [`uid_encryption::Attribute`].
+    Points(RistrettoPoint, RistrettoPoint),
+}
+
+impl AttributeValue {
+    fn fits(&self, visibility: Visibility) -> bool {
+        matches!(self, Self::Points(..)) == is_encrypted(visibility)
+    }
+}
+
+/// Describes the attributes of one kind of credential.
+///
+/// Implementors are usually uninhabited marker types. They must be `Clone` so that the types
+/// parameterized by them can be.
+pub trait CredentialType: Clone {
+    /// Separates this credential's generators from every other credential's.
+    ///
+    /// Changing the label or the attribute list invalidates all existing keys and credentials.
+    const LABEL: &'static [u8];
+
+    /// The attributes, in order.
+    const ATTRIBUTES: &'static [Visibility];
+}
+
+/// The indices of the attributes of `C` whose visibility satisfies `predicate`.
+fn indices<C: CredentialType>(predicate: fn(Visibility) -> bool) -> impl Iterator<Item = usize> {
+    C::ATTRIBUTES
+        .iter()
+        .enumerate()
+        .filter(move |(_, visibility)| predicate(**visibility))
+        .map(|(i, _)| i)
+}
+
+fn is_known_to_issuer(visibility: Visibility) -> bool {
+    visibility != Visibility::Blinded
+}
+
+fn is_blinded(visibility: Visibility) -> bool {
+    visibility == Visibility::Blinded
+}
+
+fn is_encrypted(visibility: Visibility) -> bool {
+    visibility == Visibility::Encrypted
+}
+
+/// Checks that `values` holds one value of the right kind for each attribute in `indices`.
+fn check_values(
+    indices: impl ExactSizeIterator<Item = usize>,
+    visibilities: &[Visibility],
+    values: &[AttributeValue],
+) -> Result<(), ZkGroupError> {
+    if indices.len() != values.len()
+        || !indices
+            .zip(values)
+            .all(|(i, value)| value.fits(visibilities[i]))
+    {
+        return Err(ZkGroupError::BadArgs);
+    }
+    Ok(())
+}
+
+/// Names the `i`th (zero-based) instance of `prefix`, counting from one like the hand-written
+/// proofs do.
+fn name(prefix: &str, i: usize) -> String {
+    format!("{}{}", prefix, i + 1)
+}
+
+fn add_equation(
+    st: &mut poksho::Statement,
+    lhs: &str,
+    rhs: &[(String, String)],
+) -> Result<(), PokshoError> {
+    let rhs: Vec<(&str, &str)> = rhs.iter().map(|(s, p)| (s.as_str(), p.as_str())).collect();
+    st.try_add(lhs, &rhs)
+}
+
+fn term(scalar: impl Into<String>, point: impl Into<String>) -> (String, String) {
+    (scalar.into(), point.into())
+}
+
+/// The generator points for one [`CredentialType`].
+///
+/// The MAC has one slot per scalar attribute and two per encrypted attribute, each with its own
+/// `G_y` and secret `y`.
+struct SystemParams {
+    G_w: RistrettoPoint,
+    G_wprime: RistrettoPoint,
+    G_x0: RistrettoPoint,
+    G_x1: RistrettoPoint,
+    G_V: RistrettoPoint,
+    G_y: Vec<RistrettoPoint>,
+    G_m: Vec<RistrettoPoint>,
+    /// The first slot of each attribute.
+    slots: Vec<usize>,
+}
+
+/// Identifies a credential type by its label and attribute list.
+type SystemParamsKey = (&'static [u8], &'static [Visibility]);
+
+lazy_static! {
+    /// Generators already derived for each credential type.
+    static ref SYSTEM_PARAMS: RwLock<HashMap<SystemParamsKey, &'static SystemParams>> =
+        RwLock::new(HashMap::new());
+}
+
+impl SystemParams {
+    /// The generators for `C`, derived on first use and cached after that.
+    fn get<C: CredentialType>() -> &'static Self {
+        let key = (C::LABEL, C::ATTRIBUTES);
+        if let Some(system) = SYSTEM_PARAMS.read().expect("not poisoned").get(&key) {
+            return system;
+        }
+        SYSTEM_PARAMS
+            .write()
+            .expect("not poisoned")
+            .entry(key)
+            .or_insert_with(|| Box::leak(Box::new(Self::generate::<C>())))
+    }
+
+    fn generate<C: CredentialType>() -> Self {
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20211201_Constant_Kvac_SystemParams_Generate",
+            C::LABEL,
+        );
+        let mut slots = Vec::with_capacity(C::ATTRIBUTES.len());
+        let mut num_slots = 0;
+        for visibility in C::ATTRIBUTES {
+            slots.push(num_slots);
+            num_slots += if is_encrypted(*visibility) { 2 } else { 1 };
+        }
+        Self {
+            G_w: sho.get_point(),
+            G_wprime: sho.get_point(),
+            G_x0: sho.get_point(),
+            G_x1: sho.get_point(),
+            G_V: sho.get_point(),
+            G_y: (0..num_slots).map(|_| sho.get_point()).collect(),
+            G_m: (0..C::ATTRIBUTES.len()).map(|_| sho.get_point()).collect(),
+            slots,
+        }
+    }
+
+    fn M(&self, i: usize, m: Scalar) -> RistrettoPoint {
+        m * self.G_m[i]
+    }
+
+    /// The points that `value`, as attribute `i`, adds to the MAC, with their slots.
+    fn points(&self, i: usize, value: &AttributeValue) -> Vec<(usize, RistrettoPoint)> {
+        let slot = self.slots[i];
+        match *value {
+            AttributeValue::Scalar(m) => vec![(slot, self.M(i, m))],
+            AttributeValue::Points(M1, M2) => vec![(slot, M1), (slot + 1, M2)],
+        }
+    }
+
+    /// The terms `y_j * M_j` for the slots of `attributes`, after adding the points to
+    /// `point_args`.
+    fn mac_terms<'a>(
+        &self,
+        attributes: impl Iterator<Item = (usize, &'a AttributeValue)>,
+        point_args: &mut poksho::PointArgs,
+    ) -> Vec<(String, String)> {
+        let mut terms = Vec::new();
+        for (i, value) in attributes {
+            for (j, Mj) in self.points(i, value) {
+                terms.push(term(name("y", j), name("M", j)));
+                point_args.add(&name("M", j), Mj);
+            }
+        }
+        terms
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub struct KeyPair<C> {
+    // private
+    w: Scalar,
+    wprime: Scalar,
+    W: RistrettoPoint,
+    x0: Scalar,
+    x1: Scalar,
+    y: Vec<Scalar>,
+
+    // public
+    C_W: RistrettoPoint,
+    I: RistrettoPoint,
+    credential_type: PhantomData<C>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct PublicKey<C> {
+    C_W: RistrettoPoint,
+    I: RistrettoPoint,
+    credential_type: PhantomData<C>,
+}
+
+// Implemented by hand so that `C` doesn't have to be `Copy`.
+impl<C> Clone for PublicKey<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C> Copy for PublicKey<C> {}
+
+/// The client's half of blinded issuance, holding the blinded attribute values and the key that
+/// hides them from the issuer.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub struct RequestContext<C> {
+    blinded_attributes: Vec<Scalar>,
+    y: Scalar,
+    request: IssuanceRequest<C>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub struct IssuanceRequest<C> {
+    Y: RistrettoPoint,
+    // (D1, D2) for each blinded attribute, in order.
+    ciphertexts: Vec<(RistrettoPoint, RistrettoPoint)>,
+    poksho_proof: Vec<u8>,
+    credential_type: PhantomData<C>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub struct IssuanceResponse<C> {
+    t: Scalar,
+    U: RistrettoPoint,
+    S1: RistrettoPoint,
+    S2: RistrettoPoint,
+    poksho_proof: Vec<u8>,
+    credential_type: PhantomData<C>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub struct UnblindedIssuanceResponse<C> {
+    t: Scalar,
+    U: RistrettoPoint,
+    V: RistrettoPoint,
+    poksho_proof: Vec<u8>,
+    credential_type: PhantomData<C>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub struct Credential<C> {
+    t: Scalar,
+    U: RistrettoPoint,
+    V: RistrettoPoint,
+    attributes: Vec<AttributeValue>,
+    credential_type: PhantomData<C>,
+}
+
+/// Proves possession of a [`Credential`] without revealing it.
+///
+/// Deserialization checks that the presentation has the shape `C` calls for, so its
+/// [ciphertexts](Self::get_ciphertexts) can be indexed by position.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(
+    bound(serialize = "", deserialize = "C: CredentialType"),
+    try_from = "UncheckedPresentation<C>"
+)]
+pub struct Presentation<C> {
+    C_x0: RistrettoPoint,
+    C_x1: RistrettoPoint,
+    C_y: Vec<RistrettoPoint>,
+    C_V: RistrettoPoint,
+    // One for each encrypted attribute, in order.
+    ciphertexts: Vec<uid_encryption::Ciphertext>,
+    poksho_proof: Vec<u8>,
+    credential_type: PhantomData<C>,
+}
+
+/// A [`Presentation`] whose shape hasn't been checked yet.
+#[derive(Deserialize)]
+#[serde(bound = "")]
+struct UncheckedPresentation<C> {
+    C_x0: RistrettoPoint,
+    C_x1: RistrettoPoint,
+    C_y: Vec<RistrettoPoint>,
+    C_V: RistrettoPoint,
+    ciphertexts: Vec<uid_encryption::Ciphertext>,
+    poksho_proof: Vec<u8>,
+    credential_type: PhantomData<C>,
+}
+
+impl<C: CredentialType> TryFrom<UncheckedPresentation<C>> for Presentation<C> {
+    type Error = &'static str;
+
+    fn try_from(presentation: UncheckedPresentation<C>) -> Result<Self, Self::Error> {
+        let system = SystemParams::get::<C>();
+        if presentation.C_y.len() != system.G_y.len()
+            || presentation.ciphertexts.len() != indices::<C>(is_encrypted).count()
+        {
+            return Err("presentation has the wrong number of attributes");
+        }
+        Ok(Self {
+            C_x0: presentation.C_x0,
+            C_x1: presentation.C_x1,
+            C_y: presentation.C_y,
+            C_V: presentation.C_V,
+            ciphertexts: presentation.ciphertexts,
+            poksho_proof: presentation.poksho_proof,
+            credential_type: PhantomData,
+        })
+    }
+}
+
+impl<C> ProofName for IssuanceRequest<C> {
+    const NAME: &'static str = "IssuanceRequest";
+}
+
+impl<C> ProofName for IssuanceResponse<C> {
+    const NAME: &'static str = "IssuanceResponse";
+}
+
+impl<C> ProofName for UnblindedIssuanceResponse<C> {
+    const NAME: &'static str = "UnblindedIssuanceResponse";
+}
+
+impl<C> ProofName for Presentation<C> {
+    const NAME: &'static str = "Presentation";
+}
+
+/// Adds the equations that tie a proof to the issuer's key, which every issuance proof starts
+/// with.
+fn add_key_equations(st: &mut poksho::Statement, num_slots: usize) -> Result<(), PokshoError> {
+    st.try_add("C_W", &[("w", "G_w"), ("wprime", "G_wprime")])?;
+    let mut G_V_minus_I = vec![term("x0", "G_x0"), term("x1", "G_x1")];
+    for j in 0..num_slots {
+        G_V_minus_I.push(term(name("y", j), name("G_y", j)));
+    }
+    add_equation(st, "G_V-I", &G_V_minus_I)
+}
+
+fn get_key_point_args(
+    system: &SystemParams,
+    C_W: RistrettoPoint,
+    I: RistrettoPoint,
+    t: Scalar,
+    U: RistrettoPoint,
+) -> poksho::PointArgs {
+    let mut point_args = poksho::PointArgs::new();
+    point_args.add("C_W", C_W);
+    point_args.add("G_w", system.G_w);
+    point_args.add("G_wprime", system.G_wprime);
+    point_args.add("G_V-I", system.G_V - I);
+    point_args.add("G_x0", system.G_x0);
+    point_args.add("G_x1", system.G_x1);
+    for (j, G_yj) in system.G_y.iter().enumerate() {
+        point_args.add(&name("G_y", j), *G_yj);
+    }
+    point_args.add("U", U);
+    point_args.add("tU", t * U);
+    point_args
+}
+
+impl<C: CredentialType> KeyPair<C> {
+    pub fn generate(sho: &mut Sho) -> Self {
+        let system = SystemParams::get::<C>();
+        let w = sho.get_scalar();
+        let W = w * system.G_w;
+        let wprime = sho.get_scalar();
+        let x0 = sho.get_scalar();
+        let x1 = sho.get_scalar();
+        let y: Vec<Scalar> = system.G_y.iter().map(|_| sho.get_scalar()).collect();
+
+        let C_W = (w * system.G_w) + (wprime * system.G_wprime);
+        let mut I = system.G_V - (x0 * system.G_x0) - (x1 * system.G_x1);
+        for (yj, G_yj) in y.iter().zip(&system.G_y) {
+            I -= yj * G_yj;
+        }
+
+        Self {
+            w,
+            wprime,
+            W,
+            x0,
+            x1,
+            y,
+            C_W,
+            I,
+            credential_type: PhantomData,
+        }
+    }
+
+    pub fn get_public_key(&self) -> PublicKey<C> {
+        PublicKey {
+            C_W: self.C_W,
+            I: self.I,
+            credential_type: PhantomData,
+        }
+    }
+
+    /// The MAC `W + (x0 + x1 * t) * U + sum(y_j * M_j)` over the given attributes.
+    fn mac<'a>(
+        &self,
+        system: &SystemParams,
+        t: Scalar,
+        U: RistrettoPoint,
+        attributes: impl Iterator<Item = (usize, &'a AttributeValue)>,
+    ) -> RistrettoPoint {
+        let mut V = self.W + (self.x0 + self.x1 * t) * U;
+        for (i, value) in attributes {
+            for (j, Mj) in system.points(i, value) {
+                V += self.y[j] * Mj;
+            }
+        }
+        V
+    }
+
+    fn key_scalar_args(&self) -> poksho::ScalarArgs {
+        let mut scalar_args = poksho::ScalarArgs::new();
+        scalar_args.add("w", self.w);
+        scalar_args.add("wprime", self.wprime);
+        scalar_args.add("x0", self.x0);
+        scalar_args.add("x1", self.x1);
+        for (j, yj) in self.y.iter().enumerate() {
+            scalar_args.add(&name("y", j), *yj);
+        }
+        scalar_args
+    }
+
+    /// Issues a credential for `request`.
+    ///
+    /// `attributes` holds the values of the attributes that aren't blinded, in order; the
+    /// blinded ones come from the request.
+    pub fn issue(
+        &self,
+        request: &IssuanceRequest<C>,
+        attributes: &[AttributeValue],
+        sho: &mut Sho,
+    ) -> Result<IssuanceResponse<C>, ZkGroupError> {
+        request.verify()?;
+        let known: Vec<usize> = indices::<C>(is_known_to_issuer).collect();
+        check_values(known.iter().copied(), C::ATTRIBUTES, attributes)?;
+
+        let system = SystemParams::get::<C>();
+        let t = sho.get_scalar();
+        let U = sho.get_point();
+        let Vprime = self.mac(system, t, U, known.iter().copied().zip(attributes));
+
+        let rprime = sho.get_scalar();
+        let mut S1 = rprime * RISTRETTO_BASEPOINT_POINT;
+        let mut S2 = rprime * request.Y + Vprime;
+        for (i, (D1, D2)) in indices::<C>(is_blinded).zip(&request.ciphertexts) {
+            let yi = self.y[system.slots[i]];
+            S1 += yi * D1;
+            S2 += yi * D2;
+        }
+
+        let mut scalar_args = self.key_scalar_args();
+        scalar_args.add("rprime", rprime);
+
+        let mut point_args =
+            IssuanceResponse::<C>::get_point_args(system, self.C_W, self.I, request, t, U, S1, S2);
+        let M_terms = system.mac_terms(known.iter().copied().zip(attributes), &mut point_args);
+
+        let poksho_proof = IssuanceResponse::<C>::get_poksho_statement(&M_terms)
+            .and_then(|st| {
+                st.prove(
+                    &scalar_args,
+                    &point_args,
+                    &[],
+                    &sho.squeeze(RANDOMNESS_LEN)[..],
+                )
+            })
+            .map_err(|_| ZkGroupError::BadArgs)?;
+
+        Ok(IssuanceResponse {
+            t,
+            U,
+            S1,
+            S2,
+            poksho_proof,
+            credential_type: PhantomData,
+        })
+    }
+
+    /// Issues a credential with the values of every attribute, in order, for a credential type
+    /// without blinded attributes.
+    pub fn issue_unblinded(
+        &self,
+        attributes: &[AttributeValue],
+        sho: &mut Sho,
+    ) -> Result<UnblindedIssuanceResponse<C>, ZkGroupError> {
+        if indices::<C>(is_blinded).next().is_some() {
+            return Err(ZkGroupError::BadArgs);
+        }
+        check_values(0..C::ATTRIBUTES.len(), C::ATTRIBUTES, attributes)?;
+
+        let system = SystemParams::get::<C>();
+        let t = sho.get_scalar();
+        let U = sho.get_point();
+        let V = self.mac(system, t, U, attributes.iter().enumerate());
+
+        let mut point_args =
+            UnblindedIssuanceResponse::<C>::get_point_args(system, self.C_W, self.I, t, U, V);
+        let M_terms = system.mac_terms(attributes.iter().enumerate(), &mut point_args);
+
+        let poksho_proof = UnblindedIssuanceResponse::<C>::get_poksho_statement(&M_terms)
+            .and_then(|st| {
+                st.prove(
+                    &self.key_scalar_args(),
+                    &point_args,
+                    &[],
+                    &sho.squeeze(RANDOMNESS_LEN)[..],
+                )
+            })
+            .map_err(|_| ZkGroupError::BadArgs)?;
+
+        Ok(UnblindedIssuanceResponse {
+            t,
+            U,
+            V,
+            poksho_proof,
+            credential_type: PhantomData,
+        })
+    }
+
+    /// Checks that `presentation` shows a credential issued with this key pair.
+    ///
+    /// `attributes` holds, in order, the value of each attribute the presentation discloses and
+    /// `None` for the rest. Revealed attributes must always have a value and encrypted ones never
+    /// do; the presentation's [ciphertexts](Presentation::get_ciphertexts) must be under
+    /// `uid_enc_public_key`. The proof fails if the presentation discloses a different set of
+    /// attributes, or other values for them.
+    pub fn verify(
+        &self,
+        presentation: &Presentation<C>,
+        attributes: &[Option<Scalar>],
+        uid_enc_public_key: Option<uid_encryption::PublicKey>,
+        message: &[u8],
+    ) -> Result<(), ZkGroupError> {
+        let has_encrypted = indices::<C>(is_encrypted).next().is_some();
+        if attributes.len() != C::ATTRIBUTES.len()
+            || C::ATTRIBUTES.iter().zip(attributes).any(|(visibility, m)| {
+                (*visibility == Visibility::Revealed && m.is_none())
+                    || (is_encrypted(*visibility) && m.is_some())
+            })
+            || has_encrypted != uid_enc_public_key.is_some()
+        {
+            return Err(ZkGroupError::BadArgs);
+        }
+
+        let system = SystemParams::get::<C>();
+        let mut Z =
+            presentation.C_V - self.W - self.x0 * presentation.C_x0 - self.x1 * presentation.C_x1;
+        for (yj, C_yj) in self.y.iter().zip(&presentation.C_y) {
+            Z -= yj * C_yj;
+        }
+
+        let disclosed: Vec<bool> = attributes.iter().map(Option::is_some).collect();
+        let mut point_args = presentation.get_point_args(system, Z, self.I);
+        for (i, m) in attributes.iter().enumerate() {
+            if let Some(m) = m {
+                point_args.add(&name("M", system.slots[i]), system.M(i, *m));
+            }
+        }
+        if let Some(public_key) = uid_enc_public_key {
+            Presentation::<C>::add_uid_enc_point_args(&mut point_args, public_key);
+        }
+
+        Presentation::<C>::get_poksho_statement(&disclosed)
+            .and_then(|st| st.verify_proof(&presentation.poksho_proof, &point_args, message))
+            .map_err(|_| proof_failure::<Presentation<C>>())
+    }
+}
+
+impl<C: CredentialType> RequestContext<C> {
+    /// Starts a request for a credential with the given values of the blinded attributes, in
+    /// order.
+    pub fn new(blinded_attributes: &[Scalar], sho: &mut Sho) -> Result<Self, ZkGroupError> {
+        if blinded_attributes.len() != indices::<C>(is_blinded).count() {
+            return Err(ZkGroupError::BadArgs);
+        }
+        let system = SystemParams::get::<C>();
+
+        let y = sho.get_scalar();
+        let Y = y * RISTRETTO_BASEPOINT_POINT;
+
+        let mut scalar_args = poksho::ScalarArgs::new();
+        scalar_args.add("y", y);
+        let mut ciphertexts = Vec::new();
+        for (i, m) in indices::<C>(is_blinded).zip(blinded_attributes) {
+            let r = sho.get_scalar();
+            let D1 = r * RISTRETTO_BASEPOINT_POINT;
+            let D2 = r * Y + system.M(i, *m);
+            ciphertexts.push((D1, D2));
+            scalar_args.add(&name("r", i), r);
+            scalar_args.add(&name("m", i), *m);
+        }
+
+        let mut request = IssuanceRequest {
+            Y,
+            ciphertexts,
+            poksho_proof: Vec::new(),
+            credential_type: PhantomData,
+        };
+        request.poksho_proof = IssuanceRequest::<C>::get_poksho_statement()
+            .and_then(|st| {
+                st.prove(
+                    &scalar_args,
+                    &request.get_point_args(system),
+                    &[],
+                    &sho.squeeze(RANDOMNESS_LEN)[..],
+                )
+            })
+            .map_err(|_| ZkGroupError::BadArgs)?;
+
+        Ok(Self {
+            blinded_attributes: blinded_attributes.to_vec(),
+            y,
+            request,
+        })
+    }
+
+    pub fn get_request(&self) -> IssuanceRequest<C> {
+        self.request.clone()
+    }
+
+    /// Checks the issuer's proof that `response` is a valid credential for this request and for
+    /// `attributes`, the values of the attributes that aren't blinded, in order.
+    pub fn receive(
+        &self,
+        public_key: &PublicKey<C>,
+        response: &IssuanceResponse<C>,
+        attributes: &[AttributeValue],
+    ) -> Result<Credential<C>, ZkGroupError> {
+        let known: Vec<usize> = indices::<C>(is_known_to_issuer).collect();
+        check_values(known.iter().copied(), C::ATTRIBUTES, attributes)?;
+
+        let system = SystemParams::get::<C>();
+        let mut point_args = IssuanceResponse::<C>::get_point_args(
+            system,
+            public_key.C_W,
+            public_key.I,
+            &self.request,
+            response.t,
+            response.U,
+            response.S1,
+            response.S2,
+        );
+        let M_terms = system.mac_terms(known.iter().copied().zip(attributes), &mut point_args);
+
+        IssuanceResponse::<C>::get_poksho_statement(&M_terms)
+            .and_then(|st| st.verify_proof(&response.poksho_proof, &point_args, &[]))
+            .map_err(|_| proof_failure::<IssuanceResponse<C>>())?;
+
+        let mut known_values = attributes.iter();
+        let mut blinded_values = self.blinded_attributes.iter();
+        let all_attributes = C::ATTRIBUTES
+            .iter()
+            .map(|visibility| {
+                if is_blinded(*visibility) {
+                    AttributeValue::Scalar(*blinded_values.next().expect("checked in new"))
+                } else {
+                    *known_values.next().expect("checked above")
+                }
+            })
+            .collect();
+
+        Ok(Credential {
+            t: response.t,
+            U: response.U,
+            V: response.S2 - self.y * response.S1,
+            attributes: all_attributes,
+            credential_type: PhantomData,
+        })
+    }
+}
+
+impl<C: CredentialType> IssuanceRequest<C> {
+    pub fn get_poksho_statement() -> Result<poksho::Statement, PokshoError> {
+        let mut st = poksho::Statement::new();
+        st.try_add("Y", &[("y", "G")])?;
+        for i in indices::<C>(is_blinded) {
+            add_equation(&mut st, &name("D1_", i), &[term(name("r", i), "G")])?;
+            add_equation(
+                &mut st,
+                &name("D2_", i),
+                &[term(name("r", i), "Y"), term(name("m", i), name("G_m", i))],
+            )?;
+        }
+        Ok(st)
+    }
+
+    fn get_point_args(&self, system: &SystemParams) -> poksho::PointArgs {
+        let mut point_args = poksho::PointArgs::new();
+        point_args.add("Y", self.Y);
+        for (i, (D1, D2)) in indices::<C>(is_blinded).zip(&self.ciphertexts) {
+            point_args.add(&name("D1_", i), *D1);
+            point_args.add(&name("D2_", i), *D2);
+            point_args.add(&name("G_m", i), system.G_m[i]);
+        }
+        point_args
+    }
+
+    fn verify(&self) -> Result<(), ZkGroupError> {
+        let failure = proof_failure::<Self>();
+        if self.ciphertexts.len() != indices::<C>(is_blinded).count() {
+            return Err(failure);
+        }
+        let system = SystemParams::get::<C>();
+        Self::get_poksho_statement()
+            .and_then(|st| st.verify_proof(&self.poksho_proof, &self.get_point_args(system), &[]))
+            .map_err(|_| failure)
+    }
+}
+
+impl<C: CredentialType> IssuanceResponse<C> {
+    /// `M_terms` holds `y_j * M_j` for the slots of the attributes the issuer knows.
+    pub fn get_poksho_statement(
+        M_terms: &[(String, String)],
+    ) -> Result<poksho::Statement, PokshoError> {
+        let mut st = poksho::Statement::new();
+        add_key_equations(&mut st, SystemParams::get::<C>().G_y.len())?;
+
+        let system = SystemParams::get::<C>();
+        let mut S1 = vec![];
+        let mut S2 = vec![];
+        for i in indices::<C>(is_blinded) {
+            S1.push(term(name("y", system.slots[i]), name("D1_", i)));
+            S2.push(term(name("y", system.slots[i]), name("D2_", i)));
+        }
+        S1.push(term("rprime", "G"));
+        S2.extend([
+            term("rprime", "Y"),
+            term("w", "G_w"),
+            term("x0", "U"),
+            term("x1", "tU"),
+        ]);
+        S2.extend_from_slice(M_terms);
+        add_equation(&mut st, "S1", &S1)?;
+        add_equation(&mut st, "S2", &S2)?;
+        Ok(st)
+    }
+
+    /// Everything but the attribute points `M_j`, which only one side knows how to compute.
+    #[allow(clippy::too_many_arguments)]
+    fn get_point_args(
+        system: &SystemParams,
+        C_W: RistrettoPoint,
+        I: RistrettoPoint,
+        request: &IssuanceRequest<C>,
+        t: Scalar,
+        U: RistrettoPoint,
+        S1: RistrettoPoint,
+        S2: RistrettoPoint,
+    ) -> poksho::PointArgs {
+        let mut point_args = get_key_point_args(system, C_W, I, t, U);
+        point_args.add("S1", S1);
+        point_args.add("S2", S2);
+        point_args.add("Y", request.Y);
+        for (i, (D1, D2)) in indices::<C>(is_blinded).zip(&request.ciphertexts) {
+            point_args.add(&name("D1_", i), *D1);
+            point_args.add(&name("D2_", i), *D2);
+        }
+        point_args
+    }
+}
+
+impl<C: CredentialType> UnblindedIssuanceResponse<C> {
+    /// `M_terms` holds `y_j * M_j` for every slot.
+    pub fn get_poksho_statement(
+        M_terms: &[(String, String)],
+    ) -> Result<poksho::Statement, PokshoError> {
+        let mut st = poksho::Statement::new();
+        add_key_equations(&mut st, SystemParams::get::<C>().G_y.len())?;
+        let mut V = vec![term("w", "G_w"), term("x0", "U"), term("x1", "tU")];
+        V.extend_from_slice(M_terms);
+        add_equation(&mut st, "V", &V)?;
+        Ok(st)
+    }
+
+    fn get_point_args(
+        system: &SystemParams,
+        C_W: RistrettoPoint,
+        I: RistrettoPoint,
+        t: Scalar,
+        U: RistrettoPoint,
+        V: RistrettoPoint,
+    ) -> poksho::PointArgs {
+        let mut point_args = get_key_point_args(system, C_W, I, t, U);
+        point_args.add("V", V);
+        point_args
+    }
+
+    /// Checks the issuer's proof that this is a valid credential for `attributes`, the values of
+    /// every attribute, in order.
+    pub fn receive(
+        &self,
+        public_key: &PublicKey<C>,
+        attributes: &[AttributeValue],
+    ) -> Result<Credential<C>, ZkGroupError> {
+        check_values(0..C::ATTRIBUTES.len(), C::ATTRIBUTES, attributes)?;
+
+        let system = SystemParams::get::<C>();
+        let mut point_args =
+            Self::get_point_args(system, public_key.C_W, public_key.I, self.t, self.U, self.V);
+        let M_terms = system.mac_terms(attributes.iter().enumerate(), &mut point_args);
+
+        Self::get_poksho_statement(&M_terms)
+            .and_then(|st| st.verify_proof(&self.poksho_proof, &point_args, &[]))
+            .map_err(|_| proof_failure::<Self>())?;
+
+        Ok(Credential {
+            t: self.t,
+            U: self.U,
+            V: self.V,
+            attributes: attributes.to_vec(),
+            credential_type: PhantomData,
+        })
+    }
+}
+
+impl<C: CredentialType> Credential<C> {
+    /// The value of every attribute, in order.
+    pub fn get_attributes(&self) -> &[AttributeValue] {
+        &self.attributes
+    }
+
+    /// Creates a presentation bound to `message`, which the verifier must pass to
+    /// [`KeyPair::verify`] as well.
+    ///
+    /// The presentation discloses the revealed attributes along with the hidden or blinded
+    /// attributes listed in `disclosed`, by index. It encrypts the encrypted attributes under
+    /// `uid_enc_key_pair`, which is required if there are any.
+    pub fn present(
+        &self,
+        public_key: &PublicKey<C>,
+        disclosed: &[usize],
+        uid_enc_key_pair: Option<uid_encryption::KeyPair>,
+        message: &[u8],
+        sho: &mut Sho,
+    ) -> Result<Presentation<C>, ZkGroupError> {
+        check_values(0..C::ATTRIBUTES.len(), C::ATTRIBUTES, &self.attributes)?;
+        let mut is_disclosed: Vec<bool> = C::ATTRIBUTES
+            .iter()
+            .map(|visibility| *visibility == Visibility::Revealed)
+            .collect();
+        for &i in disclosed {
+            match C::ATTRIBUTES.get(i) {
+                Some(Visibility::Hidden) | Some(Visibility::Blinded) => is_disclosed[i] = true,
+                _ => return Err(ZkGroupError::BadArgs),
+            }
+        }
+        let uid_enc_key_pair = match (indices::<C>(is_encrypted).next(), uid_enc_key_pair) {
+            (Some(_), None) | (None, Some(_)) => return Err(ZkGroupError::BadArgs),
+            (_, key_pair) => key_pair,
+        };
+
+        let system = SystemParams::get::<C>();
+        let z = sho.get_scalar();
+        let mut C_y: Vec<RistrettoPoint> = system.G_y.iter().map(|G_yj| z * G_yj).collect();
+        for (i, value) in self.attributes.iter().enumerate() {
+            for (j, Mj) in system.points(i, value) {
+                C_y[j] += Mj;
+            }
+        }
+
+        let mut scalar_args = poksho::ScalarArgs::new();
+        scalar_args.add("z", z);
+        scalar_args.add("t", self.t);
+        scalar_args.add("-zt", -z * self.t);
+
+        let mut ciphertexts = Vec::new();
+        for (i, value) in self.attributes.iter().enumerate() {
+            match (value, uid_enc_key_pair) {
+                (AttributeValue::Scalar(m), _) => scalar_args.add(&name("m", i), *m),
+                (AttributeValue::Points(M1, M2), Some(key_pair)) => {
+                    let E_A1 = key_pair.a1 * M1;
+                    let E_A2 = key_pair.a2 * E_A1 + M2;
+                    ciphertexts.push(uid_encryption::Ciphertext { E_A1, E_A2 });
+                }
+                (AttributeValue::Points(..), None) => unreachable!("checked above"),
+            }
+        }
+        if let Some(key_pair) = uid_enc_key_pair {
+            scalar_args.add("a1", key_pair.a1);
+            scalar_args.add("a2", key_pair.a2);
+            scalar_args.add("-za1", -z * key_pair.a1);
+        }
+
+        let mut presentation = Presentation {
+            C_x0: z * system.G_x0 + self.U,
+            C_x1: z * system.G_x1 + self.t * self.U,
+            C_y,
+            C_V: z * system.G_V + self.V,
+            ciphertexts,
+            poksho_proof: Vec::new(),
+            credential_type: PhantomData,
+        };
+
+        let mut point_args = presentation.get_point_args(system, z * public_key.I, public_key.I);
+        for (i, value) in self.attributes.iter().enumerate() {
+            if let (true, AttributeValue::Scalar(m)) = (is_disclosed[i], value) {
+                point_args.add(&name("M", system.slots[i]), system.M(i, *m));
+            }
+        }
+        if let Some(key_pair) = uid_enc_key_pair {
+            Presentation::<C>::add_uid_enc_point_args(&mut point_args, key_pair.get_public_key());
+        }
+
+        presentation.poksho_proof = Presentation::<C>::get_poksho_statement(&is_disclosed)
+            .and_then(|st| {
+                st.prove(
+                    &scalar_args,
+                    &point_args,
+                    message,
+                    &sho.squeeze(RANDOMNESS_LEN)[..],
+                )
+            })
+            .map_err(|_| ZkGroupError::BadArgs)?;
+        Ok(presentation)
+    }
+}
+
+impl<C: CredentialType> Presentation<C> {
+    /// `disclosed` says, for each attribute in order, whether the presentation discloses it.
+    pub fn get_poksho_statement(disclosed: &[bool]) -> Result<poksho::Statement, PokshoError> {
+        let system = SystemParams::get::<C>();
+        let mut st = poksho::Statement::new();
+        st.try_add("Z", &[("z", "I")])?;
+        st.try_add("C_x1", &[("t", "C_x0"), ("-zt", "G_x0"), ("z", "G_x1")])?;
+        if indices::<C>(is_encrypted).next().is_some() {
+            st.try_add("A", &[("a1", "G_a1"), ("a2", "G_a2")])?;
+        }
+        for (i, visibility) in C::ATTRIBUTES.iter().enumerate() {
+            let j = system.slots[i];
+            if is_encrypted(*visibility) {
+                // E_A1 = a1 * M1 and E_A2 = a2 * E_A1 + M2, where C_y_j = z * G_y_j + M1 and
+                // C_y_j+1 = z * G_y_j+1 + M2.
+                add_equation(
+                    &mut st,
+                    &name("E_A1_", i),
+                    &[term("a1", name("C_y", j)), term("-za1", name("G_y", j))],
+                )?;
+                add_equation(
+                    &mut st,
+                    &format!("{}-{}", name("C_y", j + 1), name("E_A2_", i)),
+                    &[
+                        term("z", name("G_y", j + 1)),
+                        term("a2", format!("-{}", name("E_A1_", i))),
+                    ],
+                )?;
+            } else {
+                add_equation(
+                    &mut st,
+                    &name("C_y", j),
+                    &[
+                        term("z", name("G_y", j)),
+                        term(name("m", i), name("G_m", i)),
+                    ],
+                )?;
+                if disclosed[i] {
+                    add_equation(
+                        &mut st,
+                        &name("M", j),
+                        &[term(name("m", i), name("G_m", i))],
+                    )?;
+                }
+            }
+        }
+        Ok(st)
+    }
+
+    /// The encryption of each encrypted attribute, in order.
+    pub fn get_ciphertexts(&self) -> &[uid_encryption::Ciphertext] {
+        &self.ciphertexts
+    }
+
+    /// Everything but the points of the disclosed attributes and of the group's key.
+    fn get_point_args(
+        &self,
+        system: &SystemParams,
+        Z: RistrettoPoint,
+        I: RistrettoPoint,
+    ) -> poksho::PointArgs {
+        let mut point_args = poksho::PointArgs::new();
+        point_args.add("Z", Z);
+        point_args.add("I", I);
+        point_args.add("C_x0", self.C_x0);
+        point_args.add("C_x1", self.C_x1);
+        point_args.add("G_x0", system.G_x0);
+        point_args.add("G_x1", system.G_x1);
+        for (j, G_yj) in system.G_y.iter().enumerate() {
+            point_args.add(&name("G_y", j), *G_yj);
+        }
+        for i in indices::<C>(|visibility| !is_encrypted(visibility)) {
+            let j = system.slots[i];
+            point_args.add(&name("C_y", j), self.C_y[j]);
+            point_args.add(&name("G_m", i), system.G_m[i]);
+        }
+        for (i, ciphertext) in indices::<C>(is_encrypted).zip(&self.ciphertexts) {
+            let j = system.slots[i];
+            point_args.add(&name("C_y", j), self.C_y[j]);
+            point_args.add(&name("E_A1_", i), ciphertext.E_A1);
+            point_args.add(&format!("-{}", name("E_A1_", i)), -ciphertext.E_A1);
+            point_args.add(
+                &format!("{}-{}", name("C_y", j + 1), name("E_A2_", i)),
+                self.C_y[j + 1] - ciphertext.E_A2,
+            );
+        }
+        point_args
+    }
+
+    fn add_uid_enc_point_args(
+        point_args: &mut poksho::PointArgs,
+        public_key: uid_encryption::PublicKey,
+    ) {
+        let uid_system = uid_encryption::SystemParams::get_hardcoded();
+        point_args.add("A", public_key.A);
+        point_args.add("G_a1", uid_system.G_a1);
+        point_args.add("G_a2", uid_system.G_a2);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::uid_encryption::Attribute;
+    use crate::crypto::uid_struct::UidStruct;
+
+    #[derive(Clone)]
+    enum TestCredential {}
+
+    impl CredentialType for TestCredential {
+        const LABEL: &'static [u8] = b"Signal_ZKGroup_20211201_Test_Kvac_TestCredential";
+        const ATTRIBUTES: &'static [Visibility] = &[
+            Visibility::Revealed,
+            Visibility::Hidden,
+            Visibility::Blinded,
+            Visibility::Blinded,
+            Visibility::Encrypted,
+        ];
+    }
+
+    #[derive(Clone)]
+    enum UnblindedTestCredential {}
+
+    impl CredentialType for UnblindedTestCredential {
+        const LABEL: &'static [u8] = b"Signal_ZKGroup_20211201_Test_Kvac_UnblindedTestCredential";
+        const ATTRIBUTES: &'static [Visibility] = &[
+            Visibility::Encrypted,
+            Visibility::Hidden,
+            Visibility::Revealed,
+        ];
+    }
+
+    fn uid_value(uid: UidStruct) -> AttributeValue {
+        AttributeValue::Points(uid.M1(), uid.M2())
+    }
+
+    fn issue(
+        key_pair: &KeyPair<TestCredential>,
+        known: &[AttributeValue],
+        blinded: &[Scalar],
+        sho: &mut Sho,
+    ) -> Result<Credential<TestCredential>, ZkGroupError> {
+        let context = RequestContext::<TestCredential>::new(blinded, sho)?;
+        let response = key_pair.issue(&context.get_request(), known, sho)?;
+        context.receive(&key_pair.get_public_key(), &response, known)
+    }
+
+    #[test]
+    fn test_system_params_cached() {
+        let system = SystemParams::get::<TestCredential>();
+        assert!(std::ptr::eq(system, SystemParams::get::<TestCredential>()));
+        assert!(system.G_m == SystemParams::generate::<TestCredential>().G_m);
+        assert_eq!(system.G_y.len(), 6);
+        assert_eq!(system.slots, [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut sho = Sho::new(b"Test_Kvac_RoundTrip", b"");
+        let key_pair = KeyPair::<TestCredential>::generate(&mut sho);
+        let uid_enc_key_pair = uid_encryption::KeyPair::derive_from(&mut sho);
+        let uid = UidStruct::new(TEST_ARRAY_16);
+        let m: Vec<Scalar> = (0..4).map(|_| sho.get_scalar()).collect();
+        let known = [
+            AttributeValue::Scalar(m[0]),
+            AttributeValue::Scalar(m[1]),
+            uid_value(uid),
+        ];
+
+        let credential = issue(&key_pair, &known, &m[2..], &mut sho).unwrap();
+        assert_eq!(
+            credential.get_attributes(),
+            &[
+                known[0],
+                known[1],
+                AttributeValue::Scalar(m[2]),
+                AttributeValue::Scalar(m[3]),
+                known[2],
+            ]
+        );
+
+        let public_key = key_pair.get_public_key();
+        let uid_enc_public_key = Some(uid_enc_key_pair.get_public_key());
+        let presentation = credential
+            .present(
+                &public_key,
+                &[3],
+                Some(uid_enc_key_pair),
+                b"message",
+                &mut sho,
+            )
+            .unwrap();
+        assert!(uid_enc_key_pair
+            .decrypt::<UidStruct>(presentation.get_ciphertexts()[0])
+            .unwrap()
+            .to_bytes()
+            .eq(&TEST_ARRAY_16));
+        let disclosed = [Some(m[0]), None, None, Some(m[3]), None];
+        key_pair
+            .verify(&presentation, &disclosed, uid_enc_public_key, b"message")
+            .unwrap();
+
+        assert!(key_pair
+            .verify(
+                &presentation,
+                &disclosed,
+                uid_enc_public_key,
+                b"other message"
+            )
+            .is_err());
+        let other_key_pair = KeyPair::<TestCredential>::generate(&mut sho);
+        assert!(other_key_pair
+            .verify(&presentation, &disclosed, uid_enc_public_key, b"message")
+            .is_err());
+        let other_uid_enc_public_key =
+            uid_encryption::KeyPair::derive_from(&mut sho).get_public_key();
+        assert!(key_pair
+            .verify(
+                &presentation,
+                &disclosed,
+                Some(other_uid_enc_public_key),
+                b"message"
+            )
+            .is_err());
+
+        // The verifier must agree on which attributes are disclosed, and on their values.
+        for bad_disclosed in [
+            [Some(m[1]), None, None, Some(m[3]), None],
+            [Some(m[0]), None, None, Some(m[2]), None],
+            [Some(m[0]), None, None, None, None],
+            [Some(m[0]), Some(m[1]), None, Some(m[3]), None],
+        ] {
+            assert!(key_pair
+                .verify(
+                    &presentation,
+                    &bad_disclosed,
+                    uid_enc_public_key,
+                    b"message"
+                )
+                .is_err());
+        }
+        for bad_disclosed in [
+            &[None, None, None, Some(m[3]), None][..],
+            &[Some(m[0]), None, None, Some(m[3]), Some(m[0])],
+            &disclosed[..4],
+        ] {
+            assert_eq!(
+                key_pair.verify(&presentation, bad_disclosed, uid_enc_public_key, b"message"),
+                Err(ZkGroupError::BadArgs)
+            );
+        }
+
+        let mut bad_presentation = presentation.clone();
+        bad_presentation.C_y[1] += RISTRETTO_BASEPOINT_POINT;
+        assert!(key_pair
+            .verify(
+                &bad_presentation,
+                &disclosed,
+                uid_enc_public_key,
+                b"message"
+            )
+            .is_err());
+
+        let mut bad_presentation = presentation.clone();
+        bad_presentation.ciphertexts[0] = uid_enc_key_pair.encrypt(UidStruct::new(TEST_ARRAY_16_1));
+        assert!(key_pair
+            .verify(
+                &bad_presentation,
+                &disclosed,
+                uid_enc_public_key,
+                b"message"
+            )
+            .is_err());
+
+        // A presentation with the wrong number of ciphertexts doesn't deserialize.
+        let mut bad_presentation = presentation;
+        bad_presentation.ciphertexts.clear();
+        bad_presentation.poksho_proof.extend([0; 64]);
+        let bytes = bincode::serialize(&bad_presentation).unwrap();
+        assert!(bincode::deserialize::<Presentation<TestCredential>>(&bytes).is_err());
+
+        // Revealed and encrypted attributes can't be disclosed on request.
+        for bad_disclosed in [0, 4, 5] {
+            assert_eq!(
+                credential
+                    .present(
+                        &public_key,
+                        &[bad_disclosed],
+                        Some(uid_enc_key_pair),
+                        b"message",
+                        &mut sho
+                    )
+                    .err(),
+                Some(ZkGroupError::BadArgs)
+            );
+        }
+        assert_eq!(
+            credential
+                .present(&public_key, &[], None, b"message", &mut sho)
+                .err(),
+            Some(ZkGroupError::BadArgs)
+        );
+    }
+
+    #[test]
+    fn test_unblinded_round_trip() {
+        let mut sho = Sho::new(b"Test_Kvac_UnblindedRoundTrip", b"");
+        let key_pair = KeyPair::<UnblindedTestCredential>::generate(&mut sho);
+        let public_key = key_pair.get_public_key();
+        let uid_enc_key_pair = uid_encryption::KeyPair::derive_from(&mut sho);
+        let attributes = [
+            uid_value(UidStruct::new(TEST_ARRAY_16)),
+            AttributeValue::Scalar(sho.get_scalar()),
+            AttributeValue::Scalar(sho.get_scalar()),
+        ];
+
+        assert_eq!(
+            key_pair.issue_unblinded(&attributes[1..], &mut sho).err(),
+            Some(ZkGroupError::BadArgs)
+        );
+        assert_eq!(
+            KeyPair::<TestCredential>::generate(&mut sho)
+                .issue_unblinded(&[], &mut sho)
+                .err(),
+            Some(ZkGroupError::BadArgs)
+        );
+
+        let response = key_pair.issue_unblinded(&attributes, &mut sho).unwrap();
+        let other_attributes = [
+            uid_value(UidStruct::new(TEST_ARRAY_16_1)),
+            attributes[1],
+            attributes[2],
+        ];
+        assert!(matches!(
+            response.receive(&public_key, &other_attributes),
+            Err(ZkGroupError::ProofVerificationFailure {
+                proof: "UnblindedIssuanceResponse"
+            })
+        ));
+        let credential = response.receive(&public_key, &attributes).unwrap();
+
+        let presentation = credential
+            .present(&public_key, &[], Some(uid_enc_key_pair), b"", &mut sho)
+            .unwrap();
+        let revealed = match attributes[2] {
+            AttributeValue::Scalar(m) => m,
+            AttributeValue::Points(..) => unreachable!(),
+        };
+        key_pair
+            .verify(
+                &presentation,
+                &[None, None, Some(revealed)],
+                Some(uid_enc_key_pair.get_public_key()),
+                b"",
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_issuance_checks() {
+        let mut sho = Sho::new(b"Test_Kvac_IssuanceChecks", b"");
+        let key_pair = KeyPair::<TestCredential>::generate(&mut sho);
+        let m: Vec<Scalar> = (0..4).map(|_| sho.get_scalar()).collect();
+        let known = [
+            AttributeValue::Scalar(m[0]),
+            AttributeValue::Scalar(m[1]),
+            uid_value(UidStruct::new(TEST_ARRAY_16)),
+        ];
+
+        assert_eq!(
+            RequestContext::<TestCredential>::new(&m[..3], &mut sho).err(),
+            Some(ZkGroupError::BadArgs)
+        );
+
+        let context = RequestContext::<TestCredential>::new(&m[2..], &mut sho).unwrap();
+        let request = context.get_request();
+        assert_eq!(
+            key_pair.issue(&request, &known[..2], &mut sho).err(),
+            Some(ZkGroupError::BadArgs)
+        );
+        // An encrypted attribute needs points, not a scalar.
+        assert_eq!(
+            key_pair
+                .issue(&request, &[known[0], known[1], known[0]], &mut sho)
+                .err(),
+            Some(ZkGroupError::BadArgs)
+        );
+
+        // The issuer rejects a request whose ciphertexts were swapped.
+        let mut bad_request = request.clone();
+        bad_request.ciphertexts.swap(0, 1);
+        assert!(matches!(
+            key_pair.issue(&bad_request, &known, &mut sho),
+            Err(ZkGroupError::ProofVerificationFailure { .. })
+        ));
+
+        // The client rejects a credential with different known attributes than it expects.
+        let other_known = [known[1], known[0], known[2]];
+        let response = key_pair.issue(&request, &other_known, &mut sho).unwrap();
+        assert!(matches!(
+            context.receive(&key_pair.get_public_key(), &response, &known),
+            Err(ZkGroupError::ProofVerificationFailure {
+                proof: "IssuanceResponse"
+            })
+        ));
+    }
+}
@@ -0,0 +1,63 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+#![allow(non_snake_case)]
+
+use crate::common::sho::*;
+use crate::common::simple_types::*;
+use crate::crypto::uid_encryption::Attribute;
+use crate::crypto::uid_struct::PointDecodeFailure;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+/// A PNI, encoded like a [`UidStruct`](super::uid_struct::UidStruct) but with its own `M1` label,
+/// so a PNI and a UUID with the same bytes never share a ciphertext.
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PniStruct {
+    pub(crate) bytes: UidBytes,
+    pub(crate) M1: RistrettoPoint,
+    pub(crate) M2: RistrettoPoint,
+}
+
+impl PniStruct {
+    pub fn new(pni_bytes: UidBytes) -> Self {
+        let mut sho = Sho::new(b"Signal_ZKGroup_20211201_PNI_CalcM1", &pni_bytes);
+        let M1 = sho.get_point();
+        let M2 = RistrettoPoint::lizard_encode::<Sha256>(&pni_bytes);
+        PniStruct {
+            bytes: pni_bytes,
+            M1,
+            M2,
+        }
+    }
+
+    pub fn from_M2(M2: RistrettoPoint) -> Result<Self, PointDecodeFailure> {
+        match M2.lizard_decode::<Sha256>() {
+            None => Err(PointDecodeFailure),
+            Some(bytes) => Ok(Self::new(bytes)),
+        }
+    }
+
+    pub fn to_bytes(&self) -> UidBytes {
+        self.bytes
+    }
+}
+
+impl Attribute for PniStruct {
+    const KIND: &'static str = "PNI";
+
+    fn M1(&self) -> RistrettoPoint {
+        self.M1
+    }
+
+    fn M2(&self) -> RistrettoPoint {
+        self.M2
+    }
+
+    fn from_M2(M2: RistrettoPoint) -> Result<Self, PointDecodeFailure> {
+        Self::from_M2(M2)
+    }
+}
@@ -93,7 +93,7 @@ impl KeyPair {
         uid_bytes: UidBytes,
     ) -> Result<profile_key_struct::ProfileKeyStruct, ZkGroupError> {
         if ciphertext.E_B1 == RISTRETTO_BASEPOINT_POINT {
-            return Err(DecryptionFailure);
+            return Err(DecryptionFailure(DecryptionError::ProfileKeyCiphertext));
         }
         let M4 = ciphertext.E_B2 - (self.b2 * ciphertext.E_B1);
         let (mask, candidates) = M4.decode_253_bits();
@@ -126,7 +126,7 @@ impl KeyPair {
         if n_found == 1 {
             Ok(retval)
         } else {
-            Err(DecryptionFailure)
+            Err(DecryptionFailure(DecryptionError::ProfileKeyCiphertext))
         }
     }
 
@@ -6,15 +6,17 @@
 #![allow(non_snake_case)]
 
 use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::traits::MultiscalarMul;
+use poksho::PokshoError;
 use serde::{Deserialize, Serialize};
 
 use crate::common::array_utils::OneBased;
 use crate::common::constants::*;
-use crate::common::errors::ZkGroupError::*;
 use crate::common::errors::*;
 use crate::common::sho::*;
 use crate::common::simple_types::*;
 use crate::crypto::credentials;
+use crate::crypto::invite_link;
 use crate::crypto::profile_key_commitment;
 use crate::crypto::profile_key_credential_request;
 use crate::crypto::profile_key_encryption;
@@ -22,6 +24,7 @@ use crate::crypto::profile_key_struct;
 use crate::crypto::receipt_credential_request;
 use crate::crypto::receipt_struct::ReceiptStruct;
 use crate::crypto::uid_encryption;
+use crate::crypto::uid_encryption::Attribute;
 use crate::crypto::uid_struct;
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -49,6 +52,16 @@ pub struct ReceiptCredentialIssuanceProof {
     poksho_proof: Vec<u8>,
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct InviteLinkPresentationProof {
+    poksho_proof: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AttributeEncryptionProof {
+    poksho_proof: Vec<u8>,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct AuthCredentialPresentationProof {
     C_x0: RistrettoPoint,
@@ -98,11 +111,61 @@ pub struct ReceiptCredentialPresentationProof {
     poksho_proof: Vec<u8>,
 }
 
+/// How a presentation proof is encoded.
+///
+/// A [`Batchable`](Self::Batchable) proof is larger, but many of them can be verified together
+/// with [`poksho::Statement::verify_proofs_batch`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ProofFormat {
+    Compact,
+    Batchable,
+}
+
+impl ProofFormat {
+    fn prove(
+        self,
+        st: &poksho::Statement,
+        scalar_args: &poksho::ScalarArgs,
+        point_args: &poksho::PointArgs,
+        message: &[u8],
+        randomness: &[u8],
+    ) -> Result<Vec<u8>, PokshoError> {
+        match self {
+            Self::Compact => st.prove(scalar_args, point_args, message, randomness),
+            Self::Batchable => st.prove_batchable(scalar_args, point_args, message, randomness),
+        }
+    }
+}
+
+macro_rules! proof_names {
+    ($($typ:ident),* $(,)?) => {
+        $(
+            impl ProofName for $typ {
+                const NAME: &'static str = stringify!($typ);
+            }
+        )*
+    };
+}
+
+proof_names! {
+    AuthCredentialIssuanceProof,
+    ProfileKeyCredentialRequestProof,
+    ProfileKeyCredentialIssuanceProof,
+    PniCredentialIssuanceProof,
+    ReceiptCredentialIssuanceProof,
+    InviteLinkPresentationProof,
+    AttributeEncryptionProof,
+    AuthCredentialPresentationProof,
+    ProfileKeyCredentialPresentationProof,
+    PniCredentialPresentationProof,
+    ReceiptCredentialPresentationProof,
+}
+
 impl AuthCredentialIssuanceProof {
-    pub fn get_poksho_statement() -> poksho::Statement {
+    pub fn get_poksho_statement() -> Result<poksho::Statement, PokshoError> {
         let mut st = poksho::Statement::new();
-        st.add("C_W", &[("w", "G_w"), ("wprime", "G_wprime")]);
-        st.add(
+        st.try_add("C_W", &[("w", "G_w"), ("wprime", "G_wprime")])?;
+        st.try_add(
             "G_V-I",
             &[
                 ("x0", "G_x0"),
@@ -111,8 +174,8 @@ impl AuthCredentialIssuanceProof {
                 ("y2", "G_y2"),
                 ("y3", "G_y3"),
             ],
-        );
-        st.add(
+        )?;
+        st.try_add(
             "V",
             &[
                 ("w", "G_w"),
@@ -122,8 +185,8 @@ impl AuthCredentialIssuanceProof {
                 ("y2", "M2"),
                 ("y3", "M3"),
             ],
-        );
-        st
+        )?;
+        Ok(st)
     }
 
     pub fn new(
@@ -164,12 +227,14 @@ impl AuthCredentialIssuanceProof {
         point_args.add("M3", M[2]);
 
         let poksho_proof = Self::get_poksho_statement()
-            .prove(
-                &scalar_args,
-                &point_args,
-                &[],
-                &sho.squeeze(RANDOMNESS_LEN)[..],
-            )
+            .and_then(|st| {
+                st.prove(
+                    &scalar_args,
+                    &point_args,
+                    &[],
+                    &sho.squeeze(RANDOMNESS_LEN)[..],
+                )
+            })
             .unwrap();
         Self { poksho_proof }
     }
@@ -202,25 +267,27 @@ impl AuthCredentialIssuanceProof {
         point_args.add("M2", M[1]);
         point_args.add("M3", M[2]);
 
-        match Self::get_poksho_statement().verify_proof(&self.poksho_proof, &point_args, &[]) {
-            Err(_) => Err(ProofVerificationFailure),
-            Ok(_) => Ok(()),
-        }
+        Self::get_poksho_statement()
+            .and_then(|st| st.verify_proof(&self.poksho_proof, &point_args, &[]))
+            .map_err(|_| proof_failure::<Self>())
     }
 }
 
-impl ProfileKeyCredentialRequestProof {
-    pub fn get_poksho_statement() -> poksho::Statement {
-        let mut st = poksho::Statement::new();
-        st.add("Y", &[("y", "G")]);
-        st.add("D1", &[("r1", "G")]);
-        st.add("E1", &[("r2", "G")]);
-        st.add("J3", &[("j3", "G_j3")]);
-        st.add("D2-J1", &[("r1", "Y"), ("j3", "-G_j1")]);
-        st.add("E2-J2", &[("r2", "Y"), ("j3", "-G_j2")]);
-        st
+poksho::statement! {
+    static PROFILE_KEY_CREDENTIAL_REQUEST;
+    struct ProfileKeyCredentialRequestScalars { y, r1, r2, j3 }
+    struct ProfileKeyCredentialRequestPoints {
+        Y, D1, E1, J3, G_j3, D2_minus_J1, minus_G_j1, E2_minus_J2, minus_G_j2
     }
+    Y = y * G;
+    D1 = r1 * G;
+    E1 = r2 * G;
+    J3 = j3 * G_j3;
+    D2_minus_J1 = r1 * Y + j3 * minus_G_j1;
+    E2_minus_J2 = r2 * Y + j3 * minus_G_j2;
+}
 
+impl ProfileKeyCredentialRequestProof {
     pub fn new(
         key_pair: profile_key_credential_request::KeyPair,
         ciphertext: profile_key_credential_request::CiphertextWithSecretNonce,
@@ -229,30 +296,27 @@ impl ProfileKeyCredentialRequestProof {
     ) -> ProfileKeyCredentialRequestProof {
         let commitment_system = profile_key_commitment::SystemParams::get_hardcoded();
 
-        let mut scalar_args = poksho::ScalarArgs::new();
-        scalar_args.add("y", key_pair.y);
-        scalar_args.add("r1", ciphertext.r1);
-        scalar_args.add("r2", ciphertext.r2);
-        scalar_args.add("j3", commitment.j3);
-
-        let mut point_args = poksho::PointArgs::new();
-        point_args.add("Y", key_pair.Y);
-        point_args.add("D1", ciphertext.D1);
-        point_args.add("E1", ciphertext.E1);
-        point_args.add("J3", commitment.J3);
-        point_args.add("G_j3", commitment_system.G_j3);
-        point_args.add("D2-J1", ciphertext.D2 - commitment.J1);
-        point_args.add("-G_j1", -commitment_system.G_j1);
-        point_args.add("E2-J2", ciphertext.E2 - commitment.J2);
-        point_args.add("-G_j2", -commitment_system.G_j2);
-
-        let poksho_proof = Self::get_poksho_statement()
-            .prove(
-                &scalar_args,
-                &point_args,
-                &[],
-                &sho.squeeze(RANDOMNESS_LEN)[..],
-            )
+        let scalars = ProfileKeyCredentialRequestScalars {
+            y: key_pair.y,
+            r1: ciphertext.r1,
+            r2: ciphertext.r2,
+            j3: commitment.j3,
+        };
+
+        let points = ProfileKeyCredentialRequestPoints {
+            Y: key_pair.Y,
+            D1: ciphertext.D1,
+            E1: ciphertext.E1,
+            J3: commitment.J3,
+            G_j3: commitment_system.G_j3,
+            D2_minus_J1: ciphertext.D2 - commitment.J1,
+            minus_G_j1: -commitment_system.G_j1,
+            E2_minus_J2: ciphertext.E2 - commitment.J2,
+            minus_G_j2: -commitment_system.G_j2,
+        };
+
+        let poksho_proof = PROFILE_KEY_CREDENTIAL_REQUEST
+            .prove(&scalars, &points, &[], &sho.squeeze(RANDOMNESS_LEN)[..])
             .unwrap();
         ProfileKeyCredentialRequestProof { poksho_proof }
     }
@@ -265,29 +329,29 @@ impl ProfileKeyCredentialRequestProof {
     ) -> Result<(), ZkGroupError> {
         let commitment_system = profile_key_commitment::SystemParams::get_hardcoded();
 
-        let mut point_args = poksho::PointArgs::new();
-        point_args.add("Y", public_key.Y);
-        point_args.add("D1", ciphertext.D1);
-        point_args.add("E1", ciphertext.E1);
-        point_args.add("J3", commitment.J3);
-        point_args.add("G_j3", commitment_system.G_j3);
-        point_args.add("D2-J1", ciphertext.D2 - commitment.J1);
-        point_args.add("-G_j1", -commitment_system.G_j1);
-        point_args.add("E2-J2", ciphertext.E2 - commitment.J2);
-        point_args.add("-G_j2", -commitment_system.G_j2);
-
-        match Self::get_poksho_statement().verify_proof(&self.poksho_proof, &point_args, &[]) {
-            Err(_) => Err(ProofVerificationFailure),
-            Ok(_) => Ok(()),
-        }
+        let points = ProfileKeyCredentialRequestPoints {
+            Y: public_key.Y,
+            D1: ciphertext.D1,
+            E1: ciphertext.E1,
+            J3: commitment.J3,
+            G_j3: commitment_system.G_j3,
+            D2_minus_J1: ciphertext.D2 - commitment.J1,
+            minus_G_j1: -commitment_system.G_j1,
+            E2_minus_J2: ciphertext.E2 - commitment.J2,
+            minus_G_j2: -commitment_system.G_j2,
+        };
+
+        PROFILE_KEY_CREDENTIAL_REQUEST
+            .verify_proof(&self.poksho_proof, &points, &[])
+            .map_err(|_| proof_failure::<Self>())
     }
 }
 
 impl ProfileKeyCredentialIssuanceProof {
-    pub fn get_poksho_statement() -> poksho::Statement {
+    pub fn get_poksho_statement() -> Result<poksho::Statement, PokshoError> {
         let mut st = poksho::Statement::new();
-        st.add("C_W", &[("w", "G_w"), ("wprime", "G_wprime")]);
-        st.add(
+        st.try_add("C_W", &[("w", "G_w"), ("wprime", "G_wprime")])?;
+        st.try_add(
             "G_V-I",
             &[
                 ("x0", "G_x0"),
@@ -297,9 +361,9 @@ impl ProfileKeyCredentialIssuanceProof {
                 ("y3", "G_y3"),
                 ("y4", "G_y4"),
             ],
-        );
-        st.add("S1", &[("y3", "D1"), ("y4", "E1"), ("rprime", "G")]);
-        st.add(
+        )?;
+        st.try_add("S1", &[("y3", "D1"), ("y4", "E1"), ("rprime", "G")])?;
+        st.try_add(
             "S2",
             &[
                 ("y3", "D2"),
@@ -311,8 +375,8 @@ impl ProfileKeyCredentialIssuanceProof {
                 ("y1", "M1"),
                 ("y2", "M2"),
             ],
-        );
-        st
+        )?;
+        Ok(st)
     }
 
     pub fn new(
@@ -360,12 +424,14 @@ impl ProfileKeyCredentialIssuanceProof {
         point_args.add("M2", uid.M2);
 
         let poksho_proof = Self::get_poksho_statement()
-            .prove(
-                &scalar_args,
-                &point_args,
-                &[],
-                &sho.squeeze(RANDOMNESS_LEN)[..],
-            )
+            .and_then(|st| {
+                st.prove(
+                    &scalar_args,
+                    &point_args,
+                    &[],
+                    &sho.squeeze(RANDOMNESS_LEN)[..],
+                )
+            })
             .unwrap();
         ProfileKeyCredentialIssuanceProof { poksho_proof }
     }
@@ -404,18 +470,17 @@ impl ProfileKeyCredentialIssuanceProof {
         point_args.add("M1", uid.M1);
         point_args.add("M2", uid.M2);
 
-        match Self::get_poksho_statement().verify_proof(&self.poksho_proof, &point_args, &[]) {
-            Err(_) => Err(ProofVerificationFailure),
-            Ok(_) => Ok(()),
-        }
+        Self::get_poksho_statement()
+            .and_then(|st| st.verify_proof(&self.poksho_proof, &point_args, &[]))
+            .map_err(|_| proof_failure::<Self>())
     }
 }
 
 impl PniCredentialIssuanceProof {
-    pub fn get_poksho_statement() -> poksho::Statement {
+    pub fn get_poksho_statement() -> Result<poksho::Statement, PokshoError> {
         let mut st = poksho::Statement::new();
-        st.add("C_W", &[("w", "G_w"), ("wprime", "G_wprime")]);
-        st.add(
+        st.try_add("C_W", &[("w", "G_w"), ("wprime", "G_wprime")])?;
+        st.try_add(
             "G_V-I",
             &[
                 ("x0", "G_x0"),
@@ -427,9 +492,9 @@ impl PniCredentialIssuanceProof {
                 ("y5", "G_y5"),
                 ("y6", "G_y6"),
             ],
-        );
-        st.add("S1", &[("y3", "D1"), ("y4", "E1"), ("rprime", "G")]);
-        st.add(
+        )?;
+        st.try_add("S1", &[("y3", "D1"), ("y4", "E1"), ("rprime", "G")])?;
+        st.try_add(
             "S2",
             &[
                 ("y3", "D2"),
@@ -443,8 +508,8 @@ impl PniCredentialIssuanceProof {
                 ("y5", "M5"),
                 ("y6", "M6"),
             ],
-        );
-        st
+        )?;
+        Ok(st)
     }
 
     pub fn new(
@@ -499,12 +564,14 @@ impl PniCredentialIssuanceProof {
         point_args.add("M6", pni.M2);
 
         let poksho_proof = Self::get_poksho_statement()
-            .prove(
-                &scalar_args,
-                &point_args,
-                &[],
-                &sho.squeeze(RANDOMNESS_LEN)[..],
-            )
+            .and_then(|st| {
+                st.prove(
+                    &scalar_args,
+                    &point_args,
+                    &[],
+                    &sho.squeeze(RANDOMNESS_LEN)[..],
+                )
+            })
             .unwrap();
         Self { poksho_proof }
     }
@@ -549,19 +616,18 @@ impl PniCredentialIssuanceProof {
         point_args.add("M5", pni.M1);
         point_args.add("M6", pni.M2);
 
-        match Self::get_poksho_statement().verify_proof(&self.poksho_proof, &point_args, &[]) {
-            Err(_) => Err(ProofVerificationFailure),
-            Ok(_) => Ok(()),
-        }
+        Self::get_poksho_statement()
+            .and_then(|st| st.verify_proof(&self.poksho_proof, &point_args, &[]))
+            .map_err(|_| proof_failure::<Self>())
     }
 }
 
 impl ReceiptCredentialIssuanceProof {
-    pub fn get_poksho_statement() -> poksho::Statement {
+    pub fn get_poksho_statement() -> Result<poksho::Statement, PokshoError> {
         let mut st = poksho::Statement::new();
 
-        st.add("C_W", &[("w", "G_w"), ("wprime", "G_wprime")]);
-        st.add(
+        st.try_add("C_W", &[("w", "G_w"), ("wprime", "G_wprime")])?;
+        st.try_add(
             "G_V-I",
             &[
                 ("x0", "G_x0"),
@@ -569,9 +635,9 @@ impl ReceiptCredentialIssuanceProof {
                 ("y1", "G_y1"),
                 ("y2", "G_y2"),
             ],
-        );
-        st.add("S1", &[("y2", "D1"), ("rprime", "G")]);
-        st.add(
+        )?;
+        st.try_add("S1", &[("y2", "D1"), ("rprime", "G")])?;
+        st.try_add(
             "S2",
             &[
                 ("y2", "D2"),
@@ -581,8 +647,8 @@ impl ReceiptCredentialIssuanceProof {
                 ("x1", "tU"),
                 ("y1", "M1"),
             ],
-        );
-        st
+        )?;
+        Ok(st)
     }
 
     pub fn new(
@@ -626,12 +692,14 @@ impl ReceiptCredentialIssuanceProof {
         point_args.add("M1", m1 * credentials_system.G_m1);
 
         let poksho_proof = Self::get_poksho_statement()
-            .prove(
-                &scalar_args,
-                &point_args,
-                &[],
-                &sho.squeeze(RANDOMNESS_LEN)[..],
-            )
+            .and_then(|st| {
+                st.prove(
+                    &scalar_args,
+                    &point_args,
+                    &[],
+                    &sho.squeeze(RANDOMNESS_LEN)[..],
+                )
+            })
             .unwrap();
         Self { poksho_proof }
     }
@@ -666,26 +734,26 @@ impl ReceiptCredentialIssuanceProof {
         point_args.add("tU", blinded_credential.t * blinded_credential.U);
         point_args.add("M1", M[0]);
 
-        match Self::get_poksho_statement().verify_proof(&self.poksho_proof, &point_args, &[]) {
-            Err(_) => Err(ProofVerificationFailure),
-            Ok(_) => Ok(()),
-        }
+        Self::get_poksho_statement()
+            .and_then(|st| st.verify_proof(&self.poksho_proof, &point_args, &[]))
+            .map_err(|_| proof_failure::<Self>())
     }
 }
 
 impl AuthCredentialPresentationProof {
-    pub fn get_poksho_statement() -> poksho::Statement {
+    pub fn get_poksho_statement() -> Result<poksho::Statement, PokshoError> {
         let mut st = poksho::Statement::new();
 
-        st.add("Z", &[("z", "I")]);
-        st.add("C_x1", &[("t", "C_x0"), ("z0", "G_x0"), ("z", "G_x1")]);
-        st.add("A", &[("a1", "G_a1"), ("a2", "G_a2")]);
-        st.add("C_y2-E_A2", &[("z", "G_y2"), ("a2", "-E_A1")]);
-        st.add("E_A1", &[("a1", "C_y1"), ("z1", "G_y1")]);
-        st.add("C_y3", &[("z", "G_y3")]);
-        st
+        st.try_add("Z", &[("z", "I")])?;
+        st.try_add("C_x1", &[("t", "C_x0"), ("z0", "G_x0"), ("z", "G_x1")])?;
+        st.try_add("A", &[("a1", "G_a1"), ("a2", "G_a2")])?;
+        st.try_add("C_y2-E_A2", &[("z", "G_y2"), ("a2", "-E_A1")])?;
+        st.try_add("E_A1", &[("a1", "C_y1"), ("z1", "G_y1")])?;
+        st.try_add("C_y3", &[("z", "G_y3")])?;
+        Ok(st)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         credentials_public_key: credentials::PublicKey,
         uid_enc_key_pair: uid_encryption::KeyPair,
@@ -693,6 +761,7 @@ impl AuthCredentialPresentationProof {
         uid: uid_struct::UidStruct,
         uid_ciphertext: uid_encryption::Ciphertext,
         redemption_time: RedemptionTime,
+        format: ProofFormat,
         sho: &mut Sho,
     ) -> Self {
         let credentials_system = credentials::SystemParams::get_hardcoded();
@@ -745,12 +814,15 @@ impl AuthCredentialPresentationProof {
         point_args.add("G_y3", credentials_system.G_y[3]);
 
         let poksho_proof = Self::get_poksho_statement()
-            .prove(
-                &scalar_args,
-                &point_args,
-                &[],
-                &sho.squeeze(RANDOMNESS_LEN)[..],
-            )
+            .and_then(|st| {
+                format.prove(
+                    &st,
+                    &scalar_args,
+                    &point_args,
+                    &[],
+                    &sho.squeeze(RANDOMNESS_LEN)[..],
+                )
+            })
             .unwrap();
 
         Self {
@@ -771,6 +843,61 @@ impl AuthCredentialPresentationProof {
         uid_ciphertext: uid_encryption::Ciphertext,
         redemption_time: RedemptionTime,
     ) -> Result<(), ZkGroupError> {
+        let point_args = self.get_point_args(
+            credentials_key_pair,
+            uid_enc_public_key,
+            uid_ciphertext,
+            redemption_time,
+        );
+        Self::get_poksho_statement()
+            .and_then(|st| st.verify_proof(&self.poksho_proof, &point_args, &[]))
+            .map_err(|_| proof_failure::<Self>())
+    }
+
+    /// Verifies several [`ProofFormat::Batchable`] proofs at once, each with its own uid
+    /// encryption key, ciphertext, and redemption time.
+    ///
+    /// Fails with the index of the first proof that doesn't verify.
+    pub fn verify_batch(
+        credentials_key_pair: credentials::KeyPair<credentials::AuthCredential>,
+        proofs: &[(
+            &Self,
+            uid_encryption::PublicKey,
+            uid_encryption::Ciphertext,
+            RedemptionTime,
+        )],
+    ) -> Result<(), (usize, ZkGroupError)> {
+        let point_args: Vec<_> = proofs
+            .iter()
+            .map(
+                |(proof, uid_enc_public_key, uid_ciphertext, redemption_time)| {
+                    proof.get_point_args(
+                        credentials_key_pair,
+                        *uid_enc_public_key,
+                        *uid_ciphertext,
+                        *redemption_time,
+                    )
+                },
+            )
+            .collect();
+        let batch: Vec<(&[u8], &poksho::PointArgs, &[u8])> = proofs
+            .iter()
+            .zip(&point_args)
+            .map(|((proof, ..), point_args)| (&proof.poksho_proof[..], point_args, &[][..]))
+            .collect();
+        Self::get_poksho_statement()
+            .map_err(|e| (0, e))
+            .and_then(|st| st.verify_proofs_batch(&batch))
+            .map_err(|(i, _)| (i, proof_failure::<Self>()))
+    }
+
+    fn get_point_args(
+        &self,
+        credentials_key_pair: credentials::KeyPair<credentials::AuthCredential>,
+        uid_enc_public_key: uid_encryption::PublicKey,
+        uid_ciphertext: uid_encryption::Ciphertext,
+        redemption_time: RedemptionTime,
+    ) -> poksho::PointArgs {
         let enc_system = uid_encryption::SystemParams::get_hardcoded();
         let credentials_system = credentials::SystemParams::get_hardcoded();
 
@@ -781,7 +908,7 @@ impl AuthCredentialPresentationProof {
             C_y2,
             C_y3,
             C_V,
-            poksho_proof,
+            ..
         } = self;
 
         let (C_x0, C_x1, C_y1, C_y2, C_y3, C_V) = (*C_x0, *C_x1, *C_y1, *C_y2, *C_y3, *C_V);
@@ -797,7 +924,12 @@ impl AuthCredentialPresentationProof {
 
         let m3 = encode_redemption_time(redemption_time);
         let M3 = m3 * credentials_system.G_m3;
-        let Z = C_V - W - x0 * C_x0 - x1 * C_x1 - y1 * C_y1 - y2 * C_y2 - y3 * (C_y3 + M3);
+        let Z = C_V
+            - W
+            - RistrettoPoint::multiscalar_mul(
+                &[x0, x1, y1, y2, y3],
+                &[C_x0, C_x1, C_y1, C_y2, C_y3 + M3],
+            );
 
         // Points listed in order of stmts for debugging
         let mut point_args = poksho::PointArgs::new();
@@ -818,27 +950,23 @@ impl AuthCredentialPresentationProof {
         point_args.add("G_y1", credentials_system.G_y[1]);
         point_args.add("C_y3", C_y3);
         point_args.add("G_y3", credentials_system.G_y[3]);
-
-        match Self::get_poksho_statement().verify_proof(poksho_proof, &point_args, &[]) {
-            Err(_) => Err(ZkGroupError::ProofVerificationFailure),
-            Ok(_) => Ok(()),
-        }
+        point_args
     }
 }
 
 impl ProfileKeyCredentialPresentationProof {
-    pub fn get_poksho_statement() -> poksho::Statement {
+    pub fn get_poksho_statement() -> Result<poksho::Statement, PokshoError> {
         let mut st = poksho::Statement::new();
-        st.add("C_z", &[("z", "G_z")]);
-        st.add("Z", &[("z", "I")]);
-        st.add("C_x1", &[("t", "C_x0"), ("z0", "G_x0"), ("z", "G_x1")]);
-        st.add("A", &[("a1", "G_a1"), ("a2", "G_a2")]);
-        st.add("B", &[("b1", "G_b1"), ("b2", "G_b2")]);
-        st.add("C_y2-E_A2", &[("z", "G_y2"), ("a2", "-E_A1")]);
-        st.add("E_A1", &[("a1", "C_y1"), ("z1", "G_y1")]);
-        st.add("C_y4-E_B2", &[("z", "G_y4"), ("b2", "-E_B1")]);
-        st.add("E_B1", &[("b1", "C_y3"), ("z2", "G_y3")]);
-        st
+        st.try_add("C_z", &[("z", "G_z")])?;
+        st.try_add("Z", &[("z", "I")])?;
+        st.try_add("C_x1", &[("t", "C_x0"), ("z0", "G_x0"), ("z", "G_x1")])?;
+        st.try_add("A", &[("a1", "G_a1"), ("a2", "G_a2")])?;
+        st.try_add("B", &[("b1", "G_b1"), ("b2", "G_b2")])?;
+        st.try_add("C_y2-E_A2", &[("z", "G_y2"), ("a2", "-E_A1")])?;
+        st.try_add("E_A1", &[("a1", "C_y1"), ("z1", "G_y1")])?;
+        st.try_add("C_y4-E_B2", &[("z", "G_y4"), ("b2", "-E_B1")])?;
+        st.try_add("E_B1", &[("b1", "C_y3"), ("z2", "G_y3")])?;
+        Ok(st)
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -851,6 +979,7 @@ impl ProfileKeyCredentialPresentationProof {
         profile_key_ciphertext: profile_key_encryption::Ciphertext,
         uid_bytes: UidBytes,
         profile_key_bytes: ProfileKeyBytes,
+        format: ProofFormat,
         sho: &mut Sho,
     ) -> Self {
         let credentials_system = credentials::SystemParams::get_hardcoded();
@@ -925,12 +1054,15 @@ impl ProfileKeyCredentialPresentationProof {
         point_args.add("G_y3", credentials_system.G_y[3]);
 
         let poksho_proof = Self::get_poksho_statement()
-            .prove(
-                &scalar_args,
-                &point_args,
-                &[],
-                &sho.squeeze(RANDOMNESS_LEN)[..],
-            )
+            .and_then(|st| {
+                format.prove(
+                    &st,
+                    &scalar_args,
+                    &point_args,
+                    &[],
+                    &sho.squeeze(RANDOMNESS_LEN)[..],
+                )
+            })
             .unwrap();
 
         ProfileKeyCredentialPresentationProof {
@@ -954,6 +1086,71 @@ impl ProfileKeyCredentialPresentationProof {
         profile_key_ciphertext: profile_key_encryption::Ciphertext,
         profile_key_enc_public_key: profile_key_encryption::PublicKey,
     ) -> Result<(), ZkGroupError> {
+        let point_args = self.get_point_args(
+            credentials_key_pair,
+            uid_ciphertext,
+            uid_enc_public_key,
+            profile_key_ciphertext,
+            profile_key_enc_public_key,
+        );
+        Self::get_poksho_statement()
+            .and_then(|st| st.verify_proof(&self.poksho_proof, &point_args, &[]))
+            .map_err(|_| proof_failure::<Self>())
+    }
+
+    /// Verifies several [`ProofFormat::Batchable`] proofs at once, each with its own ciphertexts
+    /// and encryption keys.
+    ///
+    /// Fails with the index of the first proof that doesn't verify.
+    pub fn verify_batch(
+        credentials_key_pair: credentials::KeyPair<credentials::ProfileKeyCredential>,
+        proofs: &[(
+            &Self,
+            uid_encryption::Ciphertext,
+            uid_encryption::PublicKey,
+            profile_key_encryption::Ciphertext,
+            profile_key_encryption::PublicKey,
+        )],
+    ) -> Result<(), (usize, ZkGroupError)> {
+        let point_args: Vec<_> = proofs
+            .iter()
+            .map(
+                |(
+                    proof,
+                    uid_ciphertext,
+                    uid_enc_public_key,
+                    profile_key_ciphertext,
+                    profile_key_enc_public_key,
+                )| {
+                    proof.get_point_args(
+                        credentials_key_pair,
+                        *uid_ciphertext,
+                        *uid_enc_public_key,
+                        *profile_key_ciphertext,
+                        *profile_key_enc_public_key,
+                    )
+                },
+            )
+            .collect();
+        let batch: Vec<(&[u8], &poksho::PointArgs, &[u8])> = proofs
+            .iter()
+            .zip(&point_args)
+            .map(|((proof, ..), point_args)| (&proof.poksho_proof[..], point_args, &[][..]))
+            .collect();
+        Self::get_poksho_statement()
+            .map_err(|e| (0, e))
+            .and_then(|st| st.verify_proofs_batch(&batch))
+            .map_err(|(i, _)| (i, proof_failure::<Self>()))
+    }
+
+    fn get_point_args(
+        &self,
+        credentials_key_pair: credentials::KeyPair<credentials::ProfileKeyCredential>,
+        uid_ciphertext: uid_encryption::Ciphertext,
+        uid_enc_public_key: uid_encryption::PublicKey,
+        profile_key_ciphertext: profile_key_encryption::Ciphertext,
+        profile_key_enc_public_key: profile_key_encryption::PublicKey,
+    ) -> poksho::PointArgs {
         let uid_enc_system = uid_encryption::SystemParams::get_hardcoded();
         let profile_key_enc_system = profile_key_encryption::SystemParams::get_hardcoded();
         let credentials_system = credentials::SystemParams::get_hardcoded();
@@ -967,7 +1164,7 @@ impl ProfileKeyCredentialPresentationProof {
             C_y4,
             C_V,
             C_z,
-            poksho_proof,
+            ..
         } = self;
 
         let (C_x0, C_x1, C_y1, C_y2, C_y3, C_y4, C_V, C_z) =
@@ -982,8 +1179,12 @@ impl ProfileKeyCredentialPresentationProof {
             ..
         } = credentials_key_pair;
 
-        let Z =
-            C_V - W - x0 * C_x0 - x1 * C_x1 - (y1 * C_y1) - (y2 * C_y2) - (y3 * C_y3) - (y4 * C_y4);
+        let Z = C_V
+            - W
+            - RistrettoPoint::multiscalar_mul(
+                &[x0, x1, y1, y2, y3, y4],
+                &[C_x0, C_x1, C_y1, C_y2, C_y3, C_y4],
+            );
 
         // Points listed in order of stmts for debugging
         let mut point_args = poksho::PointArgs::new();
@@ -1017,29 +1218,25 @@ impl ProfileKeyCredentialPresentationProof {
         point_args.add("E_B1", profile_key_ciphertext.E_B1);
         point_args.add("C_y3", C_y3);
         point_args.add("G_y3", credentials_system.G_y[3]);
-
-        match Self::get_poksho_statement().verify_proof(poksho_proof, &point_args, &[]) {
-            Err(_) => Err(ZkGroupError::ProofVerificationFailure),
-            Ok(_) => Ok(()),
-        }
+        point_args
     }
 }
 
 impl PniCredentialPresentationProof {
-    pub fn get_poksho_statement() -> poksho::Statement {
+    pub fn get_poksho_statement() -> Result<poksho::Statement, PokshoError> {
         let mut st = poksho::Statement::new();
-        st.add("C_z", &[("z", "G_z")]);
-        st.add("Z", &[("z", "I")]);
-        st.add("C_x1", &[("t", "C_x0"), ("z0", "G_x0"), ("z", "G_x1")]);
-        st.add("A", &[("a1", "G_a1"), ("a2", "G_a2")]);
-        st.add("B", &[("b1", "G_b1"), ("b2", "G_b2")]);
-        st.add("C_y2-E_A2", &[("z", "G_y2"), ("a2", "-E_A1")]);
-        st.add("E_A1", &[("a1", "C_y1"), ("z1", "G_y1")]);
-        st.add("C_y4-E_B2", &[("z", "G_y4"), ("b2", "-E_B1")]);
-        st.add("E_B1", &[("b1", "C_y3"), ("z2", "G_y3")]);
-        st.add("C_y6-E_C2", &[("z", "G_y6"), ("a2", "-E_C1")]);
-        st.add("E_C1", &[("a1", "C_y5"), ("z1", "G_y5")]);
-        st
+        st.try_add("C_z", &[("z", "G_z")])?;
+        st.try_add("Z", &[("z", "I")])?;
+        st.try_add("C_x1", &[("t", "C_x0"), ("z0", "G_x0"), ("z", "G_x1")])?;
+        st.try_add("A", &[("a1", "G_a1"), ("a2", "G_a2")])?;
+        st.try_add("B", &[("b1", "G_b1"), ("b2", "G_b2")])?;
+        st.try_add("C_y2-E_A2", &[("z", "G_y2"), ("a2", "-E_A1")])?;
+        st.try_add("E_A1", &[("a1", "C_y1"), ("z1", "G_y1")])?;
+        st.try_add("C_y4-E_B2", &[("z", "G_y4"), ("b2", "-E_B1")])?;
+        st.try_add("E_B1", &[("b1", "C_y3"), ("z2", "G_y3")])?;
+        st.try_add("C_y6-E_C2", &[("z", "G_y6"), ("a2", "-E_C1")])?;
+        st.try_add("E_C1", &[("a1", "C_y5"), ("z1", "G_y5")])?;
+        Ok(st)
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -1138,12 +1335,14 @@ impl PniCredentialPresentationProof {
         point_args.add("G_y5", credentials_system.G_y[5]);
 
         let poksho_proof = Self::get_poksho_statement()
-            .prove(
-                &scalar_args,
-                &point_args,
-                &[],
-                &sho.squeeze(RANDOMNESS_LEN)[..],
-            )
+            .and_then(|st| {
+                st.prove(
+                    &scalar_args,
+                    &point_args,
+                    &[],
+                    &sho.squeeze(RANDOMNESS_LEN)[..],
+                )
+            })
             .unwrap();
 
         Self {
@@ -1248,22 +1447,21 @@ impl PniCredentialPresentationProof {
         point_args.add("C_y5", *C_y5);
         point_args.add("G_y5", credentials_system.G_y[5]);
 
-        match Self::get_poksho_statement().verify_proof(poksho_proof, &point_args, &[]) {
-            Err(_) => Err(ZkGroupError::ProofVerificationFailure),
-            Ok(_) => Ok(()),
-        }
+        Self::get_poksho_statement()
+            .and_then(|st| st.verify_proof(poksho_proof, &point_args, &[]))
+            .map_err(|_| proof_failure::<Self>())
     }
 }
 
 impl ReceiptCredentialPresentationProof {
-    pub fn get_poksho_statement() -> poksho::Statement {
+    pub fn get_poksho_statement() -> Result<poksho::Statement, PokshoError> {
         let mut st = poksho::Statement::new();
 
-        st.add("Z", &[("z", "I")]);
-        st.add("C_x1", &[("t", "C_x0"), ("-zt", "G_x0"), ("z", "G_x1")]);
-        st.add("C_y1", &[("z", "G_y1")]);
-        st.add("C_y2", &[("z", "G_y2")]);
-        st
+        st.try_add("Z", &[("z", "I")])?;
+        st.try_add("C_x1", &[("t", "C_x0"), ("-zt", "G_x0"), ("z", "G_x1")])?;
+        st.try_add("C_y1", &[("z", "G_y1")])?;
+        st.try_add("C_y2", &[("z", "G_y2")])?;
+        Ok(st)
     }
 
     pub fn new(
@@ -1304,12 +1502,14 @@ impl ReceiptCredentialPresentationProof {
         point_args.add("G_y2", credentials_system.G_y[2]);
 
         let poksho_proof = Self::get_poksho_statement()
-            .prove(
-                &scalar_args,
-                &point_args,
-                &[],
-                &sho.squeeze(RANDOMNESS_LEN)[..],
-            )
+            .and_then(|st| {
+                st.prove(
+                    &scalar_args,
+                    &point_args,
+                    &[],
+                    &sho.squeeze(RANDOMNESS_LEN)[..],
+                )
+            })
             .unwrap();
 
         Self {
@@ -1364,9 +1564,197 @@ impl ReceiptCredentialPresentationProof {
         point_args.add("G_y1", credentials_system.G_y[1]);
         point_args.add("G_y2", credentials_system.G_y[2]);
 
-        match Self::get_poksho_statement().verify_proof(poksho_proof, &point_args, &[]) {
-            Err(_) => Err(ZkGroupError::ProofVerificationFailure),
-            Ok(_) => Ok(()),
-        }
+        Self::get_poksho_statement()
+            .and_then(|st| st.verify_proof(poksho_proof, &point_args, &[]))
+            .map_err(|_| proof_failure::<Self>())
+    }
+}
+
+impl InviteLinkPresentationProof {
+    pub fn get_poksho_statement() -> Result<poksho::Statement, PokshoError> {
+        let mut st = poksho::Statement::new();
+        st.try_add("X", &[("x", "G")])?;
+        Ok(st)
+    }
+
+    /// Proves knowledge of the invite link key, bound to `message`.
+    ///
+    /// The message should identify the group and the joining member, so that the proof can't be
+    /// replayed for anyone else.
+    pub fn new(key_pair: invite_link::KeyPair, message: &[u8], sho: &mut Sho) -> Self {
+        let mut scalar_args = poksho::ScalarArgs::new();
+        scalar_args.add("x", key_pair.x);
+
+        let mut point_args = poksho::PointArgs::new();
+        point_args.add("X", key_pair.X);
+
+        let poksho_proof = Self::get_poksho_statement()
+            .and_then(|st| {
+                st.prove(
+                    &scalar_args,
+                    &point_args,
+                    message,
+                    &sho.squeeze(RANDOMNESS_LEN)[..],
+                )
+            })
+            .unwrap();
+        InviteLinkPresentationProof { poksho_proof }
+    }
+
+    pub fn verify(
+        &self,
+        public_key: invite_link::PublicKey,
+        message: &[u8],
+    ) -> Result<(), ZkGroupError> {
+        let mut point_args = poksho::PointArgs::new();
+        point_args.add("X", public_key.X);
+
+        Self::get_poksho_statement()
+            .and_then(|st| st.verify_proof(&self.poksho_proof, &point_args, message))
+            .map_err(|_| proof_failure::<Self>())
+    }
+}
+
+impl AttributeEncryptionProof {
+    pub fn get_poksho_statement() -> Result<poksho::Statement, PokshoError> {
+        let mut st = poksho::Statement::new();
+        st.try_add("A", &[("a1", "G_a1"), ("a2", "G_a2")])?;
+        st.try_add("E_A1", &[("a1", "M1")])?;
+        st.try_add("E_A2-M2", &[("a2", "E_A1")])?;
+        Ok(st)
+    }
+
+    /// Proves that `ciphertext` is the encryption of `attr` under `uid_enc_key_pair`.
+    pub fn new<A: Attribute>(
+        uid_enc_key_pair: uid_encryption::KeyPair,
+        attr: A,
+        ciphertext: uid_encryption::Ciphertext,
+        sho: &mut Sho,
+    ) -> Self {
+        let uid_system = uid_encryption::SystemParams::get_hardcoded();
+
+        let mut scalar_args = poksho::ScalarArgs::new();
+        scalar_args.add("a1", uid_enc_key_pair.a1);
+        scalar_args.add("a2", uid_enc_key_pair.a2);
+
+        let mut point_args = poksho::PointArgs::new();
+        point_args.add("A", uid_enc_key_pair.A);
+        point_args.add("G_a1", uid_system.G_a1);
+        point_args.add("G_a2", uid_system.G_a2);
+        point_args.add("E_A1", ciphertext.E_A1);
+        point_args.add("M1", attr.M1());
+        point_args.add("E_A2-M2", ciphertext.E_A2 - attr.M2());
+
+        let poksho_proof = Self::get_poksho_statement()
+            .and_then(|st| {
+                st.prove(
+                    &scalar_args,
+                    &point_args,
+                    &[],
+                    &sho.squeeze(RANDOMNESS_LEN)[..],
+                )
+            })
+            .unwrap();
+        AttributeEncryptionProof { poksho_proof }
+    }
+
+    pub fn verify<A: Attribute>(
+        &self,
+        uid_enc_public_key: uid_encryption::PublicKey,
+        attr: A,
+        ciphertext: uid_encryption::Ciphertext,
+    ) -> Result<(), ZkGroupError> {
+        let uid_system = uid_encryption::SystemParams::get_hardcoded();
+
+        let mut point_args = poksho::PointArgs::new();
+        point_args.add("A", uid_enc_public_key.A);
+        point_args.add("G_a1", uid_system.G_a1);
+        point_args.add("G_a2", uid_system.G_a2);
+        point_args.add("E_A1", ciphertext.E_A1);
+        point_args.add("M1", attr.M1());
+        point_args.add("E_A2-M2", ciphertext.E_A2 - attr.M2());
+
+        Self::get_poksho_statement()
+            .and_then(|st| st.verify_proof(&self.poksho_proof, &point_args, &[]))
+            .map_err(|_| proof_failure::<Self>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_key_credential_request_matches_runtime_statement() {
+        // The statement ProfileKeyCredentialRequestProof used before it was declared with
+        // poksho::statement!
+        let mut st = poksho::Statement::new();
+        st.try_add("Y", &[("y", "G")]).unwrap();
+        st.try_add("D1", &[("r1", "G")]).unwrap();
+        st.try_add("E1", &[("r2", "G")]).unwrap();
+        st.try_add("J3", &[("j3", "G_j3")]).unwrap();
+        st.try_add("D2-J1", &[("r1", "Y"), ("j3", "-G_j1")])
+            .unwrap();
+        st.try_add("E2-J2", &[("r2", "Y"), ("j3", "-G_j2")])
+            .unwrap();
+        assert_eq!(
+            PROFILE_KEY_CREDENTIAL_REQUEST.description(),
+            &[
+                6, 1, 1, 0, 0, 2, 1, 1, 0, 3, 1, 2, 0, 4, 1, 3, 5, 6, 2, 1, 1, 3, 7, 8, 2, 2, 1, 3,
+                9
+            ]
+        );
+
+        let new_sho = || Sho::new(b"Test_Profile_Key_Credential_Request", &TEST_ARRAY_32);
+        let mut sho = new_sho();
+        let profile_key = profile_key_struct::ProfileKeyStruct::new(TEST_ARRAY_32, TEST_ARRAY_16);
+        let commitment =
+            profile_key_commitment::CommitmentWithSecretNonce::new(profile_key, TEST_ARRAY_16);
+        let key_pair = profile_key_credential_request::KeyPair::generate(&mut sho);
+        let ciphertext = key_pair.encrypt(profile_key, &mut sho);
+        let commitment_system = profile_key_commitment::SystemParams::get_hardcoded();
+
+        let mut scalar_args = poksho::ScalarArgs::new();
+        scalar_args.add("y", key_pair.y);
+        scalar_args.add("r1", ciphertext.r1);
+        scalar_args.add("r2", ciphertext.r2);
+        scalar_args.add("j3", commitment.j3);
+
+        let mut point_args = poksho::PointArgs::new();
+        point_args.add("Y", key_pair.Y);
+        point_args.add("D1", ciphertext.D1);
+        point_args.add("E1", ciphertext.E1);
+        point_args.add("J3", commitment.J3);
+        point_args.add("G_j3", commitment_system.G_j3);
+        point_args.add("D2-J1", ciphertext.D2 - commitment.J1);
+        point_args.add("-G_j1", -commitment_system.G_j1);
+        point_args.add("E2-J2", ciphertext.E2 - commitment.J2);
+        point_args.add("-G_j2", -commitment_system.G_j2);
+
+        // Replay the same randomness for the runtime proof
+        let mut proof_sho = new_sho();
+        profile_key_credential_request::KeyPair::generate(&mut proof_sho);
+        key_pair.encrypt(profile_key, &mut proof_sho);
+        let runtime_proof = st
+            .prove(
+                &scalar_args,
+                &point_args,
+                &[],
+                &proof_sho.squeeze(RANDOMNESS_LEN)[..],
+            )
+            .unwrap();
+
+        let proof =
+            ProfileKeyCredentialRequestProof::new(key_pair, ciphertext, commitment, &mut sho);
+        assert_eq!(proof.poksho_proof, runtime_proof);
+        st.verify_proof(&proof.poksho_proof, &point_args, &[])
+            .unwrap();
+        proof
+            .verify(
+                key_pair.get_public_key(),
+                ciphertext.get_ciphertext(),
+                commitment.get_profile_key_commitment(),
+            )
+            .unwrap();
     }
 }
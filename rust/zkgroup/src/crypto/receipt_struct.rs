@@ -9,7 +9,9 @@ use serde::{Deserialize, Serialize};
 use crate::common::sho::Sho;
 use crate::common::simple_types::ReceiptExpirationTime;
 use crate::common::simple_types::ReceiptLevel;
+use crate::common::simple_types::ReceiptPaymentType;
 use crate::common::simple_types::ReceiptSerialBytes;
+use crate::common::simple_types::ReceiptValidityStartTime;
 
 /// The full set of information known by the client after receiving the credential response from
 /// the issuing server. It will all be shared with the credential presentation. Initially the
@@ -56,3 +58,81 @@ impl ReceiptStruct {
         sho.get_scalar()
     }
 }
+
+/// A [`ReceiptStruct`] with two more attributes, each of which the client may choose to hide when
+/// presenting the credential.
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtendedReceiptStruct {
+    pub(crate) receipt: ReceiptStruct,
+    pub(crate) payment_type: ReceiptPaymentType,
+    pub(crate) validity_start_time: ReceiptValidityStartTime,
+}
+
+impl ExtendedReceiptStruct {
+    pub fn new(
+        receipt: ReceiptStruct,
+        payment_type: ReceiptPaymentType,
+        validity_start_time: ReceiptValidityStartTime,
+    ) -> Self {
+        Self {
+            receipt,
+            payment_type,
+            validity_start_time,
+        }
+    }
+
+    pub fn calc_m3(&self) -> Scalar {
+        Self::calc_m3_from(self.payment_type)
+    }
+
+    pub fn calc_m3_from(payment_type: ReceiptPaymentType) -> Scalar {
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20211201_Receipt_CalcM3",
+            &payment_type.to_be_bytes(),
+        );
+        sho.get_scalar()
+    }
+
+    pub fn calc_m4(&self) -> Scalar {
+        Self::calc_m4_from(self.validity_start_time)
+    }
+
+    pub fn calc_m4_from(validity_start_time: ReceiptValidityStartTime) -> Scalar {
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20211201_Receipt_CalcM4",
+            &validity_start_time.to_be_bytes(),
+        );
+        sho.get_scalar()
+    }
+}
+
+/// Which of the optional attributes of an [`ExtendedReceiptStruct`] a presentation reveals.
+///
+/// The expiration time, level, and serial are always revealed.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiptDisclosure {
+    pub payment_type: bool,
+    pub validity_start_time: bool,
+}
+
+impl ReceiptDisclosure {
+    pub const ALL: Self = Self {
+        payment_type: true,
+        validity_start_time: true,
+    };
+    pub const NONE: Self = Self {
+        payment_type: false,
+        validity_start_time: false,
+    };
+
+    /// Names the first attribute that `required` asks for but `self` does not reveal.
+    pub fn first_missing(&self, required: ReceiptDisclosure) -> Option<&'static str> {
+        if required.payment_type && !self.payment_type {
+            Some("payment type")
+        } else if required.validity_start_time && !self.validity_start_time {
+            Some("validity start time")
+        } else {
+            None
+        }
+    }
+}
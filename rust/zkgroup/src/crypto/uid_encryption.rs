@@ -7,7 +7,7 @@
 
 use crate::common::errors::*;
 use crate::common::sho::*;
-use crate::crypto::uid_struct;
+use crate::crypto::uid_struct::PointDecodeFailure;
 use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
 use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
@@ -15,6 +15,20 @@ use serde::{Deserialize, Serialize};
 
 use ZkGroupError::*;
 
+/// A value that can be deterministically and verifiably encrypted with a group's [`KeyPair`].
+///
+/// `M1` must be derived from the value under a label unique to the attribute kind, so that a
+/// ciphertext of one kind never decrypts successfully as another. `M2` must be reversible, since
+/// decryption recovers the value from it.
+pub trait Attribute: Copy {
+    /// Names the attribute kind in errors; never includes the value itself.
+    const KIND: &'static str;
+
+    fn M1(&self) -> RistrettoPoint;
+    fn M2(&self) -> RistrettoPoint;
+    fn from_M2(M2: RistrettoPoint) -> Result<Self, PointDecodeFailure>;
+}
+
 #[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SystemParams {
     pub(crate) G_a1: RistrettoPoint,
@@ -74,31 +88,32 @@ impl KeyPair {
         KeyPair { a1, a2, A }
     }
 
-    pub fn encrypt(&self, uid: uid_struct::UidStruct) -> Ciphertext {
-        let E_A1 = self.calc_E_A1(uid);
-        let E_A2 = (self.a2 * E_A1) + uid.M2;
+    pub fn encrypt<A: Attribute>(&self, attr: A) -> Ciphertext {
+        let E_A1 = self.calc_E_A1(&attr);
+        let E_A2 = (self.a2 * E_A1) + attr.M2();
         Ciphertext { E_A1, E_A2 }
     }
 
     // Might return DecryptionFailure
-    pub fn decrypt(&self, ciphertext: Ciphertext) -> Result<uid_struct::UidStruct, ZkGroupError> {
+    pub fn decrypt<A: Attribute>(&self, ciphertext: Ciphertext) -> Result<A, ZkGroupError> {
+        let failure = DecryptionFailure(DecryptionError::AttributeCiphertext { kind: A::KIND });
         if ciphertext.E_A1 == RISTRETTO_BASEPOINT_POINT {
-            return Err(DecryptionFailure);
+            return Err(failure);
         }
-        match uid_struct::UidStruct::from_M2(ciphertext.E_A2 - (self.a2 * ciphertext.E_A1)) {
-            Err(_) => Err(DecryptionFailure),
-            Ok(decrypted_uid) => {
-                if ciphertext.E_A1 == self.calc_E_A1(decrypted_uid) {
-                    Ok(decrypted_uid)
+        match A::from_M2(ciphertext.E_A2 - (self.a2 * ciphertext.E_A1)) {
+            Err(_) => Err(failure),
+            Ok(decrypted) => {
+                if ciphertext.E_A1 == self.calc_E_A1(&decrypted) {
+                    Ok(decrypted)
                 } else {
-                    Err(DecryptionFailure)
+                    Err(failure)
                 }
             }
         }
     }
 
-    fn calc_E_A1(&self, uid: uid_struct::UidStruct) -> RistrettoPoint {
-        self.a1 * uid.M1
+    fn calc_E_A1<A: Attribute>(&self, attr: &A) -> RistrettoPoint {
+        self.a1 * attr.M1()
     }
 
     pub fn get_public_key(&self) -> PublicKey {
@@ -110,6 +125,7 @@ impl KeyPair {
 mod tests {
     use super::*;
     use crate::common::constants::*;
+    use crate::crypto::uid_struct;
 
     #[test]
     fn test_uid_encryption() {
@@ -151,7 +167,7 @@ mod tests {
                 ]
         );
 
-        let plaintext = key_pair.decrypt(ciphertext2).unwrap();
+        let plaintext: uid_struct::UidStruct = key_pair.decrypt(ciphertext2).unwrap();
 
         assert!(plaintext == uid);
     }
@@ -7,6 +7,7 @@
 
 use crate::common::sho::*;
 use crate::common::simple_types::*;
+use crate::crypto::uid_encryption::Attribute;
 use curve25519_dalek::ristretto::RistrettoPoint;
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
@@ -43,3 +44,19 @@ impl UidStruct {
         self.bytes
     }
 }
+
+impl Attribute for UidStruct {
+    const KIND: &'static str = "UID";
+
+    fn M1(&self) -> RistrettoPoint {
+        self.M1
+    }
+
+    fn M2(&self) -> RistrettoPoint {
+        self.M2
+    }
+
+    fn from_M2(M2: RistrettoPoint) -> Result<Self, PointDecodeFailure> {
+        Self::from_M2(M2)
+    }
+}
@@ -11,4 +11,5 @@ pub mod crypto;
 pub use api::*;
 pub use common::constants::*;
 pub use common::errors::*;
+pub use common::serialization::{deserialize, serialize, VersionedSerializable};
 pub use common::simple_types::*;
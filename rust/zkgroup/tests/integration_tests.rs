@@ -417,6 +417,465 @@ fn test_integration_pni() {
     pni_credential_response_bytes.copy_from_slice(&bincode::serialize(&response).unwrap());
 }
 
+#[test]
+fn test_integration_auth_with_pni() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let master_key = zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1);
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::derive_from_master_key(master_key);
+    let group_public_params = group_secret_params.get_public_params();
+
+    let aci = zkgroup::TEST_ARRAY_16;
+    let pni = zkgroup::TEST_ARRAY_16_1;
+    let redemption_time = 123456 * zkgroup::SECONDS_PER_DAY;
+
+    // SERVER
+    // Issue credential
+    let randomness = zkgroup::TEST_ARRAY_32_2;
+    let auth_credential_response = server_secret_params
+        .issue_auth_credential_with_pni(randomness, aci, pni, redemption_time)
+        .unwrap();
+
+    // CLIENT
+    assert!(matches!(
+        server_public_params.receive_auth_credential_with_pni(
+            aci,
+            pni,
+            redemption_time + 1,
+            &auth_credential_response
+        ),
+        Err(zkgroup::ZkGroupError::RedemptionTimeNotDayAligned { .. })
+    ));
+    assert!(server_public_params
+        .receive_auth_credential_with_pni(pni, aci, redemption_time, &auth_credential_response)
+        .is_err());
+    let auth_credential = server_public_params
+        .receive_auth_credential_with_pni(aci, pni, redemption_time, &auth_credential_response)
+        .unwrap();
+
+    // Create and receive presentation
+    let randomness = zkgroup::TEST_ARRAY_32_5;
+
+    let presentation = server_public_params
+        .create_auth_credential_with_pni_presentation(
+            randomness,
+            group_secret_params,
+            &auth_credential,
+        )
+        .unwrap();
+    assert_eq!(presentation.get_redemption_time(), redemption_time);
+    assert_eq!(
+        group_secret_params
+            .decrypt_uuid(presentation.get_aci_ciphertext())
+            .unwrap(),
+        aci
+    );
+    assert_eq!(
+        group_secret_params
+            .decrypt_pni(presentation.get_pni_ciphertext())
+            .unwrap(),
+        pni
+    );
+
+    // SERVER
+    for current_time in [
+        redemption_time - zkgroup::SECONDS_PER_DAY,
+        redemption_time,
+        redemption_time + 2 * zkgroup::SECONDS_PER_DAY,
+    ] {
+        server_secret_params
+            .verify_auth_credential_with_pni_presentation(
+                group_public_params,
+                &presentation,
+                current_time,
+            )
+            .unwrap();
+    }
+    for current_time in [
+        redemption_time - zkgroup::SECONDS_PER_DAY - 1,
+        redemption_time + 2 * zkgroup::SECONDS_PER_DAY + 1,
+    ] {
+        assert_eq!(
+            server_secret_params
+                .verify_auth_credential_with_pni_presentation(
+                    group_public_params,
+                    &presentation,
+                    current_time,
+                )
+                .unwrap_err(),
+            zkgroup::ZkGroupError::RedemptionTimeOutOfRange {
+                redemption_time,
+                current_time,
+            }
+        );
+    }
+
+    // A presentation for a different group fails.
+    let other_group_public_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_2),
+    )
+    .get_public_params();
+    assert!(server_secret_params
+        .verify_auth_credential_with_pni_presentation(
+            other_group_public_params,
+            &presentation,
+            redemption_time,
+        )
+        .is_err());
+
+    // The original auth credential still works alongside the new one.
+    let old_response = server_secret_params.issue_auth_credential(randomness, aci, 123456);
+    let old_credential = server_public_params
+        .receive_auth_credential(aci, 123456, &old_response)
+        .unwrap();
+    let old_presentation = server_public_params.create_auth_credential_presentation(
+        randomness,
+        group_secret_params,
+        old_credential,
+    );
+    server_secret_params
+        .verify_auth_credential_presentation(group_public_params, &old_presentation)
+        .unwrap();
+
+    // test encoding
+    // these tests will also discover if the serialized sizes change,
+    //   necessitating an update to the LEN constants
+    let mut auth_credential_bytes = [0u8; zkgroup::common::constants::AUTH_CREDENTIAL_WITH_PNI_LEN];
+    let mut auth_credential_presentation_bytes =
+        [0u8; zkgroup::common::constants::AUTH_CREDENTIAL_WITH_PNI_PRESENTATION_LEN];
+    let mut auth_credential_response_bytes =
+        [0u8; zkgroup::common::constants::AUTH_CREDENTIAL_WITH_PNI_RESPONSE_LEN];
+
+    auth_credential_bytes.copy_from_slice(&bincode::serialize(&auth_credential).unwrap());
+    auth_credential_presentation_bytes.copy_from_slice(&bincode::serialize(&presentation).unwrap());
+    auth_credential_response_bytes
+        .copy_from_slice(&bincode::serialize(&auth_credential_response).unwrap());
+}
+
+#[test]
+fn test_server_params_v0() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    // The original version 0 params are a prefix of the current ones.
+    let mut secret_bytes = zkgroup::serialize(&server_secret_params);
+    secret_bytes.truncate(zkgroup::SERVER_SECRET_PARAMS_V0_LEN);
+    secret_bytes[0] = 0;
+    let legacy_secret_params: zkgroup::ServerSecretParams =
+        zkgroup::deserialize(&secret_bytes).unwrap();
+    assert_eq!(zkgroup::serialize(&legacy_secret_params), secret_bytes);
+
+    let mut public_bytes = zkgroup::serialize(&server_public_params);
+    public_bytes.truncate(zkgroup::SERVER_PUBLIC_PARAMS_V0_LEN);
+    public_bytes[0] = 0;
+    let legacy_public_params: zkgroup::ServerPublicParams =
+        zkgroup::deserialize(&public_bytes).unwrap();
+    assert_eq!(zkgroup::serialize(&legacy_public_params), public_bytes);
+    assert_eq!(
+        zkgroup::serialize(&legacy_secret_params.get_public_params()),
+        public_bytes
+    );
+
+    // Only those lengths are accepted for version 0.
+    assert!(matches!(
+        zkgroup::deserialize::<zkgroup::ServerSecretParams>(
+            &secret_bytes[..secret_bytes.len() - 1]
+        ),
+        Err(zkgroup::DeserializationError::WrongLength {
+            version: 0,
+            expected: zkgroup::SERVER_SECRET_PARAMS_V0_LEN,
+            ..
+        })
+    ));
+
+    let master_key = zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1);
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::derive_from_master_key(master_key);
+    let group_public_params = group_secret_params.get_public_params();
+    let aci = zkgroup::TEST_ARRAY_16;
+    let pni = zkgroup::TEST_ARRAY_16_1;
+    let redemption_time = 123456 * zkgroup::SECONDS_PER_DAY;
+    let randomness = zkgroup::TEST_ARRAY_32_2;
+
+    let missing_key = || zkgroup::ZkGroupError::MissingCredentialKey {
+        credential: "auth with PNI",
+    };
+    assert_eq!(
+        legacy_secret_params
+            .issue_auth_credential_with_pni(randomness, aci, pni, redemption_time)
+            .err(),
+        Some(missing_key())
+    );
+    let response = server_secret_params
+        .issue_auth_credential_with_pni(randomness, aci, pni, redemption_time)
+        .unwrap();
+    assert_eq!(
+        legacy_public_params
+            .receive_auth_credential_with_pni(aci, pni, redemption_time, &response)
+            .err(),
+        Some(missing_key())
+    );
+    let auth_credential = server_public_params
+        .receive_auth_credential_with_pni(aci, pni, redemption_time, &response)
+        .unwrap();
+    assert_eq!(
+        legacy_public_params
+            .create_auth_credential_with_pni_presentation(
+                randomness,
+                group_secret_params,
+                &auth_credential,
+            )
+            .err(),
+        Some(missing_key())
+    );
+    let presentation = server_public_params
+        .create_auth_credential_with_pni_presentation(
+            randomness,
+            group_secret_params,
+            &auth_credential,
+        )
+        .unwrap();
+    assert_eq!(
+        legacy_secret_params
+            .verify_auth_credential_with_pni_presentation(
+                group_public_params,
+                &presentation,
+                redemption_time,
+            )
+            .err(),
+        Some(missing_key())
+    );
+
+    // The original auth credential still works.
+    let response = legacy_secret_params.issue_auth_credential(randomness, aci, 123456);
+    let auth_credential = legacy_public_params
+        .receive_auth_credential(aci, 123456, &response)
+        .unwrap();
+    let presentation = legacy_public_params.create_auth_credential_presentation(
+        randomness,
+        group_secret_params,
+        auth_credential,
+    );
+    legacy_secret_params
+        .verify_auth_credential_presentation(group_public_params, &presentation)
+        .unwrap();
+}
+
+#[test]
+fn test_batch_auth() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1),
+    );
+    let other_group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_2),
+    );
+
+    let requests = [
+        (zkgroup::TEST_ARRAY_16, 123456u32),
+        (zkgroup::TEST_ARRAY_16_1, 123457u32),
+        (zkgroup::TEST_ARRAY_16, 123458u32),
+    ];
+    let responses =
+        server_secret_params.issue_auth_credentials(zkgroup::TEST_ARRAY_32_2, &requests);
+    assert_eq!(responses.len(), requests.len());
+
+    // Batch issuance is deterministic.
+    let responses_again =
+        server_secret_params.issue_auth_credentials(zkgroup::TEST_ARRAY_32_2, &requests);
+    for (response, response_again) in responses.iter().zip(&responses_again) {
+        assert_eq!(
+            bincode::serialize(response).unwrap(),
+            bincode::serialize(response_again).unwrap()
+        );
+    }
+
+    // The last presentation isn't batchable.
+    let presentations: Vec<_> = responses
+        .iter()
+        .zip(&requests)
+        .enumerate()
+        .map(|(i, (response, (uid, redemption_time)))| {
+            let credential = server_public_params
+                .receive_auth_credential(*uid, *redemption_time, response)
+                .unwrap();
+            if i + 1 < requests.len() {
+                server_public_params.create_batchable_auth_credential_presentation(
+                    zkgroup::TEST_ARRAY_32_5,
+                    group_secret_params,
+                    credential,
+                )
+            } else {
+                server_public_params.create_auth_credential_presentation(
+                    zkgroup::TEST_ARRAY_32_5,
+                    group_secret_params,
+                    credential,
+                )
+            }
+        })
+        .collect();
+
+    let presentation_bytes = zkgroup::serialize(&presentations[0]);
+    assert_eq!(
+        presentation_bytes.len(),
+        zkgroup::common::constants::AUTH_CREDENTIAL_PRESENTATION_V1_LEN
+    );
+    let presentation: zkgroup::auth::AuthCredentialPresentation =
+        zkgroup::deserialize(&presentation_bytes).unwrap();
+    assert_eq!(zkgroup::serialize(&presentation), presentation_bytes);
+    server_secret_params
+        .verify_auth_credential_presentation(group_secret_params.get_public_params(), &presentation)
+        .unwrap();
+
+    let batch = [
+        (group_secret_params.get_public_params(), &presentations[0]),
+        (group_secret_params.get_public_params(), &presentations[1]),
+        (group_secret_params.get_public_params(), &presentations[2]),
+    ];
+    server_secret_params
+        .verify_auth_credential_presentations(&batch)
+        .unwrap();
+    server_secret_params
+        .verify_auth_credential_presentations(&[])
+        .unwrap();
+
+    // A presentation checked against the wrong group is reported by index, whether or not it was
+    // verified as part of the batch.
+    for bad_index in 0..batch.len() {
+        let mut bad_batch = batch;
+        bad_batch[bad_index].0 = other_group_secret_params.get_public_params();
+        assert!(matches!(
+            server_secret_params.verify_auth_credential_presentations(&bad_batch),
+            Err((index, zkgroup::ZkGroupError::ProofVerificationFailure {
+                proof: "AuthCredentialPresentationProof"
+            })) if index == bad_index
+        ));
+    }
+
+    // Checking against other groups tells a presentation for the wrong group from a bad one.
+    let other_group_public_params = other_group_secret_params.get_public_params();
+    server_secret_params
+        .verify_auth_credential_presentation_among_groups(
+            group_secret_params.get_public_params(),
+            &[],
+            &presentations[1],
+        )
+        .unwrap();
+    assert!(matches!(
+        server_secret_params.verify_auth_credential_presentation_among_groups(
+            other_group_public_params,
+            &[
+                other_group_public_params,
+                group_secret_params.get_public_params()
+            ],
+            &presentations[1],
+        ),
+        Err(zkgroup::ZkGroupError::ProofForOtherGroup {
+            proof: "AuthCredentialPresentationProof",
+            index: 1
+        })
+    ));
+    assert!(matches!(
+        server_secret_params.verify_auth_credential_presentation_among_groups(
+            other_group_public_params,
+            &[other_group_public_params],
+            &presentations[1],
+        ),
+        Err(zkgroup::ZkGroupError::ProofVerificationFailure {
+            proof: "AuthCredentialPresentationProof"
+        })
+    ));
+}
+
+#[test]
+fn test_batch_profile() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1),
+    );
+    let group_public_params = group_secret_params.get_public_params();
+
+    let uids = [zkgroup::TEST_ARRAY_16, zkgroup::TEST_ARRAY_16_1];
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_1);
+    let contexts: Vec<_> = uids
+        .iter()
+        .map(|uid| {
+            server_public_params.create_profile_key_credential_request_context(
+                zkgroup::TEST_ARRAY_32_3,
+                *uid,
+                profile_key,
+            )
+        })
+        .collect();
+    let requests: Vec<_> = contexts
+        .iter()
+        .map(|context| context.get_request())
+        .collect();
+
+    // The second request is paired with the wrong commitment.
+    let issue_batch = [
+        (&requests[0], uids[0], profile_key.get_commitment(uids[0])),
+        (&requests[1], uids[1], profile_key.get_commitment(uids[0])),
+    ];
+    let mut responses = server_secret_params
+        .issue_profile_key_credentials(zkgroup::TEST_ARRAY_32_4, &issue_batch)
+        .into_iter();
+    let response = responses.next().unwrap().unwrap();
+    assert!(responses.next().unwrap().is_err());
+    assert!(responses.next().is_none());
+
+    let credential = server_public_params
+        .receive_profile_key_credential(&contexts[0], &response)
+        .unwrap();
+    let presentation = server_public_params.create_profile_key_credential_presentation(
+        zkgroup::TEST_ARRAY_32_5,
+        group_secret_params,
+        credential,
+    );
+    let batchable_presentation = server_public_params
+        .create_batchable_profile_key_credential_presentation(
+            zkgroup::TEST_ARRAY_32_5,
+            group_secret_params,
+            credential,
+        );
+    assert_eq!(
+        zkgroup::serialize(&batchable_presentation).len(),
+        zkgroup::common::constants::PROFILE_KEY_CREDENTIAL_PRESENTATION_V1_LEN
+    );
+    server_secret_params
+        .verify_profile_key_credential_presentation(group_public_params, &batchable_presentation)
+        .unwrap();
+    server_secret_params
+        .verify_profile_key_credential_presentations(&[
+            (group_public_params, &batchable_presentation),
+            (group_public_params, &presentation),
+            (group_public_params, &batchable_presentation),
+        ])
+        .unwrap();
+
+    let other_group_public_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_2).get_public_params();
+    assert!(matches!(
+        server_secret_params.verify_profile_key_credential_presentations(&[
+            (group_public_params, &batchable_presentation),
+            (group_public_params, &presentation),
+            (other_group_public_params, &batchable_presentation),
+        ]),
+        Err((
+            2,
+            zkgroup::ZkGroupError::ProofVerificationFailure {
+                proof: "ProfileKeyCredentialPresentationProof"
+            }
+        ))
+    ));
+}
+
 #[test]
 fn test_server_sigs() {
     let server_secret_params =
@@ -475,4 +934,147 @@ fn test_blob_encryption() {
         print!("0x{:02x}, ", b);
     }
     assert!(calc_ciphertext_vec == ciphertext_vec);
+
+    assert_eq!(
+        group_secret_params
+            .decrypt_blob(&ciphertext_vec[..20])
+            .unwrap_err(),
+        zkgroup::ZkGroupError::DecryptionFailure(zkgroup::DecryptionError::BlobTooShort {
+            len: 20
+        })
+    );
+    let mut corrupted_ciphertext_vec = ciphertext_vec;
+    corrupted_ciphertext_vec[0] ^= 1;
+    assert_eq!(
+        group_secret_params
+            .decrypt_blob(&corrupted_ciphertext_vec)
+            .unwrap_err(),
+        zkgroup::ZkGroupError::DecryptionFailure(zkgroup::DecryptionError::BlobAuthentication)
+    );
+}
+
+#[test]
+fn test_blob_stream_encryption() {
+    use std::io::{Read, Write};
+
+    let master_key = zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1);
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::derive_from_master_key(master_key);
+    let randomness = zkgroup::TEST_ARRAY_32_2;
+
+    let plaintext: Vec<u8> = (0..3 * zkgroup::BLOB_STREAM_CHUNK_LEN + 5)
+        .map(|i| i as u8)
+        .collect();
+
+    let mut writer = group_secret_params
+        .encrypt_blob_stream(randomness, Vec::new())
+        .unwrap();
+    for piece in plaintext.chunks(1000) {
+        writer.write_all(piece).unwrap();
+    }
+    let ciphertext = writer.finish().unwrap();
+
+    let mut decrypted = Vec::new();
+    group_secret_params
+        .decrypt_blob_stream(&ciphertext[..])
+        .read_to_end(&mut decrypted)
+        .unwrap();
+    assert!(decrypted == plaintext);
+
+    // The single-shot API accepts streamed blobs too.
+    assert!(group_secret_params.decrypt_blob(&ciphertext).unwrap() == plaintext);
+
+    // Dropping the last chunk is detected.
+    let truncated = &ciphertext[..ciphertext.len() - 5 - 16 - 1];
+    assert!(group_secret_params.decrypt_blob(truncated).is_err());
+    let mut decrypted = Vec::new();
+    let err = group_secret_params
+        .decrypt_blob_stream(truncated)
+        .read_to_end(&mut decrypted)
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_attribute_encryption() {
+    let master_key = zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1);
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::derive_from_master_key(master_key);
+    let group_public_params = group_secret_params.get_public_params();
+    let randomness = zkgroup::TEST_ARRAY_32_2;
+
+    let pni = zkgroup::TEST_ARRAY_16_1;
+    let pni_ciphertext = group_secret_params.encrypt_pni(pni);
+    assert_eq!(
+        group_secret_params.decrypt_pni(pni_ciphertext).unwrap(),
+        pni
+    );
+    let presentation = group_secret_params.create_pni_ciphertext_presentation(randomness, pni);
+    group_public_params
+        .verify_pni_ciphertext_presentation(pni_ciphertext, pni, &presentation)
+        .unwrap();
+    assert!(group_public_params
+        .verify_pni_ciphertext_presentation(pni_ciphertext, zkgroup::TEST_ARRAY_16, &presentation)
+        .is_err());
+
+    let e164 = "+14155550100";
+    let e164_ciphertext = group_secret_params.encrypt_e164(e164).unwrap();
+    assert_eq!(
+        group_secret_params.decrypt_e164(e164_ciphertext).unwrap(),
+        e164
+    );
+    let presentation = group_secret_params
+        .create_e164_ciphertext_presentation(randomness, e164)
+        .unwrap();
+    group_public_params
+        .verify_e164_ciphertext_presentation(e164_ciphertext, e164, &presentation)
+        .unwrap();
+    assert!(group_public_params
+        .verify_e164_ciphertext_presentation(e164_ciphertext, "+14155550101", &presentation)
+        .is_err());
+    assert!(matches!(
+        group_secret_params.encrypt_e164("14155550100"),
+        Err(zkgroup::ZkGroupError::InvalidAttribute { kind: "E.164" })
+    ));
+
+    let username = "alice.01";
+    let username_ciphertext = group_secret_params.encrypt_username(username).unwrap();
+    assert_eq!(
+        group_secret_params
+            .decrypt_username(username_ciphertext)
+            .unwrap(),
+        username
+    );
+    let presentation = group_secret_params
+        .create_username_ciphertext_presentation(randomness, username)
+        .unwrap();
+    group_public_params
+        .verify_username_ciphertext_presentation(username_ciphertext, username, &presentation)
+        .unwrap();
+    assert_eq!(
+        zkgroup::serialize(&presentation).len(),
+        zkgroup::ATTRIBUTE_CIPHERTEXT_PRESENTATION_LEN
+    );
+
+    // Ciphertexts of one kind don't decrypt as another.
+    let misread_username: zkgroup::groups::UsernameCiphertext =
+        zkgroup::deserialize(&zkgroup::serialize(&e164_ciphertext)).unwrap();
+    assert!(matches!(
+        group_secret_params.decrypt_username(misread_username),
+        Err(zkgroup::ZkGroupError::DecryptionFailure(
+            zkgroup::DecryptionError::AttributeCiphertext { kind: "username" }
+        ))
+    ));
+    assert_ne!(
+        zkgroup::serialize(&pni_ciphertext),
+        zkgroup::serialize(&group_secret_params.encrypt_uuid(pni))
+    );
+    let misread_uuid: zkgroup::groups::UuidCiphertext =
+        zkgroup::deserialize(&zkgroup::serialize(&pni_ciphertext)).unwrap();
+    assert!(matches!(
+        group_secret_params.decrypt_uuid(misread_uuid),
+        Err(zkgroup::ZkGroupError::DecryptionFailure(
+            zkgroup::DecryptionError::AttributeCiphertext { kind: "UID" }
+        ))
+    ));
 }
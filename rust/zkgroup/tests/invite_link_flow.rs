@@ -0,0 +1,90 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use zkgroup::groups::{GroupInviteLink, GroupMasterKey, GroupSecretParams, InviteLinkPresentation};
+use zkgroup::{
+    GROUP_INVITE_LINK_LEN, INVITE_LINK_PRESENTATION_LEN, INVITE_LINK_PUBLIC_KEY_LEN, TEST_ARRAY_16,
+    TEST_ARRAY_16_1, TEST_ARRAY_32, TEST_ARRAY_32_1, TEST_ARRAY_32_2,
+};
+
+#[test]
+fn test_invite_link_serialization() {
+    let master_key = GroupMasterKey::new(TEST_ARRAY_32);
+    let link = GroupInviteLink::generate(master_key, TEST_ARRAY_32_1);
+
+    let serialized = link.serialize();
+    assert_eq!(serialized.len(), GROUP_INVITE_LINK_LEN);
+    assert_eq!(serialized[0], 0);
+    assert_eq!(serialized[1..33], TEST_ARRAY_32[..]);
+
+    let decoded = GroupInviteLink::deserialize(&serialized).unwrap();
+    assert_eq!(decoded.get_password(), link.get_password());
+    assert_eq!(
+        decoded.get_group_secret_params().get_group_identifier(),
+        GroupSecretParams::derive_from_master_key(master_key).get_group_identifier()
+    );
+
+    // Unknown versions and bad lengths are rejected.
+    let mut future_version = serialized.clone();
+    future_version[0] = 1;
+    assert!(GroupInviteLink::deserialize(&future_version).is_err());
+    assert!(GroupInviteLink::deserialize(&serialized[1..]).is_err());
+    let mut too_long = serialized;
+    too_long.push(0);
+    assert!(GroupInviteLink::deserialize(&too_long).is_err());
+}
+
+#[test]
+fn test_invite_link_presentation() {
+    let master_key = GroupMasterKey::new(TEST_ARRAY_32);
+    let link = GroupInviteLink::generate(master_key, TEST_ARRAY_32_1);
+    let group_public_params = link.get_group_secret_params().get_public_params();
+
+    // Admin registers the public key with the server.
+    let public_key = link.get_public_key();
+    assert_eq!(
+        bincode::serialize(&public_key).unwrap().len(),
+        INVITE_LINK_PUBLIC_KEY_LEN
+    );
+
+    // Joining member proves they have the link.
+    let presentation = link.create_presentation(TEST_ARRAY_32_2, TEST_ARRAY_16);
+    assert_eq!(
+        bincode::serialize(&presentation).unwrap().len(),
+        INVITE_LINK_PRESENTATION_LEN
+    );
+    public_key
+        .verify_presentation(group_public_params, &presentation)
+        .unwrap();
+    assert!(
+        presentation.get_uuid_ciphertext()
+            == link.get_group_secret_params().encrypt_uuid(TEST_ARRAY_16)
+    );
+
+    // The proof is bound to the member's encrypted UID.
+    let other_presentation = link.create_presentation(TEST_ARRAY_32_2, TEST_ARRAY_16_1);
+    let other_ciphertext = bincode::serialize(&other_presentation.get_uuid_ciphertext()).unwrap();
+    let mut spliced_bytes = bincode::serialize(&presentation).unwrap();
+    // The ciphertext is the last field; skip the UuidCiphertext's reserved byte.
+    spliced_bytes[INVITE_LINK_PRESENTATION_LEN - 64..].copy_from_slice(&other_ciphertext[1..]);
+    let spliced: InviteLinkPresentation = bincode::deserialize(&spliced_bytes).unwrap();
+    assert!(public_key
+        .verify_presentation(group_public_params, &spliced)
+        .is_err());
+
+    // A link with a different password, or for a different group, doesn't verify.
+    let other_link = GroupInviteLink::generate(master_key, TEST_ARRAY_32_2);
+    let other_link_presentation = other_link.create_presentation(TEST_ARRAY_32_2, TEST_ARRAY_16);
+    assert!(public_key
+        .verify_presentation(group_public_params, &other_link_presentation)
+        .is_err());
+
+    let other_group_params =
+        GroupSecretParams::derive_from_master_key(GroupMasterKey::new(TEST_ARRAY_32_1))
+            .get_public_params();
+    assert!(public_key
+        .verify_presentation(other_group_params, &presentation)
+        .is_err());
+}
@@ -3,15 +3,19 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
-use zkgroup::api::receipts::ReceiptCredentialPresentation;
+use zkgroup::api::receipts::{
+    ExtendedReceiptCredentialPresentation, InMemoryReceiptRedemptionLedger,
+    ReceiptCredentialPresentation, ReceiptDisclosure,
+};
 use zkgroup::common::sho::Sho;
 use zkgroup::crypto::credentials;
 use zkgroup::crypto::proofs::{ReceiptCredentialIssuanceProof, ReceiptCredentialPresentationProof};
 use zkgroup::crypto::receipt_credential_request;
 use zkgroup::crypto::receipt_struct::ReceiptStruct;
 use zkgroup::{
-    RandomnessBytes, ReceiptExpirationTime, ReceiptLevel, ReceiptSerialBytes, ServerSecretParams,
-    RANDOMNESS_LEN, RECEIPT_SERIAL_LEN,
+    RandomnessBytes, ReceiptExpirationTime, ReceiptLevel, ReceiptPaymentType, ReceiptSerialBytes,
+    ReceiptValidityStartTime, ServerPublicParams, ServerSecretParams, ZkGroupError, RANDOMNESS_LEN,
+    RECEIPT_SERIAL_LEN, SERVER_PUBLIC_PARAMS_V0_LEN, SERVER_SECRET_PARAMS_V0_LEN,
 };
 
 #[test]
@@ -150,3 +154,380 @@ fn test_api() {
         .verify_receipt_credential_presentation(&bad_presentation)
         .expect_err("This Presentation Should Be Bad");
 }
+
+#[test]
+fn test_extended_api() {
+    let randomness0: RandomnessBytes = [0x42u8; RANDOMNESS_LEN];
+    let randomness1: RandomnessBytes = [0x43u8; RANDOMNESS_LEN];
+    let randomness2: RandomnessBytes = [0x44u8; RANDOMNESS_LEN];
+    let randomness3: RandomnessBytes = [0x45u8; RANDOMNESS_LEN];
+    let receipt_serial_bytes: ReceiptSerialBytes = [0x84u8; RECEIPT_SERIAL_LEN];
+    let server_secret_params = ServerSecretParams::generate(randomness0);
+    let server_public_params = server_secret_params.get_public_params();
+
+    // client
+    let context = server_public_params
+        .create_extended_receipt_credential_request_context(randomness1, receipt_serial_bytes)
+        .unwrap();
+    let request = context.get_request();
+
+    // issuance server
+    let receipt_expiration_time: ReceiptExpirationTime = 31337;
+    let receipt_level: ReceiptLevel = 3;
+    let payment_type: ReceiptPaymentType = 2;
+    let validity_start_time: ReceiptValidityStartTime = 86400;
+    let response = server_secret_params
+        .issue_extended_receipt_credential(
+            randomness2,
+            &request,
+            receipt_expiration_time,
+            receipt_level,
+            payment_type,
+            validity_start_time,
+        )
+        .unwrap();
+
+    // client
+    let credential = server_public_params
+        .receive_extended_receipt_credential(&context, &response)
+        .expect("Invalid Extended Receipt Credential Issuance");
+    assert_eq!(credential.get_payment_type(), payment_type);
+    assert_eq!(credential.get_validity_start_time(), validity_start_time);
+
+    assert_eq!(
+        zkgroup::common::constants::EXTENDED_RECEIPT_CREDENTIAL_RESPONSE_LEN,
+        bincode::serialize(&response).unwrap().len(),
+    );
+    assert_eq!(
+        zkgroup::common::constants::EXTENDED_RECEIPT_CREDENTIAL_LEN,
+        bincode::serialize(&credential).unwrap().len(),
+    );
+
+    let disclosures = [
+        ReceiptDisclosure::NONE,
+        ReceiptDisclosure {
+            payment_type: true,
+            validity_start_time: false,
+        },
+        ReceiptDisclosure {
+            payment_type: false,
+            validity_start_time: true,
+        },
+        ReceiptDisclosure::ALL,
+    ];
+    for disclosure in disclosures {
+        let presentation = server_public_params
+            .create_extended_receipt_credential_presentation(randomness3, &credential, disclosure)
+            .unwrap();
+        assert_eq!(
+            zkgroup::common::constants::EXTENDED_RECEIPT_CREDENTIAL_PRESENTATION_LEN,
+            bincode::serialize(&presentation).unwrap().len(),
+        );
+
+        // redemption server
+        server_secret_params
+            .verify_extended_receipt_credential_presentation(&presentation, disclosure)
+            .expect("Invalid Extended Receipt Credential Presentation");
+        assert_eq!(presentation.get_disclosure(), disclosure);
+        for required_disclosure in disclosures {
+            let result = server_secret_params.verify_extended_receipt_credential_presentation(
+                &presentation,
+                required_disclosure,
+            );
+            if required_disclosure.payment_type && !disclosure.payment_type {
+                assert_eq!(
+                    result,
+                    Err(ZkGroupError::ReceiptAttributeNotDisclosed {
+                        attribute: "payment type"
+                    })
+                );
+            } else if required_disclosure.validity_start_time && !disclosure.validity_start_time {
+                assert_eq!(
+                    result,
+                    Err(ZkGroupError::ReceiptAttributeNotDisclosed {
+                        attribute: "validity start time"
+                    })
+                );
+            } else {
+                result.expect("discloses everything required");
+            }
+        }
+        assert_eq!(presentation.get_receipt_level(), receipt_level);
+        assert_eq!(
+            presentation.get_payment_type(),
+            Some(payment_type).filter(|_| disclosure.payment_type)
+        );
+        assert_eq!(
+            presentation.get_validity_start_time(),
+            Some(validity_start_time).filter(|_| disclosure.validity_start_time)
+        );
+
+        // A disclosed attribute can't be swapped for another value, and a hidden attribute can't
+        // be revealed after the fact. The presentation ends with the disclosure flags followed
+        // by the two little-endian optional attributes.
+        let mut presentation_bytes = bincode::serialize(&presentation).unwrap();
+        let len = presentation_bytes.len();
+        presentation_bytes[len - 18] = 1;
+        presentation_bytes[len - 16] = 1;
+        let bad_presentation =
+            bincode::deserialize::<ExtendedReceiptCredentialPresentation>(&presentation_bytes)
+                .unwrap();
+        assert_eq!(bad_presentation.get_payment_type(), Some(1));
+        server_secret_params
+            .verify_extended_receipt_credential_presentation(
+                &bad_presentation,
+                ReceiptDisclosure::NONE,
+            )
+            .expect_err("This Presentation Should Be Bad");
+    }
+
+    // Nor can the always-disclosed level be changed.
+    let presentation = server_public_params
+        .create_extended_receipt_credential_presentation(
+            randomness3,
+            &credential,
+            ReceiptDisclosure::NONE,
+        )
+        .unwrap();
+    let mut presentation_bytes = bincode::serialize(&presentation).unwrap();
+    let i = presentation_bytes.len() - 42;
+    presentation_bytes[i] += 1;
+    let bad_presentation =
+        bincode::deserialize::<ExtendedReceiptCredentialPresentation>(&presentation_bytes).unwrap();
+    assert_eq!(bad_presentation.get_receipt_level(), receipt_level + 1);
+    server_secret_params
+        .verify_extended_receipt_credential_presentation(&bad_presentation, ReceiptDisclosure::NONE)
+        .expect_err("This Presentation Should Be Bad");
+}
+
+#[test]
+fn test_redemption() {
+    let randomness0: RandomnessBytes = [0x42u8; RANDOMNESS_LEN];
+    let randomness1: RandomnessBytes = [0x43u8; RANDOMNESS_LEN];
+    let randomness2: RandomnessBytes = [0x44u8; RANDOMNESS_LEN];
+    let server_secret_params = ServerSecretParams::generate(randomness0);
+    let server_public_params = server_secret_params.get_public_params();
+    let receipt_expiration_time: ReceiptExpirationTime = 86400 * 30;
+    let ledger = InMemoryReceiptRedemptionLedger::new();
+
+    let present = |receipt_serial_bytes: ReceiptSerialBytes| {
+        let context = server_public_params
+            .create_receipt_credential_request_context(randomness1, receipt_serial_bytes);
+        let response = server_secret_params.issue_receipt_credential(
+            randomness2,
+            &context.get_request(),
+            receipt_expiration_time,
+            1,
+        );
+        let credential = server_public_params
+            .receive_receipt_credential(&context, &response)
+            .unwrap();
+        server_public_params.create_receipt_credential_presentation(randomness2, &credential)
+    };
+
+    // An expired receipt is rejected without being recorded.
+    let presentation = present([0x01u8; RECEIPT_SERIAL_LEN]);
+    assert_eq!(
+        server_secret_params.redeem_receipt_credential_presentation(
+            &presentation,
+            receipt_expiration_time,
+            &ledger
+        ),
+        Err(ZkGroupError::ReceiptExpired {
+            expiration_time: receipt_expiration_time,
+            current_time: receipt_expiration_time,
+        })
+    );
+    assert!(ledger.is_empty());
+
+    server_secret_params
+        .redeem_receipt_credential_presentation(&presentation, 86400, &ledger)
+        .expect("first redemption succeeds");
+    assert_eq!(
+        server_secret_params.redeem_receipt_credential_presentation(&presentation, 86400, &ledger),
+        Err(ZkGroupError::ReceiptAlreadyRedeemed)
+    );
+
+    // A fresh presentation of the same receipt is still a double spend.
+    let context = server_public_params
+        .create_receipt_credential_request_context(randomness2, [0x01u8; RECEIPT_SERIAL_LEN]);
+    let response = server_secret_params.issue_receipt_credential(
+        randomness1,
+        &context.get_request(),
+        receipt_expiration_time,
+        1,
+    );
+    let credential = server_public_params
+        .receive_receipt_credential(&context, &response)
+        .unwrap();
+    let presentation =
+        server_public_params.create_receipt_credential_presentation(randomness1, &credential);
+    assert_eq!(
+        server_secret_params.redeem_receipt_credential_presentation(&presentation, 86400, &ledger),
+        Err(ZkGroupError::ReceiptAlreadyRedeemed)
+    );
+
+    // Other receipts are unaffected.
+    server_secret_params
+        .redeem_receipt_credential_presentation(
+            &present([0x02u8; RECEIPT_SERIAL_LEN]),
+            86400,
+            &ledger,
+        )
+        .expect("different serial");
+    assert_eq!(ledger.len(), 2);
+}
+
+#[test]
+fn test_extended_redemption() {
+    let randomness0: RandomnessBytes = [0x42u8; RANDOMNESS_LEN];
+    let randomness1: RandomnessBytes = [0x43u8; RANDOMNESS_LEN];
+    let randomness2: RandomnessBytes = [0x44u8; RANDOMNESS_LEN];
+    let server_secret_params = ServerSecretParams::generate(randomness0);
+    let server_public_params = server_secret_params.get_public_params();
+    let receipt_expiration_time: ReceiptExpirationTime = 86400 * 30;
+    let validity_start_time: ReceiptValidityStartTime = 86400 * 2;
+    let ledger = InMemoryReceiptRedemptionLedger::new();
+
+    let present = |receipt_serial_bytes: ReceiptSerialBytes, disclosure: ReceiptDisclosure| {
+        let context = server_public_params
+            .create_extended_receipt_credential_request_context(randomness1, receipt_serial_bytes)
+            .unwrap();
+        let response = server_secret_params
+            .issue_extended_receipt_credential(
+                randomness2,
+                &context.get_request(),
+                receipt_expiration_time,
+                1,
+                2,
+                validity_start_time,
+            )
+            .unwrap();
+        let credential = server_public_params
+            .receive_extended_receipt_credential(&context, &response)
+            .unwrap();
+        server_public_params
+            .create_extended_receipt_credential_presentation(randomness2, &credential, disclosure)
+            .unwrap()
+    };
+
+    // A receipt whose disclosed validity start time is still in the future is rejected without
+    // being recorded.
+    let presentation = present([0x01u8; RECEIPT_SERIAL_LEN], ReceiptDisclosure::ALL);
+    assert_eq!(
+        server_secret_params.redeem_extended_receipt_credential_presentation(
+            &presentation,
+            ReceiptDisclosure::NONE,
+            validity_start_time - 1,
+            &ledger
+        ),
+        Err(ZkGroupError::ReceiptNotYetValid {
+            validity_start_time,
+            current_time: validity_start_time - 1,
+        })
+    );
+    assert!(ledger.is_empty());
+
+    server_secret_params
+        .redeem_extended_receipt_credential_presentation(
+            &presentation,
+            ReceiptDisclosure::NONE,
+            validity_start_time,
+            &ledger,
+        )
+        .expect("valid from its start time");
+    assert_eq!(
+        server_secret_params.redeem_extended_receipt_credential_presentation(
+            &presentation,
+            ReceiptDisclosure::NONE,
+            validity_start_time,
+            &ledger
+        ),
+        Err(ZkGroupError::ReceiptAlreadyRedeemed)
+    );
+
+    // A receipt that hides its start time can't be redeemed, even one that is not yet valid.
+    assert_eq!(
+        server_secret_params.redeem_extended_receipt_credential_presentation(
+            &present([0x02u8; RECEIPT_SERIAL_LEN], ReceiptDisclosure::NONE),
+            ReceiptDisclosure::NONE,
+            validity_start_time - 1,
+            &ledger
+        ),
+        Err(ZkGroupError::ReceiptAttributeNotDisclosed {
+            attribute: "validity start time"
+        })
+    );
+    assert_eq!(ledger.len(), 1);
+}
+
+#[test]
+fn test_server_params_without_extended_receipts() {
+    let randomness0: RandomnessBytes = [0x42u8; RANDOMNESS_LEN];
+    let randomness1: RandomnessBytes = [0x43u8; RANDOMNESS_LEN];
+    let randomness2: RandomnessBytes = [0x44u8; RANDOMNESS_LEN];
+    let server_secret_params = ServerSecretParams::generate(randomness0);
+    let server_public_params = server_secret_params.get_public_params();
+
+    // Version 0 params are the current ones without the trailing keys added in version 1.
+    let mut secret_bytes = zkgroup::serialize(&server_secret_params);
+    secret_bytes.truncate(SERVER_SECRET_PARAMS_V0_LEN);
+    secret_bytes[0] = 0;
+    let legacy_secret_params: ServerSecretParams = zkgroup::deserialize(&secret_bytes).unwrap();
+    assert_eq!(zkgroup::serialize(&legacy_secret_params), secret_bytes);
+
+    let mut public_bytes = zkgroup::serialize(&server_public_params);
+    public_bytes.truncate(SERVER_PUBLIC_PARAMS_V0_LEN);
+    public_bytes[0] = 0;
+    let legacy_public_params: ServerPublicParams = zkgroup::deserialize(&public_bytes).unwrap();
+    assert_eq!(zkgroup::serialize(&legacy_public_params), public_bytes);
+    assert_eq!(
+        zkgroup::serialize(&legacy_secret_params.get_public_params()),
+        public_bytes
+    );
+
+    let missing_key = || ZkGroupError::MissingCredentialKey {
+        credential: "extended receipt",
+    };
+    let extended_context = legacy_public_params
+        .create_extended_receipt_credential_request_context(
+            randomness1,
+            [0x01u8; RECEIPT_SERIAL_LEN],
+        )
+        .unwrap();
+    let extended_request = extended_context.get_request();
+    assert_eq!(
+        legacy_secret_params
+            .issue_extended_receipt_credential(randomness2, &extended_request, 86400, 1, 2, 0)
+            .err(),
+        Some(missing_key())
+    );
+    let response = server_secret_params
+        .issue_extended_receipt_credential(randomness2, &extended_request, 86400, 1, 2, 0)
+        .unwrap();
+    assert_eq!(
+        legacy_public_params
+            .receive_extended_receipt_credential(&extended_context, &response)
+            .err(),
+        Some(missing_key())
+    );
+
+    let context = legacy_public_params
+        .create_receipt_credential_request_context(randomness1, [0x01u8; RECEIPT_SERIAL_LEN]);
+
+    // Plain receipts still work.
+    let response = legacy_secret_params.issue_receipt_credential(
+        randomness2,
+        &context.get_request(),
+        86400,
+        1,
+    );
+    let credential = legacy_public_params
+        .receive_receipt_credential(&context, &response)
+        .unwrap();
+    let presentation =
+        legacy_public_params.create_receipt_credential_presentation(randomness2, &credential);
+    legacy_secret_params
+        .verify_receipt_credential_presentation(&presentation)
+        .unwrap();
+}
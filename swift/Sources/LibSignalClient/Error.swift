@@ -35,6 +35,7 @@ public enum SignalError: Error {
     case invalidSenderKeySession(distributionId: UUID, message: String)
     case duplicatedMessage(String)
     case verificationFailed(String)
+    case invalidRedemptionTime(String)
     case callbackError(String)
     case unknown(UInt32, String)
 }
@@ -106,6 +107,8 @@ internal func checkError(_ error: SignalFfiErrorRef?) throws {
         throw SignalError.duplicatedMessage(errStr)
     case SignalErrorCode_VerificationFailure:
         throw SignalError.verificationFailed(errStr)
+    case SignalErrorCode_InvalidRedemptionTime:
+        throw SignalError.invalidRedemptionTime(errStr)
     case SignalErrorCode_CallbackError:
         throw SignalError.callbackError(errStr)
     default:
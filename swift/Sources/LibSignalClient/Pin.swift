@@ -0,0 +1,116 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import SignalFfi
+import Foundation
+
+/// Puts a PIN into the canonical form that is hashed.
+///
+/// Surrounding whitespace is removed, PINs made up of decimal digits in any script are converted
+/// to ASCII digits, and anything else is put into Unicode normalization form NFKD.
+public func normalizePin(_ pin: String) -> String {
+    return failOnError {
+        try invokeFnReturningString {
+            signal_pin_normalize($0, pin)
+        }
+    }
+}
+
+/// A PIN stretched with Argon2id, split into a key for wrapping the `MasterKey` and a key for
+/// proving knowledge of the PIN to the recovery service.
+public class PinHash: NativeHandleOwner {
+    /// Hashes a PIN that has already been passed through `normalizePin(_:)`.
+    ///
+    /// `salt` must be 32 bytes unique to the account, such as the recovery service's backup ID.
+    public convenience init<PinBytes, SaltBytes>(normalizedPin: PinBytes, salt: SaltBytes) throws
+    where PinBytes: ContiguousBytes, SaltBytes: ContiguousBytes {
+        let handle: OpaquePointer? = try normalizedPin.withUnsafeBorrowedBuffer { pinBuffer in
+            try salt.withUnsafeBorrowedBuffer { saltBuffer in
+                var result: OpaquePointer?
+                try checkError(signal_pin_hash_create(&result, pinBuffer, saltBuffer))
+                return result
+            }
+        }
+        self.init(owned: handle!)
+    }
+
+    internal override class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
+        return signal_pin_hash_destroy(handle)
+    }
+
+    /// The key used to wrap the master key; never leaves the device.
+    public var encryptionKey: [UInt8] {
+        return withNativeHandle { nativeHandle in
+            failOnError {
+                try invokeFnReturningArray {
+                    signal_pin_hash_encryption_key($0, $1, nativeHandle)
+                }
+            }
+        }
+    }
+
+    /// The key sent to the recovery service in place of the PIN.
+    public var accessKey: [UInt8] {
+        return withNativeHandle { nativeHandle in
+            failOnError {
+                try invokeFnReturningArray {
+                    signal_pin_hash_access_key($0, $1, nativeHandle)
+                }
+            }
+        }
+    }
+}
+
+/// The account's master key, from which the registration lock token is derived.
+public struct MasterKey {
+    public static let size = 32
+
+    public let bytes: [UInt8]
+
+    public init<Bytes: ContiguousBytes>(_ bytes: Bytes) throws {
+        let bytes = bytes.withUnsafeBytes { Array($0) }
+        guard bytes.count == MasterKey.size else {
+            throw SignalError.invalidKey("master key must be \(MasterKey.size) bytes")
+        }
+        self.bytes = bytes
+    }
+
+    /// Encrypts the master key under a PIN's encryption key for upload to the recovery service.
+    public func wrap(_ pinHash: PinHash) -> [UInt8] {
+        return bytes.withUnsafeBorrowedBuffer { masterKeyBuffer in
+            pinHash.withNativeHandle { pinHashHandle in
+                failOnError {
+                    try invokeFnReturningArray {
+                        signal_master_key_wrap($0, $1, masterKeyBuffer, pinHashHandle)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reverses `wrap(_:)`, throwing `SignalError.invalidMessage` if the PIN is wrong or
+    /// `wrapped` has been modified.
+    public static func unwrap<Bytes: ContiguousBytes>(_ wrapped: Bytes, with pinHash: PinHash) throws -> MasterKey {
+        let bytes = try wrapped.withUnsafeBorrowedBuffer { wrappedBuffer in
+            try pinHash.withNativeHandle { pinHashHandle in
+                try invokeFnReturningArray {
+                    signal_master_key_unwrap($0, $1, pinHashHandle, wrappedBuffer)
+                }
+            }
+        }
+        return try MasterKey(bytes)
+    }
+
+    /// The registration lock token, as the lowercase hex string the server expects.
+    public var registrationLock: String {
+        return bytes.withUnsafeBorrowedBuffer { masterKeyBuffer in
+            failOnError {
+                try invokeFnReturningString {
+                    signal_master_key_registration_lock($0, masterKeyBuffer)
+                }
+            }
+        }
+    }
+}
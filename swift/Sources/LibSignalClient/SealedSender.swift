@@ -218,6 +218,8 @@ public struct SealedSenderAddress: Hashable {
 public struct SealedSenderResult {
     public var message: [UInt8]
     public var sender: SealedSenderAddress
+    /// The trust root that the sender certificate was validated against.
+    public var trustRoot: PublicKey
 }
 
 public func sealedSenderDecrypt<Bytes: ContiguousBytes>(message: Bytes,
@@ -229,35 +231,65 @@ public func sealedSenderDecrypt<Bytes: ContiguousBytes>(message: Bytes,
                                                         preKeyStore: PreKeyStore,
                                                         signedPreKeyStore: SignedPreKeyStore,
                                                         context: StoreContext) throws -> SealedSenderResult {
+    return try sealedSenderDecrypt(message: message,
+                                   from: localAddress,
+                                   trustRoots: [TrustRoot(trustRoot)],
+                                   timestamp: timestamp,
+                                   sessionStore: sessionStore,
+                                   identityStore: identityStore,
+                                   preKeyStore: preKeyStore,
+                                   signedPreKeyStore: signedPreKeyStore,
+                                   context: context)
+}
+
+/// Like `sealedSenderDecrypt(message:from:trustRoot:...)`, but accepts a sender certificate
+/// signed by any of `trustRoots` that is valid at `timestamp`.
+public func sealedSenderDecrypt<Bytes: ContiguousBytes>(message: Bytes,
+                                                        from localAddress: SealedSenderAddress,
+                                                        trustRoots: [TrustRoot],
+                                                        timestamp: UInt64,
+                                                        sessionStore: SessionStore,
+                                                        identityStore: IdentityKeyStore,
+                                                        preKeyStore: PreKeyStore,
+                                                        signedPreKeyStore: SignedPreKeyStore,
+                                                        context: StoreContext) throws -> SealedSenderResult {
     var senderE164: UnsafePointer<CChar>?
     var senderUUID: UnsafePointer<CChar>?
     var senderDeviceId: UInt32 = 0
+    var senderTrustRoot: OpaquePointer?
 
-    let plaintext = try trustRoot.withNativeHandle { trustRootHandle in
-        try message.withUnsafeBorrowedBuffer { messageBuffer in
-            try context.withOpaquePointer { context in
-                try withSessionStore(sessionStore) { ffiSessionStore in
-                    try withIdentityKeyStore(identityStore) { ffiIdentityStore in
-                        try withPreKeyStore(preKeyStore) { ffiPreKeyStore in
-                            try withSignedPreKeyStore(signedPreKeyStore) { ffiSignedPreKeyStore in
-                                try invokeFnReturningArray {
-                                    signal_sealed_session_cipher_decrypt(
-                                        $0,
-                                        $1,
-                                        &senderE164,
-                                        &senderUUID,
-                                        &senderDeviceId,
-                                        messageBuffer,
-                                        trustRootHandle,
-                                        timestamp,
-                                        localAddress.e164,
-                                        localAddress.uuidString,
-                                        localAddress.deviceId,
-                                        ffiSessionStore,
-                                        ffiIdentityStore,
-                                        ffiPreKeyStore,
-                                        ffiSignedPreKeyStore,
-                                        context)
+    // Use withExtendedLifetime instead of withNativeHandle for the array of wrapper objects,
+    // which isn't compatible with withNativeHandle's simple lexical scoping.
+    let plaintext = try withExtendedLifetime(trustRoots) { () -> [UInt8] in
+        let trustRootHandles = trustRoots.map { $0.unsafeNativeHandle }
+        return try trustRootHandles.withUnsafeBufferPointer { trustRootHandles in
+            let trustRootHandlesBuffer = SignalBorrowedSliceOfTrustRoot(base: trustRootHandles.baseAddress, length: UInt(trustRootHandles.count))
+            return try message.withUnsafeBorrowedBuffer { messageBuffer in
+                try context.withOpaquePointer { context in
+                    try withSessionStore(sessionStore) { ffiSessionStore in
+                        try withIdentityKeyStore(identityStore) { ffiIdentityStore in
+                            try withPreKeyStore(preKeyStore) { ffiPreKeyStore in
+                                try withSignedPreKeyStore(signedPreKeyStore) { ffiSignedPreKeyStore in
+                                    try invokeFnReturningArray {
+                                        signal_sealed_session_cipher_decrypt(
+                                            $0,
+                                            $1,
+                                            &senderE164,
+                                            &senderUUID,
+                                            &senderDeviceId,
+                                            &senderTrustRoot,
+                                            messageBuffer,
+                                            trustRootHandlesBuffer,
+                                            timestamp,
+                                            localAddress.e164,
+                                            localAddress.uuidString,
+                                            localAddress.deviceId,
+                                            ffiSessionStore,
+                                            ffiIdentityStore,
+                                            ffiPreKeyStore,
+                                            ffiSignedPreKeyStore,
+                                            context)
+                                    }
                                 }
                             }
                         }
@@ -275,5 +307,6 @@ public func sealedSenderDecrypt<Bytes: ContiguousBytes>(message: Bytes,
     return SealedSenderResult(message: plaintext,
                               sender: try SealedSenderAddress(e164: senderE164.map(String.init(cString:)),
                                                               uuidString: String(cString: senderUUID!),
-                                                              deviceId: senderDeviceId))
+                                                              deviceId: senderDeviceId),
+                              trustRoot: PublicKey(owned: senderTrustRoot!))
 }
@@ -211,4 +211,59 @@ public class SenderCertificate: NativeHandleOwner {
         }
         return result
     }
+
+    /// Validates the certificate against each of `trustRoots` that is valid at `time`.
+    ///
+    /// Returns the key of the trust root that signed the certificate, or `nil` if none did.
+    public func validate(trustRoots: [TrustRoot], time: UInt64) throws -> PublicKey? {
+        // Use withExtendedLifetime instead of withNativeHandle for the array of wrapper objects,
+        // which isn't compatible with withNativeHandle's simple lexical scoping.
+        return try withExtendedLifetime(trustRoots) {
+            let trustRootHandles = trustRoots.map { $0.unsafeNativeHandle }
+            return try withNativeHandle { certificateHandle in
+                try trustRootHandles.withUnsafeBufferPointer { trustRootHandles in
+                    let trustRootHandlesBuffer = SignalBorrowedSliceOfTrustRoot(base: trustRootHandles.baseAddress, length: UInt(trustRootHandles.count))
+                    return try invokeFnReturningOptionalNativeHandle {
+                        signal_sender_certificate_validate_with_trust_roots($0, certificateHandle, trustRootHandlesBuffer, time)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A key that server certificates may be signed with, optionally only trusted within a window of
+/// time.
+public class TrustRoot: NativeHandleOwner {
+    /// A trust root that is always valid.
+    public convenience init(_ key: PublicKey) {
+        var result: OpaquePointer?
+        key.withNativeHandle { keyHandle in
+            failOnError(signal_trust_root_new(&result, keyHandle))
+        }
+        self.init(owned: result!)
+    }
+
+    /// A trust root that is only valid from `notBefore` through `notAfter`, inclusive.
+    public convenience init(_ key: PublicKey, notBefore: UInt64, notAfter: UInt64) {
+        var result: OpaquePointer?
+        key.withNativeHandle { keyHandle in
+            failOnError(signal_trust_root_new_with_validity(&result, keyHandle, notBefore, notAfter))
+        }
+        self.init(owned: result!)
+    }
+
+    internal override class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
+        return signal_trust_root_destroy(handle)
+    }
+
+    public var key: PublicKey {
+        return withNativeHandle { nativeHandle in
+            failOnError {
+                try invokeFnReturningNativeHandle {
+                    signal_trust_root_get_key($0, nativeHandle)
+                }
+            }
+        }
+    }
 }
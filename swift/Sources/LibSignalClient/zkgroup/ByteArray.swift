@@ -16,6 +16,13 @@ public class ByteArray {
         }
     }
 
+    init(_ newContents: [UInt8], checkValid: (SignalBorrowedBuffer) -> SignalFfiErrorRef?) throws {
+        contents = newContents
+        try withUnsafeBorrowedBuffer { contents in
+            try checkError(checkValid(contents))
+        }
+    }
+
     init(newContents: [UInt8], expectedLength: Int, unrecoverable: Bool = false) throws {
         if newContents.count != expectedLength {
             throw SignalError.invalidType("\(type(of: self)) uses \(expectedLength) bytes, but tried to deserialize from an array of \(newContents.count) bytes")
@@ -31,6 +38,13 @@ public class ByteArray {
         return contents
     }
 
+    /// Passes the serialized contents to `callback` as a borrowed buffer of any length.
+    ///
+    /// Used for types whose serialized length depends on their version.
+    func withUnsafeBorrowedBuffer<Result>(_ callback: (SignalBorrowedBuffer) throws -> Result) rethrows -> Result {
+        try contents.withUnsafeBorrowedBuffer(callback)
+    }
+
     /// Passes a pointer to the serialized contents to `callback`.
     ///
     /// This pointer is only valid during the call to `callback` and should not be persisted.
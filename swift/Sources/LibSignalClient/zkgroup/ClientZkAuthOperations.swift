@@ -15,7 +15,7 @@ public class ClientZkAuthOperations {
   }
 
   public func receiveAuthCredential(uuid: UUID, redemptionTime: UInt32, authCredentialResponse: AuthCredentialResponse) throws -> AuthCredential {
-    return try serverPublicParams.withUnsafePointerToSerialized { serverPublicParams in
+    return try serverPublicParams.withUnsafeBorrowedBuffer { serverPublicParams in
       try withUnsafePointer(to: uuid.uuid) { uuid in
         try authCredentialResponse.withUnsafePointerToSerialized { authCredentialResponse in
           try invokeFnReturningSerialized {
@@ -31,7 +31,7 @@ public class ClientZkAuthOperations {
   }
 
   public func createAuthCredentialPresentation(randomness: Randomness, groupSecretParams: GroupSecretParams, authCredential: AuthCredential) throws -> AuthCredentialPresentation {
-    return try serverPublicParams.withUnsafePointerToSerialized { contents in
+    return try serverPublicParams.withUnsafeBorrowedBuffer { contents in
       try randomness.withUnsafePointerToBytes { randomness in
         try groupSecretParams.withUnsafePointerToSerialized { groupSecretParams in
           try authCredential.withUnsafePointerToSerialized { authCredential in
@@ -19,7 +19,7 @@ public class ClientZkProfileOperations {
   }
 
   public func createProfileKeyCredentialRequestContext(randomness: Randomness, uuid: UUID, profileKey: ProfileKey) throws -> ProfileKeyCredentialRequestContext {
-    return try serverPublicParams.withUnsafePointerToSerialized { serverPublicParams in
+    return try serverPublicParams.withUnsafeBorrowedBuffer { serverPublicParams in
       try randomness.withUnsafePointerToBytes { randomness in
         try withUnsafePointer(to: uuid.uuid) { uuid in
           try profileKey.withUnsafePointerToSerialized { profileKey in
@@ -37,7 +37,7 @@ public class ClientZkProfileOperations {
   }
 
   public func createPniCredentialRequestContext(randomness: Randomness, aci: UUID, pni: UUID, profileKey: ProfileKey) throws -> PniCredentialRequestContext {
-    return try serverPublicParams.withUnsafePointerToSerialized { serverPublicParams in
+    return try serverPublicParams.withUnsafeBorrowedBuffer { serverPublicParams in
       try randomness.withUnsafePointerToBytes { randomness in
         try withUnsafePointer(to: aci.uuid) { aci in
           try withUnsafePointer(to: pni.uuid) { pni in
@@ -53,7 +53,7 @@ public class ClientZkProfileOperations {
   }
 
   public func receiveProfileKeyCredential(profileKeyCredentialRequestContext: ProfileKeyCredentialRequestContext, profileKeyCredentialResponse: ProfileKeyCredentialResponse) throws -> ProfileKeyCredential {
-    return try serverPublicParams.withUnsafePointerToSerialized { serverPublicParams in
+    return try serverPublicParams.withUnsafeBorrowedBuffer { serverPublicParams in
       try profileKeyCredentialRequestContext.withUnsafePointerToSerialized { requestContext in
         try profileKeyCredentialResponse.withUnsafePointerToSerialized { response in
           try invokeFnReturningSerialized {
@@ -65,7 +65,7 @@ public class ClientZkProfileOperations {
   }
 
   public func receivePniCredential(requestContext: PniCredentialRequestContext, response: PniCredentialResponse) throws -> PniCredential {
-    return try serverPublicParams.withUnsafePointerToSerialized { serverPublicParams in
+    return try serverPublicParams.withUnsafeBorrowedBuffer { serverPublicParams in
       try requestContext.withUnsafePointerToSerialized { requestContext in
         try response.withUnsafePointerToSerialized { response in
           try invokeFnReturningSerialized {
@@ -81,7 +81,7 @@ public class ClientZkProfileOperations {
   }
 
   public func createProfileKeyCredentialPresentation(randomness: Randomness, groupSecretParams: GroupSecretParams, profileKeyCredential: ProfileKeyCredential) throws -> ProfileKeyCredentialPresentation {
-    return try serverPublicParams.withUnsafePointerToSerialized { serverPublicParams in
+    return try serverPublicParams.withUnsafeBorrowedBuffer { serverPublicParams in
       try randomness.withUnsafePointerToBytes { randomness in
         try groupSecretParams.withUnsafePointerToSerialized { groupSecretParams in
           try profileKeyCredential.withUnsafePointerToSerialized { profileKeyCredential in
@@ -99,7 +99,7 @@ public class ClientZkProfileOperations {
   }
 
   public func createPniCredentialPresentation(randomness: Randomness, groupSecretParams: GroupSecretParams, credential: PniCredential) throws -> PniCredentialPresentation {
-    return try serverPublicParams.withUnsafePointerToSerialized { serverPublicParams in
+    return try serverPublicParams.withUnsafeBorrowedBuffer { serverPublicParams in
       try randomness.withUnsafePointerToBytes { randomness in
         try groupSecretParams.withUnsafePointerToSerialized { groupSecretParams in
           try credential.withUnsafePointerToSerialized { credential in
@@ -19,7 +19,7 @@ public class ClientZkReceiptOperations {
   }
 
   public func createReceiptCredentialRequestContext(randomness: Randomness, receiptSerial: ReceiptSerial) throws -> ReceiptCredentialRequestContext {
-    return try serverPublicParams.withUnsafePointerToSerialized { serverPublicParams in
+    return try serverPublicParams.withUnsafeBorrowedBuffer { serverPublicParams in
       try randomness.withUnsafePointerToBytes { randomness in
         try receiptSerial.withUnsafePointerToSerialized { receiptSerial in
           try invokeFnReturningSerialized {
@@ -31,7 +31,7 @@ public class ClientZkReceiptOperations {
   }
 
   public func receiveReceiptCredential(receiptCredentialRequestContext: ReceiptCredentialRequestContext, receiptCredentialResponse: ReceiptCredentialResponse) throws -> ReceiptCredential {
-    return try serverPublicParams.withUnsafePointerToSerialized { serverPublicParams in
+    return try serverPublicParams.withUnsafeBorrowedBuffer { serverPublicParams in
       try receiptCredentialRequestContext.withUnsafePointerToSerialized { requestContext in
         try receiptCredentialResponse.withUnsafePointerToSerialized { response in
           try invokeFnReturningSerialized {
@@ -47,7 +47,7 @@ public class ClientZkReceiptOperations {
   }
 
   public func createReceiptCredentialPresentation(randomness: Randomness, receiptCredential: ReceiptCredential) throws -> ReceiptCredentialPresentation {
-    return try serverPublicParams.withUnsafePointerToSerialized { serverPublicParams in
+    return try serverPublicParams.withUnsafeBorrowedBuffer { serverPublicParams in
       try randomness.withUnsafePointerToBytes { randomness in
         try receiptCredential.withUnsafePointerToSerialized { receiptCredential in
           try invokeFnReturningSerialized {
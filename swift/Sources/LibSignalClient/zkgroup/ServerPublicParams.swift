@@ -13,7 +13,7 @@ public class ServerPublicParams: ByteArray {
   }
 
   public func verifySignature(message: [UInt8], notarySignature: NotarySignature) throws {
-    try withUnsafePointerToSerialized { contents in
+    try withUnsafeBorrowedBuffer { contents in
       try message.withUnsafeBorrowedBuffer { message in
         try notarySignature.withUnsafePointerToSerialized { notarySignature in
           try checkError(signal_server_public_params_verify_signature(contents, message, notarySignature))
@@ -14,9 +14,9 @@ public class ServerSecretParams: ByteArray {
 
   public static func generate(randomness: Randomness) throws -> ServerSecretParams {
     return try randomness.withUnsafePointerToBytes { randomness in
-      try invokeFnReturningSerialized {
-        signal_server_secret_params_generate_deterministic($0, randomness)
-      }
+      try ServerSecretParams(contents: invokeFnReturningArray {
+        signal_server_secret_params_generate_deterministic($0, $1, randomness)
+      })
     }
   }
 
@@ -25,10 +25,10 @@ public class ServerSecretParams: ByteArray {
   }
 
   public func getPublicParams() throws -> ServerPublicParams {
-    return try withUnsafePointerToSerialized { contents in
-      try invokeFnReturningSerialized {
-        signal_server_secret_params_get_public_params($0, contents)
-      }
+    return try withUnsafeBorrowedBuffer { contents in
+      try ServerPublicParams(contents: invokeFnReturningArray {
+        signal_server_secret_params_get_public_params($0, $1, contents)
+      })
     }
   }
 
@@ -37,7 +37,7 @@ public class ServerSecretParams: ByteArray {
   }
 
   public func sign(randomness: Randomness, message: [UInt8]) throws -> NotarySignature {
-    return try withUnsafePointerToSerialized { contents in
+    return try withUnsafeBorrowedBuffer { contents in
       try randomness.withUnsafePointerToBytes { randomness in
         try message.withUnsafeBorrowedBuffer { message in
           try invokeFnReturningSerialized {
@@ -19,7 +19,7 @@ public class ServerZkAuthOperations {
   }
 
   public func issueAuthCredential(randomness: Randomness, uuid: UUID, redemptionTime: UInt32) throws -> AuthCredentialResponse {
-    return try serverSecretParams.withUnsafePointerToSerialized { serverSecretParams in
+    return try serverSecretParams.withUnsafeBorrowedBuffer { serverSecretParams in
       try randomness.withUnsafePointerToBytes { randomness in
         try withUnsafePointer(to: uuid.uuid) { uuid in
           try invokeFnReturningSerialized {
@@ -31,7 +31,7 @@ public class ServerZkAuthOperations {
   }
 
   public func verifyAuthCredentialPresentation(groupPublicParams: GroupPublicParams, authCredentialPresentation: AuthCredentialPresentation) throws {
-    try serverSecretParams.withUnsafePointerToSerialized { serverSecretParams in
+    try serverSecretParams.withUnsafeBorrowedBuffer { serverSecretParams in
       try groupPublicParams.withUnsafePointerToSerialized { groupPublicParams in
         try authCredentialPresentation.withUnsafePointerToSerialized { authCredentialPresentation in
           try checkError(signal_server_secret_params_verify_auth_credential_presentation(serverSecretParams, groupPublicParams, authCredentialPresentation))
@@ -19,7 +19,7 @@ public class ServerZkProfileOperations {
   }
 
   public func issueProfileKeyCredential(randomness: Randomness, profileKeyCredentialRequest: ProfileKeyCredentialRequest, uuid: UUID, profileKeyCommitment: ProfileKeyCommitment) throws -> ProfileKeyCredentialResponse {
-    return try serverSecretParams.withUnsafePointerToSerialized { serverSecretParams in
+    return try serverSecretParams.withUnsafeBorrowedBuffer { serverSecretParams in
       try randomness.withUnsafePointerToBytes { randomness in
         try profileKeyCredentialRequest.withUnsafePointerToSerialized { request in
           try withUnsafePointer(to: uuid.uuid) { uuid in
@@ -39,7 +39,7 @@ public class ServerZkProfileOperations {
   }
 
   public func issuePniCredential(randomness: Randomness, profileKeyCredentialRequest: ProfileKeyCredentialRequest, aci: UUID, pni: UUID, profileKeyCommitment: ProfileKeyCommitment) throws -> PniCredentialResponse {
-    return try serverSecretParams.withUnsafePointerToSerialized { serverSecretParams in
+    return try serverSecretParams.withUnsafeBorrowedBuffer { serverSecretParams in
       try randomness.withUnsafePointerToBytes { randomness in
         try profileKeyCredentialRequest.withUnsafePointerToSerialized { request in
           try withUnsafePointer(to: aci.uuid) { aci in
@@ -57,7 +57,7 @@ public class ServerZkProfileOperations {
   }
 
   public func verifyProfileKeyCredentialPresentation(groupPublicParams: GroupPublicParams, profileKeyCredentialPresentation: ProfileKeyCredentialPresentation) throws {
-    try serverSecretParams.withUnsafePointerToSerialized { serverSecretParams in
+    try serverSecretParams.withUnsafeBorrowedBuffer { serverSecretParams in
       try groupPublicParams.withUnsafePointerToSerialized { groupPublicParams in
         try profileKeyCredentialPresentation.withUnsafePointerToSerialized { presentation in
           try checkError(signal_server_secret_params_verify_profile_key_credential_presentation(serverSecretParams, groupPublicParams, presentation))
@@ -67,7 +67,7 @@ public class ServerZkProfileOperations {
   }
 
   public func verifyPniCredentialPresentation(groupPublicParams: GroupPublicParams, presentation: PniCredentialPresentation) throws {
-    try serverSecretParams.withUnsafePointerToSerialized { serverSecretParams in
+    try serverSecretParams.withUnsafeBorrowedBuffer { serverSecretParams in
       try groupPublicParams.withUnsafePointerToSerialized { groupPublicParams in
         try presentation.withUnsafePointerToSerialized { presentation in
           try checkError(signal_server_secret_params_verify_pni_credential_presentation(serverSecretParams, groupPublicParams, presentation))
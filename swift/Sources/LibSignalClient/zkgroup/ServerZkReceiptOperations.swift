@@ -19,7 +19,7 @@ public class ServerZkReceiptOperations {
   }
 
   public func issueReceiptCredential(randomness: Randomness, receiptCredentialRequest: ReceiptCredentialRequest, receiptExpirationTime: UInt64, receiptLevel: UInt64) throws -> ReceiptCredentialResponse {
-    return try serverSecretParams.withUnsafePointerToSerialized { serverSecretParams in
+    return try serverSecretParams.withUnsafeBorrowedBuffer { serverSecretParams in
       try randomness.withUnsafePointerToBytes { randomness in
         try receiptCredentialRequest.withUnsafePointerToSerialized { receiptCredentialRequest in
           try invokeFnReturningSerialized {
@@ -31,7 +31,7 @@ public class ServerZkReceiptOperations {
   }
 
   public func verifyReceiptCredentialPresentation(receiptCredentialPresentation: ReceiptCredentialPresentation) throws {
-    try serverSecretParams.withUnsafePointerToSerialized { serverSecretParams in
+    try serverSecretParams.withUnsafeBorrowedBuffer { serverSecretParams in
       try receiptCredentialPresentation.withUnsafePointerToSerialized { receiptCredentialPresentation in
         try checkError(signal_server_secret_params_verify_receipt_credential_presentation(serverSecretParams, receiptCredentialPresentation))
         }
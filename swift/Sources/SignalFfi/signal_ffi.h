@@ -34,12 +34,28 @@ SPDX-License-Identifier: AGPL-3.0-only
 
 #define SignalGROUP_IDENTIFIER_LEN 32
 
+#define SignalGROUP_INVITE_LINK_LEN 49
+
+#define SignalINVITE_LINK_PASSWORD_LEN 16
+
+#define SignalINVITE_LINK_PRESENTATION_LEN 137
+
+#define SignalINVITE_LINK_PUBLIC_KEY_LEN 33
+
 #define SignalAUTH_CREDENTIAL_LEN 181
 
 #define SignalAUTH_CREDENTIAL_PRESENTATION_LEN 493
 
+#define SignalAUTH_CREDENTIAL_PRESENTATION_V1_LEN 653
+
 #define SignalAUTH_CREDENTIAL_RESPONSE_LEN 361
 
+#define SignalAUTH_CREDENTIAL_WITH_PNI_LEN 285
+
+#define SignalAUTH_CREDENTIAL_WITH_PNI_PRESENTATION_LEN 673
+
+#define SignalAUTH_CREDENTIAL_WITH_PNI_RESPONSE_LEN 425
+
 #define SignalPNI_CREDENTIAL_LEN 161
 
 #define SignalPNI_CREDENTIAL_PRESENTATION_LEN 841
@@ -58,6 +74,8 @@ SPDX-License-Identifier: AGPL-3.0-only
 
 #define SignalPROFILE_KEY_CREDENTIAL_PRESENTATION_LEN 713
 
+#define SignalPROFILE_KEY_CREDENTIAL_PRESENTATION_V1_LEN 969
+
 #define SignalPROFILE_KEY_CREDENTIAL_REQUEST_LEN 329
 
 #define SignalPROFILE_KEY_CREDENTIAL_REQUEST_CONTEXT_LEN 473
@@ -82,9 +100,13 @@ SPDX-License-Identifier: AGPL-3.0-only
 
 #define SignalRESERVED_LEN 1
 
-#define SignalSERVER_SECRET_PARAMS_LEN 1537
+#define SignalSERVER_SECRET_PARAMS_LEN 2289
+
+#define SignalSERVER_PUBLIC_PARAMS_LEN 417
 
-#define SignalSERVER_PUBLIC_PARAMS_LEN 289
+#define SignalSERVER_SECRET_PARAMS_V0_LEN 1537
+
+#define SignalSERVER_PUBLIC_PARAMS_V0_LEN 289
 
 #define SignalUUID_CIPHERTEXT_LEN 65
 
@@ -94,6 +116,8 @@ SPDX-License-Identifier: AGPL-3.0-only
 
 #define SignalUUID_LEN 16
 
+#define SignalSECONDS_PER_DAY 86400
+
 typedef enum {
   SignalCiphertextMessageType_Whisper = 2,
   SignalCiphertextMessageType_PreKey = 3,
@@ -147,6 +171,7 @@ typedef enum {
   SignalErrorCode_DuplicatedMessage = 90,
   SignalErrorCode_CallbackError = 100,
   SignalErrorCode_VerificationFailure = 110,
+  SignalErrorCode_InvalidRedemptionTime = 111,
 } SignalErrorCode;
 
 /**
@@ -168,6 +193,12 @@ typedef struct SignalFingerprint SignalFingerprint;
 
 typedef struct SignalHsmEnclaveClient SignalHsmEnclaveClient;
 
+typedef struct SignalMessageBackupDecryptor SignalMessageBackupDecryptor;
+
+typedef struct SignalMessageBackupEncryptor SignalMessageBackupEncryptor;
+
+typedef struct SignalPinHash SignalPinHash;
+
 typedef struct SignalPlaintextContent SignalPlaintextContent;
 
 typedef struct SignalPreKeyBundle SignalPreKeyBundle;
@@ -206,6 +237,8 @@ typedef struct SignalMessage SignalMessage;
 
 typedef struct SignalSignedPreKeyRecord SignalSignedPreKeyRecord;
 
+typedef struct SignalTrustRoot SignalTrustRoot;
+
 typedef struct SignalUnidentifiedSenderMessageContent SignalUnidentifiedSenderMessageContent;
 
 typedef struct {
@@ -292,6 +325,11 @@ typedef struct {
   uintptr_t length;
 } SignalBorrowedSliceOfSessionRecord;
 
+typedef struct {
+  const SignalTrustRoot *const *base;
+  uintptr_t length;
+} SignalBorrowedSliceOfTrustRoot;
+
 typedef int (*SignalLoadSenderKey)(void *store_ctx, SignalSenderKeyRecord**, const SignalProtocolAddress*, const uint8_t (*distribution_id)[16], void *ctx);
 
 typedef int (*SignalStoreSenderKey)(void *store_ctx, const SignalProtocolAddress*, const uint8_t (*distribution_id)[16], const SignalSenderKeyRecord*, void *ctx);
@@ -329,8 +367,9 @@ SignalFfiError *signal_sealed_session_cipher_decrypt(const unsigned char **out,
                                                      const char **sender_e164,
                                                      const char **sender_uuid,
                                                      uint32_t *sender_device_id,
+                                                     SignalPublicKey **sender_trust_root,
                                                      SignalBorrowedBuffer ctext,
-                                                     const SignalPublicKey *trust_root,
+                                                     SignalBorrowedSliceOfTrustRoot trust_roots,
                                                      uint64_t timestamp,
                                                      const char *local_e164,
                                                      const char *local_uuid,
@@ -351,6 +390,8 @@ SignalFfiError *signal_aes256_gcm_encryption_destroy(SignalAes256GcmEncryption *
 
 SignalFfiError *signal_aes256_gcm_decryption_destroy(SignalAes256GcmDecryption *p);
 
+SignalFfiError *signal_pin_hash_destroy(SignalPinHash *p);
+
 SignalFfiError *signal_aes256_ctr32_new(SignalAes256Ctr32 **out,
                                         SignalBorrowedBuffer key,
                                         SignalBorrowedBuffer nonce,
@@ -405,6 +446,67 @@ SignalFfiError *signal_aes256_gcm_siv_decrypt(const unsigned char **out,
                                               SignalBorrowedBuffer nonce,
                                               SignalBorrowedBuffer associated_data);
 
+SignalFfiError *signal_storage_key_derive_from_master_key(const unsigned char **out,
+                                                          size_t *out_len,
+                                                          const uint8_t (*master_key)[32]);
+
+SignalFfiError *signal_storage_key_derive_manifest_key(const unsigned char **out,
+                                                       size_t *out_len,
+                                                       const uint8_t (*storage_key)[32],
+                                                       uint64_t version);
+
+SignalFfiError *signal_storage_key_derive_item_key(const unsigned char **out,
+                                                   size_t *out_len,
+                                                   const uint8_t (*storage_key)[32],
+                                                   SignalBorrowedBuffer raw_id);
+
+SignalFfiError *signal_storage_manifest_key_encrypt(const unsigned char **out,
+                                                    size_t *out_len,
+                                                    const uint8_t (*key)[32],
+                                                    SignalBorrowedBuffer plaintext);
+
+SignalFfiError *signal_storage_manifest_key_decrypt(const unsigned char **out,
+                                                    size_t *out_len,
+                                                    const uint8_t (*key)[32],
+                                                    SignalBorrowedBuffer ciphertext);
+
+SignalFfiError *signal_storage_item_key_encrypt(const unsigned char **out,
+                                                size_t *out_len,
+                                                const uint8_t (*key)[32],
+                                                SignalBorrowedBuffer plaintext);
+
+SignalFfiError *signal_storage_item_key_decrypt(const unsigned char **out,
+                                                size_t *out_len,
+                                                const uint8_t (*key)[32],
+                                                SignalBorrowedBuffer ciphertext);
+
+SignalFfiError *signal_pin_normalize(const char **out, const char *pin);
+
+SignalFfiError *signal_pin_hash_create(SignalPinHash **out,
+                                       SignalBorrowedBuffer normalized_pin,
+                                       SignalBorrowedBuffer salt);
+
+SignalFfiError *signal_pin_hash_encryption_key(const unsigned char **out,
+                                               size_t *out_len,
+                                               const SignalPinHash *pin_hash);
+
+SignalFfiError *signal_pin_hash_access_key(const unsigned char **out,
+                                           size_t *out_len,
+                                           const SignalPinHash *pin_hash);
+
+SignalFfiError *signal_master_key_wrap(const unsigned char **out,
+                                       size_t *out_len,
+                                       SignalBorrowedBuffer master_key,
+                                       const SignalPinHash *pin_hash);
+
+SignalFfiError *signal_master_key_unwrap(const unsigned char **out,
+                                         size_t *out_len,
+                                         const SignalPinHash *pin_hash,
+                                         SignalBorrowedBuffer wrapped);
+
+SignalFfiError *signal_master_key_registration_lock(const char **out,
+                                                    SignalBorrowedBuffer master_key);
+
 SignalFfiError *signal_ciphertext_message_destroy(SignalCiphertextMessage *p);
 
 SignalFfiError *signal_decryption_error_message_destroy(SignalDecryptionErrorMessage *p);
@@ -488,6 +590,10 @@ SignalFfiError *signal_signed_pre_key_record_destroy(SignalSignedPreKeyRecord *p
 SignalFfiError *signal_signed_pre_key_record_clone(SignalSignedPreKeyRecord **new_obj,
                                                    const SignalSignedPreKeyRecord *obj);
 
+SignalFfiError *signal_trust_root_destroy(SignalTrustRoot *p);
+
+SignalFfiError *signal_trust_root_clone(SignalTrustRoot **new_obj, const SignalTrustRoot *obj);
+
 SignalFfiError *signal_unidentified_sender_message_content_destroy(SignalUnidentifiedSenderMessageContent *p);
 
 SignalFfiError *signal_hkdf_derive(SignalBorrowedMutableBuffer output,
@@ -864,6 +970,15 @@ SignalFfiError *signal_server_certificate_new(SignalServerCertificate **out,
                                               const SignalPublicKey *server_key,
                                               const SignalPrivateKey *trust_root);
 
+SignalFfiError *signal_trust_root_new(SignalTrustRoot **out, const SignalPublicKey *key);
+
+SignalFfiError *signal_trust_root_new_with_validity(SignalTrustRoot **out,
+                                                    const SignalPublicKey *key,
+                                                    uint64_t not_before,
+                                                    uint64_t not_after);
+
+SignalFfiError *signal_trust_root_get_key(SignalPublicKey **out, const SignalTrustRoot *obj);
+
 SignalFfiError *signal_sender_certificate_deserialize(SignalSenderCertificate **out,
                                                       SignalBorrowedBuffer data);
 
@@ -899,6 +1014,11 @@ SignalFfiError *signal_sender_certificate_validate(bool *out,
                                                    const SignalPublicKey *key,
                                                    uint64_t time);
 
+SignalFfiError *signal_sender_certificate_validate_with_trust_roots(SignalPublicKey **out,
+                                                                    const SignalSenderCertificate *cert,
+                                                                    SignalBorrowedSliceOfTrustRoot trust_roots,
+                                                                    uint64_t time);
+
 SignalFfiError *signal_sender_certificate_get_server_certificate(SignalServerCertificate **out,
                                                                  const SignalSenderCertificate *cert);
 
@@ -1089,6 +1209,45 @@ SignalFfiError *signal_hsm_enclave_client_established_recv(const unsigned char *
                                                            SignalHsmEnclaveClient *cli,
                                                            SignalBorrowedBuffer received_ciphertext);
 
+SignalFfiError *signal_message_backup_encryptor_destroy(SignalMessageBackupEncryptor *p);
+
+SignalFfiError *signal_message_backup_decryptor_destroy(SignalMessageBackupDecryptor *p);
+
+SignalFfiError *signal_message_backup_encryptor_new_with_passphrase(SignalMessageBackupEncryptor **out,
+                                                                    const char *passphrase);
+
+SignalFfiError *signal_message_backup_encryptor_new_with_recovery_key(SignalMessageBackupEncryptor **out,
+                                                                      const uint8_t (*recovery_key)[32]);
+
+SignalFfiError *signal_message_backup_encryptor_get_archive_prefix(const unsigned char **out,
+                                                                   size_t *out_len,
+                                                                   const SignalMessageBackupEncryptor *encryptor);
+
+SignalFfiError *signal_message_backup_encryptor_encrypt_frame(const unsigned char **out,
+                                                              size_t *out_len,
+                                                              SignalMessageBackupEncryptor *encryptor,
+                                                              SignalBorrowedBuffer serialized_frame);
+
+SignalFfiError *signal_message_backup_encryptor_finish(const unsigned char **out,
+                                                       size_t *out_len,
+                                                       SignalMessageBackupEncryptor *encryptor);
+
+SignalFfiError *signal_message_backup_decryptor_new_with_passphrase(SignalMessageBackupDecryptor **out,
+                                                                    const char *passphrase,
+                                                                    SignalBorrowedBuffer serialized_header);
+
+SignalFfiError *signal_message_backup_decryptor_new_with_recovery_key(SignalMessageBackupDecryptor **out,
+                                                                      const uint8_t (*recovery_key)[32],
+                                                                      SignalBorrowedBuffer serialized_header);
+
+SignalFfiError *signal_message_backup_decryptor_decrypt_frame(const unsigned char **out,
+                                                              size_t *out_len,
+                                                              SignalMessageBackupDecryptor *decryptor,
+                                                              SignalBorrowedBuffer record);
+
+SignalFfiError *signal_message_backup_decryptor_is_finished(bool *out,
+                                                            const SignalMessageBackupDecryptor *decryptor);
+
 SignalFfiError *signal_auth_credential_check_valid_contents(const unsigned char (*_obj)[SignalAUTH_CREDENTIAL_LEN]);
 
 SignalFfiError *signal_auth_credential_presentation_check_valid_contents(const unsigned char (*_obj)[SignalAUTH_CREDENTIAL_PRESENTATION_LEN]);
@@ -1135,9 +1294,9 @@ SignalFfiError *signal_receipt_credential_request_context_check_valid_contents(c
 
 SignalFfiError *signal_receipt_credential_response_check_valid_contents(const unsigned char (*_obj)[SignalRECEIPT_CREDENTIAL_RESPONSE_LEN]);
 
-SignalFfiError *signal_server_public_params_check_valid_contents(const unsigned char (*_obj)[SignalSERVER_PUBLIC_PARAMS_LEN]);
+SignalFfiError *signal_server_public_params_check_valid_contents(SignalBorrowedBuffer _obj);
 
-SignalFfiError *signal_server_secret_params_check_valid_contents(const unsigned char (*_obj)[SignalSERVER_SECRET_PARAMS_LEN]);
+SignalFfiError *signal_server_secret_params_check_valid_contents(SignalBorrowedBuffer _obj);
 
 SignalFfiError *signal_uuid_ciphertext_check_valid_contents(const unsigned char (*_obj)[SignalUUID_CIPHERTEXT_LEN]);
 
@@ -1191,126 +1350,128 @@ SignalFfiError *signal_group_secret_params_decrypt_blob_with_padding(const unsig
                                                                      const unsigned char (*params)[SignalGROUP_SECRET_PARAMS_LEN],
                                                                      SignalBorrowedBuffer ciphertext);
 
-SignalFfiError *signal_server_secret_params_generate_deterministic(unsigned char (*out)[SignalSERVER_SECRET_PARAMS_LEN],
+SignalFfiError *signal_server_secret_params_generate_deterministic(const unsigned char **out,
+                                                                   size_t *out_len,
                                                                    const uint8_t (*randomness)[SignalRANDOMNESS_LEN]);
 
-SignalFfiError *signal_server_secret_params_get_public_params(unsigned char (*out)[SignalSERVER_PUBLIC_PARAMS_LEN],
-                                                              const unsigned char (*params)[SignalSERVER_SECRET_PARAMS_LEN]);
+SignalFfiError *signal_server_secret_params_get_public_params(const unsigned char **out,
+                                                              size_t *out_len,
+                                                              SignalBorrowedBuffer params);
 
 SignalFfiError *signal_server_secret_params_sign_deterministic(uint8_t (*out)[SignalSIGNATURE_LEN],
-                                                               const unsigned char (*params)[SignalSERVER_SECRET_PARAMS_LEN],
+                                                               SignalBorrowedBuffer params,
                                                                const uint8_t (*randomness)[SignalRANDOMNESS_LEN],
                                                                SignalBorrowedBuffer message);
 
 SignalFfiError *signal_server_public_params_receive_auth_credential(unsigned char (*out)[SignalAUTH_CREDENTIAL_LEN],
-                                                                    const unsigned char (*params)[SignalSERVER_PUBLIC_PARAMS_LEN],
+                                                                    SignalBorrowedBuffer params,
                                                                     const uint8_t (*uuid)[16],
                                                                     uint32_t redemption_time,
                                                                     const unsigned char (*response)[SignalAUTH_CREDENTIAL_RESPONSE_LEN]);
 
 SignalFfiError *signal_server_public_params_create_auth_credential_presentation_deterministic(unsigned char (*out)[SignalAUTH_CREDENTIAL_PRESENTATION_LEN],
-                                                                                              const unsigned char (*server_public_params)[SignalSERVER_PUBLIC_PARAMS_LEN],
+                                                                                              SignalBorrowedBuffer server_public_params,
                                                                                               const uint8_t (*randomness)[SignalRANDOMNESS_LEN],
                                                                                               const unsigned char (*group_secret_params)[SignalGROUP_SECRET_PARAMS_LEN],
                                                                                               const unsigned char (*auth_credential)[SignalAUTH_CREDENTIAL_LEN]);
 
 SignalFfiError *signal_server_public_params_create_profile_key_credential_request_context_deterministic(unsigned char (*out)[SignalPROFILE_KEY_CREDENTIAL_REQUEST_CONTEXT_LEN],
-                                                                                                        const unsigned char (*server_public_params)[SignalSERVER_PUBLIC_PARAMS_LEN],
+                                                                                                        SignalBorrowedBuffer server_public_params,
                                                                                                         const uint8_t (*randomness)[SignalRANDOMNESS_LEN],
                                                                                                         const uint8_t (*uuid)[16],
                                                                                                         const unsigned char (*profile_key)[SignalPROFILE_KEY_LEN]);
 
 SignalFfiError *signal_server_public_params_create_pni_credential_request_context_deterministic(unsigned char (*out)[SignalPNI_CREDENTIAL_REQUEST_CONTEXT_LEN],
-                                                                                                const unsigned char (*server_public_params)[SignalSERVER_PUBLIC_PARAMS_LEN],
+                                                                                                SignalBorrowedBuffer server_public_params,
                                                                                                 const uint8_t (*randomness)[SignalRANDOMNESS_LEN],
                                                                                                 const uint8_t (*aci)[16],
                                                                                                 const uint8_t (*pni)[16],
                                                                                                 const unsigned char (*profile_key)[SignalPROFILE_KEY_LEN]);
 
 SignalFfiError *signal_server_public_params_receive_profile_key_credential(unsigned char (*out)[SignalPROFILE_KEY_CREDENTIAL_LEN],
-                                                                           const unsigned char (*server_public_params)[SignalSERVER_PUBLIC_PARAMS_LEN],
+                                                                           SignalBorrowedBuffer server_public_params,
                                                                            const unsigned char (*request_context)[SignalPROFILE_KEY_CREDENTIAL_REQUEST_CONTEXT_LEN],
                                                                            const unsigned char (*response)[SignalPROFILE_KEY_CREDENTIAL_RESPONSE_LEN]);
 
 SignalFfiError *signal_server_public_params_receive_pni_credential(unsigned char (*out)[SignalPNI_CREDENTIAL_LEN],
-                                                                   const unsigned char (*server_public_params)[SignalSERVER_PUBLIC_PARAMS_LEN],
+                                                                   SignalBorrowedBuffer server_public_params,
                                                                    const unsigned char (*request_context)[SignalPNI_CREDENTIAL_REQUEST_CONTEXT_LEN],
                                                                    const unsigned char (*response)[SignalPNI_CREDENTIAL_RESPONSE_LEN]);
 
 SignalFfiError *signal_server_public_params_create_profile_key_credential_presentation_deterministic(unsigned char (*out)[SignalPROFILE_KEY_CREDENTIAL_PRESENTATION_LEN],
-                                                                                                     const unsigned char (*server_public_params)[SignalSERVER_PUBLIC_PARAMS_LEN],
+                                                                                                     SignalBorrowedBuffer server_public_params,
                                                                                                      const uint8_t (*randomness)[SignalRANDOMNESS_LEN],
                                                                                                      const unsigned char (*group_secret_params)[SignalGROUP_SECRET_PARAMS_LEN],
                                                                                                      const unsigned char (*profile_key_credential)[SignalPROFILE_KEY_CREDENTIAL_LEN]);
 
 SignalFfiError *signal_server_public_params_create_pni_credential_presentation_deterministic(unsigned char (*out)[SignalPNI_CREDENTIAL_PRESENTATION_LEN],
-                                                                                             const unsigned char (*server_public_params)[SignalSERVER_PUBLIC_PARAMS_LEN],
+                                                                                             SignalBorrowedBuffer server_public_params,
                                                                                              const uint8_t (*randomness)[SignalRANDOMNESS_LEN],
                                                                                              const unsigned char (*group_secret_params)[SignalGROUP_SECRET_PARAMS_LEN],
                                                                                              const unsigned char (*profile_key_credential)[SignalPNI_CREDENTIAL_LEN]);
 
 SignalFfiError *signal_server_public_params_create_receipt_credential_request_context_deterministic(unsigned char (*out)[SignalRECEIPT_CREDENTIAL_REQUEST_CONTEXT_LEN],
-                                                                                                    const unsigned char (*server_public_params)[SignalSERVER_PUBLIC_PARAMS_LEN],
+                                                                                                    SignalBorrowedBuffer server_public_params,
                                                                                                     const uint8_t (*randomness)[SignalRANDOMNESS_LEN],
                                                                                                     const uint8_t (*receipt_serial)[SignalRECEIPT_SERIAL_LEN]);
 
 SignalFfiError *signal_server_public_params_receive_receipt_credential(unsigned char (*out)[SignalRECEIPT_CREDENTIAL_LEN],
-                                                                       const unsigned char (*server_public_params)[SignalSERVER_PUBLIC_PARAMS_LEN],
+                                                                       SignalBorrowedBuffer server_public_params,
                                                                        const unsigned char (*request_context)[SignalRECEIPT_CREDENTIAL_REQUEST_CONTEXT_LEN],
                                                                        const unsigned char (*response)[SignalRECEIPT_CREDENTIAL_RESPONSE_LEN]);
 
 SignalFfiError *signal_server_public_params_create_receipt_credential_presentation_deterministic(unsigned char (*out)[SignalRECEIPT_CREDENTIAL_PRESENTATION_LEN],
-                                                                                                 const unsigned char (*server_public_params)[SignalSERVER_PUBLIC_PARAMS_LEN],
+                                                                                                 SignalBorrowedBuffer server_public_params,
                                                                                                  const uint8_t (*randomness)[SignalRANDOMNESS_LEN],
                                                                                                  const unsigned char (*receipt_credential)[SignalRECEIPT_CREDENTIAL_LEN]);
 
 SignalFfiError *signal_server_secret_params_issue_auth_credential_deterministic(unsigned char (*out)[SignalAUTH_CREDENTIAL_RESPONSE_LEN],
-                                                                                const unsigned char (*server_secret_params)[SignalSERVER_SECRET_PARAMS_LEN],
+                                                                                SignalBorrowedBuffer server_secret_params,
                                                                                 const uint8_t (*randomness)[SignalRANDOMNESS_LEN],
                                                                                 const uint8_t (*uuid)[16],
                                                                                 uint32_t redemption_time);
 
-SignalFfiError *signal_server_secret_params_verify_auth_credential_presentation(const unsigned char (*server_secret_params)[SignalSERVER_SECRET_PARAMS_LEN],
+SignalFfiError *signal_server_secret_params_verify_auth_credential_presentation(SignalBorrowedBuffer server_secret_params,
                                                                                 const unsigned char (*group_public_params)[SignalGROUP_PUBLIC_PARAMS_LEN],
                                                                                 const unsigned char (*presentation)[SignalAUTH_CREDENTIAL_PRESENTATION_LEN]);
 
 SignalFfiError *signal_server_secret_params_issue_profile_key_credential_deterministic(unsigned char (*out)[SignalPROFILE_KEY_CREDENTIAL_RESPONSE_LEN],
-                                                                                       const unsigned char (*server_secret_params)[SignalSERVER_SECRET_PARAMS_LEN],
+                                                                                       SignalBorrowedBuffer server_secret_params,
                                                                                        const uint8_t (*randomness)[SignalRANDOMNESS_LEN],
                                                                                        const unsigned char (*request)[SignalPROFILE_KEY_CREDENTIAL_REQUEST_LEN],
                                                                                        const uint8_t (*uuid)[16],
                                                                                        const unsigned char (*commitment)[SignalPROFILE_KEY_COMMITMENT_LEN]);
 
 SignalFfiError *signal_server_secret_params_issue_pni_credential_deterministic(unsigned char (*out)[SignalPNI_CREDENTIAL_RESPONSE_LEN],
-                                                                               const unsigned char (*server_secret_params)[SignalSERVER_SECRET_PARAMS_LEN],
+                                                                               SignalBorrowedBuffer server_secret_params,
                                                                                const uint8_t (*randomness)[SignalRANDOMNESS_LEN],
                                                                                const unsigned char (*request)[SignalPROFILE_KEY_CREDENTIAL_REQUEST_LEN],
                                                                                const uint8_t (*aci)[16],
                                                                                const uint8_t (*pni)[16],
                                                                                const unsigned char (*commitment)[SignalPROFILE_KEY_COMMITMENT_LEN]);
 
-SignalFfiError *signal_server_secret_params_verify_profile_key_credential_presentation(const unsigned char (*server_secret_params)[SignalSERVER_SECRET_PARAMS_LEN],
+SignalFfiError *signal_server_secret_params_verify_profile_key_credential_presentation(SignalBorrowedBuffer server_secret_params,
                                                                                        const unsigned char (*group_public_params)[SignalGROUP_PUBLIC_PARAMS_LEN],
                                                                                        const unsigned char (*presentation)[SignalPROFILE_KEY_CREDENTIAL_PRESENTATION_LEN]);
 
-SignalFfiError *signal_server_secret_params_verify_pni_credential_presentation(const unsigned char (*server_secret_params)[SignalSERVER_SECRET_PARAMS_LEN],
+SignalFfiError *signal_server_secret_params_verify_pni_credential_presentation(SignalBorrowedBuffer server_secret_params,
                                                                                const unsigned char (*group_public_params)[SignalGROUP_PUBLIC_PARAMS_LEN],
                                                                                const unsigned char (*presentation)[SignalPNI_CREDENTIAL_PRESENTATION_LEN]);
 
 SignalFfiError *signal_server_secret_params_issue_receipt_credential_deterministic(unsigned char (*out)[SignalRECEIPT_CREDENTIAL_RESPONSE_LEN],
-                                                                                   const unsigned char (*server_secret_params)[SignalSERVER_SECRET_PARAMS_LEN],
+                                                                                   SignalBorrowedBuffer server_secret_params,
                                                                                    const uint8_t (*randomness)[SignalRANDOMNESS_LEN],
                                                                                    const unsigned char (*request)[SignalRECEIPT_CREDENTIAL_REQUEST_LEN],
                                                                                    uint64_t receipt_expiration_time,
                                                                                    uint64_t receipt_level);
 
-SignalFfiError *signal_server_secret_params_verify_receipt_credential_presentation(const unsigned char (*server_secret_params)[SignalSERVER_SECRET_PARAMS_LEN],
+SignalFfiError *signal_server_secret_params_verify_receipt_credential_presentation(SignalBorrowedBuffer server_secret_params,
                                                                                    const unsigned char (*presentation)[SignalRECEIPT_CREDENTIAL_PRESENTATION_LEN]);
 
 SignalFfiError *signal_group_public_params_get_group_identifier(uint8_t (*out)[SignalGROUP_IDENTIFIER_LEN],
                                                                 const unsigned char (*group_public_params)[SignalGROUP_PUBLIC_PARAMS_LEN]);
 
-SignalFfiError *signal_server_public_params_verify_signature(const unsigned char (*server_public_params)[SignalSERVER_PUBLIC_PARAMS_LEN],
+SignalFfiError *signal_server_public_params_verify_signature(SignalBorrowedBuffer server_public_params,
                                                              SignalBorrowedBuffer message,
                                                              const uint8_t (*notary_signature)[SignalSIGNATURE_LEN]);
 
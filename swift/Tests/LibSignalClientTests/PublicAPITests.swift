@@ -328,6 +328,40 @@ class PublicAPITests: TestCaseBase {
         XCTAssert(try! secondary.identityKey.verifyAlternateIdentity(primary.identityKey, signature: signature))
     }
 
+    // Keep in sync with the known-answer tests in rust/crypto/tests/pin.rs.
+    func testPinHashAndMasterKey() throws {
+        func hex(_ bytes: [UInt8]) -> String {
+            return bytes.map { String(format: "%02x", $0) }.joined()
+        }
+
+        let salt: [UInt8] = Array(0x20...0x3f)
+        let pinHash = try PinHash(normalizedPin: Array(normalizePin("password").utf8), salt: salt)
+        XCTAssertEqual(hex(pinHash.encryptionKey), "175f63e11c5974891a27413776e079402ac441faaee5e7fe8b3b757888472638")
+        XCTAssertEqual(hex(pinHash.accessKey), "02a16780b3d79d48106e9196c598b36f889c5801e7c8717e3377099bf1dd319a")
+
+        let masterKey = try MasterKey(salt)
+        var wrapped = masterKey.wrap(pinHash)
+        XCTAssertEqual(hex(wrapped), "5eced03868a6c20d3edf31ecaf0180cb3e0ad7f660851d192c7e9d1c686bd9d7e1668c0773209a85e8ba72a521bb9d51")
+        XCTAssertEqual(try MasterKey.unwrap(wrapped, with: pinHash).bytes, salt)
+        XCTAssertEqual(masterKey.registrationLock, "2bf7988224ba35d3554966c65e8dc8c54974b034bdd44cabfd3f15fdb185e3c6")
+
+        wrapped[20] ^= 1
+        XCTAssertThrowsError(try MasterKey.unwrap(wrapped, with: pinHash)) {
+            guard case SignalError.invalidMessage(_) = $0 else {
+                XCTFail("wrong error thrown: \($0)")
+                return
+            }
+        }
+    }
+
+    func testPinNormalization() {
+        XCTAssertEqual(normalizePin(" 1234\n"), "1234")
+        XCTAssertEqual(normalizePin("\u{661}\u{662}\u{663}\u{664}"), "1234")
+        XCTAssertEqual(normalizePin("\u{967}\u{968}\u{969}\u{96A}"), "1234")
+        XCTAssertEqual(normalizePin("\u{FF11}\u{FF12}\u{FF13}\u{FF14}"), "1234")
+        XCTAssertEqual(Array(normalizePin("caf\u{E9}").unicodeScalars), Array("cafe\u{301}".unicodeScalars))
+    }
+
     static var allTests: [(String, (PublicAPITests) -> () throws -> Void)] {
         return [
             ("testAddreses", testAddress),
@@ -336,6 +370,8 @@ class PublicAPITests: TestCaseBase {
             ("testHkdfSimple", testHkdfSimple),
             ("testHkdfUsingRFCExample", testHkdfUsingRFCExample),
             ("testAesGcmSiv", testAesGcmSiv),
+            ("testPinHashAndMasterKey", testPinHashAndMasterKey),
+            ("testPinNormalization", testPinNormalization),
             ("testGroupCipher", testGroupCipher),
             ("testSenderCertifications", testSenderCertificates),
             ("testSerializationRoundTrip", testSerializationRoundTrip),
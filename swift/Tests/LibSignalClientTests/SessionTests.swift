@@ -190,6 +190,7 @@ class SessionTests: TestCaseBase {
 
         XCTAssertEqual(plaintext.message, message)
         XCTAssertEqual(plaintext.sender, sender_addr)
+        XCTAssertEqual(plaintext.trustRoot, trust_root.publicKey)
 
         let innerMessage = try signalEncrypt(message: [],
                                              for: bob_address,